@@ -94,6 +94,7 @@ CREATE TABLE "worktree_repositories" (
     "worktree_id" INTEGER NOT NULL,
     "work_directory_id" INTEGER NOT NULL,
     "branch" VARCHAR,
+    "kind" INTEGER NOT NULL DEFAULT 0,
     "scan_id" INTEGER NOT NULL,
     "is_deleted" BOOL NOT NULL,
     PRIMARY KEY(project_id, worktree_id, work_directory_id),
@@ -0,0 +1 @@
+ALTER TABLE worktree_repositories ADD COLUMN kind INTEGER NOT NULL DEFAULT 0;
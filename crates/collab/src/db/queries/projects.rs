@@ -310,6 +310,7 @@ impl Database {
                         work_directory_id: ActiveValue::set(repository.work_directory_id as i64),
                         scan_id: ActiveValue::set(update.scan_id as i64),
                         branch: ActiveValue::set(repository.branch.clone()),
+                        kind: ActiveValue::set(repository.kind),
                         is_deleted: ActiveValue::set(false),
                     },
                 ))
@@ -322,6 +323,7 @@ impl Database {
                     .update_columns([
                         worktree_repository::Column::ScanId,
                         worktree_repository::Column::Branch,
+                        worktree_repository::Column::Kind,
                     ])
                     .to_owned(),
                 )
@@ -691,6 +693,7 @@ impl Database {
                         proto::RepositoryEntry {
                             work_directory_id: db_repository_entry.work_directory_id as u64,
                             branch: db_repository_entry.branch,
+                            kind: db_repository_entry.kind,
                         },
                     );
                 }
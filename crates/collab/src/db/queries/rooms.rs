@@ -657,6 +657,7 @@ impl Database {
                                 worktree.updated_repositories.push(proto::RepositoryEntry {
                                     work_directory_id: db_repository.work_directory_id as u64,
                                     branch: db_repository.branch,
+                                    kind: db_repository.kind,
                                 });
                             }
                         }
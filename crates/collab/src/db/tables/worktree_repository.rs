@@ -12,6 +12,7 @@ pub struct Model {
     pub work_directory_id: i64,
     pub scan_id: i64,
     pub branch: Option<String>,
+    pub kind: i32,
     pub is_deleted: bool,
 }
 
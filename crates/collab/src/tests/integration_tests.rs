@@ -20,6 +20,7 @@ use live_kit_client::MacOSDisplay;
 use lsp::LanguageServerId;
 use project::{
     search::SearchQuery, DiagnosticSummary, FormatTrigger, HoverBlockKind, Project, ProjectPath,
+    RepositoryKind,
 };
 use rand::prelude::*;
 use serde_json::json;
@@ -2792,6 +2793,151 @@ async fn test_git_branch_name(
     });
 }
 
+#[gpui::test]
+async fn test_git_submodules_and_linked_worktrees(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+    cx_c: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let client_c = server.create_client(cx_c, "user_c").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b), (&client_c, cx_c)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {
+                    "modules": {
+                        "sub": {},
+                    },
+                    "worktrees": {
+                        "feature": {},
+                    },
+                },
+                "a.txt": "",
+                "sub": {
+                    ".git": "gitdir: ../.git/modules/sub\n",
+                    "b.txt": "",
+                },
+            }),
+        )
+        .await;
+    client_a
+        .fs()
+        .insert_tree(
+            "/feature",
+            json!({
+                ".git": "gitdir: /dir/.git/worktrees/feature\n",
+                "a.txt": "",
+            }),
+        )
+        .await;
+    client_a
+        .fs()
+        .set_branch_name(Path::new("/dir/.git"), Some("main"));
+    client_a
+        .fs()
+        .set_branch_name(Path::new("/dir/.git/modules/sub"), Some("detached"));
+    client_a
+        .fs()
+        .set_branch_name(Path::new("/dir/.git/worktrees/feature"), Some("feature"));
+    client_a.fs().set_status_for_repo_via_git_operation(
+        Path::new("/dir/.git/modules/sub"),
+        &[(Path::new("b.txt"), GitFileStatus::Added)],
+    );
+
+    let (project_local, _) = client_a.build_local_project("/dir", cx_a).await;
+    project_local
+        .update(cx_a, |project, cx| {
+            project.find_or_create_local_worktree("/feature", true, cx)
+        })
+        .await
+        .unwrap();
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(project_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+
+    let project_remote = client_b.build_remote_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    #[track_caller]
+    fn assert_repositories(
+        expected: &[(&str, &str, Option<&str>, RepositoryKind)],
+        project: &Project,
+        cx: &AppContext,
+    ) {
+        let mut repositories = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            for (work_directory, repository) in worktree.repositories() {
+                repositories.push((
+                    worktree.root_name().to_string(),
+                    work_directory.to_string_lossy().into_owned(),
+                    repository.branch().map(|branch| branch.to_string()),
+                    repository.kind(),
+                ));
+            }
+        }
+        repositories.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        let expected = expected
+            .iter()
+            .map(|(root_name, work_directory, branch, kind)| {
+                (
+                    root_name.to_string(),
+                    work_directory.to_string(),
+                    branch.map(ToString::to_string),
+                    *kind,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(repositories, expected);
+
+        let worktree = project
+            .visible_worktrees(cx)
+            .find(|worktree| worktree.read(cx).root_name() == "dir")
+            .unwrap();
+        assert_eq!(
+            worktree.read(cx).status_for_file(Path::new("sub/b.txt")),
+            Some(GitFileStatus::Added)
+        );
+    }
+
+    let expected = [
+        ("dir", "", Some("main"), RepositoryKind::Standalone),
+        ("dir", "sub", Some("detached"), RepositoryKind::Submodule),
+        (
+            "feature",
+            "",
+            Some("feature"),
+            RepositoryKind::LinkedWorktree,
+        ),
+    ];
+    project_local.read_with(cx_a, |project, cx| {
+        assert_repositories(&expected, project, cx)
+    });
+    project_remote.read_with(cx_b, |project, cx| {
+        assert_repositories(&expected, project, cx)
+    });
+
+    // Guests that join later load the repositories from the database.
+    let project_remote_c = client_c.build_remote_project(project_id, cx_c).await;
+    executor.run_until_parked();
+    project_remote_c.read_with(cx_c, |project, cx| {
+        assert_repositories(&expected, project, cx)
+    });
+}
+
 #[gpui::test]
 async fn test_git_status_sync(
     executor: BackgroundExecutor,
@@ -3475,6 +3621,56 @@ async fn test_buffer_reloading(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_loading_binary_files(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    // Use a file larger than a single message, so that it's loaded in chunks.
+    let content = (0..5 * 512 * 1024)
+        .map(|ix| (ix % 251) as u8)
+        .collect::<Vec<_>>();
+    client_a.fs().insert_tree("/dir", json!({})).await;
+    client_a
+        .fs()
+        .insert_file("/dir/image.png", content.clone())
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    let path = ProjectPath {
+        worktree_id,
+        path: Path::new("image.png").into(),
+    };
+    let loaded_content = project_b
+        .update(cx_b, |project, cx| project.load_binary_file(&path, cx))
+        .await
+        .unwrap();
+    assert_eq!(loaded_content, content);
+
+    let path = ProjectPath {
+        worktree_id,
+        path: Path::new("missing.png").into(),
+    };
+    project_b
+        .update(cx_b, |project, cx| project.load_binary_file(&path, cx))
+        .await
+        .unwrap_err();
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_editing_while_guest_opens_buffer(
     executor: BackgroundExecutor,
@@ -4898,6 +5094,98 @@ async fn test_document_highlights(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_linked_editing_ranges(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "fn main() { let value = 1; value }",
+            }),
+        )
+        .await;
+
+    let mut fake_language_servers = client_a.language_registry().register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                linked_editing_range_provider: Some(
+                    lsp::LinkedEditingRangeServerCapabilities::Simple(true),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    client_a.language_registry().add(rust_lang());
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    // Open the file on client B.
+    let open_b = project_b.update(cx_b, |p, cx| p.open_buffer((worktree_id, "main.rs"), cx));
+    let buffer_b = cx_b.executor().spawn(open_b).await.unwrap();
+
+    // Request linked editing ranges as the guest.
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    fake_language_server.handle_request::<lsp::request::LinkedEditingRange, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params
+                    .text_document_position_params
+                    .text_document
+                    .uri
+                    .as_str(),
+                "file:///root-1/main.rs"
+            );
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 18)
+            );
+            Ok(Some(lsp::LinkedEditingRanges {
+                ranges: vec![
+                    lsp::Range::new(lsp::Position::new(0, 27), lsp::Position::new(0, 32)),
+                    lsp::Range::new(lsp::Position::new(0, 16), lsp::Position::new(0, 21)),
+                ],
+                word_pattern: Some("[a-z]+".into()),
+            }))
+        },
+    );
+
+    let ranges = project_b
+        .update(cx_b, |p, cx| p.linked_edit(&buffer_b, 18, cx))
+        .await
+        .unwrap();
+
+    assert_eq!(ranges.word_pattern.as_deref(), Some("[a-z]+"));
+    buffer_b.read_with(cx_b, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let ranges = ranges
+            .ranges
+            .into_iter()
+            .map(|range| range.to_offset(&snapshot))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, &[16..21, 27..32]);
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_lsp_hover(
     executor: BackgroundExecutor,
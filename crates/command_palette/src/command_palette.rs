@@ -7,14 +7,15 @@ use std::{
 use client::{parse_zed_link, telemetry::Telemetry};
 use collections::HashMap;
 use command_palette_hooks::{
-    CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptor,
+    CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptHistory,
+    CommandPaletteInterceptor,
 };
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
     actions, Action, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Global,
     ParentElement, Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
 };
-use picker::{Picker, PickerDelegate};
+use picker::{Direction, Picker, PickerDelegate};
 
 use postage::{sink::Sink, stream::Stream};
 use ui::{h_flex, prelude::*, v_flex, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
@@ -63,14 +64,26 @@ impl CommandPalette {
             };
             let telemetry = workspace.client().telemetry().clone();
             workspace.toggle_modal(cx, move |cx| {
-                CommandPalette::new(previous_focus_handle, telemetry, cx)
+                CommandPalette::new(previous_focus_handle, telemetry, "", cx)
             });
         });
     }
 
+    /// Opens the command palette with `query` already typed into it.
+    pub fn toggle(workspace: &mut Workspace, query: &str, cx: &mut ViewContext<Workspace>) {
+        let Some(previous_focus_handle) = cx.focused() else {
+            return;
+        };
+        let telemetry = workspace.client().telemetry().clone();
+        workspace.toggle_modal(cx, move |cx| {
+            CommandPalette::new(previous_focus_handle, telemetry, query, cx)
+        });
+    }
+
     fn new(
         previous_focus_handle: FocusHandle,
         telemetry: Arc<Telemetry>,
+        query: &str,
         cx: &mut ViewContext<Self>,
     ) -> Self {
         let filter = cx.try_global::<CommandPaletteFilter>();
@@ -102,7 +115,13 @@ impl CommandPalette {
             previous_focus_handle,
         );
 
-        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        let picker = cx.new_view(|cx| {
+            let picker = Picker::uniform_list(delegate, cx);
+            if !query.is_empty() {
+                picker.set_query(query, cx);
+            }
+            picker
+        });
         Self { picker }
     }
 }
@@ -127,6 +146,14 @@ pub struct CommandPaletteDelegate {
     commands: Vec<Command>,
     matches: Vec<StringMatch>,
     selected_ix: usize,
+    /// The query that produced the last command in `commands`, if it came from the
+    /// [`CommandPaletteInterceptor`].
+    intercepted_query: Option<String>,
+    /// The entry of the [`CommandPaletteInterceptHistory`] shown in the query
+    /// while navigating it with up and down.
+    history_ix: Option<usize>,
+    /// The query that history navigation started from.
+    history_prefix: String,
     telemetry: Arc<Telemetry>,
     previous_focus_handle: FocusHandle,
     updating_matches: Option<(
@@ -170,6 +197,9 @@ impl CommandPaletteDelegate {
             matches: vec![],
             commands,
             selected_ix: 0,
+            intercepted_query: None,
+            history_ix: None,
+            history_prefix: String::new(),
             telemetry,
             previous_focus_handle,
             updating_matches: None,
@@ -191,8 +221,10 @@ impl CommandPaletteDelegate {
             } else {
                 None
             };
+        self.intercepted_query = intercept_result.as_ref().map(|_| query.clone());
 
         if parse_zed_link(&query, cx).is_some() {
+            self.intercepted_query = None;
             intercept_result = Some(CommandInterceptResult {
                 action: OpenZedUrl { url: query.clone() }.boxed_clone(),
                 string: query.clone(),
@@ -352,6 +384,59 @@ impl PickerDelegate for CommandPaletteDelegate {
         }
     }
 
+    fn has_history(&self) -> bool {
+        true
+    }
+
+    fn select_history(
+        &mut self,
+        direction: Direction,
+        query: &str,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<String> {
+        let history = &cx.try_global::<CommandPaletteInterceptHistory>()?.0;
+        // only navigate the history from an empty query, from a query that is just
+        // punctuation (such as vim's `/`), or while the query is still the history
+        // entry that was last shown. Only the entries starting with the query that
+        // navigation started from are shown.
+        let shown = self.history_ix.and_then(|ix| history.get(ix));
+        if shown.map(|entry| entry.as_str()) != Some(query) {
+            self.history_ix = None;
+            if !query.chars().all(|c| c.is_ascii_punctuation()) {
+                return None;
+            }
+            self.history_prefix = query.to_string();
+        }
+        let prefix = self.history_prefix.as_str();
+
+        match direction {
+            Direction::Up => {
+                let end = self.history_ix.unwrap_or(history.len());
+                let ix = history[..end]
+                    .iter()
+                    .rposition(|entry| entry.starts_with(prefix))?;
+                self.history_ix = Some(ix);
+                Some(history[ix].clone())
+            }
+            Direction::Down => {
+                let start = self.history_ix? + 1;
+                match history[start..]
+                    .iter()
+                    .position(|entry| entry.starts_with(prefix))
+                {
+                    Some(ix) => {
+                        self.history_ix = Some(start + ix);
+                        Some(history[start + ix].clone())
+                    }
+                    None => {
+                        self.history_ix = None;
+                        Some(self.history_prefix.clone())
+                    }
+                }
+            }
+        }
+    }
+
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
         self.command_palette
             .update(cx, |_, cx| cx.emit(DismissEvent))
@@ -364,6 +449,13 @@ impl PickerDelegate for CommandPaletteDelegate {
             return;
         }
         let action_ix = self.matches[self.selected_ix].candidate_id;
+        if action_ix + 1 == self.commands.len() {
+            if let Some(query) = self.intercepted_query.take() {
+                cx.default_global::<CommandPaletteInterceptHistory>()
+                    .0
+                    .push(query);
+            }
+        }
         let command = self.commands.swap_remove(action_ix);
 
         self.telemetry
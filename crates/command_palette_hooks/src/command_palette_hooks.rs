@@ -22,3 +22,17 @@ pub struct CommandInterceptResult {
     pub string: String,
     pub positions: Vec<usize>,
 }
+
+/// The queries of the commands that were run through the [`CommandPaletteInterceptor`],
+/// oldest first.
+#[derive(Default)]
+pub struct CommandPaletteInterceptHistory(pub Vec<String>);
+
+impl Global for CommandPaletteInterceptHistory {}
+
+impl CommandPaletteInterceptHistory {
+    /// Returns the most recently run query, if any.
+    pub fn last(&self) -> Option<&str> {
+        self.0.last().map(|query| query.as_str())
+    }
+}
@@ -738,6 +738,13 @@ impl Copilot {
         Ok(())
     }
 
+    /// Stops tracking a buffer, closing it in the server, until it's registered again.
+    pub fn remove_buffer(&mut self, buffer: &Model<Buffer>) {
+        let weak_buffer = buffer.downgrade();
+        self.buffers.remove(&weak_buffer);
+        self.unregister_buffer(&weak_buffer);
+    }
+
     fn unregister_buffer(&mut self, buffer: &WeakModel<Buffer>) {
         if let Ok(server) = self.server.as_running() {
             if let Some(buffer) = server.registered_buffers.remove(&buffer.entity_id()) {
@@ -1262,7 +1269,7 @@ mod tests {
             self.abs_path.clone()
         }
 
-        fn load(&self, _: &AppContext) -> Task<Result<String>> {
+        fn load(&self, _: language::Encoding, _: &AppContext) -> Task<Result<String>> {
             unimplemented!()
         }
 
@@ -1272,6 +1279,7 @@ mod tests {
             _: &clock::Global,
             _: language::RopeFingerprint,
             _: language::LineEnding,
+            _: language::Encoding,
             _: std::time::SystemTime,
             _: &mut AppContext,
         ) {
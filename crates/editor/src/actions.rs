@@ -126,6 +126,9 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptConflictBoth,
+        AcceptConflictOurs,
+        AcceptConflictTheirs,
         AcceptPartialCopilotSuggestion,
         AddSelectionAbove,
         AddSelectionBelow,
@@ -159,6 +162,7 @@ gpui::actions!(
         DeleteToPreviousSubwordStart,
         DeleteToPreviousWordStart,
         DisplayCursorNames,
+        EnableLargeFileFeatures,
         ExpandMacroRecursively,
         FindAllReferences,
         Fold,
@@ -166,8 +170,10 @@ gpui::actions!(
         Format,
         GoToDefinition,
         GoToDefinitionSplit,
+        GoToConflict,
         GoToDiagnostic,
         GoToHunk,
+        GoToPrevConflict,
         GoToPrevDiagnostic,
         GoToPrevHunk,
         GoToTypeDefinition,
@@ -203,6 +209,8 @@ gpui::actions!(
         NewlineAbove,
         NewlineBelow,
         NextScreen,
+        OpenBlameCommit,
+        OpenBlamePullRequest,
         OpenExcerpts,
         OpenExcerptsSplit,
         OpenPermalinkToLine,
@@ -246,6 +254,7 @@ gpui::actions!(
         Tab,
         TabPrev,
         ToggleInlayHints,
+        ToggleInlineDiagnostics,
         ToggleSoftWrap,
         ToggleLineNumbers,
         Transpose,
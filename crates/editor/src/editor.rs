@@ -18,12 +18,15 @@ pub mod display_map;
 mod editor_settings;
 mod element;
 mod inlay_hint_cache;
+mod large_file;
+mod linked_editing_ranges;
 
 mod debounced_delay;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
 mod hover_popover;
+mod indent_guides;
 pub mod items;
 mod mouse_context_menu;
 pub mod movement;
@@ -31,6 +34,8 @@ mod persistence;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod spelling;
+mod user_snippets;
 
 #[cfg(test)]
 mod editor_tests;
@@ -50,8 +55,8 @@ use debounced_delay::DebouncedDelay;
 pub use display_map::DisplayPoint;
 use display_map::*;
 pub use editor_settings::EditorSettings;
-use element::LineWithInvisibles;
 pub use element::{Cursor, EditorElement, HighlightedRange, HighlightedRangeLine};
+use element::{LineWithInvisibles, StickyHeaderSymbols};
 use futures::FutureExt;
 use fuzzy::{StringMatch, StringMatchCandidate};
 use git::diff_hunk_to_display;
@@ -66,16 +71,19 @@ use gpui::{
 };
 use highlight_matching_bracket::refresh_matching_bracket_highlights;
 use hover_popover::{hide_hover, HoverState};
+use indent_guides::CachedIndentGuides;
 use inlay_hint_cache::{InlayHintCache, InlaySplice, InvalidationStrategy};
 pub use items::MAX_TAB_TITLE_LEN;
 use itertools::Itertools;
 use language::{char_kind, CharKind};
 use language::{
-    language_settings::{self, all_language_settings, InlayHintSettings},
+    language_settings::{self, all_language_settings, InlayHintSettings, ShowWhitespaceSetting},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CodeAction,
     CodeLabel, Completion, CursorShape, Diagnostic, Documentation, IndentKind, IndentSize,
     Language, OffsetRangeExt, Point, Selection, SelectionGoal, TransactionId,
 };
+pub use large_file::LargeFileIndicator;
+use linked_editing_ranges::LinkedEditGroup;
 
 use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
 use lsp::{DiagnosticSeverity, LanguageServerId};
@@ -89,6 +97,7 @@ pub use multi_buffer::{
 use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use project::project_settings::{GitGutterSetting, ProjectSettings};
+use project::repository::{BlameEntry, GitRepository};
 use project::Item;
 use project::{FormatTrigger, Location, Project, ProjectPath, ProjectTransaction};
 use rand::prelude::*;
@@ -98,7 +107,9 @@ use selections_collection::{resolve_multiple, MutableSelectionsCollection, Selec
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
 use smallvec::SmallVec;
-use snippet::Snippet;
+use snippet::{Snippet, Transformation};
+use spell_check::SpellChecker;
+use spelling::{SpellingAction, SpellingState};
 use std::{
     any::TypeId,
     borrow::Cow,
@@ -120,6 +131,7 @@ use ui::{
     h_flex, prelude::*, ButtonSize, ButtonStyle, IconButton, IconName, IconSize, ListItem, Popover,
     Tooltip,
 };
+pub use user_snippets::UserSnippets;
 use util::{maybe, post_inc, RangeExt, ResultExt, TryFutureExt};
 use workspace::Toast;
 use workspace::{searchable::SearchEvent, ItemNavHistory, SplitDirection, ViewId, Workspace};
@@ -131,6 +143,7 @@ const MAX_LINE_LEN: usize = 1024;
 const MIN_NAVIGATION_HISTORY_ROW_DELTA: i64 = 10;
 const MAX_SELECTION_HISTORY_LEN: usize = 1024;
 const COPILOT_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(75);
+const OPEN_BLAME_LINK_FAILURE_TOAST_ID: usize = 0x6b1a3e;
 pub(crate) const CURSORS_VISIBLE_FOR: Duration = Duration::from_millis(2000);
 #[doc(hidden)]
 pub const CODE_ACTIONS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
@@ -234,7 +247,7 @@ pub fn init_settings(cx: &mut AppContext) {
 pub fn init(cx: &mut AppContext) {
     init_settings(cx);
 
-    workspace::register_project_item::<Editor>(cx);
+    workspace::register_fallback_project_item::<Editor>(cx);
     workspace::register_followable_item::<Editor>(cx);
     workspace::register_deserializable_item::<Editor>(cx);
     cx.observe_new_views(
@@ -385,6 +398,12 @@ pub struct Editor {
     show_breadcrumbs: bool,
     show_gutter: bool,
     show_wrap_guides: Option<bool>,
+    wrap_guides_override: Option<Vec<usize>>,
+    show_line_numbers: Option<bool>,
+    relative_line_numbers: Option<bool>,
+    show_whitespaces: Option<ShowWhitespaceSetting>,
+    tab_size_override: Option<NonZeroU32>,
+    hard_tabs_override: Option<bool>,
     placeholder_text: Option<Arc<str>>,
     highlighted_rows: Option<Range<u32>>,
     background_highlights: BTreeMap<TypeId, BackgroundHighlight>,
@@ -396,6 +415,11 @@ pub struct Editor {
     completion_documentation_pre_resolve_debounce: DebouncedDelay,
     available_code_actions: Option<(Model<Buffer>, Arc<[CodeAction]>)>,
     code_actions_task: Option<Task<()>>,
+    spelling: SpellingState,
+    sticky_header_symbols: Option<StickyHeaderSymbols>,
+    indent_guides: Option<CachedIndentGuides>,
+    linked_edit_ranges: Vec<LinkedEditGroup>,
+    linked_editing_task: Option<Task<()>>,
     document_highlights_task: Option<Task<()>>,
     pending_rename: Option<RenameState>,
     searchable: bool,
@@ -434,6 +458,8 @@ pub struct Editor {
 pub struct EditorSnapshot {
     pub mode: EditorMode,
     show_gutter: bool,
+    show_line_numbers: Option<bool>,
+    relative_line_numbers: Option<bool>,
     pub display_snapshot: DisplaySnapshot,
     pub placeholder_text: Option<Arc<str>>,
     is_focused: bool,
@@ -604,9 +630,22 @@ struct AutocloseRegion {
 #[derive(Debug)]
 struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
+    /// The values to choose from at each tabstop, shown in a menu when the
+    /// tabstop becomes active.
+    choices: Vec<Vec<String>>,
+    /// Text derived from the values of tabstops, updated as they are left.
+    transformations: Vec<SnippetTransformation>,
     active_index: usize,
 }
 
+struct SnippetTransformation {
+    tabstop: usize,
+    /// The first occurrence of the tabstop in the same insertion of the snippet.
+    source: Range<Anchor>,
+    target: Range<Anchor>,
+    transformation: Transformation,
+}
+
 #[doc(hidden)]
 pub struct RenameState {
     pub range: Range<Anchor>,
@@ -718,6 +757,12 @@ struct CompletionsMenu {
     initial_position: Anchor,
     buffer: Model<Buffer>,
     completions: Arc<RwLock<Box<[Completion]>>>,
+    /// The number of completions that came from the completion provider. The
+    /// rest are user snippets or snippet choices, which have no additional edits.
+    provider_completion_count: usize,
+    /// The text of the newest selection when the menu was shown, which is the
+    /// value of `TM_SELECTED_TEXT` in snippets.
+    selected_text: String,
     match_candidates: Arc<[StringMatchCandidate]>,
     matches: Arc<[StringMatch]>,
     selected_item: usize,
@@ -1098,9 +1143,24 @@ impl CompletionsMenu {
     }
 }
 
+#[derive(Clone)]
+enum CodeActionsItem {
+    CodeAction(CodeAction),
+    Spelling(SpellingAction),
+}
+
+impl CodeActionsItem {
+    fn label(&self) -> String {
+        match self {
+            CodeActionsItem::CodeAction(action) => action.lsp_action.title.clone(),
+            CodeActionsItem::Spelling(action) => action.label(),
+        }
+    }
+}
+
 #[derive(Clone)]
 struct CodeActionsMenu {
-    actions: Arc<[CodeAction]>,
+    actions: Arc<[CodeActionsItem]>,
     buffer: Model<Buffer>,
     selected_item: usize,
     scroll_handle: UniformListScrollHandle,
@@ -1194,7 +1254,7 @@ impl CodeActionsMenu {
                                 }),
                             )
                             // TASK: It would be good to make lsp_action.title a SharedString to avoid allocating here.
-                            .child(SharedString::from(action.lsp_action.title.clone()))
+                            .child(SharedString::from(action.label()))
                     })
                     .collect()
             },
@@ -1208,7 +1268,7 @@ impl CodeActionsMenu {
             self.actions
                 .iter()
                 .enumerate()
-                .max_by_key(|(_, action)| action.lsp_action.title.chars().count())
+                .max_by_key(|(_, action)| action.label().chars().count())
                 .map(|(ix, _)| ix),
         )
         .into_any_element();
@@ -1339,7 +1399,7 @@ struct ActiveDiagnosticGroup {
     is_valid: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClipboardSelection {
     pub len: usize,
     pub is_entire_line: bool,
@@ -1522,6 +1582,12 @@ impl Editor {
             show_breadcrumbs: EditorSettings::get_global(cx).toolbar.breadcrumbs,
             show_gutter: mode == EditorMode::Full,
             show_wrap_guides: None,
+            wrap_guides_override: None,
+            show_line_numbers: None,
+            relative_line_numbers: None,
+            show_whitespaces: None,
+            tab_size_override: None,
+            hard_tabs_override: None,
             placeholder_text: None,
             highlighted_rows: None,
             background_highlights: Default::default(),
@@ -1534,6 +1600,11 @@ impl Editor {
             next_inlay_id: 0,
             available_code_actions: Default::default(),
             code_actions_task: Default::default(),
+            spelling: Default::default(),
+            sticky_header_symbols: None,
+            indent_guides: None,
+            linked_edit_ranges: Default::default(),
+            linked_editing_task: Default::default(),
             document_highlights_task: Default::default(),
             pending_rename: Default::default(),
             searchable: true,
@@ -1568,6 +1639,7 @@ impl Editor {
                 cx.observe(&display_map, Self::on_display_map_changed),
                 cx.observe(&blink_manager, |_, _, cx| cx.notify()),
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
+                cx.observe_global::<SpellChecker>(Self::recheck_spelling),
                 observe_buffer_font_size_adjustment(cx, |_, cx| cx.notify()),
                 cx.observe_window_activation(|editor, cx| {
                     let active = cx.is_window_active();
@@ -1591,6 +1663,8 @@ impl Editor {
         if mode == EditorMode::Full {
             let should_auto_hide_scrollbars = cx.should_auto_hide_scrollbars();
             cx.set_global(ScrollbarAutoHide(should_auto_hide_scrollbars));
+            this.refresh_conflict_highlights(cx);
+            this.refresh_spelling(cx);
         }
 
         this.report_editor_event("open", None, cx);
@@ -1706,6 +1780,8 @@ impl Editor {
         EditorSnapshot {
             mode: self.mode,
             show_gutter: self.show_gutter,
+            show_line_numbers: self.show_line_numbers,
+            relative_line_numbers: self.relative_line_numbers,
             display_snapshot: self.display_map.update(cx, |map, cx| map.snapshot(cx)),
             scroll_anchor: self.scroll_manager.anchor(),
             ongoing_scroll: self.scroll_manager.ongoing_scroll(),
@@ -1953,6 +2029,7 @@ impl Editor {
             }
             self.refresh_code_actions(cx);
             self.refresh_document_highlights(cx);
+            self.refresh_linked_ranges(cx);
             refresh_matching_bracket_highlights(self, cx);
             self.discard_copilot_suggestion(cx);
         }
@@ -2566,6 +2643,17 @@ impl Editor {
             edits.push((selection.start..selection.end, text.clone()));
         }
 
+        let linked_edits = self.linked_edits(&edits, &snapshot);
+        if !linked_edits.is_empty() {
+            edits.extend(linked_edits.into_iter().map(|(range, text)| {
+                (
+                    range.start.to_point(&snapshot)..range.end.to_point(&snapshot),
+                    text,
+                )
+            }));
+            edits.sort_by_key(|(range, _)| range.start);
+        }
+
         drop(snapshot);
         self.transact(cx, |this, cx| {
             this.buffer.update(cx, |buffer, cx| {
@@ -2946,23 +3034,32 @@ impl Editor {
         self.transact(cx, |this, cx| {
             let old_selections = this.selections.all_adjusted(cx);
             let selection_anchors = this.buffer.update(cx, |buffer, cx| {
-                let anchors = {
+                let (anchors, edits) = {
                     let snapshot = buffer.read(cx);
-                    old_selections
+                    let anchors = old_selections
                         .iter()
                         .map(|s| {
                             let anchor = snapshot.anchor_after(s.head());
                             s.map(|_| anchor)
                         })
-                        .collect::<Vec<_>>()
-                };
-                buffer.edit(
-                    old_selections
+                        .collect::<Vec<_>>();
+                    let mut edits = old_selections
                         .iter()
-                        .map(|s| (s.start..s.end, text.clone())),
-                    autoindent_mode,
-                    cx,
-                );
+                        .map(|s| (s.start..s.end, text.clone()))
+                        .collect::<Vec<_>>();
+                    let linked_edits = this.linked_edits(&edits, &snapshot);
+                    if !linked_edits.is_empty() {
+                        edits.extend(linked_edits.into_iter().map(|(range, text)| {
+                            (
+                                range.start.to_point(&snapshot)..range.end.to_point(&snapshot),
+                                text,
+                            )
+                        }));
+                        edits.sort_by_key(|(range, _)| range.start);
+                    }
+                    (anchors, edits)
+                };
+                buffer.edit(edits, autoindent_mode, cx);
                 anchors
             });
 
@@ -3219,6 +3316,9 @@ impl Editor {
             .filter(|(_, excerpt_visible_range, _)| !excerpt_visible_range.is_empty())
             .filter_map(|(buffer_handle, excerpt_visible_range, excerpt_id)| {
                 let buffer = buffer_handle.read(cx);
+                if buffer.is_large_file() {
+                    return None;
+                }
                 let buffer_file = project::worktree::File::from_dyn(buffer.file())?;
                 let buffer_worktree = project.worktree_for_id(buffer_file.worktree_id(cx), cx)?;
                 let worktree_entry = buffer_worktree
@@ -3322,10 +3422,6 @@ impl Editor {
             return;
         }
 
-        let Some(provider) = self.completion_provider.as_ref() else {
-            return;
-        };
-
         let position = self.selections.newest_anchor().head();
         let (buffer, buffer_position) =
             if let Some(output) = self.buffer.read(cx).text_anchor_for_position(position, cx) {
@@ -3335,16 +3431,44 @@ impl Editor {
             };
 
         let query = Self::completion_query(&self.buffer.read(cx).read(cx), position);
-        let completions = provider.completions(&buffer, buffer_position, cx);
+        let selected_text = {
+            let selection = self.selections.newest_anchor();
+            self.buffer
+                .read(cx)
+                .read(cx)
+                .text_for_range(selection.start..selection.end)
+                .collect::<String>()
+        };
+        // User snippets are offered even when there is no completion provider,
+        // or when it fails to provide completions.
+        let completions = self
+            .completion_provider
+            .as_ref()
+            .map(|provider| provider.completions(&buffer, buffer_position, cx));
+        let snippet_completions = match query.as_deref() {
+            Some(query) if self.mode == EditorMode::Full => {
+                user_snippets::snippet_completions(buffer.read(cx), buffer_position, query, cx)
+            }
+            _ => Vec::new(),
+        };
 
         let id = post_inc(&mut self.next_completion_id);
         let task = cx.spawn(|this, mut cx| {
             async move {
-                let completions = completions.await.log_err();
-                let menu = if let Some(completions) = completions {
+                let mut completions = match completions {
+                    Some(completions) => completions.await.log_err().unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                let provider_completion_count = completions.len();
+                completions.extend(snippet_completions);
+                let menu = if completions.is_empty() {
+                    None
+                } else {
                     let mut menu = CompletionsMenu {
                         id,
                         initial_position: position,
+                        provider_completion_count,
+                        selected_text,
                         match_candidates: completions
                             .iter()
                             .enumerate()
@@ -3393,8 +3517,6 @@ impl Editor {
                         .ok();
                         Some(menu)
                     }
-                } else {
-                    None
                 };
 
                 this.update(&mut cx, |this, cx| {
@@ -3462,7 +3584,19 @@ impl Editor {
         let snippet;
         let text;
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            let buffer = buffer_handle.read(cx);
+            let variables = user_snippets::snippet_variables(
+                buffer,
+                completion.old_range.start.to_offset(buffer),
+                &completions_menu.selected_text,
+                cx,
+            );
+            snippet = Some(
+                Snippet::parse_with_variables(&completion.new_text, &|name| {
+                    variables.get(name).cloned()
+                })
+                .log_err()?,
+            );
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -3539,6 +3673,10 @@ impl Editor {
                     tabstop.start -= common_prefix_len as isize;
                     tabstop.end -= common_prefix_len as isize;
                 }
+                for transformation in &mut snippet.transformations {
+                    transformation.range.start -= common_prefix_len as isize;
+                    transformation.range.end -= common_prefix_len as isize;
+                }
 
                 this.insert_snippet(&ranges, snippet, cx).log_err();
             } else {
@@ -3554,6 +3692,9 @@ impl Editor {
             this.refresh_copilot_suggestions(true, cx);
         });
 
+        if mat.candidate_id >= completions_menu.provider_completion_count {
+            return Some(Task::ready(Ok(())));
+        }
         let provider = self.completion_provider.as_ref()?;
         let apply_edits = provider.apply_additional_edits_for_completion(
             buffer_handle,
@@ -3586,13 +3727,34 @@ impl Editor {
 
             this.update(&mut cx, |this, cx| {
                 if this.focus_handle.is_focused(cx) {
-                    if let Some((buffer, actions)) = this.available_code_actions.clone() {
+                    let mut actions = this
+                        .spelling_actions(cx)
+                        .into_iter()
+                        .map(CodeActionsItem::Spelling)
+                        .collect::<Vec<_>>();
+                    let mut buffer = None;
+                    if let Some((code_actions_buffer, code_actions)) =
+                        this.available_code_actions.clone()
+                    {
+                        buffer = Some(code_actions_buffer);
+                        actions.extend(
+                            code_actions
+                                .iter()
+                                .cloned()
+                                .map(CodeActionsItem::CodeAction),
+                        );
+                    }
+                    let buffer = buffer.or_else(|| {
+                        let cursor = this.selections.newest_anchor().head();
+                        Some(this.buffer.read(cx).text_anchor_for_position(cursor, cx)?.0)
+                    });
+                    if let Some(buffer) = buffer.filter(|_| !actions.is_empty()) {
                         this.completion_tasks.clear();
                         this.discard_copilot_suggestion(cx);
                         *this.context_menu.write() =
                             Some(ContextMenu::CodeActions(CodeActionsMenu {
                                 buffer,
-                                actions,
+                                actions: actions.into(),
                                 selected_item: Default::default(),
                                 scroll_handle: UniformListScrollHandle::default(),
                                 deployed_from_indicator,
@@ -3618,7 +3780,14 @@ impl Editor {
             return None;
         };
         let action_ix = action.item_ix.unwrap_or(actions_menu.selected_item);
-        let action = actions_menu.actions.get(action_ix)?.clone();
+        let action = match actions_menu.actions.get(action_ix)?.clone() {
+            CodeActionsItem::CodeAction(action) => action,
+            CodeActionsItem::Spelling(action) => {
+                let fs = self.project.as_ref()?.read(cx).fs().clone();
+                self.apply_spelling_action(action, fs, cx);
+                return Some(Task::ready(Ok(())));
+            }
+        };
         let title = action.lsp_action.title.clone();
         let buffer = actions_menu.buffer;
         let workspace = self.workspace()?;
@@ -4102,10 +4271,15 @@ impl Editor {
         snapshot: &MultiBufferSnapshot,
         cx: &mut ViewContext<Self>,
     ) -> bool {
+        let is_large_file = location
+            .buffer_id
+            .and_then(|buffer_id| self.buffer.read(cx).buffer(buffer_id))
+            .map_or(false, |buffer| buffer.read(cx).is_large_file());
         let file = snapshot.file_at(location);
         let language = snapshot.language_at(location);
         let settings = all_language_settings(file, cx);
         self.show_copilot_suggestions
+            && !is_large_file
             && settings.copilot_enabled(language, file.map(|f| f.path().as_ref()))
     }
 
@@ -4282,7 +4456,7 @@ impl Editor {
         snippet: Snippet,
         cx: &mut ViewContext<Self>,
     ) -> Result<()> {
-        let tabstops = self.buffer.update(cx, |buffer, cx| {
+        let (tabstops, transformations) = self.buffer.update(cx, |buffer, cx| {
             let snippet_text: Arc<str> = snippet.text.clone().into();
             buffer.edit(
                 insertion_ranges
@@ -4295,7 +4469,7 @@ impl Editor {
 
             let snapshot = &*buffer.read(cx);
             let snippet = &snippet;
-            snippet
+            let tabstops = snippet
                 .tabstops
                 .iter()
                 .map(|tabstop| {
@@ -4320,7 +4494,36 @@ impl Editor {
                     tabstop_ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start, snapshot));
                     tabstop_ranges
                 })
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>();
+
+            let transformations = snippet
+                .transformations
+                .iter()
+                .flat_map(|transformation| {
+                    let source = snippet
+                        .tabstops
+                        .get(transformation.tabstop)
+                        .and_then(|tabstop| tabstop.first());
+                    let mut delta = 0_isize;
+                    insertion_ranges.iter().filter_map(move |insertion_range| {
+                        let insertion_start = insertion_range.start as isize + delta;
+                        delta += snippet.text.len() as isize - insertion_range.len() as isize;
+
+                        let anchor_range = |range: &Range<isize>| {
+                            snapshot.anchor_before((insertion_start + range.start) as usize)
+                                ..snapshot.anchor_after((insertion_start + range.end) as usize)
+                        };
+                        Some(SnippetTransformation {
+                            tabstop: transformation.tabstop,
+                            source: anchor_range(source?),
+                            target: anchor_range(&transformation.range),
+                            transformation: transformation.clone(),
+                        })
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            (tabstops, transformations)
         });
 
         if let Some(tabstop) = tabstops.first() {
@@ -4330,7 +4533,10 @@ impl Editor {
             self.snippet_stack.push(SnippetState {
                 active_index: 0,
                 ranges: tabstops,
+                choices: snippet.choices,
+                transformations,
             });
+            self.show_snippet_choices(cx);
 
             // Check whether the just-entered snippet ends with an auto-closable bracket.
             if self.autoclose_regions.is_empty() {
@@ -4381,6 +4587,7 @@ impl Editor {
 
     pub fn move_to_snippet_tabstop(&mut self, bias: Bias, cx: &mut ViewContext<Self>) -> bool {
         if let Some(mut snippet) = self.snippet_stack.pop() {
+            self.apply_snippet_transformations(&snippet, cx);
             match bias {
                 Bias::Left => {
                     if snippet.active_index > 0 {
@@ -4406,6 +4613,7 @@ impl Editor {
                 // If snippet state is not at the last tabstop, push it back on the stack
                 if snippet.active_index + 1 < snippet.ranges.len() {
                     self.snippet_stack.push(snippet);
+                    self.show_snippet_choices(cx);
                 }
                 return true;
             }
@@ -4414,6 +4622,110 @@ impl Editor {
         false
     }
 
+    /// Updates the text derived from the value of the active snippet tabstop,
+    /// as the tabstop is being left.
+    fn apply_snippet_transformations(
+        &mut self,
+        snippet: &SnippetState,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let edits = snippet
+            .transformations
+            .iter()
+            .filter(|transformation| transformation.tabstop == snippet.active_index)
+            .filter_map(|transformation| {
+                let value = snapshot
+                    .text_for_range(transformation.source.clone())
+                    .collect::<String>();
+                let new_text = transformation.transformation.apply(&value);
+                let old_text = snapshot
+                    .text_for_range(transformation.target.clone())
+                    .collect::<String>();
+                (new_text != old_text).then(|| (transformation.target.clone(), new_text))
+            })
+            .collect::<Vec<_>>();
+        if !edits.is_empty() {
+            self.buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        }
+    }
+
+    /// Shows a menu with the values to choose from at the active snippet
+    /// tabstop, if it has any.
+    fn show_snippet_choices(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(snippet) = self.snippet_stack.last() else {
+            return;
+        };
+        let Some(choices) = snippet
+            .choices
+            .get(snippet.active_index)
+            .filter(|choices| !choices.is_empty())
+        else {
+            return;
+        };
+        let Some(range) = snippet.ranges[snippet.active_index].first().cloned() else {
+            return;
+        };
+        let Some(buffer) = range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| self.buffer.read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+
+        let completions = choices
+            .iter()
+            .enumerate()
+            .map(|(ix, choice)| Completion {
+                old_range: range.start.text_anchor..range.end.text_anchor,
+                new_text: choice.clone(),
+                label: CodeLabel::plain(choice.clone(), None),
+                documentation: Some(Documentation::Undocumented),
+                server_id: LanguageServerId(0),
+                lsp_completion: lsp::CompletionItem {
+                    label: choice.clone(),
+                    sort_text: Some(format!("{ix:08}")),
+                    ..Default::default()
+                },
+            })
+            .collect::<Vec<_>>();
+        let match_candidates = completions
+            .iter()
+            .enumerate()
+            .map(|(id, completion)| StringMatchCandidate::new(id, completion.label.text.clone()))
+            .collect::<Arc<[_]>>();
+        let matches = match_candidates
+            .iter()
+            .map(|candidate| StringMatch {
+                candidate_id: candidate.id,
+                score: Default::default(),
+                positions: Default::default(),
+                string: candidate.string.clone(),
+            })
+            .collect();
+
+        let menu = CompletionsMenu {
+            id: post_inc(&mut self.next_completion_id),
+            initial_position: self.selections.newest_anchor().head(),
+            buffer,
+            completions: Arc::new(RwLock::new(completions.into())),
+            provider_completion_count: 0,
+            selected_text: String::new(),
+            match_candidates,
+            matches,
+            selected_item: 0,
+            scroll_handle: UniformListScrollHandle::new(),
+            selected_completion_documentation_resolve_debounce: Arc::new(Mutex::new(
+                DebouncedDelay::new(),
+            )),
+        };
+        *self.context_menu.write() = Some(ContextMenu::Completions(menu));
+        self.discard_copilot_suggestion(cx);
+        cx.notify();
+    }
+
     pub fn clear(&mut self, cx: &mut ViewContext<Self>) {
         self.transact(cx, |this, cx| {
             this.select_all(&SelectAll, cx);
@@ -4440,9 +4752,9 @@ impl Editor {
                             let indent_size =
                                 buffer.indent_size_for_line(line_buffer_range.start.row);
                             let indent_len = match indent_size.kind {
-                                IndentKind::Space => {
-                                    buffer.settings_at(line_buffer_range.start, cx).tab_size
-                                }
+                                IndentKind::Space => this.tab_size_override.unwrap_or(
+                                    buffer.settings_at(line_buffer_range.start, cx).tab_size,
+                                ),
                                 IndentKind::Tab => NonZeroU32::new(1).unwrap(),
                             };
                             if old_head.column <= indent_size.len && old_head.column > 0 {
@@ -4517,7 +4829,7 @@ impl Editor {
             // If the selection is non-empty, then increase the indentation of the selected lines.
             if !selection.is_empty() {
                 row_delta =
-                    Self::indent_selection(buffer, &snapshot, selection, &mut edits, row_delta, cx);
+                    self.indent_selection(buffer, &snapshot, selection, &mut edits, row_delta, cx);
                 continue;
             }
 
@@ -4555,11 +4867,11 @@ impl Editor {
             }
 
             // Otherwise, insert a hard or soft tab.
-            let settings = buffer.settings_at(cursor, cx);
-            let tab_size = if settings.hard_tabs {
+            let (tab_size, hard_tabs) = self.indent_settings_at(buffer, cursor, cx);
+            let tab_size = if hard_tabs {
                 IndentSize::tab()
             } else {
-                let tab_size = settings.tab_size.get();
+                let tab_size = tab_size.get();
                 let char_column = snapshot
                     .text_for_range(Point::new(cursor.row, 0)..cursor)
                     .flat_map(str::chars)
@@ -4598,7 +4910,7 @@ impl Editor {
             prev_edited_row = selection.end.row;
 
             row_delta =
-                Self::indent_selection(buffer, &snapshot, selection, &mut edits, row_delta, cx);
+                self.indent_selection(buffer, &snapshot, selection, &mut edits, row_delta, cx);
         }
 
         self.transact(cx, |this, cx| {
@@ -4608,6 +4920,7 @@ impl Editor {
     }
 
     fn indent_selection(
+        &self,
         buffer: &MultiBuffer,
         snapshot: &MultiBufferSnapshot,
         selection: &mut Selection<Point>,
@@ -4615,9 +4928,9 @@ impl Editor {
         delta_for_start_row: u32,
         cx: &AppContext,
     ) -> u32 {
-        let settings = buffer.settings_at(selection.start, cx);
-        let tab_size = settings.tab_size.get();
-        let indent_kind = if settings.hard_tabs {
+        let (tab_size, hard_tabs) = self.indent_settings_at(buffer, selection.start, cx);
+        let tab_size = tab_size.get();
+        let indent_kind = if hard_tabs {
             IndentKind::Tab
         } else {
             IndentKind::Space
@@ -4689,8 +5002,8 @@ impl Editor {
             let buffer = self.buffer.read(cx);
             let snapshot = buffer.snapshot(cx);
             for selection in &selections {
-                let settings = buffer.settings_at(selection.start, cx);
-                let tab_size = settings.tab_size.get();
+                let (tab_size, _) = self.indent_settings_at(buffer, selection.start, cx);
+                let tab_size = tab_size.get();
                 let mut rows = selection.spanned_rows(false, &display_map);
 
                 // Avoid re-outdenting a row that has already been outdented by a
@@ -8730,17 +9043,33 @@ impl Editor {
             if let SoftWrap::Column(soft_wrap) = self.soft_wrap_mode(cx) {
                 wrap_guides.push((soft_wrap as usize, true));
             }
-            wrap_guides.extend(settings.wrap_guides.iter().map(|guide| (*guide, false)))
+            if self.wrap_guides_override.is_none() {
+                wrap_guides.extend(settings.wrap_guides.iter().map(|guide| (*guide, false)))
+            }
+        }
+        if let Some(guides) = &self.wrap_guides_override {
+            wrap_guides.extend(guides.iter().map(|guide| (*guide, false)))
         }
 
         wrap_guides
     }
 
+    /// Replaces the wrap guides from the settings with the given columns.
+    pub fn set_wrap_guides(&mut self, wrap_guides: Vec<usize>, cx: &mut ViewContext<Self>) {
+        self.wrap_guides_override = Some(wrap_guides);
+        cx.notify();
+    }
+
     pub fn soft_wrap_mode(&self, cx: &AppContext) -> SoftWrap {
         let settings = self.buffer.read(cx).settings_at(0, cx);
-        let mode = self
-            .soft_wrap_mode_override
-            .unwrap_or_else(|| settings.soft_wrap);
+        let mode = self.soft_wrap_mode_override.unwrap_or_else(|| {
+            // Wrapping every line of a large file is too slow, see `WrapMap`.
+            if self.is_large_file(cx) {
+                language_settings::SoftWrap::None
+            } else {
+                settings.soft_wrap
+            }
+        });
         match mode {
             language_settings::SoftWrap::None => SoftWrap::None,
             language_settings::SoftWrap::EditorWidth => SoftWrap::EditorWidth,
@@ -8802,6 +9131,16 @@ impl Editor {
         EditorSettings::override_global(editor_settings, cx);
     }
 
+    pub fn toggle_inline_diagnostics(
+        &mut self,
+        _: &ToggleInlineDiagnostics,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut editor_settings = EditorSettings::get_global(cx).clone();
+        editor_settings.inline_diagnostics.enabled = !editor_settings.inline_diagnostics.enabled;
+        EditorSettings::override_global(editor_settings, cx);
+    }
+
     pub fn set_show_gutter(&mut self, show_gutter: bool, cx: &mut ViewContext<Self>) {
         self.show_gutter = show_gutter;
         cx.notify();
@@ -8812,6 +9151,76 @@ impl Editor {
         cx.notify();
     }
 
+    pub fn show_line_numbers(&self, cx: &AppContext) -> bool {
+        self.show_line_numbers
+            .unwrap_or_else(|| EditorSettings::get_global(cx).gutter.line_numbers)
+    }
+
+    /// Shows or hides the line numbers in this editor, regardless of the settings.
+    pub fn set_show_line_numbers(&mut self, show_line_numbers: bool, cx: &mut ViewContext<Self>) {
+        self.show_line_numbers = Some(show_line_numbers);
+        cx.notify();
+    }
+
+    pub fn relative_line_numbers(&self, cx: &AppContext) -> bool {
+        self.relative_line_numbers
+            .unwrap_or_else(|| EditorSettings::get_global(cx).relative_line_numbers)
+    }
+
+    pub fn set_relative_line_numbers(&mut self, is_relative: bool, cx: &mut ViewContext<Self>) {
+        self.relative_line_numbers = Some(is_relative);
+        cx.notify();
+    }
+
+    pub fn show_whitespaces(&self, cx: &AppContext) -> ShowWhitespaceSetting {
+        self.show_whitespaces
+            .unwrap_or_else(|| self.buffer.read(cx).settings_at(0, cx).show_whitespaces)
+    }
+
+    /// Overrides the `show_whitespaces` setting for this editor, or goes back to
+    /// the setting when `None` is given.
+    pub fn set_show_whitespaces(
+        &mut self,
+        show_whitespaces: Option<ShowWhitespaceSetting>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.show_whitespaces = show_whitespaces;
+        cx.notify();
+    }
+
+    /// The width of a tab and whether to indent with tabs, at the start of the buffer.
+    pub fn indent_settings(&self, cx: &AppContext) -> (NonZeroU32, bool) {
+        self.indent_settings_at(self.buffer.read(cx), 0, cx)
+    }
+
+    fn indent_settings_at<T: ToOffset>(
+        &self,
+        buffer: &MultiBuffer,
+        position: T,
+        cx: &AppContext,
+    ) -> (NonZeroU32, bool) {
+        let settings = buffer.settings_at(position, cx);
+        (
+            self.tab_size_override.unwrap_or(settings.tab_size),
+            self.hard_tabs_override.unwrap_or(settings.hard_tabs),
+        )
+    }
+
+    /// Overrides the tab size from the language settings, for displaying tabs and
+    /// for indenting in this editor.
+    pub fn set_tab_size(&mut self, tab_size: NonZeroU32, cx: &mut ViewContext<Self>) {
+        self.tab_size_override = Some(tab_size);
+        self.display_map
+            .update(cx, |map, _| map.set_tab_size_override(Some(tab_size)));
+        cx.notify();
+    }
+
+    /// Overrides whether this editor indents with tabs or spaces.
+    pub fn set_hard_tabs(&mut self, hard_tabs: bool, cx: &mut ViewContext<Self>) {
+        self.hard_tabs_override = Some(hard_tabs);
+        cx.notify();
+    }
+
     pub fn reveal_in_finder(&mut self, _: &RevealInFinder, cx: &mut ViewContext<Self>) {
         if let Some(buffer) = self.buffer().read(cx).as_singleton() {
             if let Some(file) = buffer.read(cx).file().and_then(|f| f.as_local()) {
@@ -8840,10 +9249,12 @@ impl Editor {
         }
     }
 
-    fn get_permalink_to_line(&mut self, cx: &mut ViewContext<Self>) -> Result<url::Url> {
-        use git::permalink::{build_permalink, BuildPermalinkParams};
-
-        let (path, repo) = maybe!({
+    /// Returns the path of the editor's buffer within its git repository, along with that repository.
+    fn git_repository_for_buffer(
+        &self,
+        cx: &AppContext,
+    ) -> Result<(String, Arc<Mutex<dyn GitRepository>>)> {
+        maybe!({
             let project_handle = self.project.as_ref()?.clone();
             let project = project_handle.read(cx);
             let buffer = self.buffer().read(cx).as_singleton()?;
@@ -8857,7 +9268,13 @@ impl Editor {
             let repo = project.get_repo(&buffer.read(cx).project_path(cx)?, cx)?;
             Some((path, repo))
         })
-        .ok_or_else(|| anyhow!("unable to open git repository"))?;
+        .ok_or_else(|| anyhow!("unable to open git repository"))
+    }
+
+    fn get_permalink_to_line(&mut self, cx: &mut ViewContext<Self>) -> Result<url::Url> {
+        use git::permalink::{BuildPermalinkParams, GitHostingProviderRegistry};
+
+        let (path, repo) = self.git_repository_for_buffer(cx)?;
 
         const REMOTE_NAME: &str = "origin";
         let origin_url = repo
@@ -8871,12 +9288,15 @@ impl Editor {
         let selections = self.selections.all::<Point>(cx);
         let selection = selections.iter().peekable().next();
 
-        build_permalink(BuildPermalinkParams {
-            remote_url: &origin_url,
-            sha: &sha,
-            path: &path,
-            selection: selection.map(|selection| selection.range()),
-        })
+        GitHostingProviderRegistry::default_global(cx).build_permalink(
+            BuildPermalinkParams {
+                remote_url: &origin_url,
+                sha: &sha,
+                path: &path,
+                selection: selection.map(|selection| selection.range()),
+            },
+            &ProjectSettings::get_global(cx).git.hosting_providers,
+        )
     }
 
     pub fn copy_permalink_to_line(&mut self, _: &CopyPermalinkToLine, cx: &mut ViewContext<Self>) {
@@ -8900,6 +9320,73 @@ impl Editor {
         }
     }
 
+    /// Returns the URL of the `origin` remote and the commit that last changed the
+    /// line of the newest cursor.
+    fn blame_for_line(&self, cx: &mut ViewContext<Self>) -> Result<(String, BlameEntry)> {
+        let (path, repo) = self.git_repository_for_buffer(cx)?;
+
+        const REMOTE_NAME: &str = "origin";
+        let origin_url = repo
+            .lock()
+            .remote_url(REMOTE_NAME)
+            .ok_or_else(|| anyhow!("remote \"{REMOTE_NAME}\" not found"))?;
+        let row = self.selections.newest::<Point>(cx).head().row;
+        let blame = repo.lock().blame_line(&Path::new(&path).into(), row)?;
+        Ok((origin_url, blame))
+    }
+
+    pub fn open_blame_commit(&mut self, _: &OpenBlameCommit, cx: &mut ViewContext<Self>) {
+        use git::permalink::GitHostingProviderRegistry;
+
+        let url = self.blame_for_line(cx).and_then(|(origin_url, blame)| {
+            GitHostingProviderRegistry::default_global(cx).build_commit_permalink(
+                &origin_url,
+                &blame.sha,
+                &ProjectSettings::get_global(cx).git.hosting_providers,
+            )
+        });
+        self.open_blame_link(url, "commit", cx);
+    }
+
+    pub fn open_blame_pull_request(
+        &mut self,
+        _: &OpenBlamePullRequest,
+        cx: &mut ViewContext<Self>,
+    ) {
+        use git::permalink::GitHostingProviderRegistry;
+
+        let url = self.blame_for_line(cx).and_then(|(origin_url, blame)| {
+            GitHostingProviderRegistry::default_global(cx)
+                .build_pull_request_url(
+                    &origin_url,
+                    &blame.message,
+                    &ProjectSettings::get_global(cx).git.hosting_providers,
+                )
+                .ok_or_else(|| anyhow!("no pull request found for commit {}", blame.sha))
+        });
+        self.open_blame_link(url, "pull request", cx);
+    }
+
+    fn open_blame_link(&mut self, url: Result<url::Url>, kind: &str, cx: &mut ViewContext<Self>) {
+        match url {
+            Ok(url) => {
+                cx.open_url(url.as_ref());
+            }
+            Err(err) => {
+                let message = format!("Failed to open {kind}: {err}");
+
+                Err::<(), anyhow::Error>(err).log_err();
+
+                if let Some(workspace) = self.workspace() {
+                    workspace.update(cx, |workspace, cx| {
+                        workspace
+                            .show_toast(Toast::new(OPEN_BLAME_LINK_FAILURE_TOAST_ID, message), cx)
+                    })
+                }
+            }
+        }
+    }
+
     pub fn open_permalink_to_line(&mut self, _: &OpenPermalinkToLine, cx: &mut ViewContext<Self>) {
         let permalink = self.get_permalink_to_line(cx);
 
@@ -9216,6 +9703,8 @@ impl Editor {
                 if self.has_active_copilot_suggestion(cx) {
                     self.update_visible_copilot_suggestion(cx);
                 }
+                self.refresh_conflict_highlights(cx);
+                self.refresh_spelling(cx);
                 cx.emit(EditorEvent::BufferEdited);
                 cx.emit(SearchEvent::MatchesInvalidated);
 
@@ -9263,25 +9752,47 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.refresh_spelling(cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
-            multi_buffer::Event::Reparsed => cx.emit(EditorEvent::Reparsed),
+            multi_buffer::Event::Reparsed => {
+                self.refresh_spelling(cx);
+                cx.emit(EditorEvent::Reparsed);
+            }
             multi_buffer::Event::LanguageChanged => {
+                self.recheck_spelling(cx);
+                cx.emit(EditorEvent::Reparsed);
+                cx.notify();
+            }
+            multi_buffer::Event::LargeFileChanged => {
+                self.recheck_spelling(cx);
+                self.large_file_mode_changed(cx);
                 cx.emit(EditorEvent::Reparsed);
                 cx.notify();
             }
             multi_buffer::Event::DirtyChanged => cx.emit(EditorEvent::DirtyChanged),
-            multi_buffer::Event::Saved => cx.emit(EditorEvent::Saved),
+            multi_buffer::Event::Saved => {
+                self.mark_conflicts_resolved_if_needed(cx);
+                cx.emit(EditorEvent::Saved)
+            }
             multi_buffer::Event::FileHandleChanged | multi_buffer::Event::Reloaded => {
                 cx.emit(EditorEvent::TitleChanged)
             }
-            multi_buffer::Event::DiffBaseChanged => cx.emit(EditorEvent::DiffBaseChanged),
+            multi_buffer::Event::DiffBaseChanged => {
+                // The diff base is reloaded whenever the file's repository changes,
+                // which is also when its conflict status may have changed.
+                self.refresh_conflict_highlights(cx);
+                cx.emit(EditorEvent::DiffBaseChanged)
+            }
             multi_buffer::Event::Closed => cx.emit(EditorEvent::Closed),
             multi_buffer::Event::DiagnosticsUpdated => {
                 self.refresh_active_diagnostics(cx);
+                if EditorSettings::get_global(cx).inline_diagnostics.enabled {
+                    cx.notify();
+                }
             }
             _ => {}
         };
@@ -9304,6 +9815,8 @@ impl Editor {
         let editor_settings = EditorSettings::get_global(cx);
         self.scroll_manager.vertical_scroll_margin = editor_settings.vertical_scroll_margin;
         self.show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
+        self.indent_guides = None;
+        self.recheck_spelling(cx);
         cx.notify();
     }
 
@@ -9928,7 +10441,10 @@ impl EditorSnapshot {
             ProjectSettings::get_global(cx).git.git_gutter,
             Some(GitGutterSetting::TrackedFiles)
         );
-        let gutter_settings = EditorSettings::get_global(cx).gutter;
+        let mut gutter_settings = EditorSettings::get_global(cx).gutter;
+        if let Some(show_line_numbers) = self.show_line_numbers {
+            gutter_settings.line_numbers = show_line_numbers;
+        }
 
         let line_gutter_width = if gutter_settings.line_numbers {
             // Avoid flicker-like gutter resizes when the line number gains another digit and only resize the gutter on files with N*10^5 lines.
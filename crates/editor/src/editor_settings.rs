@@ -1,3 +1,4 @@
+use lsp::DiagnosticSeverity;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
@@ -13,6 +14,8 @@ pub struct EditorSettings {
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub gutter: Gutter,
+    pub sticky_scroll: StickyScroll,
+    pub inline_diagnostics: InlineDiagnostics,
     pub vertical_scroll_margin: f32,
     pub relative_line_numbers: bool,
     pub seed_search_query_from_cursor: SeedQuerySetting,
@@ -53,6 +56,50 @@ pub struct Gutter {
     pub folds: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    /// Whether to pin the headers of the scopes containing the first visible
+    /// line to the top of the editor.
+    pub enabled: bool,
+    /// The maximum number of nested scope headers to show.
+    pub max_depth: usize,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct InlineDiagnostics {
+    pub enabled: bool,
+    pub min_severity: InlineDiagnosticSeverity,
+}
+
+/// The least severe diagnostics to show at the end of lines.
+///
+/// Default: hint
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InlineDiagnosticSeverity {
+    /// Only show errors.
+    Error,
+    /// Show errors and warnings.
+    Warning,
+    /// Show errors, warnings and informational diagnostics.
+    Info,
+    /// Show all diagnostics.
+    Hint,
+}
+
+impl InlineDiagnosticSeverity {
+    /// Whether diagnostics of the given severity should be shown.
+    pub fn includes(self, severity: DiagnosticSeverity) -> bool {
+        let min_severity = match self {
+            Self::Error => DiagnosticSeverity::ERROR,
+            Self::Warning => DiagnosticSeverity::WARNING,
+            Self::Info => DiagnosticSeverity::INFORMATION,
+            Self::Hint => DiagnosticSeverity::HINT,
+        };
+        severity <= min_severity
+    }
+}
+
 /// When to show the scrollbar in the editor.
 ///
 /// Default: auto
@@ -107,6 +154,10 @@ pub struct EditorSettingsContent {
     pub scrollbar: Option<ScrollbarContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
+    /// Inline diagnostics related settings
+    pub inline_diagnostics: Option<InlineDiagnosticsContent>,
 
     /// The number of lines to keep above/below the cursor when auto-scrolling.
     ///
@@ -184,6 +235,33 @@ pub struct GutterContent {
     pub folds: Option<bool>,
 }
 
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the headers of the scopes containing the first visible
+    /// line to the top of the editor.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum number of nested scope headers to show.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
+/// Inline diagnostics related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct InlineDiagnosticsContent {
+    /// Whether to show the most severe diagnostic of each line at its end.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The least severe diagnostics to show.
+    ///
+    /// Default: hint
+    pub min_severity: Option<InlineDiagnosticSeverity>,
+}
+
 impl Settings for EditorSettings {
     const KEY: Option<&'static str> = None;
 
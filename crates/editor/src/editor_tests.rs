@@ -224,6 +224,122 @@ fn test_undo_redo_with_selection_restoration(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+fn test_linked_edits(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let buffer = MultiBuffer::build_simple("<div>a</div>", cx);
+    let editor = cx.add_window(|cx| build_editor(buffer, cx));
+
+    _ = editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer.read(cx).snapshot(cx);
+        editor.linked_edit_ranges = vec![LinkedEditGroup {
+            ranges: vec![
+                snapshot.anchor_before(1)..snapshot.anchor_after(4),
+                snapshot.anchor_before(8)..snapshot.anchor_after(11),
+            ],
+            word_pattern: None,
+        }];
+
+        // Typing a name character is mirrored in the same transaction.
+        editor.change_selections(None, cx, |s| s.select_ranges([4..4]));
+        editor.handle_input("x", cx);
+        assert_eq!(editor.text(cx), "<divx>a</divx>");
+        editor.undo(&Undo, cx);
+        assert_eq!(editor.text(cx), "<div>a</div>");
+
+        // Deletions are mirrored too.
+        editor.change_selections(None, cx, |s| s.select_ranges([4..4]));
+        editor.backspace(&Backspace, cx);
+        assert_eq!(editor.text(cx), "<di>a</di>");
+
+        // Characters that can't be part of a name aren't mirrored.
+        editor.handle_input(" ", cx);
+        assert_eq!(editor.text(cx), "<di >a</di>");
+    });
+}
+
+#[gpui::test]
+async fn test_linked_edits_from_language_server(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            linked_editing_range_provider: Some(lsp::LinkedEditingRangeServerCapabilities::Simple(
+                true,
+            )),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.set_state("<divˇ>a</div>");
+    let opening_range = cx.lsp_range("<«div»>a</div>");
+    let closing_range = cx.lsp_range("<div>a</«div»>");
+    let mut requests = cx.handle_request::<lsp::request::LinkedEditingRange, _, _>(
+        move |url, params, _| async move {
+            assert_eq!(params.text_document_position_params.text_document.uri, url);
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 4)
+            );
+            Ok(Some(lsp::LinkedEditingRanges {
+                ranges: vec![opening_range, closing_range],
+                word_pattern: Some("[a-z]+".into()),
+            }))
+        },
+    );
+    cx.background_executor
+        .advance_clock(crate::linked_editing_ranges::LINKED_EDITING_DEBOUNCE_TIMEOUT);
+    requests.next().await;
+    cx.run_until_parked();
+
+    cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+    cx.assert_editor_state("<divxˇ>a</divx>");
+    cx.update_editor(|editor, cx| editor.backspace(&Backspace, cx));
+    cx.assert_editor_state("<divˇ>a</div>");
+
+    // Text that doesn't match the server's word pattern isn't mirrored.
+    cx.update_editor(|editor, cx| editor.handle_input("1", cx));
+    cx.assert_editor_state("<div1ˇ>a</div>");
+}
+
+#[gpui::test]
+async fn test_linked_edits_from_syntax(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let language = Language::new(
+        LanguageConfig {
+            name: "HTML".into(),
+            ..Default::default()
+        },
+        Some(tree_sitter_html::language()),
+    )
+    .with_linked_editing_query(
+        r#"
+        (element
+          (start_tag (tag_name) @linked)
+          (end_tag (tag_name) @linked))
+        "#,
+    )
+    .unwrap();
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(Arc::new(language)), cx));
+
+    // Without a language server, the ranges come from the linked editing query.
+    cx.set_state("<div><spanˇ>a</span></div>");
+    cx.run_until_parked();
+    cx.executor()
+        .advance_clock(crate::linked_editing_ranges::LINKED_EDITING_DEBOUNCE_TIMEOUT);
+    cx.run_until_parked();
+
+    cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+    cx.assert_editor_state("<div><spanxˇ>a</spanx></div>");
+    cx.update_editor(|editor, cx| editor.handle_input(" ", cx));
+    cx.assert_editor_state("<div><spanx ˇ>a</spanx></div>");
+}
+
 #[gpui::test]
 fn test_ime_composition(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -5332,6 +5448,80 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_transformations(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges(
+        indoc! {"
+            ˇ
+            ˇ
+        "},
+        false,
+    );
+
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    _ = editor.update(cx, |editor, cx| {
+        let snippet =
+            Snippet::parse("let ${1:name} = ${2:1}; // ${1/(.*)/${1:/upcase}/}$0").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, cx)
+            .unwrap();
+
+        fn assert(editor: &mut Editor, cx: &mut ViewContext<Editor>, marked_text: &str) {
+            let (expected_text, selection_ranges) = marked_text_ranges(marked_text, false);
+            assert_eq!(editor.text(cx), expected_text);
+            assert_eq!(editor.selections.ranges::<usize>(cx), selection_ranges);
+        }
+
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let «name» = 1; // NAME
+                let «name» = 1; // NAME
+            "},
+        );
+
+        // Transformed text is only updated when leaving the tabstop.
+        editor.insert("max_len", cx);
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let max_lenˇ = 1; // NAME
+                let max_lenˇ = 1; // NAME
+            "},
+        );
+
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let max_len = «1»; // MAX_LEN
+                let max_len = «1»; // MAX_LEN
+            "},
+        );
+
+        // Going back and changing the tabstop again updates it again.
+        assert!(editor.move_to_prev_snippet_tabstop(cx));
+        editor.insert("min", cx);
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let min = 1; // MINˇ
+                let min = 1; // MINˇ
+            "},
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -6006,6 +6196,107 @@ async fn test_completion(cx: &mut gpui::TestAppContext) {
     apply_additional_edits.await.unwrap();
 }
 
+#[gpui::test]
+async fn test_user_snippet_completion(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            completion_provider: Some(lsp::CompletionOptions::default()),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+    cx.update(|cx| {
+        UserSnippets::set_snippet_file(
+            "/snippets/rust.json".into(),
+            Some(
+                r#"{
+                    "Log": {
+                        "prefix": "log",
+                        "body": "// ${TM_FILENAME_BASE/(.*)/${1:/upcase}/}: ${1|one,two|}$0"
+                    },
+                    "Debug": {
+                        "prefix": "dbg",
+                        "body": "dbg!(${TM_SELECTED_TEXT:value})$0"
+                    }
+                }"#,
+            ),
+            cx,
+        )
+        .unwrap();
+        UserSnippets::set_snippet_file(
+            "/snippets/python.json".into(),
+            Some(r#"{ "Python": { "prefix": "lost", "body": "unreachable" } }"#),
+            cx,
+        )
+        .unwrap();
+    });
+
+    cx.set_state("loˇ");
+    cx.update_editor(|editor, cx| editor.show_completions(&ShowCompletions, cx));
+    handle_completion_request(&mut cx, "lo|<>", vec![]).await;
+    cx.condition(|editor, _| editor.context_menu_visible())
+        .await;
+    cx.update_editor(|editor, cx| {
+        if let Some(ContextMenu::Completions(menu)) = editor.context_menu.read().as_ref() {
+            let labels = menu
+                .completions
+                .read()
+                .iter()
+                .map(|completion| completion.label.text.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(labels, ["log", "dbg"]);
+        } else {
+            panic!("expected completion menu to be open");
+        }
+        editor
+            .confirm_completion(&ConfirmCompletion::default(), cx)
+            .unwrap()
+            .detach();
+    });
+
+    // The first tabstop offers its choices in a menu.
+    cx.assert_editor_state("// FILE: «oneˇ»");
+    cx.update_editor(|editor, cx| {
+        assert!(editor.context_menu_visible());
+        editor.context_menu_next(&Default::default(), cx);
+        editor
+            .confirm_completion(&ConfirmCompletion::default(), cx)
+            .unwrap()
+            .detach();
+    });
+    cx.assert_editor_state("// FILE: twoˇ");
+
+    // The selection that the snippet was triggered with becomes its selected text.
+    cx.set_state("db«ˇfoo»");
+    cx.update_editor(|editor, cx| editor.show_completions(&ShowCompletions, cx));
+    handle_completion_request(&mut cx, "db|<>foo", vec![]).await;
+    cx.condition(|editor, _| editor.context_menu_visible())
+        .await;
+    cx.update_editor(|editor, cx| {
+        editor
+            .confirm_completion(&ConfirmCompletion::default(), cx)
+            .unwrap()
+            .detach();
+    });
+    cx.assert_editor_state("dbg!(foo)ˇ");
+
+    cx.set_state("dbˇ");
+    cx.update_editor(|editor, cx| editor.show_completions(&ShowCompletions, cx));
+    handle_completion_request(&mut cx, "db|<>", vec![]).await;
+    cx.condition(|editor, _| editor.context_menu_visible())
+        .await;
+    cx.update_editor(|editor, cx| {
+        editor
+            .confirm_completion(&ConfirmCompletion::default(), cx)
+            .unwrap()
+            .detach();
+    });
+    cx.assert_editor_state("dbg!(value)ˇ");
+}
+
 #[gpui::test]
 async fn test_toggle_comment(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -9297,6 +9588,70 @@ async fn test_multibuffer_reverts(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_open_blame_commit_and_pull_request(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".git": {},
+            "main.rs": "fn main() {\n    run();\n}\n",
+        }),
+    )
+    .await;
+    let dot_git = Path::new("/root/.git");
+    fs.set_remote_url_for_repo(dot_git, "origin", "git@github.com:zed-industries/zed.git");
+    let initial_commit = BlameEntry {
+        sha: "8e2c3f1".into(),
+        message: "Initial commit".into(),
+    };
+    fs.set_blame_for_repo(
+        dot_git,
+        Path::new("main.rs"),
+        vec![
+            initial_commit.clone(),
+            BlameEntry {
+                sha: "b41d07a".into(),
+                message: "Run the app on startup (#42)".into(),
+            },
+            initial_commit,
+        ],
+    );
+
+    let project = Project::test(fs, ["/root".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/root/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) =
+        cx.add_window_view(|cx| Editor::new(EditorMode::Full, buffer, Some(project.clone()), cx));
+
+    _ = editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(1, 4)..Point::new(1, 4)])
+        });
+        editor.open_blame_commit(&OpenBlameCommit, cx);
+    });
+    assert_eq!(
+        cx.opened_url().as_deref(),
+        Some("https://github.com/zed-industries/zed/commit/b41d07a")
+    );
+
+    _ = editor.update(cx, |editor, cx| {
+        editor.open_blame_pull_request(&OpenBlamePullRequest, cx);
+    });
+    assert_eq!(
+        cx.opened_url().as_deref(),
+        Some("https://github.com/zed-industries/zed/pull/42")
+    );
+}
+
 fn empty_range(row: usize, column: usize) -> Range<DisplayPoint> {
     let point = DisplayPoint::new(row as u32, column as u32);
     point..point
@@ -8,9 +8,10 @@ use crate::{
     hover_popover::{
         self, hover_at, HOVER_POPOVER_GAP, MIN_POPOVER_CHARACTER_WIDTH, MIN_POPOVER_LINE_HEIGHT,
     },
+    indent_guides::{indent_guides_in_range, CachedIndentGuides},
     items::BufferSearchHighlights,
     mouse_context_menu,
-    scroll::scroll_amount::ScrollAmount,
+    scroll::{scroll_amount::ScrollAmount, Autoscroll},
     CursorShape, DisplayPoint, DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode,
     EditorSettings, EditorSnapshot, EditorStyle, GutterDimensions, HalfPageDown, HalfPageUp,
     HoveredCursor, LineDown, LineUp, OpenExcerpts, PageDown, PageUp, Point, SelectPhase, Selection,
@@ -29,7 +30,7 @@ use gpui::{
     TextStyle, View, ViewContext, WindowContext,
 };
 use itertools::Itertools;
-use language::language_settings::ShowWhitespaceSetting;
+use language::{language_settings::ShowWhitespaceSetting, DiagnosticEntry, OutlineItem};
 use lsp::DiagnosticSeverity;
 use multi_buffer::Anchor;
 use project::{
@@ -48,7 +49,7 @@ use std::{
     sync::Arc,
 };
 use sum_tree::Bias;
-use theme::{ActiveTheme, PlayerColor};
+use theme::{ActiveTheme, PlayerColor, SyntaxTheme};
 use ui::prelude::*;
 use ui::{h_flex, ButtonLike, ButtonStyle, IconButton, Tooltip};
 use util::ResultExt;
@@ -258,6 +259,11 @@ impl EditorElement {
         register_action(view, cx, Editor::go_to_prev_diagnostic);
         register_action(view, cx, Editor::go_to_hunk);
         register_action(view, cx, Editor::go_to_prev_hunk);
+        register_action(view, cx, Editor::go_to_conflict);
+        register_action(view, cx, Editor::go_to_prev_conflict);
+        register_action(view, cx, Editor::accept_conflict_ours);
+        register_action(view, cx, Editor::accept_conflict_theirs);
+        register_action(view, cx, Editor::accept_conflict_both);
         register_action(view, cx, Editor::go_to_definition);
         register_action(view, cx, Editor::go_to_definition_split);
         register_action(view, cx, Editor::go_to_implementation);
@@ -275,8 +281,10 @@ impl EditorElement {
         register_action(view, cx, Editor::open_excerpts);
         register_action(view, cx, Editor::open_excerpts_in_split);
         register_action(view, cx, Editor::toggle_soft_wrap);
+        register_action(view, cx, Editor::enable_large_file_features);
         register_action(view, cx, Editor::toggle_line_numbers);
         register_action(view, cx, Editor::toggle_inlay_hints);
+        register_action(view, cx, Editor::toggle_inline_diagnostics);
         register_action(view, cx, hover_popover::hover);
         register_action(view, cx, Editor::reveal_in_finder);
         register_action(view, cx, Editor::copy_path);
@@ -284,6 +292,8 @@ impl EditorElement {
         register_action(view, cx, Editor::copy_highlight_json);
         register_action(view, cx, Editor::copy_permalink_to_line);
         register_action(view, cx, Editor::open_permalink_to_line);
+        register_action(view, cx, Editor::open_blame_commit);
+        register_action(view, cx, Editor::open_blame_pull_request);
         register_action(view, cx, |editor, action, cx| {
             if let Some(task) = editor.format(action, cx) {
                 task.detach_and_log_err(cx);
@@ -896,6 +906,33 @@ impl EditorElement {
         }
     }
 
+    fn paint_indent_guides(
+        &self,
+        content_origin: gpui::Point<Pixels>,
+        layout: &LayoutState,
+        cx: &mut ElementContext,
+    ) {
+        let position_map = &layout.position_map;
+        for guide in &layout.indent_guides {
+            let x = content_origin.x + guide.offset - position_map.scroll_position.x;
+            let y = content_origin.y + guide.display_rows.start as f32 * position_map.line_height
+                - position_map.scroll_position.y;
+            let height = guide.display_rows.len() as f32 * position_map.line_height;
+            let color = if guide.active {
+                cx.theme().colors().editor_indent_guide_active
+            } else {
+                cx.theme().colors().editor_indent_guide
+            };
+            cx.paint_quad(fill(
+                Bounds {
+                    origin: point(x, y),
+                    size: size(guide.width, height),
+                },
+                color,
+            ));
+        }
+    }
+
     fn paint_text(
         &mut self,
         text_bounds: Bounds<Pixels>,
@@ -907,13 +944,7 @@ impl EditorElement {
         let content_origin =
             text_bounds.origin + point(layout.gutter_dimensions.margin, Pixels::ZERO);
         let line_end_overshoot = 0.15 * layout.position_map.line_height;
-        let whitespace_setting = self
-            .editor
-            .read(cx)
-            .buffer
-            .read(cx)
-            .settings_at(0, cx)
-            .show_whitespaces;
+        let whitespace_setting = self.editor.read(cx).show_whitespaces(cx);
 
         cx.with_content_mask(
             Some(ContentMask {
@@ -944,6 +975,8 @@ impl EditorElement {
                     }
                 }
 
+                self.paint_indent_guides(content_origin, layout, cx);
+
                 let fold_corner_radius = 0.15 * layout.position_map.line_height;
                 cx.with_element_id(Some("folds"), |cx| {
                     let snapshot = &layout.position_map.snapshot;
@@ -1163,6 +1196,20 @@ impl EditorElement {
                     )
                 }
 
+                for diagnostic in &layout.inline_diagnostics {
+                    let y = diagnostic.display_row as f32 * layout.position_map.line_height
+                        - layout.position_map.scroll_position.y;
+                    diagnostic
+                        .line
+                        .paint(
+                            content_origin
+                                + point(diagnostic.x - layout.position_map.scroll_position.x, y),
+                            layout.position_map.line_height,
+                            cx,
+                        )
+                        .log_err();
+                }
+
                 cx.with_z_index(0, |cx| self.paint_redactions(text_bounds, &layout, cx));
 
                 cx.with_z_index(1, |cx| {
@@ -1174,6 +1221,99 @@ impl EditorElement {
         )
     }
 
+    fn paint_sticky_headers(
+        &mut self,
+        text_bounds: Bounds<Pixels>,
+        layout: &LayoutState,
+        cx: &mut ElementContext,
+    ) {
+        let line_height = layout.position_map.line_height;
+        let content_origin =
+            text_bounds.origin + point(layout.gutter_dimensions.margin, Pixels::ZERO);
+        let background = cx.theme().colors().editor_background;
+        let hover_background = cx.theme().colors().editor_active_line_background;
+        let headers_bottom = layout
+            .sticky_headers
+            .iter()
+            .map(|header| header.y + line_height)
+            .fold(Pixels::ZERO, Pixels::max);
+
+        cx.with_content_mask(
+            Some(ContentMask {
+                bounds: text_bounds,
+            }),
+            |cx| {
+                // Inner headers get pushed up under their parents as their scope ends.
+                let header_count = layout.sticky_headers.len();
+                for (ix, header) in layout.sticky_headers.iter().enumerate() {
+                    let origin = point(text_bounds.origin.x, text_bounds.origin.y + header.y);
+                    let position = header.position;
+                    cx.with_z_index((header_count - ix) as u16 * 2, |cx| {
+                        div()
+                            .id(("sticky_header", ix))
+                            .w(text_bounds.size.width)
+                            .h(line_height)
+                            .cursor_pointer()
+                            .bg(background)
+                            .hover(|style| style.bg(hover_background))
+                            .on_mouse_down(MouseButton::Left, |_, cx| cx.stop_propagation())
+                            .on_click(cx.listener_for(
+                                &self.editor,
+                                move |editor: &mut Editor, _, cx| {
+                                    editor.change_selections(
+                                        Some(Autoscroll::center()),
+                                        cx,
+                                        |selections| {
+                                            selections.select_anchor_ranges([position..position])
+                                        },
+                                    );
+                                    editor.focus(cx);
+                                    cx.stop_propagation();
+                                },
+                            ))
+                            .into_any()
+                            .draw(
+                                origin,
+                                size(
+                                    AvailableSpace::Definite(text_bounds.size.width),
+                                    AvailableSpace::Definite(line_height),
+                                ),
+                                cx,
+                            );
+
+                        cx.with_z_index(1, |cx| {
+                            header
+                                .line
+                                .paint(
+                                    point(
+                                        content_origin.x + header.offset
+                                            - layout.position_map.scroll_position.x,
+                                        origin.y,
+                                    ),
+                                    line_height,
+                                    cx,
+                                )
+                                .log_err();
+                        });
+                    });
+                }
+
+                cx.with_z_index((header_count * 2 + 1) as u16, |cx| {
+                    cx.paint_quad(fill(
+                        Bounds {
+                            origin: point(
+                                text_bounds.origin.x,
+                                text_bounds.origin.y + headers_bottom,
+                            ),
+                            size: size(text_bounds.size.width, px(1.)),
+                        },
+                        cx.theme().colors().border_variant,
+                    ));
+                });
+            },
+        );
+    }
+
     fn paint_redactions(
         &mut self,
         text_bounds: Bounds<Pixels>,
@@ -1734,6 +1874,292 @@ impl EditorElement {
         }
     }
 
+    fn layout_indent_guides(
+        &self,
+        editor: &mut Editor,
+        rows: Range<u32>,
+        cursor: DisplayPoint,
+        snapshot: &EditorSnapshot,
+        cx: &WindowContext,
+    ) -> Vec<IndentGuideLayout> {
+        let buffer_snapshot = &snapshot.buffer_snapshot;
+        let start_row = DisplayPoint::new(rows.start, 0).to_point(snapshot).row;
+        let end_row = DisplayPoint::new(rows.end, 0).to_point(snapshot).row + 1;
+        let cursor_row = Some(cursor.to_point(snapshot).row);
+        let tab_size_override = editor.tab_size_override;
+
+        let cached = editor.indent_guides.as_ref().filter(|cached| {
+            cached.edit_count == buffer_snapshot.edit_count()
+                && cached.rows == (start_row..end_row)
+                && cached.cursor_row == cursor_row
+                && cached.tab_size_override == tab_size_override
+        });
+        if cached.is_none() {
+            editor.indent_guides = Some(CachedIndentGuides {
+                edit_count: buffer_snapshot.edit_count(),
+                rows: start_row..end_row,
+                cursor_row,
+                tab_size_override,
+                guides: indent_guides_in_range(
+                    start_row..end_row,
+                    cursor_row,
+                    tab_size_override,
+                    buffer_snapshot,
+                    cx,
+                ),
+            });
+        }
+        let Some(cached) = editor.indent_guides.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut column_offsets = HashMap::default();
+        cached
+            .guides
+            .iter()
+            .map(|guide| {
+                let last_row = guide.rows.end - 1;
+                let display_rows = Point::new(guide.rows.start, 0)
+                    .to_display_point(snapshot)
+                    .row()
+                    ..Point::new(last_row, buffer_snapshot.line_len(last_row))
+                        .to_display_point(snapshot)
+                        .row()
+                        + 1;
+                let offset = *column_offsets
+                    .entry(guide.column)
+                    .or_insert_with(|| self.column_pixels(guide.column as usize, cx));
+                let width = if guide.active {
+                    guide.settings.active_line_width
+                } else {
+                    guide.settings.line_width
+                };
+                IndentGuideLayout {
+                    offset,
+                    display_rows,
+                    width: px(width.clamp(1, 10) as f32),
+                    active: guide.active,
+                }
+            })
+            .collect()
+    }
+
+    /// Lays out the most severe diagnostic of each visible line after the
+    /// end of the line, truncated to the visible width of the editor.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_inline_diagnostics(
+        &self,
+        rows: Range<u32>,
+        line_layouts: &[LineWithInvisibles],
+        snapshot: &EditorSnapshot,
+        visible_right: Pixels,
+        em_width: Pixels,
+        em_advance: Pixels,
+        cx: &WindowContext,
+    ) -> Vec<InlineDiagnosticLayout> {
+        let settings = EditorSettings::get_global(cx).inline_diagnostics;
+        if !settings.enabled || snapshot.mode != EditorMode::Full {
+            return Vec::new();
+        }
+
+        let start = DisplayPoint::new(rows.start, 0).to_point(snapshot);
+        let end = snapshot.clip_point(DisplayPoint::new(rows.end, 0), Bias::Left);
+        let mut diagnostics_by_row = BTreeMap::<u32, DiagnosticEntry<Point>>::new();
+        for entry in snapshot
+            .buffer_snapshot
+            .diagnostics_in_range::<_, Point>(start..end.to_point(snapshot), false)
+        {
+            if !entry.diagnostic.is_primary
+                || !settings.min_severity.includes(entry.diagnostic.severity)
+            {
+                continue;
+            }
+            let row = entry.range.start.row;
+            match diagnostics_by_row.get(&row) {
+                Some(existing) if existing.diagnostic.severity <= entry.diagnostic.severity => {}
+                _ => {
+                    diagnostics_by_row.insert(row, entry);
+                }
+            }
+        }
+
+        let font_size = self.style.text.font_size.to_pixels(cx.rem_size());
+        let padding = em_width * 4.;
+        let mut layouts = Vec::new();
+        for (row, entry) in diagnostics_by_row {
+            let line_end =
+                Point::new(row, snapshot.buffer_snapshot.line_len(row)).to_display_point(snapshot);
+            if !rows.contains(&line_end.row()) {
+                continue;
+            }
+            let Some(line_layout) = line_layouts.get((line_end.row() - rows.start) as usize) else {
+                continue;
+            };
+
+            let x = line_layout.line.width + padding;
+            let max_chars = ((visible_right - x - em_width) / em_advance).floor() as usize;
+            if max_chars < 8 {
+                continue;
+            }
+            let message = entry
+                .diagnostic
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim();
+            let message: SharedString = if message.chars().count() > max_chars {
+                let mut truncated = message.chars().take(max_chars - 1).collect::<String>();
+                truncated.push('…');
+                truncated.into()
+            } else {
+                message.to_string().into()
+            };
+
+            let color = match entry.diagnostic.severity {
+                DiagnosticSeverity::ERROR => cx.theme().status().error,
+                DiagnosticSeverity::WARNING => cx.theme().status().warning,
+                DiagnosticSeverity::INFORMATION => cx.theme().status().info,
+                _ => cx.theme().status().hint,
+            };
+            let len = message.len();
+            let Some(line) = cx
+                .text_system()
+                .shape_line(
+                    message,
+                    font_size,
+                    &[TextRun {
+                        len,
+                        font: self.style.text.font(),
+                        color,
+                        background_color: None,
+                        underline: None,
+                        strikethrough: None,
+                    }],
+                )
+                .log_err()
+            else {
+                continue;
+            };
+
+            layouts.push(InlineDiagnosticLayout {
+                display_row: line_end.row(),
+                x,
+                line,
+            });
+        }
+
+        layouts
+    }
+
+    fn layout_sticky_headers(
+        &self,
+        editor: &mut Editor,
+        snapshot: &EditorSnapshot,
+        scroll_position: gpui::Point<f32>,
+        line_height: Pixels,
+        cx: &WindowContext,
+    ) -> Vec<StickyHeaderLayout> {
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if !settings.enabled || snapshot.mode != EditorMode::Full {
+            editor.sticky_header_symbols = None;
+            return Vec::new();
+        }
+
+        let top_row = scroll_position.y as u32;
+        let top_point = DisplayPoint::new(top_row, 0).to_point(snapshot);
+        let buffer = &snapshot.buffer_snapshot;
+        let cached = editor.sticky_header_symbols.as_ref().filter(|symbols| {
+            symbols.edit_count == buffer.edit_count()
+                && symbols.parse_count == buffer.parse_count()
+                && symbols.top_point == top_point
+                && Arc::ptr_eq(&symbols.syntax, &self.style.syntax)
+        });
+        if cached.is_none() {
+            // Symbols are resolved within the excerpt containing the top row, so
+            // each excerpt of a multibuffer shows its own headers.
+            let items = buffer
+                .symbols_containing(top_point, Some(&self.style.syntax))
+                .map(|(_, items)| items)
+                .unwrap_or_default();
+            editor.sticky_header_symbols = Some(StickyHeaderSymbols {
+                edit_count: buffer.edit_count(),
+                parse_count: buffer.parse_count(),
+                top_point,
+                syntax: self.style.syntax.clone(),
+                items,
+            });
+        }
+        let Some(symbols) = editor.sticky_header_symbols.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut headers = Vec::new();
+        for item in &symbols.items {
+            if headers.len() >= settings.max_depth {
+                break;
+            }
+
+            // Headers that are visible in place don't need to be pinned.
+            let start = item.range.start.to_display_point(snapshot);
+            if start.row() >= top_row + headers.len() as u32 {
+                break;
+            }
+
+            // Keep the header above the last line of its scope, so that it
+            // scrolls out of view together with it.
+            let end_row = item.range.end.to_display_point(snapshot).row();
+            let y = (headers.len() as f32 * line_height)
+                .min((end_row as f32 - scroll_position.y) * line_height - line_height);
+
+            let mut chunks = Vec::new();
+            let mut offset = 0;
+            for (range, style) in &item.highlight_ranges {
+                if offset < range.start {
+                    chunks.push(HighlightedChunk {
+                        chunk: &item.text[offset..range.start],
+                        style: None,
+                        is_tab: false,
+                    });
+                }
+                chunks.push(HighlightedChunk {
+                    chunk: &item.text[range.clone()],
+                    style: Some(*style),
+                    is_tab: false,
+                });
+                offset = range.end;
+            }
+            if offset < item.text.len() {
+                chunks.push(HighlightedChunk {
+                    chunk: &item.text[offset..],
+                    style: None,
+                    is_tab: false,
+                });
+            }
+            let Some(line) = LineWithInvisibles::from_chunks(
+                chunks.into_iter(),
+                &self.style.text,
+                MAX_LINE_LEN,
+                1,
+                &[],
+                snapshot.mode,
+                cx,
+            )
+            .pop() else {
+                continue;
+            };
+
+            headers.push(StickyHeaderLayout {
+                position: item.range.start,
+                line: line.line,
+                offset: self.column_pixels(start.column() as usize, cx),
+                y,
+            });
+        }
+
+        headers
+    }
+
     fn column_pixels(&self, column: usize, cx: &WindowContext) -> Pixels {
         let style = &self.style;
         let font_size = style.text.font_size.to_pixels(cx.rem_size());
@@ -1847,14 +2273,18 @@ impl EditorElement {
         Vec<Option<(FoldStatus, BufferRow, bool)>>,
     ) {
         let font_size = self.style.text.font_size.to_pixels(cx.rem_size());
-        let include_line_numbers =
-            EditorSettings::get_global(cx).gutter.line_numbers && snapshot.mode == EditorMode::Full;
+        let include_line_numbers = snapshot
+            .show_line_numbers
+            .unwrap_or(EditorSettings::get_global(cx).gutter.line_numbers)
+            && snapshot.mode == EditorMode::Full;
         let include_fold_statuses =
             EditorSettings::get_global(cx).gutter.folds && snapshot.mode == EditorMode::Full;
         let mut shaped_line_numbers = Vec::with_capacity(rows.len());
         let mut fold_statuses = Vec::with_capacity(rows.len());
         let mut line_number = String::new();
-        let is_relative = EditorSettings::get_global(cx).relative_line_numbers;
+        let is_relative = snapshot
+            .relative_line_numbers
+            .unwrap_or(EditorSettings::get_global(cx).relative_line_numbers);
         let relative_to = if is_relative {
             Some(newest_selection_head.row())
         } else {
@@ -2228,6 +2658,21 @@ impl EditorElement {
 
             let display_hunks = self.layout_git_gutters(start_row..end_row, &snapshot);
 
+            let indent_guides = if snapshot.mode == EditorMode::Full {
+                self.layout_indent_guides(
+                    editor,
+                    start_row..end_row,
+                    head_for_relative,
+                    &snapshot,
+                    cx,
+                )
+            } else {
+                Vec::new()
+            };
+
+            let sticky_headers =
+                self.layout_sticky_headers(editor, &snapshot, scroll_position, line_height, cx);
+
             let scrollbar_row_range = scroll_position.y..(scroll_position.y + height_in_lines);
 
             let mut max_visible_line_width = Pixels::ZERO;
@@ -2238,6 +2683,16 @@ impl EditorElement {
                 }
             }
 
+            let inline_diagnostics = self.layout_inline_diagnostics(
+                start_row..end_row,
+                &line_layouts,
+                &snapshot,
+                scroll_position.x * em_width + text_width,
+                em_width,
+                em_advance,
+                cx,
+            );
+
             let longest_line_width = layout_line(snapshot.longest_row(), &snapshot, &style, cx)
                 .unwrap()
                 .width;
@@ -2412,6 +2867,9 @@ impl EditorElement {
                 visible_anchor_range: start_anchor..end_anchor,
                 visible_display_row_range: start_row..end_row,
                 wrap_guides,
+                indent_guides,
+                sticky_headers,
+                inline_diagnostics,
                 gutter_size,
                 gutter_dimensions,
                 text_size,
@@ -3161,6 +3619,14 @@ impl Element for EditorElement {
                                 })
                             }
 
+                            if !layout.sticky_headers.is_empty() {
+                                cx.with_z_index(1, |cx| {
+                                    cx.with_element_id(Some("sticky_headers"), |cx| {
+                                        self.paint_sticky_headers(text_bounds, &layout, cx);
+                                    });
+                                });
+                            }
+
                             cx.with_z_index(1, |cx| {
                                 self.paint_overlays(text_bounds, &mut layout, cx);
                             });
@@ -3195,6 +3661,9 @@ pub struct LayoutState {
     text_size: gpui::Size<Pixels>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
+    indent_guides: Vec<IndentGuideLayout>,
+    sticky_headers: Vec<StickyHeaderLayout>,
+    inline_diagnostics: Vec<InlineDiagnosticLayout>,
     visible_anchor_range: Range<Anchor>,
     visible_display_row_range: Range<u32>,
     active_rows: BTreeMap<u32, bool>,
@@ -3223,6 +3692,37 @@ impl LayoutState {
     }
 }
 
+struct IndentGuideLayout {
+    offset: Pixels,
+    display_rows: Range<u32>,
+    width: Pixels,
+    active: bool,
+}
+
+/// The outline items containing the top row of an editor, kept between frames
+/// so that sticky headers are only recomputed when the buffer or the scroll
+/// row changes.
+pub(crate) struct StickyHeaderSymbols {
+    edit_count: usize,
+    parse_count: usize,
+    top_point: Point,
+    syntax: Arc<SyntaxTheme>,
+    items: Vec<OutlineItem<Anchor>>,
+}
+
+struct StickyHeaderLayout {
+    position: Anchor,
+    line: ShapedLine,
+    offset: Pixels,
+    y: Pixels,
+}
+
+struct InlineDiagnosticLayout {
+    display_row: u32,
+    x: Pixels,
+    line: ShapedLine,
+}
+
 struct CodeActionsIndicator {
     row: u32,
     button: IconButton,
@@ -3596,13 +4096,22 @@ mod tests {
     use super::*;
     use crate::{
         display_map::{BlockDisposition, BlockProperties},
+        editor_settings::{
+            InlineDiagnosticSeverity, InlineDiagnosticsContent, StickyScrollContent,
+        },
         editor_tests::{init_test, update_test_language_settings},
         Editor, MultiBuffer,
     };
-    use gpui::TestAppContext;
-    use language::language_settings;
+    use gpui::{TestAppContext, WindowHandle};
+    use indoc::indoc;
+    use language::{
+        language_settings, Buffer, Diagnostic, DiagnosticSet, Language, LanguageConfig, PointUtf16,
+    };
     use log::info;
+    use lsp::LanguageServerId;
+    use settings::SettingsStore;
     use std::{num::NonZeroU32, sync::Arc};
+    use text::BufferId;
     use util::test::sample_text;
 
     #[gpui::test]
@@ -4042,6 +4551,223 @@ mod tests {
         }
     }
 
+    #[gpui::test]
+    fn test_sticky_headers_nesting(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        update_sticky_scroll_settings(cx, 5);
+        let (window, element) = build_sticky_header_editor(cx);
+        let line_height = px(10.);
+
+        // Every scope containing the top row gets a header, outermost first.
+        assert_eq!(
+            sticky_header_rows(&element, window, 3, line_height, cx),
+            &[(0, px(0.)), (1, px(10.)), (2, px(20.))]
+        );
+
+        // Headers that are visible in place are not pinned.
+        assert!(sticky_header_rows(&element, window, 0, line_height, cx).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_sticky_headers_max_depth(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        update_sticky_scroll_settings(cx, 2);
+        let (window, element) = build_sticky_header_editor(cx);
+        let line_height = px(10.);
+
+        assert_eq!(
+            sticky_header_rows(&element, window, 3, line_height, cx),
+            &[(0, px(0.)), (1, px(10.))]
+        );
+
+        update_sticky_scroll_settings(cx, 0);
+        assert!(sticky_header_rows(&element, window, 3, line_height, cx).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_sticky_headers_at_scope_boundaries(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        update_sticky_scroll_settings(cx, 5);
+        let (window, element) = build_sticky_header_editor(cx);
+        let line_height = px(10.);
+
+        // The header of a scope that is about to end is pushed up with it.
+        assert_eq!(
+            sticky_header_rows(&element, window, 4, line_height, cx),
+            &[(0, px(0.)), (1, px(10.)), (2, px(10.))]
+        );
+        assert_eq!(
+            sticky_header_rows(&element, window, 5, line_height, cx),
+            &[(0, px(0.)), (1, px(10.)), (2, px(0.))]
+        );
+
+        // Once the next sibling scope reaches the top, its header replaces
+        // the previous one.
+        assert_eq!(
+            sticky_header_rows(&element, window, 8, line_height, cx),
+            &[(0, px(0.)), (1, px(10.)), (7, px(20.))]
+        );
+
+        // Edits invalidate the cached symbols.
+        window
+            .update(cx, |editor, cx| {
+                editor.buffer().update(cx, |buffer, cx| {
+                    buffer.edit([(Point::new(0, 0)..Point::new(0, 0), "\n")], None, cx)
+                });
+            })
+            .unwrap();
+        cx.executor().run_until_parked();
+        assert_eq!(
+            sticky_header_rows(&element, window, 9, line_height, cx),
+            &[(1, px(0.)), (2, px(10.)), (8, px(20.))]
+        );
+    }
+
+    #[gpui::test]
+    fn test_sticky_headers_painting(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        update_sticky_scroll_settings(cx, 5);
+        let (window, mut element) = build_sticky_header_editor(cx);
+        window
+            .update(cx, |editor, cx| {
+                editor.set_scroll_position(gpui::Point::new(0., 3.), cx);
+            })
+            .unwrap();
+
+        let state = cx
+            .update_window(window.into(), |view, cx| {
+                cx.with_element_context(|cx| {
+                    cx.with_view_id(view.entity_id(), |cx| {
+                        element.compute_layout(
+                            Bounds {
+                                origin: point(px(500.), px(500.)),
+                                size: size(px(500.), px(500.)),
+                            },
+                            cx,
+                        )
+                    })
+                })
+            })
+            .unwrap();
+        assert_eq!(state.sticky_headers.len(), 3);
+
+        // Don't panic.
+        let bounds = Bounds::<Pixels>::new(Default::default(), state.position_map.size);
+        cx.update_window(window.into(), |_, cx| {
+            cx.with_element_context(|cx| element.paint(bounds, &mut (), cx))
+        })
+        .unwrap()
+    }
+
+    #[gpui::test]
+    fn test_inline_diagnostics_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        update_inline_diagnostics_settings(cx, InlineDiagnosticSeverity::Hint);
+
+        let long_message = "x".repeat(200);
+        let window = cx.add_window(|cx| {
+            let buffer = MultiBuffer::build_simple("one\ntwo\nthree\nfour\n", cx);
+            Editor::new(EditorMode::Full, buffer, None, cx)
+        });
+        window
+            .update(cx, |editor, cx| {
+                let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+                buffer.update(cx, |buffer, cx| {
+                    let diagnostic = |row, severity, message: &str| DiagnosticEntry {
+                        range: PointUtf16::new(row, 0)..PointUtf16::new(row, 1),
+                        diagnostic: Diagnostic {
+                            severity,
+                            message: message.to_string(),
+                            is_primary: true,
+                            ..Default::default()
+                        },
+                    };
+                    let diagnostics = DiagnosticSet::new(
+                        [
+                            diagnostic(0, DiagnosticSeverity::WARNING, "unused variable"),
+                            diagnostic(
+                                0,
+                                DiagnosticSeverity::ERROR,
+                                "mismatched types\nexpected u32",
+                            ),
+                            diagnostic(1, DiagnosticSeverity::HINT, "consider borrowing"),
+                            diagnostic(2, DiagnosticSeverity::INFORMATION, &long_message),
+                            DiagnosticEntry {
+                                range: PointUtf16::new(3, 0)..PointUtf16::new(3, 1),
+                                diagnostic: Diagnostic {
+                                    message: "related".to_string(),
+                                    is_primary: false,
+                                    ..Default::default()
+                                },
+                            },
+                        ],
+                        buffer,
+                    );
+                    buffer.update_diagnostics(LanguageServerId(0), diagnostics, cx);
+                });
+            })
+            .unwrap();
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+        let element = EditorElement::new(&editor, style);
+
+        // Only the most severe primary diagnostic of each row is shown, with
+        // just the first line of its message, and long messages are truncated
+        // to fit before the right edge.
+        let layouts = inline_diagnostic_layouts(&element, window, px(1000.), cx);
+        assert_eq!(
+            layouts
+                .iter()
+                .map(|(row, message, _)| (*row, message.chars().take(20).collect::<String>()))
+                .collect::<Vec<_>>(),
+            &[
+                (0, "mismatched types".to_string()),
+                (1, "consider borrowing".to_string()),
+                (2, "x".repeat(20)),
+            ]
+        );
+        let (_, truncated, x) = &layouts[2];
+        let max_chars = ((px(1000.) - *x - px(10.)) / px(10.)).floor() as usize;
+        assert_eq!(truncated.chars().count(), max_chars);
+        assert!(truncated.ends_with('…'));
+
+        // Diagnostics are hidden when there is no room for them.
+        assert_eq!(
+            inline_diagnostic_layouts(&element, window, px(50.), cx).len(),
+            0
+        );
+
+        // Less severe diagnostics are filtered out.
+        update_inline_diagnostics_settings(cx, InlineDiagnosticSeverity::Warning);
+        assert_eq!(
+            inline_diagnostic_layouts(&element, window, px(1000.), cx)
+                .into_iter()
+                .map(|(row, message, _)| (row, message))
+                .collect::<Vec<_>>(),
+            &[(0, "mismatched types".into())]
+        );
+    }
+
+    #[test]
+    fn test_inline_diagnostic_severity_includes() {
+        let severities = [
+            DiagnosticSeverity::ERROR,
+            DiagnosticSeverity::WARNING,
+            DiagnosticSeverity::INFORMATION,
+            DiagnosticSeverity::HINT,
+        ];
+        let included = |min_severity: InlineDiagnosticSeverity| {
+            severities
+                .iter()
+                .filter(|severity| min_severity.includes(**severity))
+                .count()
+        };
+        assert_eq!(included(InlineDiagnosticSeverity::Error), 1);
+        assert_eq!(included(InlineDiagnosticSeverity::Warning), 2);
+        assert_eq!(included(InlineDiagnosticSeverity::Info), 3);
+        assert_eq!(included(InlineDiagnosticSeverity::Hint), 4);
+    }
+
     fn collect_invisibles_from_new_editor(
         cx: &mut TestAppContext,
         editor_mode: EditorMode,
@@ -4087,6 +4813,151 @@ mod tests {
             .cloned()
             .collect()
     }
+
+    fn update_inline_diagnostics_settings(
+        cx: &mut TestAppContext,
+        min_severity: InlineDiagnosticSeverity,
+    ) {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.inline_diagnostics = Some(InlineDiagnosticsContent {
+                        enabled: Some(true),
+                        min_severity: Some(min_severity),
+                    });
+                });
+            });
+        });
+    }
+
+    fn inline_diagnostic_layouts(
+        element: &EditorElement,
+        window: WindowHandle<Editor>,
+        visible_right: Pixels,
+        cx: &mut TestAppContext,
+    ) -> Vec<(u32, SharedString, Pixels)> {
+        window
+            .update(cx, |editor, cx| {
+                let snapshot = editor.snapshot(cx);
+                let rows = 0..snapshot.max_point().row() + 1;
+                let line_numbers = vec![None; rows.len()];
+                let line_layouts = element.layout_lines(rows.clone(), &line_numbers, &snapshot, cx);
+                element
+                    .layout_inline_diagnostics(
+                        rows,
+                        &line_layouts,
+                        &snapshot,
+                        visible_right,
+                        px(10.),
+                        px(10.),
+                        cx,
+                    )
+                    .into_iter()
+                    .map(|layout| (layout.display_row, layout.line.text.clone(), layout.x))
+                    .collect()
+            })
+            .unwrap()
+    }
+
+    fn update_sticky_scroll_settings(cx: &mut TestAppContext, max_depth: usize) {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(true),
+                        max_depth: Some(max_depth),
+                    });
+                });
+            });
+        });
+    }
+
+    fn build_sticky_header_editor(
+        cx: &mut TestAppContext,
+    ) -> (WindowHandle<Editor>, EditorElement) {
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_outline_query(
+                r#"
+                (mod_item
+                    "mod" @context
+                    name: (_) @name) @item
+                (impl_item
+                    "impl" @context
+                    type: (_) @name) @item
+                (function_item
+                    "fn" @context
+                    name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = indoc! {"
+            mod a {
+                impl B {
+                    fn c() {
+                        let x = 1;
+                        let y = 2;
+                        let z = 3;
+                    }
+                    fn d() {
+                        let x = 4;
+                        let y = 5;
+                        let z = 6;
+                        let w = 7;
+                    }
+                }
+            }
+        "};
+
+        let window = cx.add_window(|cx| {
+            let buffer = cx.new_model(|cx| {
+                Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+                    .with_language(language, cx)
+            });
+            let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+            Editor::new(EditorMode::Full, buffer, None, cx)
+        });
+        cx.executor().run_until_parked();
+
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+        (window, EditorElement::new(&editor, style))
+    }
+
+    fn sticky_header_rows(
+        element: &EditorElement,
+        window: WindowHandle<Editor>,
+        top_row: u32,
+        line_height: Pixels,
+        cx: &mut TestAppContext,
+    ) -> Vec<(u32, Pixels)> {
+        window
+            .update(cx, |editor, cx| {
+                let snapshot = editor.snapshot(cx);
+                element
+                    .layout_sticky_headers(
+                        editor,
+                        &snapshot,
+                        gpui::Point::new(0., top_row as f32),
+                        line_height,
+                        cx,
+                    )
+                    .into_iter()
+                    .map(|header| {
+                        let row = header.position.to_point(&snapshot.buffer_snapshot).row;
+                        (row, header.y)
+                    })
+                    .collect()
+            })
+            .unwrap()
+    }
 }
 
 pub fn register_action<T: Action>(
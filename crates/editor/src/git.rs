@@ -1,3 +1,4 @@
+mod conflict;
 pub mod permalink;
 
 use std::ops::Range;
@@ -282,4 +283,241 @@ mod tests {
                 .as_slice(),
         );
     }
+
+    #[gpui::test]
+    async fn test_merge_conflict_resolution(cx: &mut TestAppContext) {
+        use crate::{AcceptConflictBoth, AcceptConflictTheirs, Editor, GoToConflict, Undo};
+        use project::repository::GitFileStatus;
+        use serde_json::json;
+        use std::path::Path;
+        init_test(cx, |_| {});
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "file.txt": "
+                    one
+                    <<<<<<< HEAD
+                    two
+                    =======
+                    TWO
+                    >>>>>>> feature
+                    three
+                    <<<<<<< HEAD
+                    four
+                    =======
+                    FOUR
+                    >>>>>>> feature
+                "
+                .unindent(),
+            }),
+        )
+        .await;
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/dir/.git"),
+            &[(Path::new("file.txt"), GitFileStatus::Conflict)],
+        );
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/file.txt", cx)
+            })
+            .await
+            .unwrap();
+        cx.executor().run_until_parked();
+
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let (editor, cx) =
+            cx.add_window_view(|cx| Editor::for_multibuffer(buffer, Some(project.clone()), cx));
+
+        _ = editor.update(cx, |editor, cx| {
+            assert_eq!(editor.conflicts(cx).len(), 2);
+
+            editor.go_to_conflict(&GoToConflict, cx);
+            editor.accept_conflict_theirs(&AcceptConflictTheirs, cx);
+            assert_eq!(
+                editor.text(cx),
+                "
+                    one
+                    TWO
+                    three
+                    <<<<<<< HEAD
+                    four
+                    =======
+                    FOUR
+                    >>>>>>> feature
+                "
+                .unindent()
+            );
+
+            editor.go_to_conflict(&GoToConflict, cx);
+            editor.accept_conflict_both(&AcceptConflictBoth, cx);
+            assert_eq!(editor.text(cx), "one\nTWO\nthree\nfour\nFOUR\n");
+            assert!(editor.conflicts(cx).is_empty());
+
+            editor.undo(&Undo, cx);
+            assert_eq!(editor.conflicts(cx).len(), 1);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_merge_conflict_highlights(cx: &mut TestAppContext) {
+        use super::conflict::{
+            ConflictBaseHighlight, ConflictOursHighlight, ConflictTheirsHighlight,
+        };
+        use crate::{Editor, ToPoint};
+        use project::repository::GitFileStatus;
+        use serde_json::json;
+        use std::{any::TypeId, path::Path};
+        init_test(cx, |_| {});
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "file.txt": "
+                    <<<<<<< HEAD
+                    two
+                    ||||||| base
+                    2
+                    =======
+                    TWO
+                    >>>>>>> feature
+                    three
+                "
+                .unindent(),
+            }),
+        )
+        .await;
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/dir/.git"),
+            &[(Path::new("file.txt"), GitFileStatus::Conflict)],
+        );
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/file.txt", cx)
+            })
+            .await
+            .unwrap();
+        cx.executor().run_until_parked();
+
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let (editor, cx) =
+            cx.add_window_view(|cx| Editor::for_multibuffer(buffer, Some(project.clone()), cx));
+
+        _ = editor.update(cx, |editor, cx| {
+            editor.refresh_conflict_highlights(cx);
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let highlighted_rows = |type_id| {
+                editor.background_highlights[&type_id]
+                    .1
+                    .iter()
+                    .map(|range| {
+                        range.start.to_point(&snapshot).row..range.end.to_point(&snapshot).row
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            // The "theirs" side includes both the separator and the closing marker.
+            assert_eq!(
+                highlighted_rows(TypeId::of::<ConflictOursHighlight>()),
+                [0..2]
+            );
+            assert_eq!(
+                highlighted_rows(TypeId::of::<ConflictBaseHighlight>()),
+                [2..4]
+            );
+            assert_eq!(
+                highlighted_rows(TypeId::of::<ConflictTheirsHighlight>()),
+                [4..7]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_mark_conflicts_resolved_on_save(cx: &mut TestAppContext) {
+        use crate::{AcceptConflictOurs, Editor};
+        use project::repository::{GitFileStatus, RepoPath};
+        use serde_json::json;
+        use std::path::Path;
+        init_test(cx, |_| {});
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "file.txt": "
+                    <<<<<<< HEAD
+                    one
+                    =======
+                    ONE
+                    >>>>>>> feature
+                    <<<<<<< HEAD
+                    two
+                    =======
+                    TWO
+                    >>>>>>> feature
+                "
+                .unindent(),
+            }),
+        )
+        .await;
+        let dot_git = Path::new("/dir/.git");
+        fs.set_status_for_repo_via_git_operation(
+            dot_git,
+            &[(Path::new("file.txt"), GitFileStatus::Conflict)],
+        );
+        let status = || {
+            let mut status = None;
+            fs.with_git_state(dot_git, false, |state| {
+                status = state
+                    .worktree_statuses
+                    .get(&RepoPath::from(Path::new("file.txt")))
+                    .copied();
+            });
+            status
+        };
+
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/file.txt", cx)
+            })
+            .await
+            .unwrap();
+        cx.executor().run_until_parked();
+
+        let multibuffer = cx.new_model(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+        let (editor, cx) = cx
+            .add_window_view(|cx| Editor::for_multibuffer(multibuffer, Some(project.clone()), cx));
+        let save = |cx: &mut gpui::VisualTestContext| {
+            project.update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        };
+
+        // Saving with conflict markers left keeps the file conflicted.
+        _ = editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| s.select_ranges([0..0]));
+            editor.accept_conflict_ours(&AcceptConflictOurs, cx);
+        });
+        save(cx).await.unwrap();
+        cx.executor().run_until_parked();
+        assert_eq!(status(), Some(GitFileStatus::Conflict));
+
+        // Saving once the last marker is gone marks the file as resolved.
+        _ = editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| s.select_ranges([4..4]));
+            editor.accept_conflict_ours(&AcceptConflictOurs, cx);
+            assert_eq!(editor.text(cx), "one\ntwo\n");
+        });
+        save(cx).await.unwrap();
+        cx.executor().run_until_parked();
+        assert_eq!(status(), Some(GitFileStatus::Modified));
+    }
 }
@@ -0,0 +1,287 @@
+use ::git::conflict::{conflict_regions, has_conflict_markers, ConflictRegion, ConflictResolution};
+use gpui::{AppContext, ViewContext};
+use language::OffsetRangeExt;
+use multi_buffer::{Anchor, ToOffset};
+use project::{repository::GitFileStatus, Item as _, ProjectPath};
+use util::ResultExt;
+use workspace::Toast;
+
+use crate::{
+    AcceptConflictBoth, AcceptConflictOurs, AcceptConflictTheirs, Autoscroll, Editor, GoToConflict,
+    GoToPrevConflict,
+};
+
+const MARK_RESOLVED_FAILURE_TOAST_ID: usize = 0x2d6f1c4b;
+
+pub(crate) enum ConflictOursHighlight {}
+pub(crate) enum ConflictBaseHighlight {}
+pub(crate) enum ConflictTheirsHighlight {}
+
+impl Editor {
+    pub fn accept_conflict_ours(&mut self, _: &AcceptConflictOurs, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_conflicts(ConflictResolution::Ours, cx);
+    }
+
+    pub fn accept_conflict_theirs(&mut self, _: &AcceptConflictTheirs, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_conflicts(ConflictResolution::Theirs, cx);
+    }
+
+    pub fn accept_conflict_both(&mut self, _: &AcceptConflictBoth, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_conflicts(ConflictResolution::Both, cx);
+    }
+
+    pub fn go_to_conflict(&mut self, _: &GoToConflict, cx: &mut ViewContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest::<usize>(cx).head();
+        let next_conflict = self
+            .conflicts(cx)
+            .into_iter()
+            .find(|conflict| conflict.range.start.to_offset(&snapshot) > head);
+        match next_conflict {
+            Some(conflict) => self.select_conflict(&conflict, cx),
+            None => self.open_adjacent_conflicted_file(true, cx),
+        }
+    }
+
+    pub fn go_to_prev_conflict(&mut self, _: &GoToPrevConflict, cx: &mut ViewContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest::<usize>(cx).head();
+        let prev_conflict = self
+            .conflicts(cx)
+            .into_iter()
+            .rev()
+            .find(|conflict| conflict.range.end.to_offset(&snapshot) <= head);
+        match prev_conflict {
+            Some(conflict) => self.select_conflict(&conflict, cx),
+            None => self.open_adjacent_conflicted_file(false, cx),
+        }
+    }
+
+    /// Returns the conflict regions of this editor's buffer, if its file has a
+    /// conflicted git status. Only singleton buffers are considered.
+    pub fn conflicts(&self, cx: &AppContext) -> Vec<ConflictRegion<Anchor>> {
+        if !self.has_conflicted_git_status(cx) {
+            return Vec::new();
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let Some((&excerpt_id, _, buffer)) = snapshot.as_singleton() else {
+            return Vec::new();
+        };
+        let anchor = |anchor| snapshot.anchor_in_excerpt(excerpt_id, anchor);
+        conflict_regions(buffer)
+            .into_iter()
+            .filter_map(|region| {
+                Some(ConflictRegion {
+                    range: anchor(region.range.start)?..anchor(region.range.end)?,
+                    ours: anchor(region.ours.start)?..anchor(region.ours.end)?,
+                    base: match region.base {
+                        Some(base) => Some(anchor(base.start)?..anchor(base.end)?),
+                        None => None,
+                    },
+                    theirs: anchor(region.theirs.start)?..anchor(region.theirs.end)?,
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn refresh_conflict_highlights(&mut self, cx: &mut ViewContext<Self>) {
+        let conflicts = self.conflicts(cx);
+        if conflicts.is_empty() {
+            self.clear_background_highlights::<ConflictOursHighlight>(cx);
+            self.clear_background_highlights::<ConflictBaseHighlight>(cx);
+            self.clear_background_highlights::<ConflictTheirsHighlight>(cx);
+            cx.notify();
+            return;
+        }
+
+        let mut ours = Vec::with_capacity(conflicts.len());
+        let mut base = Vec::new();
+        let mut theirs = Vec::with_capacity(conflicts.len());
+        for conflict in conflicts {
+            // Highlight each side together with the marker line that opens it,
+            // and "theirs" with the closing marker too.
+            ours.push(conflict.range.start..conflict.ours.end);
+            let separator_start = match conflict.base {
+                Some(base_range) => {
+                    base.push(conflict.ours.end..base_range.end);
+                    base_range.end
+                }
+                None => conflict.ours.end,
+            };
+            theirs.push(separator_start..conflict.range.end);
+        }
+        self.highlight_background::<ConflictOursHighlight>(
+            ours,
+            |theme| theme.editor_conflict_ours_background,
+            cx,
+        );
+        self.highlight_background::<ConflictBaseHighlight>(
+            base,
+            |theme| theme.editor_conflict_base_background,
+            cx,
+        );
+        self.highlight_background::<ConflictTheirsHighlight>(
+            theirs,
+            |theme| theme.editor_conflict_theirs_background,
+            cx,
+        );
+    }
+
+    /// Marks the file as resolved in the git index once its last conflict marker has been removed.
+    pub(crate) fn mark_conflicts_resolved_if_needed(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.has_conflicted_git_status(cx) {
+            return;
+        }
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        if has_conflict_markers(&buffer.read(cx).snapshot()) {
+            return;
+        }
+        let Some(project_path) = buffer.read(cx).project_path(cx) else {
+            return;
+        };
+        let Some(project) = self.project.as_ref() else {
+            return;
+        };
+        if let Err(error) = project.read(cx).mark_conflict_resolved(&project_path, cx) {
+            let message = format!("Failed to mark conflicts as resolved: {error}");
+            Err::<(), anyhow::Error>(error).log_err();
+            if let Some(workspace) = self.workspace() {
+                workspace.update(cx, |workspace, cx| {
+                    workspace.show_toast(Toast::new(MARK_RESOLVED_FAILURE_TOAST_ID, message), cx)
+                })
+            }
+        }
+    }
+
+    fn has_conflicted_git_status(&self, cx: &AppContext) -> bool {
+        self.buffer
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| buffer.read(cx).project_path(cx))
+            .and_then(|path| self.project.as_ref()?.read(cx).entry_for_path(&path, cx))
+            .map_or(false, |entry| {
+                entry.git_status() == Some(GitFileStatus::Conflict)
+            })
+    }
+
+    fn resolve_selected_conflicts(
+        &mut self,
+        resolution: ConflictResolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if !self.has_conflicted_git_status(cx) {
+            return;
+        }
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+
+        // In a singleton multibuffer, selection offsets are buffer offsets.
+        let selections = self.selections.all::<usize>(cx);
+        let snapshot = buffer.read(cx).snapshot();
+        let edits = conflict_regions(&snapshot)
+            .into_iter()
+            .filter(|conflict| {
+                let range = conflict.range.to_offset(&snapshot);
+                selections
+                    .iter()
+                    .any(|selection| selection.start < range.end && selection.end >= range.start)
+            })
+            .map(|conflict| {
+                let text = conflict.resolved_text(resolution, &snapshot);
+                (conflict.range, text)
+            })
+            .collect::<Vec<_>>();
+        if edits.is_empty() {
+            return;
+        }
+
+        self.transact(cx, |editor, cx| {
+            buffer.update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+            editor.change_selections(None, cx, |selections| selections.refresh());
+        });
+    }
+
+    fn select_conflict(&mut self, conflict: &ConflictRegion<Anchor>, cx: &mut ViewContext<Self>) {
+        self.change_selections(Some(Autoscroll::center()), cx, |selections| {
+            selections.select_anchor_ranges([conflict.range.start..conflict.range.start]);
+        });
+    }
+
+    /// Selects the first conflict of the buffer, or the last one when moving backwards.
+    fn select_first_conflict(&mut self, forward: bool, cx: &mut ViewContext<Self>) {
+        let conflicts = self.conflicts(cx);
+        let conflict = if forward {
+            conflicts.first()
+        } else {
+            conflicts.last()
+        };
+        if let Some(conflict) = conflict {
+            self.select_conflict(conflict, cx);
+        }
+    }
+
+    /// Opens the next (or previous) file in the project with merge conflicts,
+    /// wrapping around, and moves the cursor to its first (or last) conflict.
+    fn open_adjacent_conflicted_file(&mut self, forward: bool, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let current_path = self
+            .buffer
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| buffer.read(cx).project_path(cx));
+        let conflicted_paths = project.read(cx).conflicted_paths(cx);
+        let Some(target_path) = adjacent_path(&conflicted_paths, current_path.as_ref(), forward)
+        else {
+            return;
+        };
+        if Some(&target_path) == current_path.as_ref() {
+            self.select_first_conflict(forward, cx);
+            return;
+        }
+
+        let open_task = workspace.update(cx, |workspace, cx| {
+            workspace.open_path(target_path, None, true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let item = open_task.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update(&mut cx, |editor, cx| {
+                    editor.refresh_conflict_highlights(cx);
+                    editor.select_first_conflict(forward, cx);
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+fn adjacent_path(
+    paths: &[ProjectPath],
+    current_path: Option<&ProjectPath>,
+    forward: bool,
+) -> Option<ProjectPath> {
+    let mut paths = paths.to_vec();
+    paths.sort();
+    let Some(current_path) = current_path else {
+        return paths.first().cloned();
+    };
+
+    let adjacent = if forward {
+        paths.iter().find(|path| *path > current_path)
+    } else {
+        paths.iter().rev().find(|path| *path < current_path)
+    };
+    adjacent
+        .or(if forward { paths.first() } else { paths.last() })
+        .cloned()
+}
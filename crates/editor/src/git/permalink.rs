@@ -1,202 +1,449 @@
-use std::ops::Range;
+use std::{ops::Range, sync::Arc};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
+use gpui::{AppContext, Global};
 use language::Point;
+use parking_lot::RwLock;
+use project::project_settings::{GitHostingProviderKind, GitHostingProviderSettings};
 use url::Url;
+use util::paths::PathMatcher;
+
+/// A service hosting git repositories, used to build links to files, commits
+/// and pull requests of a repository from its remote URL.
+pub trait GitHostingProvider: Send + Sync {
+    /// The name of the provider, e.g. "GitHub".
+    fn name(&self) -> String;
+
+    /// Returns whether repositories on the given host are served by this provider.
+    fn matches_host(&self, host: &str) -> bool;
+
+    /// Extracts the owner and repository from the path of a remote URL,
+    /// e.g. `zed-industries/zed.git`.
+    fn parse_remote_path<'a>(&self, path: &'a str) -> Option<ParsedGitRemote<'a>> {
+        let path = path.trim_end_matches('/').trim_end_matches(".git");
+        let (owner, repo) = path.rsplit_once('/')?;
+        Some(ParsedGitRemote { owner, repo })
+    }
+
+    /// Returns a link to a file at a given commit, optionally selecting some of its lines.
+    fn build_permalink(
+        &self,
+        remote: &ParsedGitRemote,
+        sha: &str,
+        path: &str,
+        selection: Option<Range<Point>>,
+    ) -> Result<Url>;
+
+    /// Returns a link to a commit.
+    fn build_commit_permalink(&self, remote: &ParsedGitRemote, sha: &str) -> Result<Url>;
+
+    /// Returns a link to the pull request with the given number, if the provider has them.
+    fn build_pull_request_url(&self, _remote: &ParsedGitRemote, _number: u32) -> Option<Url> {
+        None
+    }
 
-enum GitHostingProvider {
-    Github,
-    Gitlab,
-    Gitee,
-    Bitbucket,
-    Sourcehut,
-    Codeberg,
+    /// Extracts the number of the pull request a commit was merged with from its message.
+    fn extract_pull_request_number(&self, _commit_message: &str) -> Option<u32> {
+        None
+    }
 }
 
-impl GitHostingProvider {
-    fn base_url(&self) -> Url {
-        let base_url = match self {
-            Self::Github => "https://github.com",
-            Self::Gitlab => "https://gitlab.com",
-            Self::Gitee => "https://gitee.com",
-            Self::Bitbucket => "https://bitbucket.org",
-            Self::Sourcehut => "https://git.sr.ht",
-            Self::Codeberg => "https://codeberg.org",
-        };
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsedGitRemote<'a> {
+    pub owner: &'a str,
+    pub repo: &'a str,
+}
+
+#[derive(Default)]
+struct GlobalGitHostingProviderRegistry(Arc<GitHostingProviderRegistry>);
+
+impl Global for GlobalGitHostingProviderRegistry {}
+
+/// The set of known [`GitHostingProvider`]s.
+///
+/// Providers configured in the `git.hosting_providers` setting take precedence over
+/// registered providers, which in turn take precedence over the built-in ones.
+pub struct GitHostingProviderRegistry {
+    providers: RwLock<Vec<Arc<dyn GitHostingProvider>>>,
+}
 
-        Url::parse(&base_url).unwrap()
+impl Default for GitHostingProviderRegistry {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    /// Returns the fragment portion of the URL for the selected lines in
-    /// the representation the [`GitHostingProvider`] expects.
-    fn line_fragment(&self, selection: &Range<Point>) -> String {
-        if selection.start.row == selection.end.row {
-            let line = selection.start.row + 1;
-
-            match self {
-                Self::Github | Self::Gitlab | Self::Gitee | Self::Sourcehut | Self::Codeberg => {
-                    format!("L{}", line)
-                }
-                Self::Bitbucket => format!("lines-{}", line),
-            }
-        } else {
-            let start_line = selection.start.row + 1;
-            let end_line = selection.end.row + 1;
-
-            match self {
-                Self::Github | Self::Codeberg => format!("L{}-L{}", start_line, end_line),
-                Self::Gitlab | Self::Gitee | Self::Sourcehut => {
-                    format!("L{}-{}", start_line, end_line)
-                }
-                Self::Bitbucket => format!("lines-{}:{}", start_line, end_line),
-            }
+impl GitHostingProviderRegistry {
+    /// Creates a registry containing the built-in providers.
+    pub fn new() -> Self {
+        use GitHostingProviderKind::*;
+
+        let providers = [
+            ("GitHub", "github.com", Github),
+            ("GitLab", "gitlab.com", Gitlab),
+            ("Gitee", "gitee.com", Gitee),
+            ("Bitbucket", "bitbucket.org", Bitbucket),
+            ("SourceHut", "git.sr.ht", Sourcehut),
+            ("Codeberg", "codeberg.org", Gitea),
+            ("Gitea", "gitea.com", Gitea),
+        ]
+        .into_iter()
+        .map(|(name, host, kind)| {
+            Arc::new(ConfiguredGitHostingProvider {
+                name: name.into(),
+                host_matcher: PathMatcher::new(host).unwrap(),
+                base_url: Url::parse(&format!("https://{host}/")).unwrap(),
+                kind: Some(kind),
+                templates: Default::default(),
+            }) as Arc<dyn GitHostingProvider>
+        })
+        .collect();
+
+        Self {
+            providers: RwLock::new(providers),
         }
     }
-}
 
-pub struct BuildPermalinkParams<'a> {
-    pub remote_url: &'a str,
-    pub sha: &'a str,
-    pub path: &'a str,
-    pub selection: Option<Range<Point>>,
+    pub fn global(cx: &AppContext) -> Arc<Self> {
+        cx.global::<GlobalGitHostingProviderRegistry>().0.clone()
+    }
+
+    pub fn default_global(cx: &mut AppContext) -> Arc<Self> {
+        cx.default_global::<GlobalGitHostingProviderRegistry>()
+            .0
+            .clone()
+    }
+
+    /// Registers a provider, which takes precedence over the ones registered before it.
+    pub fn register_hosting_provider(&self, provider: Arc<dyn GitHostingProvider>) {
+        self.providers.write().insert(0, provider);
+    }
+
+    /// Returns the provider hosting the repository with the given remote URL,
+    /// along with the owner and name of the repository.
+    pub fn provider_for_remote_url<'a>(
+        &self,
+        remote_url: &'a str,
+        custom_providers: &[GitHostingProviderSettings],
+    ) -> Option<(Arc<dyn GitHostingProvider>, ParsedGitRemote<'a>)> {
+        let (host, path) = split_remote_url(remote_url)?;
+        custom_providers
+            .iter()
+            .filter_map(|settings| {
+                ConfiguredGitHostingProvider::from_settings(settings, host)
+                    .context("invalid git hosting provider in settings")
+                    .map_err(|error| log::error!("{error:#}"))
+                    .ok()
+            })
+            .map(|provider| Arc::new(provider) as Arc<dyn GitHostingProvider>)
+            .chain(self.providers.read().iter().cloned())
+            .filter(|provider| provider.matches_host(host))
+            .find_map(|provider| {
+                let remote = provider.parse_remote_path(path)?;
+                Some((provider, remote))
+            })
+    }
+
+    pub fn build_permalink(
+        &self,
+        params: BuildPermalinkParams,
+        custom_providers: &[GitHostingProviderSettings],
+    ) -> Result<Url> {
+        let BuildPermalinkParams {
+            remote_url,
+            sha,
+            path,
+            selection,
+        } = params;
+
+        let (provider, remote) = self
+            .provider_for_remote_url(remote_url, custom_providers)
+            .ok_or_else(|| anyhow!("failed to parse Git remote URL"))?;
+        provider.build_permalink(&remote, sha, path, selection)
+    }
+
+    pub fn build_commit_permalink(
+        &self,
+        remote_url: &str,
+        sha: &str,
+        custom_providers: &[GitHostingProviderSettings],
+    ) -> Result<Url> {
+        let (provider, remote) = self
+            .provider_for_remote_url(remote_url, custom_providers)
+            .ok_or_else(|| anyhow!("failed to parse Git remote URL"))?;
+        provider.build_commit_permalink(&remote, sha)
+    }
+
+    /// Returns a link to the pull request a commit was merged with, based on its message.
+    pub fn build_pull_request_url(
+        &self,
+        remote_url: &str,
+        commit_message: &str,
+        custom_providers: &[GitHostingProviderSettings],
+    ) -> Option<Url> {
+        let (provider, remote) = self.provider_for_remote_url(remote_url, custom_providers)?;
+        let number = provider.extract_pull_request_number(commit_message)?;
+        provider.build_pull_request_url(&remote, number)
+    }
 }
 
-pub fn build_permalink(params: BuildPermalinkParams) -> Result<Url> {
-    let BuildPermalinkParams {
-        remote_url,
-        sha,
-        path,
-        selection,
-    } = params;
-
-    let ParsedGitRemote {
-        provider,
-        owner,
-        repo,
-    } = parse_git_remote_url(remote_url)
-        .ok_or_else(|| anyhow!("failed to parse Git remote URL"))?;
-
-    let path = match provider {
-        GitHostingProvider::Github => format!("{owner}/{repo}/blob/{sha}/{path}"),
-        GitHostingProvider::Gitlab => format!("{owner}/{repo}/-/blob/{sha}/{path}"),
-        GitHostingProvider::Gitee => format!("{owner}/{repo}/blob/{sha}/{path}"),
-        GitHostingProvider::Bitbucket => format!("{owner}/{repo}/src/{sha}/{path}"),
-        GitHostingProvider::Sourcehut => format!("~{owner}/{repo}/tree/{sha}/item/{path}"),
-        GitHostingProvider::Codeberg => format!("{owner}/{repo}/src/commit/{sha}/{path}"),
+/// Splits a remote URL into its host and the path of the repository on that host.
+///
+/// Supports URLs like `https://user@host:port/owner/repo.git` and `ssh://git@host/owner/repo.git`
+/// as well as the scp-like `git@host:owner/repo.git`.
+fn split_remote_url(url: &str) -> Option<(&str, &str)> {
+    let url = url.trim();
+    let (authority, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => url.split_once(':')?,
     };
-    let line_fragment = selection.map(|selection| provider.line_fragment(&selection));
-
-    let mut permalink = provider.base_url().join(&path).unwrap();
-    permalink.set_fragment(line_fragment.as_deref());
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = host.split(':').next()?;
+    if host.is_empty() {
+        return None;
+    }
+    Some((host, path.trim_start_matches('/')))
+}
 
-    Ok(permalink)
+#[derive(Default)]
+struct UrlTemplates {
+    permalink: Option<String>,
+    line_fragment: Option<String>,
+    commit: Option<String>,
+    pull_request: Option<String>,
 }
 
-struct ParsedGitRemote<'a> {
-    pub provider: GitHostingProvider,
-    pub owner: &'a str,
-    pub repo: &'a str,
+/// A provider whose URLs follow the scheme of a known kind of forge,
+/// or templates defined in the settings.
+struct ConfiguredGitHostingProvider {
+    name: String,
+    host_matcher: PathMatcher,
+    base_url: Url,
+    kind: Option<GitHostingProviderKind>,
+    templates: UrlTemplates,
 }
 
-fn parse_git_remote_url(url: &str) -> Option<ParsedGitRemote> {
-    if url.starts_with("git@github.com:") || url.starts_with("https://github.com/") {
-        let repo_with_owner = url
-            .trim_start_matches("git@github.com:")
-            .trim_start_matches("https://github.com/")
-            .trim_end_matches(".git");
+impl ConfiguredGitHostingProvider {
+    fn from_settings(settings: &GitHostingProviderSettings, host: &str) -> Result<Self> {
+        let mut base_url = match &settings.base_url {
+            Some(base_url) => Url::parse(base_url)?,
+            None => Url::parse(&format!("https://{host}/"))?,
+        };
+        // Ensure relative paths are joined below the base URL's path.
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        Ok(Self {
+            name: settings
+                .name
+                .clone()
+                .unwrap_or_else(|| settings.host_pattern.clone()),
+            host_matcher: PathMatcher::new(&settings.host_pattern)?,
+            base_url,
+            kind: settings.provider,
+            templates: UrlTemplates {
+                permalink: settings.permalink_template.clone(),
+                line_fragment: settings.line_fragment_template.clone(),
+                commit: settings.commit_template.clone(),
+                pull_request: settings.pull_request_template.clone(),
+            },
+        })
+    }
 
-        let (owner, repo) = repo_with_owner.split_once('/')?;
+    fn required_kind(&self, url_description: &str) -> Result<GitHostingProviderKind> {
+        self.kind.ok_or_else(|| {
+            anyhow!(
+                "no {url_description} template configured for git hosting provider {}",
+                self.name
+            )
+        })
+    }
 
-        return Some(ParsedGitRemote {
-            provider: GitHostingProvider::Github,
-            owner,
-            repo,
-        });
+    fn expand_template(
+        &self,
+        template: &str,
+        remote: &ParsedGitRemote,
+        values: &[(&str, &str)],
+    ) -> String {
+        let mut result = template
+            .replace("{base_url}", self.base_url.as_str().trim_end_matches('/'))
+            .replace("{owner}", remote.owner)
+            .replace("{repo}", remote.repo);
+        for (key, value) in values {
+            result = result.replace(&format!("{{{key}}}"), value);
+        }
+        result
     }
 
-    if url.starts_with("git@gitlab.com:") || url.starts_with("https://gitlab.com/") {
-        let repo_with_owner = url
-            .trim_start_matches("git@gitlab.com:")
-            .trim_start_matches("https://gitlab.com/")
-            .trim_end_matches(".git");
+    /// Returns the fragment portion of the URL for the selected lines in
+    /// the representation the provider expects.
+    fn line_fragment(&self, selection: &Range<Point>) -> Result<String> {
+        let start_line = selection.start.row + 1;
+        let end_line = selection.end.row + 1;
+
+        if let Some(template) = &self.templates.line_fragment {
+            return Ok(template
+                .replace("{start_line}", &start_line.to_string())
+                .replace("{end_line}", &end_line.to_string()));
+        }
 
-        let (owner, repo) = repo_with_owner.split_once('/')?;
+        use GitHostingProviderKind::*;
+        let kind = self.required_kind("line fragment")?;
+        Ok(if start_line == end_line {
+            match kind {
+                Github | Gitlab | Gitee | Sourcehut | Gitea => format!("L{}", start_line),
+                Bitbucket => format!("lines-{}", start_line),
+            }
+        } else {
+            match kind {
+                Github | Gitea => format!("L{}-L{}", start_line, end_line),
+                Gitlab | Gitee | Sourcehut => format!("L{}-{}", start_line, end_line),
+                Bitbucket => format!("lines-{}:{}", start_line, end_line),
+            }
+        })
+    }
+}
 
-        return Some(ParsedGitRemote {
-            provider: GitHostingProvider::Gitlab,
-            owner,
-            repo,
-        });
+impl GitHostingProvider for ConfiguredGitHostingProvider {
+    fn name(&self) -> String {
+        self.name.clone()
     }
 
-    if url.starts_with("git@gitee.com:") || url.starts_with("https://gitee.com/") {
-        let repo_with_owner = url
-            .trim_start_matches("git@gitee.com:")
-            .trim_start_matches("https://gitee.com/")
-            .trim_end_matches(".git");
+    fn matches_host(&self, host: &str) -> bool {
+        self.host_matcher.is_match(host)
+    }
 
-        let (owner, repo) = repo_with_owner.split_once('/')?;
+    fn parse_remote_path<'a>(&self, path: &'a str) -> Option<ParsedGitRemote<'a>> {
+        if self.kind == Some(GitHostingProviderKind::Sourcehut) {
+            // sourcehut indicates a repo with '.git' suffix as a separate repo.
+            // For example, "git@git.sr.ht:~username/repo" and "git@git.sr.ht:~username/repo.git"
+            // are two distinct repositories.
+            let (owner, repo) = path.trim_end_matches('/').split_once('/')?;
+            return Some(ParsedGitRemote {
+                owner: owner.trim_start_matches('~'),
+                repo,
+            });
+        }
 
-        return Some(ParsedGitRemote {
-            provider: GitHostingProvider::Gitee,
-            owner,
-            repo,
-        });
+        let path = path.trim_end_matches('/').trim_end_matches(".git");
+        let (owner, repo) = path.rsplit_once('/')?;
+        Some(ParsedGitRemote { owner, repo })
     }
 
-    if url.contains("bitbucket.org") {
-        let (_, repo_with_owner) = url.trim_end_matches(".git").split_once("bitbucket.org")?;
-        let (owner, repo) = repo_with_owner
-            .trim_start_matches('/')
-            .trim_start_matches(':')
-            .split_once('/')?;
+    fn build_permalink(
+        &self,
+        remote: &ParsedGitRemote,
+        sha: &str,
+        path: &str,
+        selection: Option<Range<Point>>,
+    ) -> Result<Url> {
+        let ParsedGitRemote { owner, repo } = remote;
+        let mut permalink = if let Some(template) = &self.templates.permalink {
+            let url = self.expand_template(template, remote, &[("sha", sha), ("path", path)]);
+            Url::parse(&url).with_context(|| format!("invalid permalink {url:?}"))?
+        } else {
+            use GitHostingProviderKind::*;
+            let path = match self.required_kind("permalink")? {
+                Github | Gitee => format!("{owner}/{repo}/blob/{sha}/{path}"),
+                Gitlab => format!("{owner}/{repo}/-/blob/{sha}/{path}"),
+                Bitbucket => format!("{owner}/{repo}/src/{sha}/{path}"),
+                Sourcehut => format!("~{owner}/{repo}/tree/{sha}/item/{path}"),
+                Gitea => format!("{owner}/{repo}/src/commit/{sha}/{path}"),
+            };
+            self.base_url.join(&path)?
+        };
 
-        return Some(ParsedGitRemote {
-            provider: GitHostingProvider::Bitbucket,
-            owner,
-            repo,
-        });
+        let line_fragment = selection
+            .map(|selection| self.line_fragment(&selection))
+            .transpose()?;
+        permalink.set_fragment(line_fragment.as_deref());
+        Ok(permalink)
     }
 
-    if url.starts_with("git@git.sr.ht:") || url.starts_with("https://git.sr.ht/") {
-        // sourcehut indicates a repo with '.git' suffix as a separate repo.
-        // For example, "git@git.sr.ht:~username/repo" and "git@git.sr.ht:~username/repo.git"
-        // are two distinct repositories.
-        let repo_with_owner = url
-            .trim_start_matches("git@git.sr.ht:~")
-            .trim_start_matches("https://git.sr.ht/~");
-
-        let (owner, repo) = repo_with_owner.split_once('/')?;
+    fn build_commit_permalink(&self, remote: &ParsedGitRemote, sha: &str) -> Result<Url> {
+        let ParsedGitRemote { owner, repo } = remote;
+        if let Some(template) = &self.templates.commit {
+            let url = self.expand_template(template, remote, &[("sha", sha)]);
+            return Url::parse(&url).with_context(|| format!("invalid commit link {url:?}"));
+        }
 
-        return Some(ParsedGitRemote {
-            provider: GitHostingProvider::Sourcehut,
-            owner,
-            repo,
-        });
+        use GitHostingProviderKind::*;
+        let path = match self.required_kind("commit")? {
+            Github | Gitee | Gitea => format!("{owner}/{repo}/commit/{sha}"),
+            Gitlab => format!("{owner}/{repo}/-/commit/{sha}"),
+            Bitbucket => format!("{owner}/{repo}/commits/{sha}"),
+            Sourcehut => format!("~{owner}/{repo}/commit/{sha}"),
+        };
+        Ok(self.base_url.join(&path)?)
     }
 
-    if url.starts_with("git@codeberg.org:") || url.starts_with("https://codeberg.org/") {
-        let repo_with_owner = url
-            .trim_start_matches("git@codeberg.org:")
-            .trim_start_matches("https://codeberg.org/")
-            .trim_end_matches(".git");
+    fn build_pull_request_url(&self, remote: &ParsedGitRemote, number: u32) -> Option<Url> {
+        let ParsedGitRemote { owner, repo } = remote;
+        if let Some(template) = &self.templates.pull_request {
+            let url = self.expand_template(template, remote, &[("number", &number.to_string())]);
+            return Url::parse(&url).ok();
+        }
 
-        let (owner, repo) = repo_with_owner.split_once('/')?;
+        use GitHostingProviderKind::*;
+        let path = match self.kind? {
+            Github => format!("{owner}/{repo}/pull/{number}"),
+            Gitlab => format!("{owner}/{repo}/-/merge_requests/{number}"),
+            Gitee | Gitea => format!("{owner}/{repo}/pulls/{number}"),
+            Bitbucket => format!("{owner}/{repo}/pull-requests/{number}"),
+            Sourcehut => return None,
+        };
+        self.base_url.join(&path).ok()
+    }
 
-        return Some(ParsedGitRemote {
-            provider: GitHostingProvider::Codeberg,
-            owner,
-            repo,
-        });
+    fn extract_pull_request_number(&self, commit_message: &str) -> Option<u32> {
+        use GitHostingProviderKind::*;
+        match self.kind {
+            // e.g. "See merge request group/project!123"
+            Some(Gitlab) | Some(Gitee) => commit_message
+                .lines()
+                .find_map(|line| number_after(line.split_once("merge request")?.1, '!')),
+            Some(Sourcehut) => None,
+            // e.g. "Fix the frobnicator (#123)" or "Merge pull request #123 from owner/branch"
+            _ => number_after(commit_message.lines().next()?, '#'),
+        }
     }
+}
+
+/// Returns the number following the last occurrence of `sigil` in the text, if any.
+fn number_after(text: &str, sigil: char) -> Option<u32> {
+    text.match_indices(sigil).rev().find_map(|(ix, _)| {
+        let digits = &text[ix + 1..];
+        let end = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(digits.len());
+        digits[..end].parse().ok()
+    })
+}
 
-    None
+pub struct BuildPermalinkParams<'a> {
+    pub remote_url: &'a str,
+    pub sha: &'a str,
+    pub path: &'a str,
+    pub selection: Option<Range<Point>>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn build_permalink(params: BuildPermalinkParams) -> Result<Url> {
+        GitHostingProviderRegistry::new().build_permalink(params, &[])
+    }
+
+    fn parse_git_remote_url(url: &str) -> Option<(String, ParsedGitRemote)> {
+        GitHostingProviderRegistry::new()
+            .provider_for_remote_url(url, &[])
+            .map(|(provider, remote)| (provider.name(), remote))
+    }
+
     #[test]
     fn test_build_github_permalink_from_ssh_url() {
         let permalink = build_permalink(BuildPermalinkParams {
@@ -451,8 +698,8 @@ mod tests {
     #[test]
     fn test_parse_git_remote_url_bitbucket_https_with_username() {
         let url = "https://thorstenballzed@bitbucket.org/thorstenzed/testingrepo.git";
-        let parsed = parse_git_remote_url(url).unwrap();
-        assert!(matches!(parsed.provider, GitHostingProvider::Bitbucket));
+        let (provider, parsed) = parse_git_remote_url(url).unwrap();
+        assert_eq!(provider, "Bitbucket");
         assert_eq!(parsed.owner, "thorstenzed");
         assert_eq!(parsed.repo, "testingrepo");
     }
@@ -460,8 +707,8 @@ mod tests {
     #[test]
     fn test_parse_git_remote_url_bitbucket_https_without_username() {
         let url = "https://bitbucket.org/thorstenzed/testingrepo.git";
-        let parsed = parse_git_remote_url(url).unwrap();
-        assert!(matches!(parsed.provider, GitHostingProvider::Bitbucket));
+        let (provider, parsed) = parse_git_remote_url(url).unwrap();
+        assert_eq!(provider, "Bitbucket");
         assert_eq!(parsed.owner, "thorstenzed");
         assert_eq!(parsed.repo, "testingrepo");
     }
@@ -469,8 +716,8 @@ mod tests {
     #[test]
     fn test_parse_git_remote_url_bitbucket_git() {
         let url = "git@bitbucket.org:thorstenzed/testingrepo.git";
-        let parsed = parse_git_remote_url(url).unwrap();
-        assert!(matches!(parsed.provider, GitHostingProvider::Bitbucket));
+        let (provider, parsed) = parse_git_remote_url(url).unwrap();
+        assert_eq!(provider, "Bitbucket");
         assert_eq!(parsed.owner, "thorstenzed");
         assert_eq!(parsed.repo, "testingrepo");
     }
@@ -700,4 +947,131 @@ mod tests {
         let expected_url = "https://codeberg.org/rajveermalviya/zed/src/commit/faa6f979be417239b2e070dbbf6392b909224e0b/crates/zed/src/main.rs#L24-L48";
         assert_eq!(permalink.to_string(), expected_url.to_string())
     }
+
+    #[test]
+    fn test_build_permalink_for_self_hosted_provider() {
+        let custom_providers = [GitHostingProviderSettings {
+            host_pattern: "gitlab.example.com".into(),
+            provider: Some(GitHostingProviderKind::Gitlab),
+            ..Default::default()
+        }];
+        let permalink = GitHostingProviderRegistry::new()
+            .build_permalink(
+                BuildPermalinkParams {
+                    remote_url: "ssh://git@gitlab.example.com:2222/group/subgroup/zed.git",
+                    sha: "e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7",
+                    path: "crates/zed/src/main.rs",
+                    selection: Some(Point::new(23, 1)..Point::new(47, 10)),
+                },
+                &custom_providers,
+            )
+            .unwrap();
+
+        let expected_url = "https://gitlab.example.com/group/subgroup/zed/-/blob/e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7/crates/zed/src/main.rs#L24-48";
+        assert_eq!(permalink.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_permalink_from_templates() {
+        let custom_providers = [GitHostingProviderSettings {
+            name: Some("Example".into()),
+            host_pattern: "*.example.com".into(),
+            base_url: Some("https://code.example.com/git".into()),
+            permalink_template: Some("{base_url}/{owner}/{repo}/file/{path}?at={sha}".into()),
+            line_fragment_template: Some("line{start_line}-{end_line}".into()),
+            commit_template: Some("{base_url}/{owner}/{repo}/revision/{sha}".into()),
+            pull_request_template: Some("{base_url}/{owner}/{repo}/review/{number}".into()),
+            ..Default::default()
+        }];
+        let registry = GitHostingProviderRegistry::new();
+        let remote_url = "git@git.example.com:team/zed.git";
+
+        let permalink = registry
+            .build_permalink(
+                BuildPermalinkParams {
+                    remote_url,
+                    sha: "faa6f979be417239b2e070dbbf6392b909224e0b",
+                    path: "crates/zed/src/main.rs",
+                    selection: Some(Point::new(6, 1)..Point::new(6, 10)),
+                },
+                &custom_providers,
+            )
+            .unwrap();
+        let expected_url = "https://code.example.com/git/team/zed/file/crates/zed/src/main.rs?at=faa6f979be417239b2e070dbbf6392b909224e0b#line7-7";
+        assert_eq!(permalink.to_string(), expected_url.to_string());
+
+        let commit_url = registry
+            .build_commit_permalink(remote_url, "faa6f979", &custom_providers)
+            .unwrap();
+        assert_eq!(
+            commit_url.to_string(),
+            "https://code.example.com/git/team/zed/revision/faa6f979"
+        );
+
+        let pull_request_url = registry
+            .build_pull_request_url(remote_url, "Fix crash (#42)", &custom_providers)
+            .unwrap();
+        assert_eq!(
+            pull_request_url.to_string(),
+            "https://code.example.com/git/team/zed/review/42"
+        );
+
+        // Hosts that don't match the pattern aren't affected.
+        assert!(registry
+            .build_commit_permalink(
+                "git@example.org:team/zed.git",
+                "faa6f979",
+                &custom_providers
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_build_commit_and_pull_request_urls() {
+        let registry = GitHostingProviderRegistry::new();
+
+        let commit_url = registry
+            .build_commit_permalink("git@github.com:zed-industries/zed.git", "e6ebe79", &[])
+            .unwrap();
+        assert_eq!(
+            commit_url.to_string(),
+            "https://github.com/zed-industries/zed/commit/e6ebe79"
+        );
+
+        let pull_request_url = registry
+            .build_pull_request_url(
+                "https://github.com/zed-industries/zed.git",
+                "Add permalinks for more forges (#1234)\n\nFixes #99",
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            pull_request_url.to_string(),
+            "https://github.com/zed-industries/zed/pull/1234"
+        );
+
+        let merge_request_url = registry
+            .build_pull_request_url(
+                "git@gitlab.com:zed-industries/zed.git",
+                "Merge branch 'feature' into 'main'\n\nSee merge request zed-industries/zed!56",
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            merge_request_url.to_string(),
+            "https://gitlab.com/zed-industries/zed/-/merge_requests/56"
+        );
+
+        let commit_url = registry
+            .build_commit_permalink(
+                "https://codeberg.org/rajveermalviya/zed.git",
+                "faa6f97",
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            commit_url.to_string(),
+            "https://codeberg.org/rajveermalviya/zed/commit/faa6f97"
+        );
+    }
 }
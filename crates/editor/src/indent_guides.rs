@@ -0,0 +1,328 @@
+use std::{cell::RefCell, num::NonZeroU32, ops::Range};
+
+use collections::HashMap;
+use gpui::AppContext;
+use language::{language_settings::IndentGuideSettings, Point};
+use multi_buffer::MultiBufferSnapshot;
+
+/// How far beyond the visible rows to look when resolving the indentation of
+/// blank lines and the extent of the scope containing the cursor.
+const MAX_SCAN_ROWS: u32 = 2000;
+
+/// A vertical guide marking one level of indentation over a run of buffer rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndentGuide {
+    /// The multibuffer rows covered by this guide.
+    pub rows: Range<u32>,
+    /// The indentation level, starting at zero for the outermost guide.
+    pub depth: u32,
+    /// The column at which the guide is drawn.
+    pub column: u32,
+    /// Whether this guide belongs to the scope containing the cursor.
+    pub active: bool,
+    pub settings: IndentGuideSettings,
+}
+
+/// The guides computed for the last layout of an editor, reused until its
+/// buffer is edited, it is scrolled or the cursor moves to another row.
+pub(crate) struct CachedIndentGuides {
+    pub edit_count: usize,
+    pub rows: Range<u32>,
+    pub cursor_row: Option<u32>,
+    pub tab_size_override: Option<NonZeroU32>,
+    pub guides: Vec<IndentGuide>,
+}
+
+#[derive(Clone, Copy)]
+struct LineIndent {
+    /// The indentation level of the line, or `None` for blank lines.
+    level: Option<u32>,
+    tab_size: u32,
+    settings: IndentGuideSettings,
+}
+
+/// Computes the indent guides intersecting the given multibuffer rows.
+///
+/// Blank lines take the smaller indentation of their surrounding non-blank
+/// lines, so that guides continue through gaps within a block but stop at
+/// its end. Guides never span across excerpt boundaries.
+pub fn indent_guides_in_range(
+    rows: Range<u32>,
+    cursor_row: Option<u32>,
+    tab_size_override: Option<NonZeroU32>,
+    snapshot: &MultiBufferSnapshot,
+    cx: &AppContext,
+) -> Vec<IndentGuide> {
+    let max_row = snapshot.max_buffer_row();
+    let rows = rows.start.min(max_row)..rows.end.min(max_row + 1);
+    if rows.is_empty() {
+        return Vec::new();
+    }
+
+    let compute_line_indent = |row: u32| -> LineIndent {
+        let settings = snapshot.settings_at(Point::new(row, 0), cx);
+        let tab_size = tab_size_override.unwrap_or(settings.tab_size).get();
+        if !settings.indent_guides.enabled {
+            return LineIndent {
+                level: Some(0),
+                tab_size,
+                settings: settings.indent_guides,
+            };
+        }
+
+        let mut column = 0;
+        let mut blank = true;
+        for c in snapshot.chars_at(Point::new(row, 0)) {
+            match c {
+                ' ' => column += 1,
+                '\t' => column += tab_size - column % tab_size,
+                '\n' => break,
+                c if c.is_whitespace() => column += 1,
+                _ => {
+                    blank = false;
+                    break;
+                }
+            }
+        }
+        LineIndent {
+            level: (!blank).then_some(column / tab_size),
+            tab_size,
+            settings: settings.indent_guides,
+        }
+    };
+
+    // The scans for blank lines and the active scope visit rows repeatedly, and
+    // resolving the settings of a row is not cheap.
+    let line_indents = RefCell::new(HashMap::<u32, LineIndent>::default());
+    let line_indent = |row: u32| -> LineIndent {
+        if let Some(line) = line_indents.borrow().get(&row) {
+            return *line;
+        }
+        let line = compute_line_indent(row);
+        line_indents.borrow_mut().insert(row, line);
+        line
+    };
+
+    let excerpt_starts = snapshot
+        .excerpt_boundaries_in_range(Point::new(rows.start, 0)..Point::new(rows.end, 0))
+        .map(|boundary| boundary.row)
+        .filter(|row| rows.contains(row))
+        .collect::<Vec<_>>();
+
+    let mut lines = rows.clone().map(line_indent).collect::<Vec<_>>();
+    resolve_blank_lines(&mut lines, rows.start, &line_indent, max_row);
+
+    let active_scope = cursor_row.and_then(|row| active_scope(row, &line_indent, max_row));
+
+    let mut guides = Vec::new();
+    let mut open: Vec<(u32, u32, IndentGuideSettings)> = Vec::new();
+    let mut close_to = |level: usize, end: u32, open: &mut Vec<(u32, u32, IndentGuideSettings)>| {
+        while open.len() > level {
+            let depth = open.len() as u32 - 1;
+            let (start, column, settings) = open.pop().unwrap();
+            let active = settings.highlight_active
+                && active_scope
+                    .as_ref()
+                    .map_or(false, |(active_depth, range)| {
+                        *active_depth == depth && range.start < end && start < range.end
+                    });
+            guides.push(IndentGuide {
+                rows: start..end,
+                depth,
+                column,
+                active,
+                settings,
+            });
+        }
+    };
+
+    for (row, line) in rows.clone().zip(lines) {
+        if excerpt_starts.contains(&row) {
+            close_to(0, row, &mut open);
+        }
+        let level = line.level.unwrap_or(0) as usize;
+        close_to(level, row, &mut open);
+        while open.len() < level {
+            let column = open.len() as u32 * line.tab_size;
+            open.push((row, column, line.settings));
+        }
+    }
+    close_to(0, rows.end, &mut open);
+
+    guides.sort_by_key(|guide| (guide.rows.start, guide.depth));
+    guides
+}
+
+fn resolve_blank_lines(
+    lines: &mut [LineIndent],
+    start_row: u32,
+    line_indent: &impl Fn(u32) -> LineIndent,
+    max_row: u32,
+) {
+    let mut prev_level = None;
+    if lines.first().map_or(false, |line| line.level.is_none()) {
+        prev_level = prev_non_blank_level(start_row, line_indent);
+    }
+
+    let mut ix = 0;
+    while ix < lines.len() {
+        if let Some(level) = lines[ix].level {
+            prev_level = Some(level);
+            ix += 1;
+            continue;
+        }
+
+        let blank_start = ix;
+        while ix < lines.len() && lines[ix].level.is_none() {
+            ix += 1;
+        }
+        let next_level = match lines.get(ix) {
+            Some(line) => line.level,
+            None => next_non_blank_level(start_row + ix as u32, line_indent, max_row),
+        };
+        let level = prev_level.unwrap_or(0).min(next_level.unwrap_or(0));
+        for line in &mut lines[blank_start..ix] {
+            line.level = Some(level);
+        }
+    }
+}
+
+fn prev_non_blank_level(row: u32, line_indent: &impl Fn(u32) -> LineIndent) -> Option<u32> {
+    (row.saturating_sub(MAX_SCAN_ROWS)..row)
+        .rev()
+        .find_map(|row| line_indent(row).level)
+}
+
+fn next_non_blank_level(
+    row: u32,
+    line_indent: &impl Fn(u32) -> LineIndent,
+    max_row: u32,
+) -> Option<u32> {
+    (row..=max_row.min(row.saturating_add(MAX_SCAN_ROWS))).find_map(|row| line_indent(row).level)
+}
+
+/// Returns the depth and rows of the innermost indented scope containing the
+/// given row. A line opening an indented block counts as part of that block.
+fn active_scope(
+    row: u32,
+    line_indent: &impl Fn(u32) -> LineIndent,
+    max_row: u32,
+) -> Option<(u32, Range<u32>)> {
+    let level_at = |row: u32| match line_indent(row).level {
+        Some(level) => level,
+        None => prev_non_blank_level(row, line_indent)
+            .unwrap_or(0)
+            .min(next_non_blank_level(row + 1, line_indent, max_row).unwrap_or(0)),
+    };
+
+    let level = level_at(row);
+    let next_level = next_non_blank_level(row + 1, line_indent, max_row).unwrap_or(0);
+    let (depth, mut start) = if next_level > level && line_indent(row).level.is_some() {
+        (level, row + 1)
+    } else if level > 0 {
+        (level - 1, row)
+    } else {
+        return None;
+    };
+
+    // Blank lines only belong to the scope if a deeper line follows them, so
+    // the scope is delimited by its first and last non-blank lines.
+    let min_row = start.saturating_sub(MAX_SCAN_ROWS);
+    let mut scan_row = start;
+    while scan_row > min_row {
+        scan_row -= 1;
+        match line_indent(scan_row).level {
+            Some(level) if level <= depth => break,
+            Some(_) => start = scan_row,
+            None => {}
+        }
+    }
+    let mut end = row + 1;
+    let max_scan_row = max_row.min(end.saturating_add(MAX_SCAN_ROWS));
+    for scan_row in end..=max_scan_row {
+        match line_indent(scan_row).level {
+            Some(level) if level <= depth => break,
+            Some(_) => end = scan_row + 1,
+            None => {}
+        }
+    }
+
+    Some((depth, start..end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_tests::init_test;
+    use gpui::Context;
+    use language::Buffer;
+    use multi_buffer::MultiBuffer;
+    use text::BufferId;
+
+    fn guides(
+        text: &str,
+        cursor_row: Option<u32>,
+        cx: &mut gpui::TestAppContext,
+    ) -> Vec<(Range<u32>, u32, bool)> {
+        let buffer = cx
+            .new_model(|cx| Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text));
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        cx.update(|cx| {
+            let snapshot = buffer.read(cx).snapshot(cx);
+            indent_guides_in_range(
+                0..snapshot.max_buffer_row() + 1,
+                cursor_row,
+                NonZeroU32::new(4),
+                &snapshot,
+                cx,
+            )
+            .into_iter()
+            .map(|guide| (guide.rows, guide.column, guide.active))
+            .collect()
+        })
+    }
+
+    #[gpui::test]
+    fn test_indent_guides(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let text = "fn a() {\n    if x {\n        b();\n\n        c();\n    }\n}\n";
+        assert_eq!(
+            guides(text, None, cx),
+            vec![(1..6, 0, false), (2..5, 4, false)]
+        );
+
+        // The cursor on a line opening a block activates the guide of that block.
+        assert_eq!(
+            guides(text, Some(1), cx),
+            vec![(1..6, 0, false), (2..5, 4, true)]
+        );
+
+        // Inside a block, the guide of the enclosing scope is active.
+        assert_eq!(
+            guides(text, Some(5), cx),
+            vec![(1..6, 0, true), (2..5, 4, false)]
+        );
+
+        // Trailing blank lines don't extend guides.
+        let text = "a\n\tb\n\t\tc\n\n";
+        assert_eq!(
+            guides(text, Some(2), cx),
+            vec![(1..3, 0, false), (2..3, 4, true)]
+        );
+    }
+
+    #[gpui::test]
+    fn test_indent_guides_disabled(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.indent_guides = Some(IndentGuideSettings {
+                enabled: false,
+                line_width: 1,
+                active_line_width: 1,
+                highlight_active: true,
+            })
+        });
+
+        assert_eq!(guides("a\n    b\n", Some(1), cx), vec![]);
+    }
+}
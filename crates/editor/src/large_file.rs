@@ -0,0 +1,141 @@
+use gpui::{
+    div, AppContext, IntoElement, ParentElement, Render, Subscription, View, ViewContext, WeakView,
+};
+use ui::{Button, ButtonCommon, Clickable, FluentBuilder, LabelSize, Tooltip};
+use util::ResultExt;
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::{actions::EnableLargeFileFeatures, Editor, InlayHintRefreshReason};
+
+impl Editor {
+    /// Whether this editor shows a single buffer that is in large-file mode.
+    pub fn is_large_file(&self, cx: &AppContext) -> bool {
+        self.buffer
+            .read(cx)
+            .as_singleton()
+            .map_or(false, |buffer| buffer.read(cx).is_large_file())
+    }
+
+    /// Drops the inlay hints and Copilot suggestions of buffers that entered
+    /// large-file mode, and fetches hints for those that left it.
+    pub(crate) fn large_file_mode_changed(&mut self, cx: &mut ViewContext<Self>) {
+        let multi_buffer = self.buffer.read(cx);
+        let large_file_excerpts = multi_buffer
+            .all_buffers()
+            .into_iter()
+            .filter(|buffer| buffer.read(cx).is_large_file())
+            .flat_map(|buffer| multi_buffer.excerpts_for_buffer(&buffer, cx))
+            .map(|(excerpt_id, _)| excerpt_id)
+            .collect::<Vec<_>>();
+        if !large_file_excerpts.is_empty() {
+            self.clear_copilot_suggestions(cx);
+            self.refresh_inlay_hints(
+                InlayHintRefreshReason::ExcerptsRemoved(large_file_excerpts),
+                cx,
+            );
+        }
+        self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+    }
+
+    /// Takes this editor's buffers out of large-file mode, turning syntax
+    /// highlighting, language servers, git diffs, inlay hints and soft wrapping
+    /// back on for them.
+    pub fn enable_large_file_features(
+        &mut self,
+        _: &EnableLargeFileFeatures,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let buffers = self
+            .buffer
+            .read(cx)
+            .all_buffers()
+            .into_iter()
+            .filter(|buffer| buffer.read(cx).is_large_file())
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            if let Some(project) = self.project.clone() {
+                project
+                    .update(cx, |project, cx| {
+                        project.enable_large_file_features(&buffer, cx)
+                    })
+                    .detach_and_log_err(cx);
+            } else {
+                buffer.update(cx, |buffer, cx| buffer.set_large_file(false, cx));
+            }
+        }
+    }
+}
+
+/// A status bar item shown for files opened in large-file mode, which turns
+/// their features back on when clicked.
+pub struct LargeFileIndicator {
+    large_file_editor: Option<WeakView<Editor>>,
+    _observe_active_editor: Option<Subscription>,
+}
+
+impl LargeFileIndicator {
+    pub fn new() -> Self {
+        Self {
+            large_file_editor: None,
+            _observe_active_editor: None,
+        }
+    }
+
+    fn update_large_file(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
+        self.large_file_editor = editor
+            .read(cx)
+            .is_large_file(cx)
+            .then(|| editor.downgrade());
+        cx.notify();
+    }
+}
+
+impl Default for LargeFileIndicator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Render for LargeFileIndicator {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div().when_some(self.large_file_editor.clone(), |el, editor| {
+            el.child(
+                Button::new("enable-large-file-features", "Large File")
+                    .label_size(LabelSize::Small)
+                    .on_click(cx.listener(move |_, _, cx| {
+                        editor
+                            .update(cx, |editor, cx| {
+                                editor.enable_large_file_features(&EnableLargeFileFeatures, cx)
+                            })
+                            .log_err();
+                    }))
+                    .tooltip(|cx| {
+                        Tooltip::with_meta(
+                            "Enable Large File Features",
+                            Some(&EnableLargeFileFeatures),
+                            "Syntax highlighting, language servers and git diffs are disabled",
+                            cx,
+                        )
+                    }),
+            )
+        })
+    }
+}
+
+impl StatusItemView for LargeFileIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.observe(&editor, Self::update_large_file));
+            self.update_large_file(editor, cx);
+        } else {
+            self.large_file_editor = None;
+            self._observe_active_editor = None;
+        }
+
+        cx.notify();
+    }
+}
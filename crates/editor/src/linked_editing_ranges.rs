@@ -0,0 +1,195 @@
+use futures::future::join_all;
+use gpui::ViewContext;
+use language::language_settings::language_settings;
+use multi_buffer::{Anchor, MultiBufferSnapshot, ToOffset};
+use regex::Regex;
+use std::{ops::Range, sync::Arc, time::Duration};
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+pub(crate) const LINKED_EDITING_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Ranges that are edited together, such as the names of an opening and a
+/// closing tag.
+pub(crate) struct LinkedEditGroup {
+    pub ranges: Vec<Range<Anchor>>,
+    /// Matches the whole text of the ranges while they stay linked, when the
+    /// language server provides a word pattern.
+    pub word_pattern: Option<Regex>,
+}
+
+impl LinkedEditGroup {
+    /// Returns whether replacing `range` of the ranges' common `text` with
+    /// `new_text` should be mirrored into the other ranges.
+    fn accepts_edit(&self, text: &str, range: Range<usize>, new_text: &str) -> bool {
+        match &self.word_pattern {
+            Some(word_pattern) => {
+                let mut edited_text = text.to_string();
+                edited_text.replace_range(range, new_text);
+                edited_text.is_empty() || word_pattern.is_match(&edited_text)
+            }
+            None => new_text.chars().all(is_linked_edit_char),
+        }
+    }
+}
+
+impl Editor {
+    /// Requests the ranges linked to the ones containing each selection's head,
+    /// such as the names of an opening and a closing tag, so that edits to one
+    /// of them can be mirrored into the others.
+    pub(crate) fn refresh_linked_ranges(&mut self, cx: &mut ViewContext<Self>) {
+        if self.mode != EditorMode::Full || self.pending_rename.is_some() {
+            return;
+        }
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+
+        let multibuffer = self.buffer.read(cx);
+        let positions = self
+            .selections
+            .disjoint_anchors()
+            .iter()
+            .filter_map(|selection| {
+                let head = selection.head();
+                let (buffer, position) = multibuffer.text_anchor_for_position(head, cx)?;
+                let enabled = {
+                    let buffer = buffer.read(cx);
+                    let language = buffer.language_at(position);
+                    language_settings(language.as_ref(), buffer.file(), cx).linked_edits
+                };
+                enabled.then_some((head.excerpt_id, buffer, position))
+            })
+            .collect::<Vec<_>>();
+        if positions.is_empty() {
+            self.linked_editing_task = None;
+            self.linked_edit_ranges.clear();
+            return;
+        }
+
+        self.linked_editing_task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(LINKED_EDITING_DEBOUNCE_TIMEOUT)
+                .await;
+
+            let Some(requests) = project
+                .update(&mut cx, |project, cx| {
+                    positions
+                        .into_iter()
+                        .map(|(excerpt_id, buffer, position)| {
+                            let request = project.linked_edit(&buffer, position, cx);
+                            async move { (excerpt_id, request.await) }
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .log_err()
+            else {
+                return;
+            };
+            let responses = join_all(requests).await;
+
+            this.update(&mut cx, |this, cx| {
+                let snapshot = this.buffer.read(cx).snapshot(cx);
+                this.linked_edit_ranges = responses
+                    .into_iter()
+                    .filter_map(|(excerpt_id, linked_ranges)| {
+                        let linked_ranges = linked_ranges.log_err()?;
+                        let ranges = linked_ranges
+                            .ranges
+                            .into_iter()
+                            .map(|range| {
+                                Some(
+                                    snapshot.anchor_in_excerpt(excerpt_id, range.start)?
+                                        ..snapshot.anchor_in_excerpt(excerpt_id, range.end)?,
+                                )
+                            })
+                            .collect::<Option<Vec<_>>>()?;
+                        if ranges.len() < 2 {
+                            return None;
+                        }
+                        let word_pattern = linked_ranges
+                            .word_pattern
+                            .and_then(|pattern| Regex::new(&format!("^(?:{pattern})$")).log_err());
+                        Some(LinkedEditGroup {
+                            ranges,
+                            word_pattern,
+                        })
+                    })
+                    .collect();
+            })
+            .ok();
+        }));
+    }
+
+    /// Returns the edits that mirror the given edits into the ranges linked to
+    /// the ones they're contained in.
+    ///
+    /// Edits are only mirrored when all of the linked ranges still have the
+    /// same text, and when the edited text matches the language server's word
+    /// pattern or, without one, when they insert characters that can be part
+    /// of a tag name or delete text.
+    pub(crate) fn linked_edits<T: ToOffset>(
+        &self,
+        edits: &[(Range<T>, Arc<str>)],
+        snapshot: &MultiBufferSnapshot,
+    ) -> Vec<(Range<usize>, Arc<str>)> {
+        if self.linked_edit_ranges.is_empty() {
+            return Vec::new();
+        }
+
+        let edited_ranges = edits
+            .iter()
+            .map(|(range, _)| range.start.to_offset(snapshot)..range.end.to_offset(snapshot))
+            .collect::<Vec<_>>();
+        let mut linked_edits = Vec::new();
+        for (edited_range, (_, text)) in edited_ranges.iter().zip(edits) {
+            for linked_group in &self.linked_edit_ranges {
+                let group = linked_group
+                    .ranges
+                    .iter()
+                    .map(|range| range.start.to_offset(snapshot)..range.end.to_offset(snapshot))
+                    .collect::<Vec<_>>();
+                let Some(edited_ix) = group.iter().position(|range| {
+                    range.start <= edited_range.start && edited_range.end <= range.end
+                }) else {
+                    continue;
+                };
+                let edited_text = snapshot
+                    .text_for_range(group[edited_ix].clone())
+                    .collect::<String>();
+                let texts_match = group.iter().all(|range| {
+                    snapshot.text_for_range(range.clone()).collect::<String>() == edited_text
+                });
+                if !texts_match {
+                    break;
+                }
+
+                let start_delta = edited_range.start - group[edited_ix].start;
+                let edited_text_range = start_delta..start_delta + edited_range.len();
+                if !linked_group.accepts_edit(&edited_text, edited_text_range, text) {
+                    break;
+                }
+                for (ix, range) in group.iter().enumerate() {
+                    let start = range.start + start_delta;
+                    let linked_range = start..start + edited_range.len();
+                    let overlaps_edit = edited_ranges.iter().any(|edited_range| {
+                        edited_range.start <= linked_range.end
+                            && linked_range.start <= edited_range.end
+                    });
+                    if ix != edited_ix && !overlaps_edit {
+                        linked_edits.push((linked_range, text.clone()));
+                    }
+                }
+                break;
+            }
+        }
+        linked_edits.sort_unstable_by_key(|(range, _)| range.start);
+        linked_edits.dedup_by_key(|(range, _)| range.start);
+        linked_edits
+    }
+}
+
+fn is_linked_edit_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '$')
+}
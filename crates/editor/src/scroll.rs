@@ -321,7 +321,8 @@ impl Editor {
             cx.spawn(|editor, mut cx| async move {
                 editor
                     .update(&mut cx, |editor, cx| {
-                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx)
+                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                        editor.refresh_spelling(cx);
                     })
                     .ok()
             })
@@ -378,6 +379,7 @@ impl Editor {
         );
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+        self.refresh_spelling(cx);
     }
 
     pub fn scroll_position(&self, cx: &mut ViewContext<Self>) -> gpui::Point<f32> {
@@ -394,6 +396,7 @@ impl Editor {
             .row;
         self.scroll_manager
             .set_anchor(scroll_anchor, top_row, true, false, workspace_id, cx);
+        self.refresh_spelling(cx);
     }
 
     pub(crate) fn set_scroll_anchor_remote(
@@ -0,0 +1,644 @@
+use collections::HashMap;
+use gpui::{px, AppContext, HighlightStyle, Task, UnderlineStyle, ViewContext};
+use language::{
+    language_settings::{language_settings, SpellCheckMode},
+    Bias, BufferId, BufferSnapshot, OffsetRangeExt, Point,
+};
+use multi_buffer::{Anchor, ToOffset as _, ToPoint as _};
+use project::{Fs, Worktree};
+use spell_check::{words_to_check, SpellChecker, WordChecker};
+use std::{ops::Range, path::Path, sync::Arc, time::Duration};
+use theme::ActiveTheme as _;
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+const SPELLING_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+pub(crate) enum SpellingError {}
+
+/// A code action offered for a misspelled word under the cursor.
+#[derive(Clone, Debug)]
+pub(crate) enum SpellingAction {
+    Replace {
+        range: Range<Anchor>,
+        replacement: String,
+    },
+    AddToUserDictionary {
+        word: String,
+    },
+    AddToProjectDictionary {
+        word: String,
+        worktree_id: usize,
+        worktree_abs_path: Arc<Path>,
+    },
+}
+
+impl SpellingAction {
+    pub(crate) fn label(&self) -> String {
+        match self {
+            SpellingAction::Replace { replacement, .. } => format!("Change to \"{replacement}\""),
+            SpellingAction::AddToUserDictionary { word } => {
+                format!("Add \"{word}\" to user dictionary")
+            }
+            SpellingAction::AddToProjectDictionary { word, .. } => {
+                format!("Add \"{word}\" to project dictionary")
+            }
+        }
+    }
+}
+
+/// The state of checking the spelling of an editor's buffers, which is only
+/// done for the visible rows and for the rows edited since the last check.
+#[derive(Default)]
+pub(crate) struct SpellingState {
+    /// The version of each buffer when its spelling was last checked, used to
+    /// find the rows edited since.
+    checked_versions: HashMap<BufferId, clock::Global>,
+    /// Whether the misspelled words found so far are outdated, because the
+    /// settings or the dictionaries changed after they were checked.
+    outdated: bool,
+    task: Option<Task<()>>,
+}
+
+impl Editor {
+    /// Checks the spelling of the visible rows and of the rows edited since the
+    /// last check, according to the `spell_check` setting of their languages,
+    /// and underlines the misspelled words. The words found in other rows are
+    /// kept.
+    pub(crate) fn refresh_spelling(&mut self, cx: &mut ViewContext<Self>) {
+        if self.mode != EditorMode::Full {
+            return;
+        }
+
+        let multibuffer = self.buffer.read(cx);
+        let snapshot = multibuffer.snapshot(cx);
+        let visible_start = self.scroll_manager.anchor().anchor.to_point(&snapshot);
+        let visible_end = snapshot.clip_point(
+            visible_start + Point::new(self.visible_line_count().unwrap_or(0.).ceil() as u32, 0),
+            Bias::Left,
+        );
+        let visible_ranges = multibuffer
+            .range_to_buffer_ranges(visible_start..visible_end, cx)
+            .into_iter()
+            .map(|(_, range, excerpt_id)| (excerpt_id, range))
+            .collect::<HashMap<_, _>>();
+
+        let mut excerpts = Vec::new();
+        let mut versions = HashMap::default();
+        if let Some(spell_checker) = SpellChecker::try_global(cx) {
+            for (excerpt_id, buffer, range) in snapshot.excerpts() {
+                let mode = language_settings(buffer.language(), buffer.file(), cx).spell_check;
+                let is_large_file = multibuffer
+                    .buffer(buffer.remote_id())
+                    .map_or(false, |buffer| buffer.read(cx).is_large_file());
+                if mode == SpellCheckMode::Off || is_large_file {
+                    continue;
+                }
+                let worktree_id = buffer.file().map(|file| file.worktree_id());
+                let Some(checker) = spell_checker.word_checker(worktree_id) else {
+                    continue;
+                };
+                versions.insert(buffer.remote_id(), buffer.version().clone());
+
+                let edited_ranges = self
+                    .spelling
+                    .checked_versions
+                    .get(&buffer.remote_id())
+                    .into_iter()
+                    .flat_map(|version| buffer.edits_since::<usize>(version))
+                    .map(|edit| edit.new);
+                let context = range.context.to_offset(buffer);
+                let mut rows = edited_ranges
+                    .chain(visible_ranges.get(&excerpt_id).cloned())
+                    .filter_map(|range| {
+                        let rows = expand_to_rows(buffer, range);
+                        let rows = rows.start.max(context.start)..rows.end.min(context.end);
+                        (rows.start <= rows.end).then_some(rows)
+                    })
+                    .collect::<Vec<_>>();
+                if rows.is_empty() {
+                    continue;
+                }
+                rows.sort_unstable_by_key(|rows| rows.start);
+                let mut merged_rows: Vec<Range<usize>> = Vec::new();
+                for rows in rows {
+                    if let Some(last) = merged_rows.last_mut().filter(|last| rows.start <= last.end)
+                    {
+                        last.end = last.end.max(rows.end);
+                    } else {
+                        merged_rows.push(rows);
+                    }
+                }
+                excerpts.push((excerpt_id, buffer.clone(), merged_rows, mode, checker));
+            }
+        }
+        if versions.is_empty() {
+            self.spelling = SpellingState::default();
+            self.clear_highlights::<SpellingError>(cx);
+            return;
+        }
+        if excerpts.is_empty() {
+            return;
+        }
+
+        let style = HighlightStyle {
+            underline: Some(UnderlineStyle {
+                thickness: px(1.),
+                color: Some(cx.theme().status().error),
+                wavy: true,
+            }),
+            ..Default::default()
+        };
+        let outdated = self.spelling.outdated;
+        self.spelling.task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(SPELLING_DEBOUNCE_TIMEOUT)
+                .await;
+
+            let (checked_rows, mut misspelled) = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut checked_rows = Vec::new();
+                    let mut misspelled = Vec::new();
+                    for (excerpt_id, buffer, rows, mode, checker) in excerpts {
+                        let anchor_range = |range: Range<usize>| {
+                            let start = snapshot
+                                .anchor_in_excerpt(excerpt_id, buffer.anchor_after(range.start))?;
+                            let end = snapshot
+                                .anchor_in_excerpt(excerpt_id, buffer.anchor_before(range.end))?;
+                            Some(start..end)
+                        };
+                        for rows in rows {
+                            checked_rows.extend(anchor_range(rows.clone()));
+                            let checked_ranges = match mode {
+                                SpellCheckMode::Off => continue,
+                                SpellCheckMode::CommentsAndStrings => {
+                                    buffer.override_scope_ranges(rows, &["comment", "string"])
+                                }
+                                SpellCheckMode::AllText => subtract_ranges(
+                                    rows.clone(),
+                                    &buffer.override_scope_ranges(rows, &["code"]),
+                                ),
+                            };
+                            for checked_range in checked_ranges {
+                                misspelled.extend(
+                                    misspelled_words(&buffer, checked_range, &checker)
+                                        .filter_map(&anchor_range),
+                                );
+                            }
+                        }
+                    }
+                    (checked_rows, misspelled)
+                })
+                .await;
+
+            this.update(&mut cx, |this, cx| {
+                let snapshot = this.buffer.read(cx).snapshot(cx);
+                if let Some((_, previous)) = this
+                    .text_highlights::<SpellingError>(cx)
+                    .filter(|_| !outdated)
+                {
+                    let checked_rows = checked_rows
+                        .iter()
+                        .map(|rows| rows.start.to_offset(&snapshot)..=rows.end.to_offset(&snapshot))
+                        .collect::<Vec<_>>();
+                    misspelled.extend(
+                        previous
+                            .iter()
+                            .filter(|word| {
+                                let start = word.start.to_offset(&snapshot);
+                                !checked_rows.iter().any(|rows| rows.contains(&start))
+                            })
+                            .cloned(),
+                    );
+                }
+                misspelled.sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+                this.spelling.checked_versions = versions;
+                this.spelling.outdated = false;
+                this.highlight_text::<SpellingError>(misspelled, style, cx);
+            })
+            .log_err();
+        }));
+    }
+
+    /// Checks the spelling of the visible rows again, discarding the misspelled
+    /// words found so far, as when the settings or the dictionaries change.
+    pub(crate) fn recheck_spelling(&mut self, cx: &mut ViewContext<Self>) {
+        self.spelling.checked_versions.clear();
+        self.spelling.outdated = true;
+        self.refresh_spelling(cx);
+    }
+
+    /// Returns the corrections and dictionary actions for the misspelled word
+    /// under the newest cursor, if any.
+    pub(crate) fn spelling_actions(&self, cx: &AppContext) -> Vec<SpellingAction> {
+        let Some((_, misspelled)) = self.text_highlights::<SpellingError>(cx) else {
+            return Vec::new();
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let cursor = self.selections.newest_anchor().head();
+        let Some(range) = misspelled
+            .iter()
+            .find(|range| {
+                range.start.cmp(&cursor, &snapshot).is_le()
+                    && range.end.cmp(&cursor, &snapshot).is_ge()
+            })
+            .cloned()
+        else {
+            return Vec::new();
+        };
+        let Some((buffer, _)) = self
+            .buffer
+            .read(cx)
+            .text_anchor_for_position(range.start, cx)
+        else {
+            return Vec::new();
+        };
+
+        let file = buffer.read(cx).file();
+        let worktree_id = file.map(|file| file.worktree_id());
+        let Some(checker) =
+            SpellChecker::try_global(cx).and_then(|checker| checker.word_checker(worktree_id))
+        else {
+            return Vec::new();
+        };
+
+        let word = snapshot.text_for_range(range.clone()).collect::<String>();
+        let mut actions = checker
+            .suggestions(&word)
+            .into_iter()
+            .map(|replacement| SpellingAction::Replace {
+                range: range.clone(),
+                replacement,
+            })
+            .collect::<Vec<_>>();
+        actions.push(SpellingAction::AddToUserDictionary { word: word.clone() });
+        if let Some(worktree) = project::File::from_dyn(file).map(|file| file.worktree.read(cx)) {
+            if is_local_directory(worktree) {
+                actions.push(SpellingAction::AddToProjectDictionary {
+                    word,
+                    worktree_id: worktree_id.unwrap_or_default(),
+                    worktree_abs_path: worktree.abs_path(),
+                });
+            }
+        }
+        actions
+    }
+
+    pub(crate) fn apply_spelling_action(
+        &mut self,
+        action: SpellingAction,
+        fs: Arc<dyn Fs>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match action {
+            SpellingAction::Replace { range, replacement } => {
+                self.transact(cx, |editor, cx| {
+                    editor.buffer.update(cx, |buffer, cx| {
+                        buffer.edit([(range, replacement)], None, cx)
+                    });
+                });
+            }
+            SpellingAction::AddToUserDictionary { word } => {
+                SpellChecker::add_user_word(word, fs, cx).detach_and_log_err(cx);
+            }
+            SpellingAction::AddToProjectDictionary {
+                word,
+                worktree_id,
+                worktree_abs_path,
+            } => {
+                SpellChecker::add_worktree_word(word, worktree_id, &worktree_abs_path, fs, cx)
+                    .detach_and_log_err(cx);
+            }
+        }
+    }
+}
+
+fn is_local_directory(worktree: &Worktree) -> bool {
+    worktree.as_local().is_some() && worktree.root_entry().map_or(false, |entry| entry.is_dir())
+}
+
+/// Returns the ranges of the words within `range` that aren't spelled correctly.
+fn misspelled_words<'a>(
+    buffer: &'a BufferSnapshot,
+    range: Range<usize>,
+    checker: &'a WordChecker,
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    let content = buffer.text_for_range(range.clone()).collect::<String>();
+    words_to_check(&content)
+        .into_iter()
+        .filter(move |word| !checker.is_correct(&content[word.clone()]))
+        .map(move |word| range.start + word.start..range.start + word.end)
+}
+
+/// Extends `range` to the start of its first row and to the end of its last.
+fn expand_to_rows(buffer: &BufferSnapshot, range: Range<usize>) -> Range<usize> {
+    let start = buffer.offset_to_point(range.start);
+    let end = buffer.offset_to_point(range.end);
+    buffer.point_to_offset(Point::new(start.row, 0))
+        ..buffer.point_to_offset(Point::new(end.row, buffer.line_len(end.row)))
+}
+
+/// Returns the parts of `range` that aren't covered by the sorted, disjoint
+/// `excluded` ranges.
+fn subtract_ranges(range: Range<usize>, excluded: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut result = Vec::new();
+    let mut start = range.start;
+    for excluded in excluded {
+        if excluded.start > start {
+            result.push(start..excluded.start.min(range.end));
+        }
+        start = start.max(excluded.end);
+    }
+    if start < range.end {
+        result.push(start..range.end);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{ConfirmCodeAction, ToggleCodeActions},
+        editor_tests::{init_test, update_test_language_settings},
+        ContextMenu,
+    };
+    use gpui::{TestAppContext, View, VisualTestContext};
+    use language::{Language, LanguageConfig, LanguageMatcher, Point};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use util::paths;
+    use workspace::Workspace;
+
+    const AFF: &str = "SET UTF-8\n";
+    const DIC: &str = "7\nthis\nis\nsome\nabout\nstring\ncomment\nhello\n";
+    const TEXT: &str =
+        "// This is some coment about helo\nfn main() {\n    let wrod = \"some strng\";\n}\n";
+
+    #[gpui::test]
+    async fn test_spelling_underlines(cx: &mut TestAppContext) {
+        let (_, editor, mut cx) = init_spelling_test(TEXT, cx).await;
+        assert_eq!(
+            underlined_words(&editor, &mut cx),
+            ["coment", "helo", "strng"]
+        );
+
+        update_test_language_settings(&mut cx, |settings| {
+            settings.defaults.spell_check = Some(SpellCheckMode::Off);
+        });
+        settle(&mut cx);
+        assert!(underlined_words(&editor, &mut cx).is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_spelling_of_visible_and_edited_rows(cx: &mut TestAppContext) {
+        let text = format!("{TEXT}{}// speling\n", "\n".repeat(200));
+        let (_, editor, mut cx) = init_spelling_test(&text, cx).await;
+        assert_eq!(
+            underlined_words(&editor, &mut cx),
+            ["coment", "helo", "strng"],
+            "only the visible rows are checked"
+        );
+
+        editor.update(&mut cx, |editor, cx| {
+            editor.set_scroll_position(gpui::Point::new(0., 200.), cx)
+        });
+        settle(&mut cx);
+        assert_eq!(
+            underlined_words(&editor, &mut cx),
+            ["coment", "helo", "strng", "speling"],
+            "the words found in rows scrolled out of view are kept"
+        );
+
+        editor.update(&mut cx, |editor, cx| {
+            editor.buffer().update(cx, |buffer, cx| {
+                buffer.edit(
+                    [(Point::new(0, 16)..Point::new(0, 22), "comment")],
+                    None,
+                    cx,
+                )
+            })
+        });
+        settle(&mut cx);
+        assert_eq!(
+            underlined_words(&editor, &mut cx),
+            ["helo", "strng", "speling"],
+            "edited rows are checked even when they aren't visible"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_spelling_code_actions(cx: &mut TestAppContext) {
+        let (fs, editor, mut cx) = init_spelling_test(TEXT, cx).await;
+
+        assert_eq!(
+            code_action_labels(&editor, Point::new(0, 18), &mut cx),
+            [
+                "Change to \"comment\"",
+                "Add \"coment\" to user dictionary",
+                "Add \"coment\" to project dictionary",
+            ]
+        );
+        confirm_code_action(&editor, 0, &mut cx);
+        assert_eq!(
+            editor.update(&mut cx, |editor, cx| editor.text(cx)),
+            TEXT.replace("coment", "comment")
+        );
+        assert_eq!(underlined_words(&editor, &mut cx), ["helo", "strng"]);
+
+        assert_eq!(
+            code_action_labels(&editor, Point::new(0, 31), &mut cx),
+            [
+                "Change to \"hello\"",
+                "Add \"helo\" to user dictionary",
+                "Add \"helo\" to project dictionary",
+            ]
+        );
+        confirm_code_action(&editor, 1, &mut cx);
+        assert_eq!(underlined_words(&editor, &mut cx), ["strng"]);
+        assert_eq!(
+            fs.load(&paths::USER_DICTIONARY).await.unwrap(),
+            "helo\n".to_string()
+        );
+
+        code_action_labels(&editor, Point::new(2, 22), &mut cx);
+        confirm_code_action(&editor, 2, &mut cx);
+        assert!(underlined_words(&editor, &mut cx).is_empty());
+        assert_eq!(
+            fs.load("/root/.zed/dictionary.txt".as_ref()).await.unwrap(),
+            "strng\n".to_string()
+        );
+
+        assert!(
+            code_action_labels(&editor, Point::new(1, 4), &mut cx).is_empty(),
+            "no spelling actions are offered outside of misspelled words"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_project_dictionary_changes(cx: &mut TestAppContext) {
+        let (fs, editor, mut cx) = init_spelling_test(TEXT, cx).await;
+        assert_eq!(
+            underlined_words(&editor, &mut cx),
+            ["coment", "helo", "strng"]
+        );
+
+        fs.insert_file(
+            "/root/.zed/dictionary.txt",
+            b"# Project words\nstrng\nhelo\n".to_vec(),
+        )
+        .await;
+        settle(&mut cx);
+        assert_eq!(underlined_words(&editor, &mut cx), ["coment"]);
+        editor.update(&mut cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            let worktree_id = buffer.read(cx).file().unwrap().worktree_id();
+            let checker = SpellChecker::global(cx)
+                .word_checker(Some(worktree_id))
+                .unwrap();
+            assert!(checker.is_correct("strng"));
+            assert!(!checker.is_correct("coment"));
+        });
+
+        fs.remove_file("/root/.zed/dictionary.txt".as_ref(), Default::default())
+            .await
+            .unwrap();
+        settle(&mut cx);
+        assert_eq!(
+            underlined_words(&editor, &mut cx),
+            ["coment", "helo", "strng"]
+        );
+    }
+
+    async fn init_spelling_test(
+        text: &str,
+        cx: &mut TestAppContext,
+    ) -> (Arc<FakeFs>, View<Editor>, VisualTestContext) {
+        init_test(cx, |settings| {
+            settings.defaults.spell_check = Some(SpellCheckMode::CommentsAndStrings)
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            paths::DICTIONARIES_DIR.as_path(),
+            json!({ "en_US.aff": AFF, "en_US.dic": DIC }),
+        )
+        .await;
+        fs.insert_tree("/root", json!({ ".zed": {}, "main.rs": text }))
+            .await;
+        cx.update(|cx| spell_check::init(fs.clone(), cx));
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(
+                Language::new(
+                    LanguageConfig {
+                        name: "Rust".into(),
+                        matcher: LanguageMatcher {
+                            path_suffixes: vec!["rs".to_string()],
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    Some(tree_sitter_rust::language()),
+                )
+                .with_override_query("(line_comment) @comment (string_literal) @string")
+                .unwrap(),
+            ))
+        });
+
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root_view(cx).unwrap();
+        let mut cx = VisualTestContext::from_window(*window, cx);
+        let worktree_id = project.update(&mut cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let editor = workspace
+            .update(&mut cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "main.rs"), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        editor.update(&mut cx, |editor, cx| editor.focus(cx));
+        settle(&mut cx);
+
+        (fs, editor, cx)
+    }
+
+    /// Waits for the dictionaries to load and for the spelling to be checked.
+    fn settle(cx: &mut VisualTestContext) {
+        cx.run_until_parked();
+        cx.executor().advance_clock(SPELLING_DEBOUNCE_TIMEOUT);
+        cx.run_until_parked();
+    }
+
+    fn underlined_words(editor: &View<Editor>, cx: &mut VisualTestContext) -> Vec<String> {
+        editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer.read(cx).snapshot(cx);
+            editor
+                .text_highlights::<SpellingError>(cx)
+                .map(|(_, ranges)| {
+                    ranges
+                        .iter()
+                        .map(|range| snapshot.text_for_range(range.clone()).collect())
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// Moves the cursor to `position` and returns the labels of the code
+    /// actions offered there, leaving the code actions menu open.
+    fn code_action_labels(
+        editor: &View<Editor>,
+        position: Point,
+        cx: &mut VisualTestContext,
+    ) -> Vec<String> {
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| s.select_ranges([position..position]));
+            editor.toggle_code_actions(
+                &ToggleCodeActions {
+                    deployed_from_indicator: false,
+                },
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        editor.update(cx, |editor, _| match editor.context_menu.read().as_ref() {
+            Some(ContextMenu::CodeActions(menu)) => {
+                menu.actions.iter().map(|action| action.label()).collect()
+            }
+            _ => Vec::new(),
+        })
+    }
+
+    fn confirm_code_action(editor: &View<Editor>, item_ix: usize, cx: &mut VisualTestContext) {
+        editor
+            .update(cx, |editor, cx| {
+                editor.confirm_code_action(
+                    &ConfirmCodeAction {
+                        item_ix: Some(item_ix),
+                    },
+                    cx,
+                )
+            })
+            .unwrap()
+            .detach();
+        settle(cx);
+    }
+
+    #[test]
+    fn test_subtract_ranges() {
+        assert_eq!(subtract_ranges(0..10, &[]), vec![0..10]);
+        assert_eq!(
+            subtract_ranges(0..10, &[2..4, 6..8]),
+            vec![0..2, 4..6, 8..10]
+        );
+        assert_eq!(subtract_ranges(2..10, &[0..4, 8..12]), vec![4..8]);
+        assert_eq!(subtract_ranges(0..10, &[0..10]), Vec::<Range<usize>>::new());
+    }
+}
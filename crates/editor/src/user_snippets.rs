@@ -0,0 +1,217 @@
+use anyhow::{Context as _, Result};
+use chrono::{Datelike as _, Local, Timelike as _};
+use collections::HashMap;
+use gpui::{AppContext, Global};
+use language::{Buffer, CodeLabel, Completion, Documentation, Point, ToOffset, ToPoint};
+use lsp::LanguageServerId;
+use snippet::{parse_snippet_file, UserSnippet};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// The snippets defined in the user's snippets directory.
+///
+/// Files named after a language, such as `rust.json`, contain snippets for that
+/// language, while `*.code-snippets` files contain snippets for every language,
+/// optionally restricted by their `scope`.
+#[derive(Default)]
+pub struct UserSnippets {
+    files: BTreeMap<PathBuf, Vec<UserSnippet>>,
+}
+
+impl Global for UserSnippets {}
+
+impl UserSnippets {
+    /// Replaces the snippets defined in the file at the given path, removing
+    /// them if `content` is `None` because the file was deleted.
+    pub fn set_snippet_file(
+        path: PathBuf,
+        content: Option<&str>,
+        cx: &mut AppContext,
+    ) -> Result<()> {
+        let snippets = match content {
+            Some(content) => parse_snippet_file(content)
+                .with_context(|| format!("failed to parse snippet file {path:?}"))?,
+            None => Vec::new(),
+        };
+
+        let this = cx.default_global::<Self>();
+        if snippets.is_empty() {
+            this.files.remove(&path);
+        } else {
+            this.files.insert(path, snippets);
+        }
+        Ok(())
+    }
+
+    /// Returns the snippets that apply to the given language.
+    pub fn snippets_for_language<'a>(
+        &'a self,
+        language: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a UserSnippet> + 'a {
+        self.files.iter().flat_map(move |(path, snippets)| {
+            let is_global = path
+                .extension()
+                .map_or(false, |extension| extension == "code-snippets");
+            let file_applies = is_global
+                || path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .zip(language)
+                    .map_or(false, |(stem, language)| language_matches(language, stem));
+
+            snippets.iter().filter(move |snippet| {
+                if !file_applies {
+                    return false;
+                }
+                match (&snippet.scope, is_global) {
+                    (Some(scope), true) => language.map_or(false, |language| {
+                        scope.iter().any(|id| language_matches(language, id))
+                    }),
+                    _ => true,
+                }
+            })
+        })
+    }
+}
+
+/// Returns whether a language name, such as `Shell Script`, refers to the same
+/// language as an identifier used in snippet files, such as `shellscript`.
+fn language_matches(language_name: &str, id: &str) -> bool {
+    fn normalize(name: &str) -> String {
+        name.to_lowercase()
+            .replace("++", "pp")
+            .replace('#', "sharp")
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect()
+    }
+
+    language_name.eq_ignore_ascii_case(id) || normalize(language_name) == normalize(id)
+}
+
+/// Returns completions for the user snippets that apply at the given position,
+/// replacing the word prefix `query` that precedes it.
+pub(crate) fn snippet_completions(
+    buffer: &Buffer,
+    position: text::Anchor,
+    query: &str,
+    cx: &AppContext,
+) -> Vec<Completion> {
+    let Some(user_snippets) = cx.try_global::<UserSnippets>() else {
+        return Vec::new();
+    };
+
+    let language = buffer.language_at(position).map(|language| language.name());
+    let end = position.to_offset(buffer);
+    let start = end.saturating_sub(query.len());
+    let old_range = buffer.anchor_before(start)..buffer.anchor_after(end);
+
+    user_snippets
+        .snippets_for_language(language.as_deref())
+        .flat_map(|snippet| {
+            let old_range = old_range.clone();
+            snippet.prefixes.iter().map(move |prefix| {
+                let mut documentation = snippet.description.clone().unwrap_or_default();
+                if !documentation.is_empty() {
+                    documentation.push_str("\n\n");
+                }
+                documentation.push_str(&snippet.body);
+
+                Completion {
+                    old_range: old_range.clone(),
+                    new_text: snippet.body.clone(),
+                    label: CodeLabel {
+                        text: prefix.clone(),
+                        runs: Vec::new(),
+                        filter_range: 0..prefix.len(),
+                    },
+                    documentation: Some(Documentation::MultiLinePlainText(documentation)),
+                    server_id: LanguageServerId(0),
+                    lsp_completion: lsp::CompletionItem {
+                        label: prefix.clone(),
+                        kind: Some(lsp::CompletionItemKind::SNIPPET),
+                        detail: Some(snippet.name.clone()),
+                        insert_text_format: Some(lsp::InsertTextFormat::SNIPPET),
+                        ..Default::default()
+                    },
+                }
+            })
+        })
+        .collect()
+}
+
+/// Returns the values of the snippet variables, such as `TM_FILENAME` and
+/// `CURRENT_YEAR`, for a snippet inserted at the given offset while
+/// `selected_text` was selected.
+pub(crate) fn snippet_variables(
+    buffer: &Buffer,
+    offset: usize,
+    selected_text: &str,
+    cx: &AppContext,
+) -> HashMap<&'static str, String> {
+    let mut variables = HashMap::default();
+
+    let point = offset.to_point(buffer);
+    let line = buffer
+        .text_for_range(Point::new(point.row, 0)..Point::new(point.row, buffer.line_len(point.row)))
+        .collect::<String>();
+    variables.insert("TM_CURRENT_LINE", line);
+    variables.insert("TM_LINE_INDEX", point.row.to_string());
+    variables.insert("TM_LINE_NUMBER", (point.row + 1).to_string());
+    variables.insert("TM_SELECTED_TEXT", selected_text.to_string());
+
+    if let Some(file) = buffer.file() {
+        let path = match file.as_local() {
+            Some(file) => file.abs_path(cx),
+            None => file.full_path(cx),
+        };
+        if let Some(file_name) = path.file_name() {
+            variables.insert("TM_FILENAME", file_name.to_string_lossy().into_owned());
+        }
+        if let Some(file_stem) = path.file_stem() {
+            variables.insert("TM_FILENAME_BASE", file_stem.to_string_lossy().into_owned());
+        }
+        if let Some(directory) = path.parent() {
+            variables.insert("TM_DIRECTORY", directory.to_string_lossy().into_owned());
+        }
+        variables.insert("TM_FILEPATH", path.to_string_lossy().into_owned());
+        variables.insert(
+            "RELATIVE_FILEPATH",
+            file.path().to_string_lossy().into_owned(),
+        );
+    }
+
+    if let Some(clipboard) = cx.read_from_clipboard() {
+        variables.insert("CLIPBOARD", clipboard.text().clone());
+    }
+
+    let now = Local::now();
+    variables.insert("CURRENT_YEAR", now.year().to_string());
+    variables.insert("CURRENT_YEAR_SHORT", format!("{:02}", now.year() % 100));
+    variables.insert("CURRENT_MONTH", format!("{:02}", now.month()));
+    variables.insert("CURRENT_MONTH_NAME", now.format("%B").to_string());
+    variables.insert("CURRENT_MONTH_NAME_SHORT", now.format("%b").to_string());
+    variables.insert("CURRENT_DATE", format!("{:02}", now.day()));
+    variables.insert("CURRENT_DAY_NAME", now.format("%A").to_string());
+    variables.insert("CURRENT_DAY_NAME_SHORT", now.format("%a").to_string());
+    variables.insert("CURRENT_HOUR", format!("{:02}", now.hour()));
+    variables.insert("CURRENT_MINUTE", format!("{:02}", now.minute()));
+    variables.insert("CURRENT_SECOND", format!("{:02}", now.second()));
+    variables.insert("CURRENT_SECONDS_UNIX", now.timestamp().to_string());
+
+    variables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_matches() {
+        assert!(language_matches("Rust", "rust"));
+        assert!(language_matches("Shell Script", "shellscript"));
+        assert!(language_matches("C++", "cpp"));
+        assert!(language_matches("C#", "csharp"));
+        assert!(!language_matches("C", "cpp"));
+        assert!(!language_matches("TypeScript", "javascript"));
+    }
+}
@@ -11,7 +11,7 @@ use git2::Repository as LibGitRepository;
 use parking_lot::Mutex;
 use repository::GitRepository;
 use rope::Rope;
-use smol::io::{AsyncReadExt, AsyncWriteExt};
+use smol::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use std::io::Write;
 use std::sync::Arc;
 use std::{
@@ -21,13 +21,13 @@ use std::{
     time::{Duration, SystemTime},
 };
 use tempfile::{NamedTempFile, TempDir};
-use text::LineEnding;
+use text::{Encoding, LineEnding};
 use util::{paths, ResultExt};
 
 #[cfg(any(test, feature = "test-support"))]
 use collections::{btree_map, BTreeMap};
 #[cfg(any(test, feature = "test-support"))]
-use repository::{FakeGitRepositoryState, GitFileStatus};
+use repository::{BlameEntry, FakeGitRepositoryState, GitFileStatus};
 #[cfg(any(test, feature = "test-support"))]
 use std::ffi::OsStr;
 
@@ -52,8 +52,31 @@ pub trait Fs: Send + Sync {
     async fn remove_file(&self, path: &Path, options: RemoveOptions) -> Result<()>;
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>>;
     async fn load(&self, path: &Path) -> Result<String>;
+    /// Loads the raw contents of the file, without decoding them.
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads at most `len` raw bytes of the file, starting at `offset`, along
+    /// with the total length of the file.
+    async fn load_bytes_range(
+        &self,
+        path: &Path,
+        offset: u64,
+        len: usize,
+    ) -> Result<(Vec<u8>, u64)>;
+    /// Loads the file in the given encoding, or in the detected one if `None`.
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)>;
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
     async fn metadata(&self, path: &Path) -> Result<Option<Metadata>>;
@@ -104,6 +127,8 @@ pub struct RemoveOptions {
 pub struct Metadata {
     pub inode: u64,
     pub mtime: SystemTime,
+    /// The size of the file in bytes.
+    pub len: u64,
     pub is_symlink: bool,
     pub is_dir: bool,
 }
@@ -213,12 +238,39 @@ impl Fs for RealFs {
     }
 
     async fn load(&self, path: &Path) -> Result<String> {
+        Ok(String::from_utf8(load_bytes(path).await?)?)
+    }
+
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        load_bytes(path).await
+    }
+
+    async fn load_bytes_range(
+        &self,
+        path: &Path,
+        offset: u64,
+        len: usize,
+    ) -> Result<(Vec<u8>, u64)> {
         let mut file = smol::fs::File::open(path).await?;
-        // We use `read_exact` here instead of `read_to_string` as the latter is *very*
-        // happy to reallocate often, which comes into play when we're loading large files.
-        let mut storage = vec![0; file.metadata().await?.len() as usize];
-        file.read_exact(&mut storage).await?;
-        Ok(String::from_utf8(storage)?)
+        let total_len = file.metadata().await?.len();
+        file.seek(io::SeekFrom::Start(offset.min(total_len)))
+            .await?;
+        let mut storage = Vec::with_capacity(len.min(total_len.saturating_sub(offset) as usize));
+        file.take(len as u64).read_to_end(&mut storage).await?;
+        Ok((storage, total_len))
+    }
+
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)> {
+        let bytes = load_bytes(path).await?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => Encoding::detect(&bytes)?,
+        };
+        Ok((encoding.decode(bytes)?, encoding))
     }
 
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
@@ -241,14 +293,38 @@ impl Fs for RealFs {
     }
 
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+        self.save_with_encoding(path, text, line_ending, Encoding::default())
+            .await
+    }
+
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
+        // Encode up front, so that unrepresentable text doesn't truncate the file.
+        let encoded = if encoding.is_utf8() {
+            None
+        } else {
+            let text = chunks(text, line_ending).collect::<String>();
+            Some(encoding.encode(&text)?.into_owned())
+        };
+
         let buffer_size = text.summary().len.min(10 * 1024);
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
         let file = smol::fs::File::create(path).await?;
         let mut writer = smol::io::BufWriter::with_capacity(buffer_size, file);
-        for chunk in chunks(text, line_ending) {
-            writer.write_all(chunk.as_bytes()).await?;
+        if let Some(encoded) = encoded {
+            writer.write_all(&encoded).await?;
+        } else {
+            writer.write_all(&encoding.encode("")?).await?;
+            for chunk in chunks(text, line_ending) {
+                writer.write_all(chunk.as_bytes()).await?;
+            }
         }
         writer.flush().await?;
         Ok(())
@@ -292,6 +368,7 @@ impl Fs for RealFs {
         Ok(Some(Metadata {
             inode,
             mtime: metadata.modified().unwrap(),
+            len: metadata.len(),
             is_symlink,
             is_dir: metadata.file_type().is_dir(),
         }))
@@ -794,6 +871,43 @@ impl FakeFs {
         });
     }
 
+    pub fn set_revision_text_for_repo(
+        &self,
+        dot_git: &Path,
+        revision: &str,
+        contents: &[(&Path, String)],
+    ) {
+        self.with_git_state(dot_git, true, |state| {
+            state.revision_contents.insert(
+                revision.to_string(),
+                contents
+                    .iter()
+                    .map(|(path, content)| (path.to_path_buf(), content.clone()))
+                    .collect(),
+            );
+        });
+    }
+
+    pub fn set_merge_base_for_repo(&self, dot_git: &Path, revision: &str, merge_base: &str) {
+        self.with_git_state(dot_git, true, |state| {
+            state
+                .merge_bases
+                .insert(revision.to_string(), merge_base.to_string());
+        });
+    }
+
+    pub fn set_remote_url_for_repo(&self, dot_git: &Path, name: &str, url: &str) {
+        self.with_git_state(dot_git, true, |state| {
+            state.remote_urls.insert(name.to_string(), url.to_string());
+        });
+    }
+
+    pub fn set_blame_for_repo(&self, dot_git: &Path, path: &Path, blame: Vec<BlameEntry>) {
+        self.with_git_state(dot_git, false, |state| {
+            state.blames.insert(path.into(), blame);
+        });
+    }
+
     pub fn set_status_for_repo_via_working_copy_change(
         &self,
         dot_git: &Path,
@@ -1220,6 +1334,35 @@ impl Fs for FakeFs {
         Ok(String::from_utf8(content.clone())?)
     }
 
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        self.load_internal(path).await
+    }
+
+    async fn load_bytes_range(
+        &self,
+        path: &Path,
+        offset: u64,
+        len: usize,
+    ) -> Result<(Vec<u8>, u64)> {
+        let content = self.load_internal(path).await?;
+        let start = (offset as usize).min(content.len());
+        let end = start.saturating_add(len).min(content.len());
+        Ok((content[start..end].to_vec(), content.len() as u64))
+    }
+
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)> {
+        let content = self.load_internal(path).await?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => Encoding::detect(&content)?,
+        };
+        Ok((encoding.decode(content)?, encoding))
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path.as_path());
@@ -1228,13 +1371,25 @@ impl Fs for FakeFs {
     }
 
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+        self.save_with_encoding(path, text, line_ending, Encoding::default())
+            .await
+    }
+
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path);
         let content = chunks(text, line_ending).collect::<String>();
+        let content = encoding.encode(&content)?.into_owned();
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
-        self.write_file_internal(path, content.into_bytes())?;
+        self.write_file_internal(path, content)?;
         Ok(())
     }
 
@@ -1277,15 +1432,21 @@ impl Fs for FakeFs {
 
             let entry = entry.lock();
             Ok(Some(match &*entry {
-                FakeFsEntry::File { inode, mtime, .. } => Metadata {
+                FakeFsEntry::File {
+                    inode,
+                    mtime,
+                    content,
+                } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
+                    len: content.len() as u64,
                     is_dir: false,
                     is_symlink,
                 },
                 FakeFsEntry::Dir { inode, mtime, .. } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
+                    len: 0,
                     is_dir: true,
                     is_symlink,
                 },
@@ -1378,6 +1539,15 @@ impl Fs for FakeFs {
     }
 }
 
+async fn load_bytes(path: &Path) -> Result<Vec<u8>> {
+    let mut file = smol::fs::File::open(path).await?;
+    // We use `read_exact` here instead of `read_to_string` as the latter is *very*
+    // happy to reallocate often, which comes into play when we're loading large files.
+    let mut storage = vec![0; file.metadata().await?.len() as usize];
+    file.read_exact(&mut storage).await?;
+    Ok(storage)
+}
+
 fn chunks(rope: &Rope, line_ending: LineEnding) -> impl Iterator<Item = &str> {
     rope.chunks().flat_map(move |chunk| {
         let mut newline = false;
@@ -1562,4 +1732,35 @@ mod tests {
             "D",
         );
     }
+
+    #[gpui::test]
+    async fn test_load_bytes_range(executor: BackgroundExecutor) {
+        executor.allow_parking();
+        let content = (0..100).collect::<Vec<u8>>();
+
+        let dir = TempDir::new().unwrap();
+        let real_path = dir.path().join("file");
+        std::fs::write(&real_path, &content).unwrap();
+        let fake_fs = FakeFs::new(executor.clone());
+        fake_fs.insert_tree("/root", json!({})).await;
+        fake_fs.insert_file("/root/file", content.clone()).await;
+
+        for (fs, path) in [
+            (&RealFs as &dyn Fs, real_path.as_path()),
+            (fake_fs.as_ref() as &dyn Fs, Path::new("/root/file")),
+        ] {
+            assert_eq!(
+                fs.load_bytes_range(path, 0, 10).await.unwrap(),
+                (content[..10].to_vec(), 100)
+            );
+            assert_eq!(
+                fs.load_bytes_range(path, 95, 10).await.unwrap(),
+                (content[95..].to_vec(), 100)
+            );
+            assert_eq!(
+                fs.load_bytes_range(path, 200, 10).await.unwrap(),
+                (Vec::new(), 100)
+            );
+        }
+    }
 }
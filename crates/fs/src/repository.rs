@@ -1,11 +1,13 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
 use git2::{BranchType, StatusShow};
 use parking_lot::Mutex;
 use serde_derive::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    io::Read,
     path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
     sync::Arc,
     time::SystemTime,
 };
@@ -21,10 +23,88 @@ pub struct Branch {
     pub unix_timestamp: Option<i64>,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StashEntry {
+    /// Position of the entry in the stash, where 0 is the most recent one.
+    pub index: usize,
+    pub message: String,
+}
+
+/// The commit that last changed a line of a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlameEntry {
+    pub sha: String,
+    pub message: String,
+}
+
+/// How to integrate upstream changes when pulling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PullStrategy {
+    /// Only update the branch if it can be fast-forwarded to its upstream.
+    FastForward,
+    /// Rebase local commits on top of the upstream branch.
+    Rebase,
+}
+
+/// The branch that the current branch is pulled from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upstream {
+    pub branch_name: String,
+    pub remote: String,
+    /// The remote-tracking reference of the upstream, e.g. `refs/remotes/origin/main`.
+    pub tracking_ref: String,
+}
+
+/// Where the current branch is pushed to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PushTarget {
+    pub branch_name: String,
+    pub remote: String,
+    pub refspec: String,
+    /// The upstream to set for the branch once it has been pushed, if it has none yet.
+    pub new_upstream: Option<String>,
+}
+
+/// Progress of an object transfer to or from a remote.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RemoteProgress {
+    pub completed_objects: usize,
+    pub total_objects: usize,
+}
+
+/// Performs the network part of fetch and push operations.
+///
+/// Kept separate from [`GitRepository`] so that tests can talk to a local bare
+/// repository through libgit2 instead of going through the network, and so that
+/// slow network operations don't need access to an open repository.
+pub trait GitRemoteTransport: Send + Sync {
+    fn fetch(
+        &self,
+        git_dir: &Path,
+        remote: &str,
+        refspecs: &[String],
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()>;
+
+    fn push(
+        &self,
+        git_dir: &Path,
+        remote: &str,
+        refspecs: &[String],
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()>;
+}
+
 pub trait GitRepository: Send {
     fn reload_index(&self);
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads the contents of a file at the given revision, such as a branch name or a SHA.
+    fn load_revision_text(&self, relative_file_path: &Path, revision: &str) -> Option<String>;
+
+    /// Returns the SHA of the best common ancestor of HEAD and the given revision.
+    fn merge_base(&self, revision: &str) -> Option<String>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
@@ -32,6 +112,10 @@ pub trait GitRepository: Send {
     /// Returns the SHA of the current HEAD.
     fn head_sha(&self) -> Option<String>;
 
+    /// Returns the commit that last changed the given zero-based row of a file,
+    /// as of the HEAD commit.
+    fn blame_line(&self, path: &RepoPath, row: u32) -> Result<BlameEntry>;
+
     /// Get the statuses of all of the files in the index that start with the given
     /// path and have changes with respect to the HEAD commit. This is fast because
     /// the index stores hashes of trees, so that unchanged directories can be skipped.
@@ -53,6 +137,73 @@ pub trait GitRepository: Send {
     fn branches(&self) -> Result<Vec<Branch>>;
     fn change_branch(&self, _: &str) -> Result<()>;
     fn create_branch(&self, _: &str) -> Result<()>;
+
+    /// Stages the file at the given path, removing its conflict entries from the index.
+    fn mark_resolved(&self, path: &RepoPath) -> Result<()>;
+
+    /// Stashes all changes in the working directory, including untracked files.
+    fn stash_push(&mut self, message: Option<&str>) -> Result<()>;
+    /// Applies the stash entry at the given index and removes it from the stash.
+    fn stash_pop(&mut self, index: usize) -> Result<()>;
+    fn stash_list(&mut self) -> Result<Vec<StashEntry>>;
+
+    /// The path of the repository's git directory, which remote transports operate on.
+    fn git_dir(&self) -> PathBuf;
+
+    /// Returns the upstream of the current branch.
+    fn upstream(&self) -> Result<Upstream>;
+
+    /// Integrates the upstream of the current branch, which must have been fetched
+    /// already, according to the strategy.
+    fn integrate_upstream(&mut self, upstream: &Upstream, strategy: PullStrategy) -> Result<()>;
+
+    /// Returns where to push the current branch: its upstream, or the branch with the
+    /// same name on the given remote if it has no upstream yet.
+    fn push_target(&self, remote: &str) -> Result<PushTarget>;
+
+    /// Makes the pushed branch the upstream of the current branch, if it had none.
+    fn set_pushed_upstream(&mut self, target: &PushTarget) -> Result<()>;
+
+    /// Fetches all branches of the given remote.
+    fn fetch(
+        &mut self,
+        remote: &str,
+        transport: &dyn GitRemoteTransport,
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()> {
+        transport.fetch(&self.git_dir(), remote, &[], progress)
+    }
+
+    /// Fetches the upstream of the current branch and integrates it according to the strategy.
+    fn pull(
+        &mut self,
+        strategy: PullStrategy,
+        transport: &dyn GitRemoteTransport,
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()> {
+        let upstream = self.upstream()?;
+        transport.fetch(&self.git_dir(), &upstream.remote, &[], progress)?;
+        self.integrate_upstream(&upstream, strategy)
+    }
+
+    /// Pushes the current branch to its upstream, or to the branch with the same name on the
+    /// given remote if it has no upstream yet. In the latter case the pushed branch becomes
+    /// the upstream.
+    fn push(
+        &mut self,
+        remote: &str,
+        transport: &dyn GitRemoteTransport,
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()> {
+        let target = self.push_target(remote)?;
+        transport.push(
+            &self.git_dir(),
+            &target.remote,
+            &[target.refspec.clone()],
+            progress,
+        )?;
+        self.set_pushed_upstream(&target)
+    }
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -92,6 +243,42 @@ impl GitRepository for LibGitRepository {
         None
     }
 
+    fn load_revision_text(&self, relative_file_path: &Path, revision: &str) -> Option<String> {
+        fn logic(
+            repo: &LibGitRepository,
+            relative_file_path: &Path,
+            revision: &str,
+        ) -> Result<Option<String>> {
+            check_path_to_repo_path_errors(relative_file_path)?;
+
+            let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+            let entry = match tree.get_path(relative_file_path) {
+                Ok(entry) => entry,
+                Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            let content = repo.find_blob(entry.id())?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        }
+
+        match logic(self, relative_file_path, revision) {
+            Ok(value) => return value,
+            Err(err) => log::error!("Error loading text at revision {revision:?}: {:?}", err),
+        }
+        None
+    }
+
+    fn merge_base(&self, revision: &str) -> Option<String> {
+        let head = self.head().ok()?.peel_to_commit().ok()?;
+        let other = self
+            .revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .log_err()?;
+        let merge_base = git2::Repository::merge_base(self, head.id(), other.id()).log_err()?;
+        Some(merge_base.to_string())
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let remote = self.find_remote(name).ok()?;
         remote.url().map(|url| url.to_string())
@@ -108,6 +295,22 @@ impl GitRepository for LibGitRepository {
         head.target().map(|oid| oid.to_string())
     }
 
+    fn blame_line(&self, path: &RepoPath, row: u32) -> Result<BlameEntry> {
+        let line = row as usize + 1;
+        let mut options = git2::BlameOptions::new();
+        options.min_line(line).max_line(line);
+        let blame = self.blame_file(path, Some(&mut options))?;
+        let hunk = blame
+            .get_line(line)
+            .ok_or_else(|| anyhow!("line {line} of {path:?} is not committed"))?;
+        let sha = hunk.final_commit_id();
+        let commit = self.find_commit(sha)?;
+        Ok(BlameEntry {
+            sha: sha.to_string(),
+            message: String::from_utf8_lossy(commit.message_bytes()).to_string(),
+        })
+    }
+
     fn staged_statuses(&self, path_prefix: &Path) -> TreeMap<RepoPath, GitFileStatus> {
         let mut map = TreeMap::default();
 
@@ -209,6 +412,332 @@ impl GitRepository for LibGitRepository {
 
         Ok(())
     }
+
+    fn mark_resolved(&self, path: &RepoPath) -> Result<()> {
+        check_path_to_repo_path_errors(path)?;
+        let mut index = self.index()?;
+        index.add_path(path)?;
+        index.write()?;
+        Ok(())
+    }
+
+    fn stash_push(&mut self, message: Option<&str>) -> Result<()> {
+        let signature = self.signature()?;
+        let message = match message {
+            Some(message) => message.to_string(),
+            None => format!("WIP on {}", self.branch_name().unwrap_or_default()),
+        };
+        self.stash_save(
+            &signature,
+            &message,
+            Some(git2::StashFlags::INCLUDE_UNTRACKED),
+        )?;
+        Ok(())
+    }
+
+    fn stash_pop(&mut self, index: usize) -> Result<()> {
+        let mut options = git2::StashApplyOptions::new();
+        options.reinstantiate_index();
+        LibGitRepository::stash_pop(self, index, Some(&mut options))?;
+        Ok(())
+    }
+
+    fn stash_list(&mut self) -> Result<Vec<StashEntry>> {
+        let mut entries = Vec::new();
+        self.stash_foreach(|index, message, _| {
+            entries.push(StashEntry {
+                index,
+                message: message.to_string(),
+            });
+            true
+        })?;
+        Ok(entries)
+    }
+
+    fn git_dir(&self) -> PathBuf {
+        self.path().to_path_buf()
+    }
+
+    fn upstream(&self) -> Result<Upstream> {
+        let head = self.head()?;
+        let branch_name = head
+            .shorthand()
+            .filter(|_| head.is_branch())
+            .ok_or_else(|| anyhow!("cannot pull with a detached HEAD"))?
+            .to_string();
+        let branch = self.find_branch(&branch_name, BranchType::Local)?;
+        let upstream = branch
+            .upstream()
+            .with_context(|| format!("branch '{branch_name}' has no upstream"))?;
+        let tracking_ref = upstream
+            .get()
+            .name()
+            .ok_or_else(|| anyhow!("upstream name is not valid UTF-8"))?
+            .to_string();
+        let remote = self.branch_remote_name(&tracking_ref)?;
+        let remote = remote
+            .as_str()
+            .ok_or_else(|| anyhow!("remote name is not valid UTF-8"))?
+            .to_string();
+        Ok(Upstream {
+            branch_name,
+            remote,
+            tracking_ref,
+        })
+    }
+
+    fn integrate_upstream(&mut self, upstream: &Upstream, strategy: PullStrategy) -> Result<()> {
+        let branch_name = &upstream.branch_name;
+        let upstream_commit =
+            self.reference_to_annotated_commit(&self.find_reference(&upstream.tracking_ref)?)?;
+        let (analysis, _) = self.merge_analysis(&[&upstream_commit])?;
+        if analysis.is_up_to_date() {
+            return Ok(());
+        }
+
+        if analysis.is_fast_forward() {
+            let local_ref = format!("refs/heads/{branch_name}");
+            let mut reference = self.find_reference(&local_ref)?;
+            reference.set_target(upstream_commit.id(), "pull: fast-forward")?;
+            self.set_head(&local_ref)?;
+            self.checkout_head(Some(git2::build::CheckoutBuilder::default().safe()))?;
+            return Ok(());
+        }
+
+        match strategy {
+            PullStrategy::FastForward => Err(anyhow!(
+                "cannot fast-forward '{branch_name}' to its upstream; pull with rebase instead"
+            )),
+            PullStrategy::Rebase => {
+                let signature = self.signature()?;
+                let head_commit = self.reference_to_annotated_commit(&self.head()?)?;
+                let mut rebase =
+                    self.rebase(Some(&head_commit), Some(&upstream_commit), None, None)?;
+                while let Some(operation) = rebase.next() {
+                    let result = operation.map_err(anyhow::Error::from).and_then(|_| {
+                        match rebase.commit(None, &signature, None) {
+                            Ok(_) => Ok(()),
+                            // The commit became empty, because upstream already contains it.
+                            Err(error) if error.code() == git2::ErrorCode::Applied => Ok(()),
+                            Err(error) => Err(error.into()),
+                        }
+                    });
+                    if let Err(error) = result {
+                        rebase.abort()?;
+                        return Err(error.context("rebase failed, check for conflicts"));
+                    }
+                }
+                rebase.finish(Some(&signature))?;
+                Ok(())
+            }
+        }
+    }
+
+    fn push_target(&self, remote: &str) -> Result<PushTarget> {
+        let head = self.head()?;
+        let branch_name = head
+            .shorthand()
+            .filter(|_| head.is_branch())
+            .ok_or_else(|| anyhow!("cannot push with a detached HEAD"))?
+            .to_string();
+        drop(head);
+
+        let branch = self.find_branch(&branch_name, BranchType::Local)?;
+        let upstream_ref = branch
+            .upstream()
+            .ok()
+            .and_then(|upstream| upstream.get().name().map(|name| name.to_string()));
+        let (remote, remote_branch, new_upstream) = match upstream_ref {
+            Some(upstream_ref) => {
+                let remote_name = self.branch_remote_name(&upstream_ref)?;
+                let remote_name = remote_name
+                    .as_str()
+                    .ok_or_else(|| anyhow!("remote name is not valid UTF-8"))?
+                    .to_string();
+                let remote_branch = upstream_ref
+                    .strip_prefix(&format!("refs/remotes/{remote_name}/"))
+                    .unwrap_or(&branch_name)
+                    .to_string();
+                (remote_name, remote_branch, None)
+            }
+            None => (
+                remote.to_string(),
+                branch_name.clone(),
+                Some(format!("{remote}/{branch_name}")),
+            ),
+        };
+
+        Ok(PushTarget {
+            refspec: format!("refs/heads/{branch_name}:refs/heads/{remote_branch}"),
+            branch_name,
+            remote,
+            new_upstream,
+        })
+    }
+
+    fn set_pushed_upstream(&mut self, target: &PushTarget) -> Result<()> {
+        if let Some(new_upstream) = &target.new_upstream {
+            self.find_branch(&target.branch_name, BranchType::Local)?
+                .set_upstream(Some(new_upstream))?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs remote operations through libgit2.
+///
+/// Only local and unauthenticated transports are available in the bundled
+/// libgit2, which makes this suitable for remotes on the local filesystem.
+pub struct LibGitRemoteTransport;
+
+impl GitRemoteTransport for LibGitRemoteTransport {
+    fn fetch(
+        &self,
+        git_dir: &Path,
+        remote: &str,
+        refspecs: &[String],
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()> {
+        let repo = LibGitRepository::open(git_dir)?;
+        let mut callbacks = git2::RemoteCallbacks::new();
+        callbacks.transfer_progress(|stats| {
+            progress(RemoteProgress {
+                completed_objects: stats.received_objects(),
+                total_objects: stats.total_objects(),
+            });
+            true
+        });
+        let mut options = git2::FetchOptions::new();
+        options.remote_callbacks(callbacks);
+        repo.find_remote(remote)?
+            .fetch(refspecs, Some(&mut options), None)?;
+        Ok(())
+    }
+
+    fn push(
+        &self,
+        git_dir: &Path,
+        remote: &str,
+        refspecs: &[String],
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()> {
+        let repo = LibGitRepository::open(git_dir)?;
+        let mut rejection = None;
+        {
+            let mut callbacks = git2::RemoteCallbacks::new();
+            callbacks.push_transfer_progress(|completed, total, _| {
+                progress(RemoteProgress {
+                    completed_objects: completed,
+                    total_objects: total,
+                });
+            });
+            callbacks.push_update_reference(|reference, status| {
+                if let Some(status) = status {
+                    rejection = Some(format!("{reference}: {status}"));
+                }
+                Ok(())
+            });
+            let mut options = git2::PushOptions::new();
+            options.remote_callbacks(callbacks);
+            repo.find_remote(remote)?
+                .push(refspecs, Some(&mut options))?;
+        }
+        match rejection {
+            Some(rejection) => Err(anyhow!("push was rejected: {rejection}")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Runs remote operations through the `git` executable, so that they use the
+/// user's credential helpers, SSH configuration and supported protocols.
+pub struct CliGitRemoteTransport;
+
+impl CliGitRemoteTransport {
+    fn run(
+        git_dir: &Path,
+        args: &[&str],
+        refspecs: &[String],
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()> {
+        let mut child = Command::new("git")
+            .arg("--git-dir")
+            .arg(git_dir)
+            .args(args)
+            .args(refspecs)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .spawn()
+            .context("failed to spawn git")?;
+
+        // Progress lines are separated by carriage returns, e.g.
+        // `Receiving objects:  45% (45/100)\r`.
+        let mut stderr = child.stderr.take().context("failed to read git output")?;
+        let mut output = String::new();
+        let mut buffer = [0; 1024];
+        loop {
+            let len = stderr.read(&mut buffer)?;
+            if len == 0 {
+                break;
+            }
+            output.push_str(&String::from_utf8_lossy(&buffer[..len]));
+            if let Some(line) = output.rsplit(['\r', '\n']).find(|line| !line.is_empty()) {
+                if let Some(remote_progress) = parse_progress_line(line) {
+                    progress(remote_progress);
+                }
+            }
+        }
+
+        let status = child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            let message = output
+                .lines()
+                .filter(|line| line.starts_with("error:") || line.starts_with("fatal:"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Err(anyhow!("git {} failed: {message}", args.join(" ")))
+        }
+    }
+}
+
+impl GitRemoteTransport for CliGitRemoteTransport {
+    fn fetch(
+        &self,
+        git_dir: &Path,
+        remote: &str,
+        refspecs: &[String],
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()> {
+        Self::run(
+            git_dir,
+            &["fetch", "--progress", remote],
+            refspecs,
+            progress,
+        )
+    }
+
+    fn push(
+        &self,
+        git_dir: &Path,
+        remote: &str,
+        refspecs: &[String],
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<()> {
+        Self::run(git_dir, &["push", "--progress", remote], refspecs, progress)
+    }
+}
+
+fn parse_progress_line(line: &str) -> Option<RemoteProgress> {
+    let counts = line.rsplit_once('(')?.1.split_once(')')?.0;
+    let (completed, total) = counts.split_once('/')?;
+    Some(RemoteProgress {
+        completed_objects: completed.trim().parse().ok()?,
+        total_objects: total.trim().parse().ok()?,
+    })
 }
 
 fn matches_index(repo: &LibGitRepository, path: &RepoPath, mtime: SystemTime) -> bool {
@@ -253,6 +782,15 @@ pub struct FakeGitRepositoryState {
     pub index_contents: HashMap<PathBuf, String>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub branch_name: Option<String>,
+    /// File contents at revisions other than the index, keyed by revision.
+    pub revision_contents: HashMap<String, HashMap<PathBuf, String>>,
+    /// Merge-base SHAs of HEAD and other revisions, keyed by revision.
+    pub merge_bases: HashMap<String, String>,
+    /// Stashed statuses, most recent first.
+    pub stashes: Vec<(String, HashMap<RepoPath, GitFileStatus>)>,
+    pub remote_urls: HashMap<String, String>,
+    /// The commits that last changed each row of a file.
+    pub blames: HashMap<RepoPath, Vec<BlameEntry>>,
 }
 
 impl FakeGitRepository {
@@ -269,8 +807,19 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
-    fn remote_url(&self, _name: &str) -> Option<String> {
-        None
+    fn load_revision_text(&self, path: &Path, revision: &str) -> Option<String> {
+        let state = self.state.lock();
+        state.revision_contents.get(revision)?.get(path).cloned()
+    }
+
+    fn merge_base(&self, revision: &str) -> Option<String> {
+        let state = self.state.lock();
+        state.merge_bases.get(revision).cloned()
+    }
+
+    fn remote_url(&self, name: &str) -> Option<String> {
+        let state = self.state.lock();
+        state.remote_urls.get(name).cloned()
     }
 
     fn branch_name(&self) -> Option<String> {
@@ -282,6 +831,16 @@ impl GitRepository for FakeGitRepository {
         None
     }
 
+    fn blame_line(&self, path: &RepoPath, row: u32) -> Result<BlameEntry> {
+        let state = self.state.lock();
+        state
+            .blames
+            .get(path)
+            .and_then(|blame| blame.get(row as usize))
+            .cloned()
+            .ok_or_else(|| anyhow!("line {} of {path:?} is not committed", row + 1))
+    }
+
     fn staged_statuses(&self, path_prefix: &Path) -> TreeMap<RepoPath, GitFileStatus> {
         let mut map = TreeMap::default();
         let state = self.state.lock();
@@ -317,6 +876,67 @@ impl GitRepository for FakeGitRepository {
         state.branch_name = Some(name.to_owned());
         Ok(())
     }
+
+    fn mark_resolved(&self, path: &RepoPath) -> Result<()> {
+        let mut state = self.state.lock();
+        if let Some(status) = state.worktree_statuses.get_mut(path) {
+            if *status == GitFileStatus::Conflict {
+                *status = GitFileStatus::Modified;
+            }
+        }
+        Ok(())
+    }
+
+    fn stash_push(&mut self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        let statuses = std::mem::take(&mut state.worktree_statuses);
+        let message = message.unwrap_or("WIP").to_string();
+        state.stashes.insert(0, (message, statuses));
+        Ok(())
+    }
+
+    fn stash_pop(&mut self, index: usize) -> Result<()> {
+        let mut state = self.state.lock();
+        if index >= state.stashes.len() {
+            anyhow::bail!("stash@{{{index}}} does not exist");
+        }
+        let (_, statuses) = state.stashes.remove(index);
+        state.worktree_statuses.extend(statuses);
+        Ok(())
+    }
+
+    fn stash_list(&mut self) -> Result<Vec<StashEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .stashes
+            .iter()
+            .enumerate()
+            .map(|(index, (message, _))| StashEntry {
+                index,
+                message: message.clone(),
+            })
+            .collect())
+    }
+
+    fn git_dir(&self) -> PathBuf {
+        PathBuf::new()
+    }
+
+    fn upstream(&self) -> Result<Upstream> {
+        Err(anyhow!("fake repositories have no upstream"))
+    }
+
+    fn integrate_upstream(&mut self, _upstream: &Upstream, _strategy: PullStrategy) -> Result<()> {
+        Ok(())
+    }
+
+    fn push_target(&self, _remote: &str) -> Result<PushTarget> {
+        Err(anyhow!("fake repositories have no remotes"))
+    }
+
+    fn set_pushed_upstream(&mut self, _target: &PushTarget) -> Result<()> {
+        Ok(())
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -435,3 +1055,211 @@ impl<'a> MapSeekTarget<RepoPath> for RepoPathDescendants<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn commit_file(repo: &LibGitRepository, name: &str, content: &str) {
+        fs::write(repo.workdir().unwrap().join(name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+
+        let signature = Signature::now("test", "test@zed.dev").unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            name,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    }
+
+    fn init_repo(path: &Path) -> LibGitRepository {
+        let repo = LibGitRepository::init(path).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "test").unwrap();
+        config.set_str("user.email", "test@zed.dev").unwrap();
+        repo
+    }
+
+    #[test]
+    fn test_blame_line() {
+        let dir = TempDir::new().unwrap();
+        let repo = init_repo(&dir.path().join("repo"));
+        commit_file(&repo, "a.txt", "one\n");
+        let first_sha = repo.head_sha().unwrap();
+        commit_file(&repo, "a.txt", "one\ntwo\n");
+        let second_sha = repo.head_sha().unwrap();
+
+        let path = RepoPath::from(Path::new("a.txt"));
+        let blame = repo.blame_line(&path, 0).unwrap();
+        assert_eq!(blame.sha, first_sha);
+        assert_eq!(blame.message, "a.txt");
+        assert_eq!(repo.blame_line(&path, 1).unwrap().sha, second_sha);
+        assert!(repo.blame_line(&path, 2).is_err());
+    }
+
+    #[test]
+    fn test_push_fetch_and_pull_with_local_remote() {
+        let dir = TempDir::new().unwrap();
+        let remote_path = dir.path().join("remote.git");
+        LibGitRepository::init_bare(&remote_path).unwrap();
+
+        let mut first = init_repo(&dir.path().join("first"));
+        first
+            .remote("origin", remote_path.to_str().unwrap())
+            .unwrap();
+        commit_file(&first, "a.txt", "a");
+        GitRepository::push(&mut first, "origin", &LibGitRemoteTransport, &mut |_| {}).unwrap();
+
+        let branch_name = first.branch_name().unwrap();
+        let upstream = first
+            .find_branch(&branch_name, BranchType::Local)
+            .unwrap()
+            .upstream()
+            .unwrap();
+        assert_eq!(
+            upstream.name().unwrap(),
+            Some(format!("origin/{branch_name}").as_str())
+        );
+
+        let mut second = init_repo(&dir.path().join("second"));
+        second
+            .remote("origin", remote_path.to_str().unwrap())
+            .unwrap();
+        GitRepository::fetch(&mut second, "origin", &LibGitRemoteTransport, &mut |_| {}).unwrap();
+        let remote_commit = second
+            .find_reference(&format!("refs/remotes/origin/{branch_name}"))
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        second
+            .branch(&branch_name, &remote_commit, false)
+            .unwrap()
+            .set_upstream(Some(&format!("origin/{branch_name}")))
+            .unwrap();
+        second
+            .set_head(&format!("refs/heads/{branch_name}"))
+            .unwrap();
+        second
+            .checkout_head(Some(git2::build::CheckoutBuilder::default().force()))
+            .unwrap();
+
+        commit_file(&first, "b.txt", "b");
+        GitRepository::push(&mut first, "origin", &LibGitRemoteTransport, &mut |_| {}).unwrap();
+
+        GitRepository::pull(
+            &mut second,
+            PullStrategy::FastForward,
+            &LibGitRemoteTransport,
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(second.head_sha(), first.head_sha());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("second/b.txt")).unwrap(),
+            "b"
+        );
+
+        // Diverging histories can only be integrated by rebasing.
+        commit_file(&first, "c.txt", "c");
+        GitRepository::push(&mut first, "origin", &LibGitRemoteTransport, &mut |_| {}).unwrap();
+        commit_file(&second, "d.txt", "d");
+        assert!(GitRepository::pull(
+            &mut second,
+            PullStrategy::FastForward,
+            &LibGitRemoteTransport,
+            &mut |_| {},
+        )
+        .is_err());
+        GitRepository::pull(
+            &mut second,
+            PullStrategy::Rebase,
+            &LibGitRemoteTransport,
+            &mut |_| {},
+        )
+        .unwrap();
+        let head = second.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary(), Some("d.txt"));
+        assert_eq!(
+            head.parent(0).unwrap().id().to_string(),
+            first.head_sha().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_stash() {
+        let dir = TempDir::new().unwrap();
+        let mut repo = init_repo(dir.path());
+        commit_file(&repo, "a.txt", "a");
+
+        fs::write(dir.path().join("a.txt"), "modified").unwrap();
+        fs::write(dir.path().join("untracked.txt"), "new").unwrap();
+        repo.stash_push(Some("first stash")).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "a");
+        assert!(!dir.path().join("untracked.txt").exists());
+
+        fs::write(dir.path().join("a.txt"), "modified again").unwrap();
+        repo.stash_push(Some("second stash")).unwrap();
+        let stashes = repo.stash_list().unwrap();
+        assert_eq!(
+            stashes.iter().map(|stash| stash.index).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert!(stashes[0].message.ends_with("second stash"));
+        assert!(stashes[1].message.ends_with("first stash"));
+
+        GitRepository::stash_pop(&mut repo, 1).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "modified"
+        );
+        assert!(dir.path().join("untracked.txt").exists());
+        assert_eq!(repo.stash_list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_load_revision_text_and_merge_base() {
+        let dir = TempDir::new().unwrap();
+        let repo = init_repo(dir.path());
+        commit_file(&repo, "a.txt", "one");
+        let first_commit = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("other", &first_commit, false).unwrap();
+        commit_file(&repo, "a.txt", "two");
+
+        assert_eq!(
+            repo.load_revision_text(Path::new("a.txt"), "other"),
+            Some("one".to_string())
+        );
+        assert_eq!(
+            repo.load_revision_text(Path::new("a.txt"), "HEAD"),
+            Some("two".to_string())
+        );
+        assert_eq!(repo.load_revision_text(Path::new("b.txt"), "other"), None);
+        assert_eq!(
+            GitRepository::merge_base(&repo, "other"),
+            Some(first_commit.id().to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_progress_line() {
+        assert_eq!(
+            parse_progress_line("Receiving objects:  45% (45/100), 1.2 MiB | 3 MiB/s"),
+            Some(RemoteProgress {
+                completed_objects: 45,
+                total_objects: 100,
+            })
+        );
+        assert_eq!(parse_progress_line("remote: Enumerating objects: 5"), None);
+    }
+}
@@ -0,0 +1,262 @@
+use std::ops::Range;
+use text::{Anchor, BufferSnapshot, Point, ToOffset};
+
+const MARKER_LEN: usize = 7;
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// Which side of a merge conflict should be kept when resolving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+/// A region of a buffer delimited by Git conflict markers.
+///
+/// ```text
+/// <<<<<<< ours
+/// ...
+/// ||||||| base (only present with `merge.conflictStyle = diff3`)
+/// ...
+/// =======
+/// ...
+/// >>>>>>> theirs
+/// ```
+///
+/// Every range spans whole lines, including their trailing newline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictRegion<T> {
+    /// The whole region, from the start of the `<<<<<<<` line to the end of the `>>>>>>>` line.
+    pub range: Range<T>,
+    /// The lines between the `<<<<<<<` marker and the `|||||||` or `=======` marker.
+    pub ours: Range<T>,
+    /// The lines between the `|||||||` marker and the `=======` marker, if present.
+    pub base: Option<Range<T>>,
+    /// The lines between the `=======` marker and the `>>>>>>>` marker.
+    pub theirs: Range<T>,
+}
+
+impl ConflictRegion<Anchor> {
+    /// Returns the text that should replace [`ConflictRegion::range`] to resolve the conflict.
+    pub fn resolved_text(&self, resolution: ConflictResolution, buffer: &BufferSnapshot) -> String {
+        let ours = buffer.text_for_range(self.ours.clone());
+        let theirs = buffer.text_for_range(self.theirs.clone());
+        match resolution {
+            ConflictResolution::Ours => ours.collect(),
+            ConflictResolution::Theirs => theirs.collect(),
+            ConflictResolution::Both => ours.chain(theirs).collect(),
+        }
+    }
+}
+
+/// Finds all well-formed conflict regions in the buffer.
+///
+/// Markers only count when they start a line, and unterminated regions are ignored.
+pub fn conflict_regions(buffer: &BufferSnapshot) -> Vec<ConflictRegion<Anchor>> {
+    conflict_regions_in_rows(buffer)
+        .into_iter()
+        .map(|region| ConflictRegion {
+            range: buffer.anchor_before(region.range.start)..buffer.anchor_after(region.range.end),
+            ours: buffer.anchor_before(region.ours.start)..buffer.anchor_after(region.ours.end),
+            base: region
+                .base
+                .map(|base| buffer.anchor_before(base.start)..buffer.anchor_after(base.end)),
+            theirs: buffer.anchor_before(region.theirs.start)
+                ..buffer.anchor_after(region.theirs.end),
+        })
+        .collect()
+}
+
+/// Returns whether the buffer contains any conflict markers.
+pub fn has_conflict_markers(buffer: &BufferSnapshot) -> bool {
+    !conflict_regions_in_rows(buffer).is_empty()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Ours,
+    Base,
+    Separator,
+    Theirs,
+}
+
+fn marker_at_row(buffer: &BufferSnapshot, row: u32) -> Option<Marker> {
+    if (buffer.line_len(row) as usize) < MARKER_LEN {
+        return None;
+    }
+
+    let mut prefix = String::with_capacity(MARKER_LEN + 1);
+    prefix.extend(buffer.chars_at(Point::new(row, 0)).take(MARKER_LEN + 1));
+    let (marker, rest) = prefix.split_at(MARKER_LEN);
+    // Markers are either followed by a label, separated by a space, or end the line.
+    if !(rest.is_empty() || rest == " " || rest == "\n" || rest == "\r") {
+        return None;
+    }
+
+    match marker {
+        OURS_MARKER => Some(Marker::Ours),
+        BASE_MARKER => Some(Marker::Base),
+        SEPARATOR_MARKER if rest != " " => Some(Marker::Separator),
+        THEIRS_MARKER => Some(Marker::Theirs),
+        _ => None,
+    }
+}
+
+fn conflict_regions_in_rows(buffer: &BufferSnapshot) -> Vec<ConflictRegion<usize>> {
+    let max_row = buffer.max_point().row;
+    let line_start = |row: u32| {
+        if row > max_row {
+            buffer.len()
+        } else {
+            Point::new(row, 0).to_offset(buffer)
+        }
+    };
+
+    let mut regions = Vec::new();
+    let mut ours_row = None;
+    let mut base_row = None;
+    let mut separator_row = None;
+    for row in 0..=max_row {
+        match marker_at_row(buffer, row) {
+            Some(Marker::Ours) => {
+                // A new region starts, discarding any unterminated one.
+                ours_row = Some(row);
+                base_row = None;
+                separator_row = None;
+            }
+            Some(Marker::Base) if ours_row.is_some() && separator_row.is_none() => {
+                base_row = Some(row);
+            }
+            Some(Marker::Separator) if ours_row.is_some() && separator_row.is_none() => {
+                separator_row = Some(row);
+            }
+            Some(Marker::Theirs) => {
+                if let Some((start_row, separator_row)) = ours_row.zip(separator_row) {
+                    let ours_end_row = base_row.unwrap_or(separator_row);
+                    regions.push(ConflictRegion {
+                        range: line_start(start_row)..line_start(row + 1),
+                        ours: line_start(start_row + 1)..line_start(ours_end_row),
+                        base: base_row
+                            .map(|base_row| line_start(base_row + 1)..line_start(separator_row)),
+                        theirs: line_start(separator_row + 1)..line_start(row),
+                    });
+                }
+                ours_row = None;
+                base_row = None;
+                separator_row = None;
+            }
+            _ => {}
+        }
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text::{Buffer, BufferId};
+    use unindent::Unindent as _;
+
+    fn region_texts(buffer: &BufferSnapshot) -> Vec<(String, Option<String>, String)> {
+        conflict_regions(buffer)
+            .into_iter()
+            .map(|region| {
+                (
+                    buffer.text_for_range(region.ours).collect(),
+                    region
+                        .base
+                        .map(|base| buffer.text_for_range(base).collect()),
+                    buffer.text_for_range(region.theirs).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_conflict_regions() {
+        let text = "
+            one
+            <<<<<<< HEAD
+            two
+            =======
+            TWO
+            >>>>>>> feature
+            three
+            <<<<<<< HEAD
+            four
+            ||||||| base
+            FOUR?
+            =======
+            FOUR
+            >>>>>>> feature
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), text);
+        let snapshot = buffer.snapshot();
+        assert_eq!(
+            region_texts(&snapshot),
+            vec![
+                ("two\n".into(), None, "TWO\n".into()),
+                ("four\n".into(), Some("FOUR?\n".into()), "FOUR\n".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_and_indented_markers() {
+        let text = "
+            <<<<<<< HEAD
+            one
+            =======
+            <<<<<<< HEAD
+            two
+            =======
+            TWO
+            >>>>>>>
+              <<<<<<< not a marker
+            =========
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), text);
+        let snapshot = buffer.snapshot();
+        assert_eq!(
+            region_texts(&snapshot),
+            vec![("two\n".into(), None, "TWO\n".into())]
+        );
+    }
+
+    #[test]
+    fn test_resolved_text() {
+        let text = "
+            <<<<<<< HEAD
+            ours
+            =======
+            theirs
+            >>>>>>> feature
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), text);
+        let snapshot = buffer.snapshot();
+        let region = conflict_regions(&snapshot).pop().unwrap();
+        assert_eq!(
+            region.resolved_text(ConflictResolution::Ours, &snapshot),
+            "ours\n"
+        );
+        assert_eq!(
+            region.resolved_text(ConflictResolution::Theirs, &snapshot),
+            "theirs\n"
+        );
+        assert_eq!(
+            region.resolved_text(ConflictResolution::Both, &snapshot),
+            "ours\ntheirs\n"
+        );
+        assert!(has_conflict_markers(&snapshot));
+    }
+}
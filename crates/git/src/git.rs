@@ -3,6 +3,7 @@ use std::ffi::OsStr;
 pub use git2 as libgit;
 pub use lazy_static::lazy_static;
 
+pub mod conflict;
 pub mod diff;
 
 lazy_static! {
@@ -0,0 +1,94 @@
+use gpui::{Subscription, View, WeakView};
+use ui::{
+    div, Button, ButtonCommon, Clickable, FluentBuilder, IntoElement, LabelSize, ParentElement,
+    Render, Tooltip, ViewContext,
+};
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::{format_file_size, ImageView, ZoomToFit};
+
+/// A status bar item showing the dimensions, file size and zoom level of the
+/// active image.
+pub struct ImageInfo {
+    text: Option<String>,
+    image_view: Option<WeakView<ImageView>>,
+    _observe_active_image: Option<Subscription>,
+}
+
+impl ImageInfo {
+    pub fn new() -> Self {
+        Self {
+            text: None,
+            image_view: None,
+            _observe_active_image: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    fn update_info(&mut self, image_view: View<ImageView>, cx: &mut ViewContext<Self>) {
+        let image_view = image_view.read(cx);
+        let image_item = image_view.image_item().read(cx);
+        let (width, height) = image_item.dimensions();
+        let zoom = match image_view.zoom_level() {
+            Some(zoom) => format!("{:.0}%", zoom * 100.),
+            None => "Fit".to_string(),
+        };
+        self.text = Some(format!(
+            "{width} × {height} {} · {} · {zoom}",
+            image_item.format().name(),
+            format_file_size(image_item.file_size()),
+        ));
+        cx.notify();
+    }
+}
+
+impl Default for ImageInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Render for ImageInfo {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div().when_some(self.text.clone(), |el, text| {
+            el.child(
+                Button::new("image-info", text)
+                    .label_size(LabelSize::Small)
+                    .on_click(cx.listener(|this, _, cx| {
+                        if let Some(image_view) =
+                            this.image_view.as_ref().and_then(|view| view.upgrade())
+                        {
+                            image_view.update(cx, |image_view, cx| {
+                                image_view.zoom_to_fit(&ZoomToFit, cx)
+                            });
+                        }
+                    }))
+                    .tooltip(|cx| Tooltip::for_action("Zoom to Fit", &ZoomToFit, cx)),
+            )
+        })
+    }
+}
+
+impl StatusItemView for ImageInfo {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(image_view) = active_pane_item.and_then(|item| item.act_as::<ImageView>(cx)) {
+            self.image_view = Some(image_view.downgrade());
+            self._observe_active_image = Some(cx.observe(&image_view, Self::update_info));
+            self.update_info(image_view, cx);
+        } else {
+            self.text = None;
+            self.image_view = None;
+            self._observe_active_image = None;
+        }
+
+        cx.notify();
+    }
+}
@@ -0,0 +1,803 @@
+mod image_info;
+
+use anyhow::{anyhow, Result};
+use gpui::{
+    actions, canvas, div, fill, point, px, size, AnyElement, AppContext, BackgroundExecutor,
+    Bounds, Context, EntityId, EventEmitter, FocusHandle, FocusableView, Hsla, ImageData,
+    InteractiveElement, IntoElement, Model, ModelContext, ParentElement, Pixels, Point, Render,
+    ScrollWheelEvent, SharedString, Size, Styled, Subscription, Task, View, ViewContext,
+    VisualContext, WeakModel, WindowContext,
+};
+use image::{Bgra, ImageBuffer};
+use project::{worktree, Project, ProjectEntryId, ProjectPath};
+use std::{cell::Cell, path::Path, rc::Rc, sync::Arc, time::SystemTime};
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent, ProjectItem},
+    WorkspaceId,
+};
+
+pub use image_info::ImageInfo;
+
+actions!(
+    image_viewer,
+    [ZoomIn, ZoomOut, ZoomToFit, ActualSize, TogglePixelGrid]
+);
+
+const ZOOM_STEP: f32 = 1.25;
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 64.;
+/// The zoom level above which the pixel grid is drawn, if enabled.
+const PIXEL_GRID_MIN_ZOOM: f32 = 8.;
+/// The size of the squares in the checkerboard drawn behind transparent images.
+const CHECKERBOARD_SIZE: f32 = 8.;
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_project_item::<ImageView>(cx);
+}
+
+/// The image formats that can be opened in the image viewer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Svg,
+    WebP,
+}
+
+impl ImageFormat {
+    /// Returns the format of the image at `path`, based on its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "gif" => Some(Self::Gif),
+            "svg" => Some(Self::Svg),
+            "webp" => Some(Self::WebP),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Jpeg => "JPEG",
+            Self::Gif => "GIF",
+            Self::Svg => "SVG",
+            Self::WebP => "WebP",
+        }
+    }
+}
+
+/// Decodes an image into BGRA pixels, rasterizing SVGs at their intrinsic size.
+pub fn decode_image(bytes: &[u8], format: ImageFormat) -> Result<ImageBuffer<Bgra<u8>, Vec<u8>>> {
+    let format = match format {
+        ImageFormat::Png => image::ImageFormat::Png,
+        ImageFormat::Jpeg => image::ImageFormat::Jpeg,
+        ImageFormat::Gif => image::ImageFormat::Gif,
+        ImageFormat::WebP => image::ImageFormat::WebP,
+        ImageFormat::Svg => return rasterize_svg(bytes),
+    };
+    Ok(image::load_from_memory_with_format(bytes, format)?.into_bgra8())
+}
+
+fn rasterize_svg(bytes: &[u8]) -> Result<ImageBuffer<Bgra<u8>, Vec<u8>>> {
+    let tree = usvg::Tree::from_data(bytes, &usvg::Options::default())?;
+    let svg_size = tree.svg_node().size;
+    let width = svg_size.width().ceil() as u32;
+    let height = svg_size.height().ceil() as u32;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(width, height).ok_or_else(|| anyhow!("invalid SVG size"))?;
+    resvg::render(&tree, usvg::FitTo::Original, pixmap.as_mut());
+
+    // The pixmap holds premultiplied RGBA pixels.
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.blue(), color.green(), color.red(), color.alpha()]
+        })
+        .collect();
+    ImageBuffer::from_raw(width, height, pixels).ok_or_else(|| anyhow!("invalid SVG size"))
+}
+
+/// Formats a file size for display, e.g. "1.5 MB".
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+async fn load_image(
+    load: Task<Result<Vec<u8>>>,
+    format: ImageFormat,
+    executor: BackgroundExecutor,
+) -> Result<(Arc<ImageData>, u64)> {
+    let bytes = load.await?;
+    let file_size = bytes.len() as u64;
+    let image = executor
+        .spawn(async move { decode_image(&bytes, format) })
+        .await?;
+    Ok((Arc::new(ImageData::new(image)), file_size))
+}
+
+/// A decoded image file in a project, which is reloaded when the file
+/// changes on disk.
+pub struct ImageItem {
+    project: WeakModel<Project>,
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    mtime: Option<SystemTime>,
+    format: ImageFormat,
+    image: Arc<ImageData>,
+    file_size: u64,
+    _reload_task: Option<Task<()>>,
+    _worktree_subscription: Option<Subscription>,
+}
+
+impl ImageItem {
+    fn load(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        let format = ImageFormat::from_path(&path.path)?;
+        let load = project.update(cx, |project, cx| project.load_binary_file(path, cx));
+        let executor = cx.background_executor().clone();
+        let project = project.clone();
+        let path = path.clone();
+        Some(cx.spawn(|mut cx| async move {
+            let (image, file_size) = load_image(load, format, executor).await?;
+            cx.new_model(|cx| Self::new(project, path, format, image, file_size, cx))
+        }))
+    }
+
+    fn new(
+        project: Model<Project>,
+        project_path: ProjectPath,
+        format: ImageFormat,
+        image: Arc<ImageData>,
+        file_size: u64,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let entry = project.read(cx).entry_for_path(&project_path, cx);
+        let worktree_subscription = project
+            .read(cx)
+            .worktree_for_id(project_path.worktree_id, cx)
+            .map(|worktree| {
+                cx.subscribe(&worktree, |this, _, event, cx| {
+                    if let worktree::Event::UpdatedEntries(_) = event {
+                        this.file_updated(cx);
+                    }
+                })
+            });
+
+        Self {
+            project: project.downgrade(),
+            project_path,
+            entry_id: entry.as_ref().map(|entry| entry.id),
+            mtime: entry.map(|entry| entry.mtime),
+            format,
+            image,
+            file_size,
+            _reload_task: None,
+            _worktree_subscription: worktree_subscription,
+        }
+    }
+
+    pub fn image(&self) -> Arc<ImageData> {
+        self.image.clone()
+    }
+
+    pub fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// The width and height of the image, in pixels.
+    pub fn dimensions(&self) -> (u32, u32) {
+        let size = self.image.size();
+        (i32::from(size.width) as u32, i32::from(size.height) as u32)
+    }
+
+    fn file_updated(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        // Keep showing the last version of deleted files.
+        let Some(entry) = project.read(cx).entry_for_path(&self.project_path, cx) else {
+            return;
+        };
+        if self.mtime == Some(entry.mtime) {
+            return;
+        }
+        self.mtime = Some(entry.mtime);
+        self.entry_id = Some(entry.id);
+
+        let load = project.update(cx, |project, cx| {
+            project.load_binary_file(&self.project_path, cx)
+        });
+        let format = self.format;
+        let executor = cx.background_executor().clone();
+        self._reload_task = Some(cx.spawn(|this, mut cx| async move {
+            let Some((image, file_size)) = load_image(load, format, executor).await.log_err()
+            else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.image = image;
+                this.file_size = file_size;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+}
+
+impl project::Item for ImageItem {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        Self::load(project, path, cx)
+    }
+
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &AppContext) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+}
+
+/// The size of the viewport and the zoom level that fits the image into it,
+/// as of the last time the view was painted.
+#[derive(Clone, Copy, Default)]
+struct ViewportLayout {
+    size: Size<Pixels>,
+    image_size: Size<f32>,
+    fit_zoom: f32,
+}
+
+pub struct ImageView {
+    image_item: Model<ImageItem>,
+    focus_handle: FocusHandle,
+    /// The zoom level, or `None` to fit the image into the viewport.
+    zoom: Option<f32>,
+    /// How far the center of the image is panned from the center of the viewport.
+    offset: Point<Pixels>,
+    show_pixel_grid: bool,
+    layout: Rc<Cell<ViewportLayout>>,
+    _observe_image: Subscription,
+}
+
+impl ImageView {
+    fn new(image_item: Model<ImageItem>, cx: &mut ViewContext<Self>) -> Self {
+        Self {
+            _observe_image: cx.observe(&image_item, |_, _, cx| cx.notify()),
+            image_item,
+            focus_handle: cx.focus_handle(),
+            zoom: None,
+            offset: Point::default(),
+            show_pixel_grid: true,
+            layout: Default::default(),
+        }
+    }
+
+    pub fn image_item(&self) -> &Model<ImageItem> {
+        &self.image_item
+    }
+
+    /// The zoom level chosen by the user, or `None` if the image is fit into the viewport.
+    pub fn zoom_level(&self) -> Option<f32> {
+        self.zoom
+    }
+
+    /// The current zoom level, resolving the zoom level that fits the image.
+    fn zoom(&self) -> f32 {
+        self.zoom.unwrap_or_else(|| self.layout.get().fit_zoom)
+    }
+
+    fn set_zoom(&mut self, zoom: Option<f32>, cx: &mut ViewContext<Self>) {
+        let previous_zoom = self.zoom();
+        self.zoom = zoom.map(|zoom| zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+        match self.zoom {
+            // Keep the same part of the image in the center of the viewport.
+            Some(zoom) if previous_zoom > 0. => {
+                self.offset = self.offset * (zoom / previous_zoom);
+                self.clamp_offset();
+            }
+            _ => self.offset = Point::default(),
+        }
+        cx.emit(ImageViewEvent::ZoomChanged);
+        cx.notify();
+    }
+
+    fn zoom_in(&mut self, _: &ZoomIn, cx: &mut ViewContext<Self>) {
+        self.set_zoom(Some(self.zoom() * ZOOM_STEP), cx);
+    }
+
+    fn zoom_out(&mut self, _: &ZoomOut, cx: &mut ViewContext<Self>) {
+        self.set_zoom(Some(self.zoom() / ZOOM_STEP), cx);
+    }
+
+    fn zoom_to_fit(&mut self, _: &ZoomToFit, cx: &mut ViewContext<Self>) {
+        self.set_zoom(None, cx);
+    }
+
+    fn actual_size(&mut self, _: &ActualSize, cx: &mut ViewContext<Self>) {
+        self.set_zoom(Some(1.), cx);
+    }
+
+    fn toggle_pixel_grid(&mut self, _: &TogglePixelGrid, cx: &mut ViewContext<Self>) {
+        self.show_pixel_grid = !self.show_pixel_grid;
+        cx.notify();
+    }
+
+    fn scroll(&mut self, event: &ScrollWheelEvent, cx: &mut ViewContext<Self>) {
+        if self.zoom.is_none() {
+            return;
+        }
+        self.offset = self.offset + event.delta.pixel_delta(cx.line_height());
+        self.clamp_offset();
+        cx.notify();
+    }
+
+    /// Prevents panning the image beyond its edges.
+    fn clamp_offset(&mut self) {
+        let layout = self.layout.get();
+        let zoom = self.zoom();
+        let max_x = ((px(layout.image_size.width * zoom) - layout.size.width) / 2.).max(px(0.));
+        let max_y = ((px(layout.image_size.height * zoom) - layout.size.height) / 2.).max(px(0.));
+        self.offset = point(
+            self.offset.x.clamp(-max_x, max_x),
+            self.offset.y.clamp(-max_y, max_y),
+        );
+    }
+}
+
+fn image_size(image: &ImageData) -> Size<f32> {
+    let pixels = image.size();
+    size(
+        i32::from(pixels.width) as f32,
+        i32::from(pixels.height) as f32,
+    )
+}
+
+/// The zoom level that fits an image into the viewport without enlarging it.
+fn fit_zoom(image_size: Size<f32>, viewport: Size<Pixels>) -> f32 {
+    if image_size.width <= 0. || image_size.height <= 0. {
+        return 1.;
+    }
+    (f32::from(viewport.width) / image_size.width)
+        .min(f32::from(viewport.height) / image_size.height)
+        .min(1.)
+}
+
+struct ImagePaintParams {
+    image: Arc<ImageData>,
+    zoom: Option<f32>,
+    offset: Point<Pixels>,
+    show_pixel_grid: bool,
+    layout: Rc<Cell<ViewportLayout>>,
+    checkerboard_light: Hsla,
+    checkerboard_dark: Hsla,
+    grid_color: Hsla,
+}
+
+fn paint_image(params: ImagePaintParams, viewport: &Bounds<Pixels>, cx: &mut ElementContext) {
+    let image_size = image_size(&params.image);
+    let fit_zoom = fit_zoom(image_size, viewport.size);
+    params.layout.set(ViewportLayout {
+        size: viewport.size,
+        image_size,
+        fit_zoom,
+    });
+
+    let zoom = params.zoom.unwrap_or(fit_zoom);
+    let scaled_size = size(px(image_size.width * zoom), px(image_size.height * zoom));
+    let center = viewport.center() + params.offset;
+    let image_bounds = Bounds::new(
+        point(
+            (center.x - scaled_size.width / 2.).round(),
+            (center.y - scaled_size.height / 2.).round(),
+        ),
+        scaled_size,
+    );
+    let visible_bounds = image_bounds.intersect(&cx.content_mask().bounds.intersect(viewport));
+    if visible_bounds.size.width <= px(0.) || visible_bounds.size.height <= px(0.) {
+        return;
+    }
+
+    // Draw a checkerboard behind the image, so that transparency is visible.
+    cx.paint_quad(fill(visible_bounds, params.checkerboard_light));
+    let cell = px(CHECKERBOARD_SIZE);
+    let first_column = ((visible_bounds.origin.x - image_bounds.origin.x) / cell).floor() as i64;
+    let first_row = ((visible_bounds.origin.y - image_bounds.origin.y) / cell).floor() as i64;
+    let last_column =
+        ((visible_bounds.lower_right().x - image_bounds.origin.x) / cell).ceil() as i64;
+    let last_row = ((visible_bounds.lower_right().y - image_bounds.origin.y) / cell).ceil() as i64;
+    for row in first_row..last_row {
+        for column in first_column..last_column {
+            if (row + column) % 2 == 0 {
+                continue;
+            }
+            let cell_bounds = Bounds::new(
+                image_bounds.origin + point(cell * column as f32, cell * row as f32),
+                size(cell, cell),
+            )
+            .intersect(&visible_bounds);
+            cx.paint_quad(fill(cell_bounds, params.checkerboard_dark));
+        }
+    }
+
+    cx.with_z_index(1, |cx| {
+        cx.paint_image(image_bounds, Default::default(), params.image, false)
+            .log_err();
+    });
+
+    if params.show_pixel_grid && zoom >= PIXEL_GRID_MIN_ZOOM {
+        let pixel = px(zoom);
+        let line_width = px(1.);
+        cx.with_z_index(2, |cx| {
+            let first_column = ((visible_bounds.origin.x - image_bounds.origin.x) / pixel).ceil();
+            let last_column =
+                ((visible_bounds.lower_right().x - image_bounds.origin.x) / pixel).floor();
+            for column in first_column as i64..=last_column as i64 {
+                let x = image_bounds.origin.x + pixel * column as f32;
+                cx.paint_quad(fill(
+                    Bounds::new(
+                        point(x, visible_bounds.origin.y),
+                        size(line_width, visible_bounds.size.height),
+                    ),
+                    params.grid_color,
+                ));
+            }
+            let first_row = ((visible_bounds.origin.y - image_bounds.origin.y) / pixel).ceil();
+            let last_row =
+                ((visible_bounds.lower_right().y - image_bounds.origin.y) / pixel).floor();
+            for row in first_row as i64..=last_row as i64 {
+                let y = image_bounds.origin.y + pixel * row as f32;
+                cx.paint_quad(fill(
+                    Bounds::new(
+                        point(visible_bounds.origin.x, y),
+                        size(visible_bounds.size.width, line_width),
+                    ),
+                    params.grid_color,
+                ));
+            }
+        });
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageViewEvent {
+    ZoomChanged,
+}
+
+impl EventEmitter<ImageViewEvent> for ImageView {}
+
+impl FocusableView for ImageView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for ImageView {
+    type Event = ImageViewEvent;
+
+    fn tab_content(
+        &self,
+        _detail: Option<usize>,
+        selected: bool,
+        cx: &WindowContext,
+    ) -> AnyElement {
+        let path = &self.image_item.read(cx).project_path.path;
+        let title = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string());
+        Label::new(title)
+            .color(if selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        Some(
+            self.image_item
+                .read(cx)
+                .project_path
+                .path
+                .to_string_lossy()
+                .to_string()
+                .into(),
+        )
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("image viewer")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(EntityId, &dyn project::Item),
+    ) {
+        f(self.image_item.entity_id(), self.image_item.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        Some(cx.new_view(|cx| Self::new(self.image_item.clone(), cx)))
+    }
+
+    fn to_item_events(_event: &Self::Event, _f: impl FnMut(ItemEvent)) {}
+}
+
+impl ProjectItem for ImageView {
+    type Item = ImageItem;
+
+    fn for_project_item(
+        _project: Model<Project>,
+        item: Model<Self::Item>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        Self::new(item, cx)
+    }
+}
+
+impl Render for ImageView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let params = ImagePaintParams {
+            image: self.image_item.read(cx).image(),
+            zoom: self.zoom,
+            offset: self.offset,
+            show_pixel_grid: self.show_pixel_grid,
+            layout: self.layout.clone(),
+            checkerboard_light: colors.editor_background,
+            checkerboard_dark: colors.element_background,
+            grid_color: colors.border_variant,
+        };
+
+        div()
+            .key_context("ImageViewer")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(colors.editor_background)
+            .on_action(cx.listener(Self::zoom_in))
+            .on_action(cx.listener(Self::zoom_out))
+            .on_action(cx.listener(Self::zoom_to_fit))
+            .on_action(cx.listener(Self::actual_size))
+            .on_action(cx.listener(Self::toggle_pixel_grid))
+            .on_scroll_wheel(cx.listener(Self::scroll))
+            .child(canvas(move |bounds, cx| paint_image(params, bounds, cx)).size_full())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{ScrollDelta, TestAppContext, VisualTestContext};
+    use image::{DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use workspace::{item::ItemHandle, StatusItemView, Workspace};
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            super::init(cx);
+        });
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, Rgba([0, 0, 255, 255])))
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .unwrap();
+        png
+    }
+
+    async fn open_image(
+        path: &'static str,
+        fs: Arc<FakeFs>,
+        cx: &mut TestAppContext,
+    ) -> (View<ImageView>, View<Workspace>, &mut VisualTestContext) {
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let item = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, path), None, true, cx)
+            })
+            .await
+            .unwrap();
+        (item.downcast::<ImageView>().unwrap(), workspace, cx)
+    }
+
+    #[gpui::test]
+    async fn test_zoom_and_pan(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({})).await;
+        fs.insert_file("/root/a.png", png(400, 200)).await;
+        let (image_view, _, cx) = open_image("a.png", fs, cx).await;
+
+        image_view.update(cx, |image_view, cx| {
+            image_view.layout.set(ViewportLayout {
+                size: size(px(100.), px(100.)),
+                image_size: size(400., 200.),
+                fit_zoom: 0.25,
+            });
+            assert_eq!(image_view.zoom_level(), None);
+
+            // Panning does nothing while the image fits into the viewport.
+            let scroll = ScrollWheelEvent {
+                delta: ScrollDelta::Pixels(point(px(500.), px(30.))),
+                ..Default::default()
+            };
+            image_view.scroll(&scroll, cx);
+            assert_eq!(image_view.offset, Point::default());
+
+            image_view.zoom_in(&ZoomIn, cx);
+            assert_eq!(image_view.zoom_level(), Some(0.25 * ZOOM_STEP));
+
+            // The image can't be panned beyond its edges.
+            image_view.actual_size(&ActualSize, cx);
+            assert_eq!(image_view.zoom_level(), Some(1.));
+            image_view.scroll(&scroll, cx);
+            assert_eq!(image_view.offset, point(px(150.), px(30.)));
+
+            // Zooming keeps the same part of the image in the center.
+            image_view.zoom_in(&ZoomIn, cx);
+            assert_eq!(image_view.offset, point(px(187.5), px(37.5)));
+            image_view.zoom_out(&ZoomOut, cx);
+            image_view.zoom_out(&ZoomOut, cx);
+            assert_eq!(image_view.zoom_level(), Some(0.8));
+            assert_eq!(image_view.offset, point(px(110.), px(24.)));
+
+            image_view.zoom_to_fit(&ZoomToFit, cx);
+            assert_eq!(image_view.zoom_level(), None);
+            assert_eq!(image_view.offset, Point::default());
+
+            image_view.set_zoom(Some(1000.), cx);
+            assert_eq!(image_view.zoom_level(), Some(MAX_ZOOM));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_image_info_and_reload(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({})).await;
+        let content = png(4, 2);
+        let file_size = content.len() as u64;
+        fs.insert_file("/root/a.png", content).await;
+        let (image_view, _, cx) = open_image("a.png", fs.clone(), cx).await;
+
+        let image_info = cx.new_view(|_| ImageInfo::new());
+        image_info.update(cx, |image_info, cx| {
+            image_info.set_active_pane_item(Some(&image_view as &dyn ItemHandle), cx)
+        });
+        image_info.update(cx, |image_info, _| {
+            assert_eq!(
+                image_info.text(),
+                Some(format!("4 × 2 PNG · {} · Fit", format_file_size(file_size)).as_str())
+            );
+        });
+
+        // The status item follows the zoom level of the image.
+        image_view.update(cx, |image_view, cx| image_view.actual_size(&ActualSize, cx));
+        cx.run_until_parked();
+        image_info.update(cx, |image_info, _| {
+            assert!(image_info.text().unwrap().ends_with(" · 100%"));
+        });
+
+        // The image is reloaded when the file changes.
+        let content = png(3, 5);
+        let file_size = content.len() as u64;
+        fs.insert_file("/root/a.png", content).await;
+        cx.run_until_parked();
+        image_view.update(cx, |image_view, cx| {
+            let image_item = image_view.image_item().read(cx);
+            assert_eq!(image_item.dimensions(), (3, 5));
+            assert_eq!(image_item.file_size(), file_size);
+        });
+        image_info.update(cx, |image_info, _| {
+            assert_eq!(
+                image_info.text(),
+                Some(format!("3 × 5 PNG · {} · 100%", format_file_size(file_size)).as_str())
+            );
+        });
+
+        image_info.update(cx, |image_info, cx| {
+            image_info.set_active_pane_item(None, cx)
+        });
+        image_info.update(cx, |image_info, _| assert_eq!(image_info.text(), None));
+    }
+
+    #[test]
+    fn test_image_format_from_path() {
+        assert_eq!(
+            ImageFormat::from_path(Path::new("a/b.PNG")),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            ImageFormat::from_path(Path::new("photo.jpeg")),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(
+            ImageFormat::from_path(Path::new("icon.svg")),
+            Some(ImageFormat::Svg)
+        );
+        assert_eq!(ImageFormat::from_path(Path::new("main.rs")), None);
+        assert_eq!(ImageFormat::from_path(Path::new("png")), None);
+    }
+
+    #[test]
+    fn test_decode_image() {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(3, 2, Rgba([255, 0, 0, 128])))
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .unwrap();
+        let image = decode_image(&png, ImageFormat::Png).unwrap();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.get_pixel(0, 0), &Bgra([0, 0, 255, 128]));
+
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="5">
+            <rect width="4" height="5" fill="blue"/>
+        </svg>"#;
+        let image = decode_image(svg, ImageFormat::Svg).unwrap();
+        assert_eq!(image.dimensions(), (4, 5));
+        assert_eq!(image.get_pixel(1, 1), &Bgra([255, 0, 0, 255]));
+
+        assert!(decode_image(b"not an image", ImageFormat::Gif).is_err());
+    }
+
+    #[test]
+    fn test_fit_zoom_and_file_size() {
+        assert_eq!(fit_zoom(size(200., 100.), size(px(100.), px(100.))), 0.5);
+        assert_eq!(fit_zoom(size(10., 10.), size(px(100.), px(100.))), 1.);
+
+        assert_eq!(format_file_size(512), "512 B");
+        assert_eq!(format_file_size(1536), "1.5 KB");
+        assert_eq!(format_file_size(3 * 1024 * 1024), "3.0 MB");
+    }
+}
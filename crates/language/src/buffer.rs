@@ -81,6 +81,11 @@ pub struct Buffer {
     diff_base: Option<String>,
     git_diff: git::diff::BufferDiff,
     file: Option<Arc<dyn File>>,
+    /// The encoding of the file on disk.
+    encoding: Encoding,
+    /// Whether the buffer is in large-file mode, in which it isn't parsed and
+    /// its git diff isn't computed.
+    large_file: bool,
     /// The mtime of the file when this buffer was last loaded from
     /// or saved to disk.
     saved_mtime: SystemTime,
@@ -342,6 +347,8 @@ pub enum Event {
     DiagnosticsUpdated,
     /// The buffer gained or lost editing capabilities.
     CapabilityChanged,
+    /// The buffer entered or left large-file mode.
+    LargeFileChanged,
     /// The buffer was explicitly requested to close.
     Closed,
 }
@@ -394,8 +401,8 @@ pub trait LocalFile: File {
     /// Returns the absolute path of this file.
     fn abs_path(&self, cx: &AppContext) -> PathBuf;
 
-    /// Loads the file's contents from disk.
-    fn load(&self, cx: &AppContext) -> Task<Result<String>>;
+    /// Loads the file's contents from disk, decoding them with the given encoding.
+    fn load(&self, encoding: Encoding, cx: &AppContext) -> Task<Result<String>>;
 
     /// Called when the buffer is reloaded from disk.
     fn buffer_reloaded(
@@ -404,6 +411,7 @@ pub trait LocalFile: File {
         version: &clock::Global,
         fingerprint: RopeFingerprint,
         line_ending: LineEnding,
+        encoding: Encoding,
         mtime: SystemTime,
         cx: &mut AppContext,
     );
@@ -571,6 +579,14 @@ impl Buffer {
             rpc::proto::LineEnding::from_i32(message.line_ending)
                 .ok_or_else(|| anyhow!("missing line_ending"))?,
         ));
+        if let Some(encoding) = message
+            .encoding
+            .as_deref()
+            .and_then(proto::deserialize_encoding)
+        {
+            this.encoding = encoding;
+        }
+        this.large_file = message.large_file;
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.file_fingerprint = proto::deserialize_fingerprint(&message.saved_version_fingerprint)?;
         this.saved_mtime = message
@@ -588,6 +604,8 @@ impl Buffer {
             base_text: self.base_text().to_string(),
             diff_base: self.diff_base.as_ref().map(|h| h.to_string()),
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
+            encoding: Some(proto::serialize_encoding(self.encoding)),
+            large_file: self.large_file,
             saved_version: proto::serialize_version(&self.saved_version),
             saved_version_fingerprint: proto::serialize_fingerprint(self.file_fingerprint),
             saved_mtime: Some(self.saved_mtime.into()),
@@ -681,6 +699,8 @@ impl Buffer {
             diff_base,
             git_diff: git::diff::BufferDiff::new(),
             file,
+            encoding: Encoding::default(),
+            large_file: false,
             capability,
             syntax_map: Mutex::new(SyntaxMap::new()),
             parsing_in_background: false,
@@ -758,6 +778,43 @@ impl Buffer {
         self.saved_mtime
     }
 
+    /// The encoding used to load and save the buffer's file.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Sets the encoding that will be used the next time the buffer is saved.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut ModelContext<Self>) {
+        if self.encoding != encoding {
+            self.encoding = encoding;
+            cx.notify();
+        }
+    }
+
+    /// Whether the buffer is in large-file mode, see [Buffer::set_large_file].
+    pub fn is_large_file(&self) -> bool {
+        self.large_file
+    }
+
+    /// Enters or leaves large-file mode. While in large-file mode, the buffer
+    /// keeps its language but isn't parsed, and its git diff isn't computed.
+    pub fn set_large_file(&mut self, large_file: bool, cx: &mut ModelContext<Self>) {
+        if self.large_file == large_file {
+            return;
+        }
+        self.large_file = large_file;
+        self.parse_count += 1;
+        self.syntax_map.lock().clear();
+        if large_file {
+            self.git_diff = git::diff::BufferDiff::new();
+            self.git_diff_update_count += 1;
+        } else {
+            self.reparse(cx);
+        }
+        cx.emit(Event::LargeFileChanged);
+        cx.notify();
+    }
+
     /// Assign a language to the buffer.
     pub fn set_language(&mut self, language: Option<Arc<Language>>, cx: &mut ModelContext<Self>) {
         self.parse_count += 1;
@@ -801,13 +858,37 @@ impl Buffer {
     pub fn reload(
         &mut self,
         cx: &mut ModelContext<Self>,
+    ) -> oneshot::Receiver<Option<Transaction>> {
+        self.reload_from_disk(self.encoding, cx)
+    }
+
+    /// Reloads the contents of the buffer from disk, decoding them with the given
+    /// encoding. Fails if the buffer has unsaved edits, as they would be lost.
+    pub fn reload_with_encoding(
+        &mut self,
+        encoding: Encoding,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<oneshot::Receiver<Option<Transaction>>> {
+        if self.is_dirty() {
+            return Err(anyhow!(
+                "Save or discard the unsaved changes before reopening the file as {}",
+                encoding.display_name()
+            ));
+        }
+        Ok(self.reload_from_disk(encoding, cx))
+    }
+
+    fn reload_from_disk(
+        &mut self,
+        encoding: Encoding,
+        cx: &mut ModelContext<Self>,
     ) -> oneshot::Receiver<Option<Transaction>> {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
             let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.mtime(), file.load(cx)))
+                Some((file.mtime(), file.load(encoding, cx)))
             })?
             else {
                 return Ok(());
@@ -819,8 +900,17 @@ impl Buffer {
                 .await;
             this.update(&mut cx, |this, cx| {
                 if this.version() == diff.base_version {
+                    this.encoding = encoding;
                     this.finalize_last_transaction();
                     this.apply_diff(diff, cx);
+                    // A line ending configured for the file, e.g. by an `.editorconfig`,
+                    // takes precedence over the one detected in the new contents.
+                    if let Some(line_ending) = language_settings(this.language(), this.file(), cx)
+                        .line_ending
+                        .line_ending()
+                    {
+                        this.text.set_line_ending(line_ending);
+                    }
                     tx.send(this.finalize_last_transaction().cloned()).ok();
                     this.has_conflict = false;
                     this.did_reload(
@@ -874,6 +964,7 @@ impl Buffer {
                 &self.saved_version,
                 self.file_fingerprint,
                 self.line_ending(),
+                self.encoding,
                 self.saved_mtime,
                 cx,
             );
@@ -945,6 +1036,9 @@ impl Buffer {
 
     /// Recomputes the Git diff status.
     pub fn git_diff_recalc(&mut self, cx: &mut ModelContext<Self>) -> Option<Task<()>> {
+        if self.large_file {
+            return None;
+        }
         let diff_base = self.diff_base.clone()?; // TODO: Make this an Arc
         let snapshot = self.snapshot();
 
@@ -1046,7 +1140,7 @@ impl Buffer {
     /// for the same buffer, we only initiate a new parse if we are not already
     /// parsing in the background.
     pub fn reparse(&mut self, cx: &mut ModelContext<Self>) {
-        if self.parsing_in_background {
+        if self.parsing_in_background || self.large_file {
             return;
         }
         let language = if let Some(language) = self.language.clone() {
@@ -2864,6 +2958,49 @@ impl BufferSnapshot {
         self.syntax.matches(range, self, query)
     }
 
+    /// Returns the ranges of the syntax nodes overlapping `range` that are
+    /// captured by any of the given scopes of the `overrides.scm` query, such
+    /// as `comment` or `string`. The ranges are sorted and don't overlap.
+    pub fn override_scope_ranges(
+        &self,
+        range: Range<usize>,
+        scope_names: &[&str],
+    ) -> Vec<Range<usize>> {
+        let mut matches = self.syntax.matches(range.clone(), &self.text, |grammar| {
+            grammar.override_config.as_ref().map(|c| &c.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.override_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut ranges = Vec::new();
+        while let Some(mat) = matches.peek() {
+            let config = &configs[mat.grammar_index];
+            for capture in mat.captures {
+                if let Some((name, _)) = config.values.get(&capture.index) {
+                    if scope_names.contains(&name.as_str()) {
+                        let node_range = capture.node.byte_range();
+                        ranges
+                            .push(node_range.start.max(range.start)..node_range.end.min(range.end));
+                    }
+                }
+            }
+            matches.advance();
+        }
+
+        ranges.sort_unstable_by_key(|range| range.start);
+        let mut merged_ranges: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged_ranges.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged_ranges.push(range),
+            }
+        }
+        merged_ranges
+    }
+
     /// Returns bracket range pairs overlapping or adjacent to `range`
     pub fn bracket_ranges<T: ToOffset>(
         &self,
@@ -2995,6 +3132,49 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the ranges that should be edited together with the one containing
+    /// `position`, such as the names in an opening and a closing tag, according
+    /// to the linked editing query.
+    pub fn linked_editing_ranges<T: ToOffset>(&self, position: T) -> Vec<Range<usize>> {
+        let offset = position.to_offset(self);
+        let mut syntax_matches = self.syntax.matches(offset..offset, self, |grammar| {
+            grammar
+                .linked_editing_config
+                .as_ref()
+                .map(|config| &config.query)
+        });
+
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.linked_editing_config.as_ref())
+            .collect::<Vec<_>>();
+
+        let mut result = None;
+        while let Some(mat) = syntax_matches.peek() {
+            if let Some(config) = configs[mat.grammar_index] {
+                let ranges = mat
+                    .captures
+                    .iter()
+                    .filter(|capture| capture.index == config.linked_capture_ix)
+                    .map(|capture| capture.node.byte_range())
+                    .collect::<Vec<_>>();
+                let contains_offset = ranges
+                    .iter()
+                    .any(|range| range.start <= offset && offset <= range.end);
+                if ranges.len() > 1 && contains_offset {
+                    result = Some(ranges);
+                    break;
+                }
+            }
+            syntax_matches.advance();
+        }
+
+        let mut result = result.unwrap_or_default();
+        result.sort_unstable_by_key(|range| range.start);
+        result
+    }
+
     /// Returns selections for remote peers intersecting the given range.
     #[allow(clippy::type_complexity)]
     pub fn remote_selections_in_range(
@@ -19,7 +19,7 @@ use std::{
     time::{Duration, Instant},
 };
 use text::network::Network;
-use text::{BufferId, LineEnding};
+use text::{BufferId, Encoding, LineEnding};
 use text::{Point, ToPoint};
 use unindent::Unindent as _;
 use util::{assert_set_eq, post_inc, test::marked_text_ranges, RandomCharIter};
@@ -479,6 +479,31 @@ async fn test_resetting_language(cx: &mut gpui::TestAppContext) {
     assert_eq!(get_tree_sexp(&buffer, cx), "(document (object))");
 }
 
+#[gpui::test]
+async fn test_large_file_mode(cx: &mut gpui::TestAppContext) {
+    let buffer = cx.new_model(|cx| {
+        let mut buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), "{}");
+        buffer.set_large_file(true, cx);
+        buffer.set_language(Some(Arc::new(rust_lang())), cx);
+        buffer
+    });
+
+    // The buffer keeps its language, but isn't parsed.
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert!(buffer.language().is_some());
+        assert!(!buffer.is_parsing());
+        assert_eq!(buffer.snapshot().syntax_layers().count(), 0);
+    });
+
+    buffer.update(cx, |buffer, cx| buffer.set_large_file(false, cx));
+    cx.executor().run_until_parked();
+    assert_eq!(
+        get_tree_sexp(&buffer, cx),
+        "(source_file (expression_statement (block)))"
+    );
+}
+
 #[gpui::test]
 async fn test_outline(cx: &mut gpui::TestAppContext) {
     let text = r#"
@@ -938,6 +963,34 @@ fn test_range_for_syntax_ancestor(cx: &mut AppContext) {
     }
 }
 
+#[gpui::test]
+fn test_linked_editing_ranges(cx: &mut AppContext) {
+    let language = html_lang()
+        .with_linked_editing_query(
+            "
+            (element
+              (start_tag (tag_name) @linked)
+              (end_tag (tag_name) @linked))
+            ",
+        )
+        .unwrap();
+
+    cx.new_model(|cx| {
+        let text = "<div><span>a</span><br></div>";
+        let buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(language), cx);
+        let snapshot = buffer.snapshot();
+
+        assert_eq!(snapshot.linked_editing_ranges(2), vec![1..4, 25..28]);
+        assert_eq!(snapshot.linked_editing_ranges(4), vec![1..4, 25..28]);
+        assert_eq!(snapshot.linked_editing_ranges(16), vec![6..10, 14..18]);
+        assert_eq!(snapshot.linked_editing_ranges(11), vec![]);
+        assert_eq!(snapshot.linked_editing_ranges(20), vec![]);
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_autoindent_with_soft_tabs(cx: &mut AppContext) {
     init_settings(cx, |_| {});
@@ -1971,6 +2024,30 @@ fn test_serialization(cx: &mut gpui::AppContext) {
     assert_eq!(buffer2.read(cx).text(), "abcDF");
 }
 
+#[gpui::test]
+fn test_encoding_serialization(cx: &mut gpui::AppContext) {
+    for encoding in Encoding::all() {
+        assert_eq!(
+            proto::deserialize_encoding(&proto::serialize_encoding(encoding)),
+            Some(encoding),
+            "round-tripping {}",
+            encoding.display_name()
+        );
+    }
+    assert_eq!(proto::deserialize_encoding("not-an-encoding"), None);
+
+    let encoding = Encoding::from_label("utf-16le").unwrap().with_bom(true);
+    let buffer1 = cx.new_model(|cx| {
+        let mut buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), "abc");
+        buffer.set_encoding(encoding, cx);
+        buffer
+    });
+    let state = buffer1.read(cx).to_proto();
+    let buffer2 =
+        cx.new_model(|_| Buffer::from_proto(1, Capability::ReadWrite, state, None).unwrap());
+    assert_eq!(buffer2.read(cx).encoding(), encoding);
+}
+
 #[gpui::test(iterations = 100)]
 fn test_random_collaboration(cx: &mut AppContext, mut rng: StdRng) {
     let min_peers = env::var("MIN_PEERS")
@@ -68,7 +68,7 @@ pub use language_registry::{
 pub use lsp::LanguageServerId;
 pub use outline::{Outline, OutlineItem};
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer};
-pub use text::LineEnding;
+pub use text::{Encoding, LineEnding};
 pub use tree_sitter::{Parser, Tree};
 
 /// Initializes the `language` crate.
@@ -792,6 +792,7 @@ pub struct Grammar {
     pub embedding_config: Option<EmbeddingConfig>,
     pub(crate) injection_config: Option<InjectionConfig>,
     pub(crate) override_config: Option<OverrideConfig>,
+    pub(crate) linked_editing_config: Option<LinkedEditingConfig>,
     pub(crate) highlight_map: Mutex<HighlightMap>,
 }
 
@@ -838,6 +839,11 @@ struct OverrideConfig {
     values: HashMap<u32, (String, LanguageConfigOverride)>,
 }
 
+struct LinkedEditingConfig {
+    query: Query,
+    linked_capture_ix: u32,
+}
+
 #[derive(Default, Clone)]
 struct InjectionPatternConfig {
     language: Option<Box<str>>,
@@ -878,6 +884,7 @@ impl Language {
                     injection_config: None,
                     override_config: None,
                     redactions_config: None,
+                    linked_editing_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").unwrap(),
                     ts_language,
                     highlight_map: Default::default(),
@@ -936,6 +943,11 @@ impl Language {
                 .with_redaction_query(query.as_ref())
                 .context("Error loading redaction query")?;
         }
+        if let Some(query) = queries.linked_editing {
+            self = self
+                .with_linked_editing_query(query.as_ref())
+                .context("Error loading linked editing query")?;
+        }
         Ok(self)
     }
 
@@ -1182,6 +1194,22 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_linked_editing_query(mut self, source: &str) -> anyhow::Result<Self> {
+        let grammar = self.grammar_mut();
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut linked_capture_ix = None;
+        get_capture_indices(&query, &mut [("linked", &mut linked_capture_ix)]);
+
+        if let Some(linked_capture_ix) = linked_capture_ix {
+            grammar.linked_editing_config = Some(LinkedEditingConfig {
+                query,
+                linked_capture_ix,
+            });
+        }
+
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> &mut Grammar {
         Arc::get_mut(self.grammar.as_mut().unwrap()).unwrap()
     }
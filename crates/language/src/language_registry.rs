@@ -100,6 +100,7 @@ pub const QUERY_FILENAME_PREFIXES: &[(
     ("injections", |q| &mut q.injections),
     ("overrides", |q| &mut q.overrides),
     ("redactions", |q| &mut q.redactions),
+    ("linked_editing", |q| &mut q.linked_editing),
 ];
 
 /// Tree-sitter language queries for a given language.
@@ -113,6 +114,7 @@ pub struct LanguageQueries {
     pub injections: Option<Cow<'static, str>>,
     pub overrides: Option<Cow<'static, str>>,
     pub redactions: Option<Cow<'static, str>>,
+    pub linked_editing: Option<Cow<'static, str>>,
 }
 
 #[derive(Clone, Default)]
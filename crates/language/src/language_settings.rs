@@ -1,6 +1,6 @@
 //! Provides `language`-related settings.
 
-use crate::{File, Language};
+use crate::{Encoding, File, Language, LineEnding};
 use anyhow::Result;
 use collections::{HashMap, HashSet};
 use globset::GlobMatcher;
@@ -11,7 +11,7 @@ use schemars::{
 };
 use serde::{Deserialize, Serialize};
 use settings::Settings;
-use std::{num::NonZeroU32, path::Path, sync::Arc};
+use std::{iter, num::NonZeroU32, path::Path, sync::Arc};
 
 /// Initializes the language settings.
 pub fn init(cx: &mut AppContext) {
@@ -74,6 +74,11 @@ pub struct LanguageSettings {
     /// Whether or not to ensure there's a single newline at the end of a buffer
     /// when saving it.
     pub ensure_final_newline_on_save: bool,
+    /// Which line endings to use when loading a file.
+    pub line_ending: LineEndingSetting,
+    /// The encoding to decode files with instead of detecting it, such as
+    /// "utf-8", "utf-8-bom" or "shift_jis".
+    pub encoding: Option<String>,
     /// How to perform a buffer format.
     pub formatter: Formatter,
     /// Zed's Prettier integration settings.
@@ -91,8 +96,15 @@ pub struct LanguageSettings {
     pub extend_comment_on_newline: bool,
     /// Inlay hint related settings.
     pub inlay_hints: InlayHintSettings,
+    /// Indent guide related settings.
+    pub indent_guides: IndentGuideSettings,
     /// Whether to automatically close brackets.
     pub use_autoclose: bool,
+    /// Whether to edit linked ranges, such as the names of an opening and a
+    /// closing tag, together.
+    pub linked_edits: bool,
+    /// Which text to check for spelling mistakes.
+    pub spell_check: SpellCheckMode,
     /// Which code actions to run on save
     pub code_actions_on_format: HashMap<String, bool>,
 }
@@ -178,6 +190,17 @@ pub struct LanguageSettingsContent {
     /// Default: true
     #[serde(default)]
     pub ensure_final_newline_on_save: Option<bool>,
+    /// Which line endings to use when loading a file.
+    ///
+    /// Default: detect
+    #[serde(default)]
+    pub line_ending: Option<LineEndingSetting>,
+    /// The encoding to decode files with instead of detecting it, such as
+    /// "utf-8", "utf-8-bom" or "shift_jis".
+    ///
+    /// Default: null
+    #[serde(default)]
+    pub encoding: Option<String>,
     /// How to perform a buffer format.
     ///
     /// Default: auto
@@ -212,11 +235,26 @@ pub struct LanguageSettingsContent {
     /// Inlay hint related settings.
     #[serde(default)]
     pub inlay_hints: Option<InlayHintSettings>,
+    /// Indent guide related settings.
+    #[serde(default)]
+    pub indent_guides: Option<IndentGuideSettings>,
     /// Whether to automatically type closing characters for you. For example,
     /// when you type (, Zed will automatically add a closing ) at the correct position.
     ///
     /// Default: true
     pub use_autoclose: Option<bool>,
+    /// Whether to edit linked ranges together, such as the names of an
+    /// opening and a closing tag in HTML or JSX. Linked ranges are provided by
+    /// language servers, or found with the language's tree-sitter grammar.
+    ///
+    /// Default: true
+    #[serde(default)]
+    pub linked_edits: Option<bool>,
+    /// Which text to check for spelling mistakes.
+    ///
+    /// Default: off
+    #[serde(default)]
+    pub spell_check: Option<SpellCheckMode>,
 
     /// Which code actions to run on save
     ///
@@ -271,6 +309,29 @@ pub enum FormatOnSave {
     },
 }
 
+/// Controls which line endings are used for files.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEndingSetting {
+    /// Keep the line endings found in the file.
+    Detect,
+    /// Use `\n` line endings.
+    Unix,
+    /// Use `\r\n` line endings.
+    Windows,
+}
+
+impl LineEndingSetting {
+    /// Returns the line ending to use, or `None` if it should be detected.
+    pub fn line_ending(self) -> Option<LineEnding> {
+        match self {
+            Self::Detect => None,
+            Self::Unix => Some(LineEnding::Unix),
+            Self::Windows => Some(LineEnding::Windows),
+        }
+    }
+}
+
 /// Controls how whitespace should be displayedin the editor.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -283,6 +344,19 @@ pub enum ShowWhitespaceSetting {
     All,
 }
 
+/// Controls which text in a buffer is checked for spelling mistakes.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SpellCheckMode {
+    /// Don't check spelling.
+    Off,
+    /// Check the comments and strings captured by the `comment` and `string`
+    /// scopes of the language's `overrides.scm` query.
+    CommentsAndStrings,
+    /// Check all text except code captured by the `code` scope, as in Markdown.
+    AllText,
+}
+
 /// Controls which formatter should be used when formatting code.
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -343,6 +417,35 @@ pub struct InlayHintSettings {
     pub scroll_debounce_ms: u64,
 }
 
+/// The settings for indent guides.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct IndentGuideSettings {
+    /// Whether to show indent guides in the editor.
+    ///
+    /// Default: true
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// The width of the indent guides in pixels, between 1 and 10.
+    ///
+    /// Default: 1
+    #[serde(default = "indent_guide_line_width")]
+    pub line_width: u32,
+    /// The width of the active indent guide in pixels, between 1 and 10.
+    ///
+    /// Default: 1
+    #[serde(default = "indent_guide_line_width")]
+    pub active_line_width: u32,
+    /// Whether to highlight the indent guide of the scope containing the cursor.
+    ///
+    /// Default: true
+    #[serde(default = "default_true")]
+    pub highlight_active: bool,
+}
+
+fn indent_guide_line_width() -> u32 {
+    1
+}
+
 fn default_true() -> bool {
     true
 }
@@ -372,6 +475,17 @@ impl InlayHintSettings {
     }
 }
 
+impl LanguageSettings {
+    /// Returns the configured encoding for files, if it names a known encoding.
+    pub fn file_encoding(&self) -> Option<Encoding> {
+        let label = self.encoding.as_deref()?;
+        match label.strip_suffix("-bom") {
+            Some(label) => Some(Encoding::from_label(label)?.with_bom(true)),
+            None => Encoding::from_label(label),
+        }
+    }
+}
+
 impl AllLanguageSettings {
     /// Returns the [`LanguageSettings`] for the language with the specified name.
     pub fn language<'a>(&'a self, language_name: Option<&str>) -> &'a LanguageSettings {
@@ -407,6 +521,12 @@ impl AllLanguageSettings {
         self.language(language.map(|l| l.name()).as_deref())
             .show_copilot_suggestions
     }
+    /// Returns whether spelling is checked in any language.
+    pub fn spell_check_enabled(&self) -> bool {
+        iter::once(&self.defaults)
+            .chain(self.languages.values())
+            .any(|settings| settings.spell_check != SpellCheckMode::Off)
+    }
 }
 
 /// The kind of an inlay hint.
@@ -577,6 +697,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
     merge(&mut settings.hard_tabs, src.hard_tabs);
     merge(&mut settings.soft_wrap, src.soft_wrap);
     merge(&mut settings.use_autoclose, src.use_autoclose);
+    merge(&mut settings.linked_edits, src.linked_edits);
     merge(&mut settings.show_wrap_guides, src.show_wrap_guides);
     merge(&mut settings.wrap_guides, src.wrap_guides.clone());
     merge(
@@ -599,6 +720,10 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         &mut settings.ensure_final_newline_on_save,
         src.ensure_final_newline_on_save,
     );
+    merge(&mut settings.line_ending, src.line_ending);
+    if src.encoding.is_some() {
+        settings.encoding = src.encoding.clone();
+    }
     merge(
         &mut settings.enable_language_server,
         src.enable_language_server,
@@ -613,6 +738,8 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.extend_comment_on_newline,
     );
     merge(&mut settings.inlay_hints, src.inlay_hints);
+    merge(&mut settings.indent_guides, src.indent_guides);
+    merge(&mut settings.spell_check, src.spell_check);
     fn merge<T>(target: &mut T, value: Option<T>) {
         if let Some(value) = value {
             *target = value;
@@ -40,6 +40,26 @@ pub fn serialize_line_ending(message: text::LineEnding) -> proto::LineEnding {
     }
 }
 
+/// Marks encodings that are written with a byte order mark.
+const BOM_SUFFIX: &str = "+bom";
+
+/// Serializes a [`text::Encoding`] to be sent over RPC.
+pub fn serialize_encoding(encoding: text::Encoding) -> String {
+    if encoding.has_bom() {
+        format!("{}{BOM_SUFFIX}", encoding.name())
+    } else {
+        encoding.name().to_string()
+    }
+}
+
+/// Deserializes a [`text::Encoding`] from the RPC representation.
+pub fn deserialize_encoding(encoding: &str) -> Option<text::Encoding> {
+    match encoding.strip_suffix(BOM_SUFFIX) {
+        Some(name) => Some(text::Encoding::from_label(name)?.with_bom(true)),
+        None => text::Encoding::from_label(encoding),
+    }
+}
+
 /// Serializes a [`crate::Operation`] to be sent over RPC.
 pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
     proto::Operation {
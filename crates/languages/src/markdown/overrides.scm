@@ -0,0 +1,6 @@
+[
+  (code_span)
+  (fenced_code_block)
+  (indented_code_block)
+  (link_destination)
+] @code
@@ -0,0 +1,11 @@
+(element
+  (start_tag (tag_name) @linked)
+  (end_tag (tag_name) @linked))
+
+(script_element
+  (start_tag (tag_name) @linked)
+  (end_tag (tag_name) @linked))
+
+(style_element
+  (start_tag (tag_name) @linked)
+  (end_tag (tag_name) @linked))
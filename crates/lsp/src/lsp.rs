@@ -636,6 +636,9 @@ impl LanguageServer {
                         related_document_support: Some(true),
                         dynamic_registration: None,
                     }),
+                    linked_editing_range: Some(LinkedEditingRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
                     ..Default::default()
                 }),
                 experimental: Some(json!({
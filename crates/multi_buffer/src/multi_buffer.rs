@@ -88,6 +88,7 @@ pub enum Event {
     DiffBaseChanged,
     LanguageChanged,
     CapabilityChanged,
+    LargeFileChanged,
     Reparsed,
     Saved,
     FileHandleChanged,
@@ -1466,6 +1467,7 @@ impl MultiBuffer {
             language::Event::Reparsed => Event::Reparsed,
             language::Event::DiagnosticsUpdated => Event::DiagnosticsUpdated,
             language::Event::Closed => Event::Closed,
+            language::Event::LargeFileChanged => Event::LargeFileChanged,
             language::Event::CapabilityChanged => {
                 self.capability = buffer.read(cx).capability();
                 Event::CapabilityChanged
@@ -36,6 +36,13 @@ pub struct Picker<D: PickerDelegate> {
     is_modal: bool,
 }
 
+/// The direction to move through a delegate's query history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
 pub trait PickerDelegate: Sized + 'static {
     type ListItem: IntoElement;
 
@@ -62,6 +69,22 @@ pub trait PickerDelegate: Sized + 'static {
         false
     }
 
+    /// Whether `select_history` should be consulted when moving up or down.
+    fn has_history(&self) -> bool {
+        false
+    }
+
+    /// Delegates that keep a history of queries can return one to replace the
+    /// current query when moving up or down, instead of changing the selection.
+    fn select_history(
+        &mut self,
+        _direction: Direction,
+        _query: &str,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<String> {
+        None
+    }
+
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>);
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>);
     fn selected_as_query(&self) -> Option<String> {
@@ -171,6 +194,13 @@ impl<D: PickerDelegate> Picker<D> {
     }
 
     pub fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        if self.delegate.has_history() {
+            let query = self.query(cx);
+            if let Some(query) = self.delegate.select_history(Direction::Down, &query, cx) {
+                self.set_query(query, cx);
+                return;
+            }
+        }
         let count = self.delegate.match_count();
         if count > 0 {
             let index = self.delegate.selected_index();
@@ -182,6 +212,13 @@ impl<D: PickerDelegate> Picker<D> {
     }
 
     fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.delegate.has_history() {
+            let query = self.query(cx);
+            if let Some(query) = self.delegate.select_history(Direction::Up, &query, cx) {
+                self.set_query(query, cx);
+                return;
+            }
+        }
         let count = self.delegate.match_count();
         if count > 0 {
             let index = self.delegate.selected_index();
@@ -18,7 +18,7 @@ use clock::ReplicaId;
 use collections::{hash_map, BTreeMap, HashMap, HashSet, VecDeque};
 use copilot::Copilot;
 use debounced_delay::DebouncedDelay;
-use fs::repository::GitRepository;
+use fs::repository::{GitFileStatus, GitRepository};
 use futures::{
     channel::mpsc::{self, UnboundedReceiver},
     future::{try_join_all, Shared},
@@ -36,8 +36,8 @@ use language::{
     language_settings::{language_settings, FormatOnSave, Formatter, InlayHintKind},
     markdown, point_to_lsp,
     proto::{
-        deserialize_anchor, deserialize_fingerprint, deserialize_line_ending, deserialize_version,
-        serialize_anchor, serialize_version, split_operations,
+        deserialize_anchor, deserialize_encoding, deserialize_fingerprint, deserialize_line_ending,
+        deserialize_version, serialize_anchor, serialize_version, split_operations,
     },
     range_from_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, Capability, CodeAction,
     CodeLabel, Completion, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation,
@@ -56,7 +56,10 @@ use node_runtime::NodeRuntime;
 use parking_lot::{Mutex, RwLock};
 use postage::watch;
 use prettier_support::{DefaultPrettier, PrettierInstance};
-use project_core::project_settings::{LspSettings, ProjectSettings};
+use project_core::{
+    editorconfig,
+    project_settings::{GitDiffBase, LspSettings, ProjectSettings},
+};
 pub use project_core::{DiagnosticSummary, ProjectEntryId};
 use rand::prelude::*;
 
@@ -68,6 +71,7 @@ use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
 use smol::channel::{Receiver, Sender};
 use smol::lock::Semaphore;
+use spell_check::SpellChecker;
 use std::{
     cmp::{self, Ordering},
     convert::TryInto,
@@ -93,7 +97,9 @@ use util::{
     debug_panic, defer,
     http::HttpClient,
     merge_json_value_into,
-    paths::{LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH},
+    paths::{
+        LOCAL_DICTIONARY_RELATIVE_PATH, LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH,
+    },
     post_inc, ResultExt, TryFutureExt as _,
 };
 
@@ -112,7 +118,19 @@ const SERVER_REINSTALL_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 pub const SERVER_PROGRESS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100);
 
+/// The largest chunk of a binary file sent to guests in a single message.
+const BINARY_FILE_CHUNK_LEN: usize = 1024 * 1024;
+
 pub trait Item {
+    /// Opens the item at the given path, or returns `None` if this kind of
+    /// item can't be used for that path.
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>>
+    where
+        Self: Sized;
     fn entry_id(&self, cx: &AppContext) -> Option<ProjectEntryId>;
     fn project_path(&self, cx: &AppContext) -> Option<ProjectPath>;
 }
@@ -161,6 +179,9 @@ pub struct Project {
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
     buffers_being_formatted: HashSet<BufferId>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
+    /// Buffers whose large-file mode was turned off by the user, which stay
+    /// out of it when they're reloaded.
+    buffers_with_large_file_features: HashSet<BufferId>,
     git_diff_debouncer: DebouncedDelay,
     nonce: u128,
     _maintain_buffer_languages: Task<()>,
@@ -169,6 +190,7 @@ pub struct Project {
     copilot_lsp_subscription: Option<gpui::Subscription>,
     copilot_log_subscription: Option<lsp::Subscription>,
     current_lsp_settings: HashMap<Arc<str>, LspSettings>,
+    current_git_diff_bases: HashMap<WorktreeId, GitDiffBase>,
     node: Option<Arc<dyn NodeRuntime>>,
     default_prettier: DefaultPrettier,
     prettiers_per_worktree: HashMap<WorktreeId, HashSet<Option<PathBuf>>>,
@@ -396,6 +418,16 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// The ranges that are edited together, such as the names of an opening and a
+/// closing tag.
+#[derive(Clone, Debug, Default)]
+pub struct LinkedEditRanges {
+    pub ranges: Vec<Range<language::Anchor>>,
+    /// The pattern that the text of the ranges must match for them to stay
+    /// linked, when given by the language server.
+    pub word_pattern: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -526,6 +558,7 @@ impl Project {
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
         client.add_model_request_handler(Self::handle_reload_buffers);
+        client.add_model_request_handler(Self::handle_enable_large_file_features);
         client.add_model_request_handler(Self::handle_synchronize_buffers);
         client.add_model_request_handler(Self::handle_format_buffers);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCodeActions>);
@@ -534,6 +567,7 @@ impl Project {
         client.add_model_request_handler(Self::handle_lsp_command::<GetDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
+        client.add_model_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetReferences>);
         client.add_model_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
@@ -542,8 +576,10 @@ impl Project {
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_model_request_handler(Self::handle_open_buffer_by_id);
         client.add_model_request_handler(Self::handle_open_buffer_by_path);
+        client.add_model_request_handler(Self::handle_load_binary_file);
         client.add_model_request_handler(Self::handle_save_buffer);
         client.add_model_message_handler(Self::handle_update_diff_base);
+        client.add_model_message_handler(Self::handle_update_buffer_large_file);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
     }
 
@@ -603,6 +639,7 @@ impl Project {
                 last_workspace_edits_by_language_server: Default::default(),
                 buffers_being_formatted: Default::default(),
                 buffers_needing_diff: Default::default(),
+                buffers_with_large_file_features: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
@@ -611,6 +648,7 @@ impl Project {
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                current_git_diff_bases: HashMap::default(),
                 node: Some(node),
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
@@ -735,6 +773,7 @@ impl Project {
                 opened_buffers: Default::default(),
                 buffers_being_formatted: Default::default(),
                 buffers_needing_diff: Default::default(),
+                buffers_with_large_file_features: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
@@ -744,6 +783,7 @@ impl Project {
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                current_git_diff_bases: HashMap::default(),
                 node: None,
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
@@ -948,6 +988,33 @@ impl Project {
         }
         self.current_lsp_settings = new_lsp_settings;
 
+        // Reload the diff bases of buffers in worktrees whose git diff base has changed.
+        let local_worktrees = self
+            .worktrees()
+            .filter(|worktree| worktree.read(cx).is_local())
+            .collect::<Vec<_>>();
+        for worktree in local_worktrees {
+            let worktree_id = worktree.read(cx).id();
+            let diff_base = self.git_diff_base(worktree_id, cx);
+            let previous_diff_base = self
+                .current_git_diff_bases
+                .insert(worktree_id, diff_base.clone())
+                .unwrap_or_default();
+            if previous_diff_base != diff_base {
+                let repositories = worktree
+                    .read(cx)
+                    .repositories()
+                    .map(|(work_directory, _)| {
+                        let change = GitRepositoryChange {
+                            old_repository: None,
+                        };
+                        (work_directory.clone(), change)
+                    })
+                    .collect::<UpdatedGitRepositoriesSet>();
+                self.update_local_worktree_buffers_git_repos(worktree, &repositories, cx);
+            }
+        }
+
         // Stop all newly-disabled language servers.
         for (worktree_id, adapter_name) in language_servers_to_stop {
             self.stop_language_server(worktree_id, adapter_name, cx)
@@ -1858,6 +1925,61 @@ impl Project {
         })
     }
 
+    /// Loads the raw contents of a file. In remote projects, the contents are
+    /// streamed from the host in chunks.
+    pub fn load_binary_file(
+        &self,
+        path: &ProjectPath,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<u8>>> {
+        let Some(worktree) = self.worktree_for_id(path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no such worktree")));
+        };
+
+        if worktree.read(cx).is_local() {
+            let abs_path = worktree.read(cx).absolutize(&path.path);
+            let fs = self.fs.clone();
+            return cx
+                .background_executor()
+                .spawn(async move { fs.load_bytes(&abs_path?).await });
+        }
+
+        let Some(project_id) = self.remote_id() else {
+            return Task::ready(Err(anyhow!("project is not shared")));
+        };
+        let rpc = self.client.clone();
+        let worktree_id = path.worktree_id.to_proto();
+        let path = path.path.to_string_lossy().to_string();
+        cx.spawn(move |_, _| async move {
+            let mut content = Vec::new();
+            let mut first_response = None;
+            loop {
+                let response = rpc
+                    .request(proto::LoadBinaryFile {
+                        project_id,
+                        worktree_id,
+                        path: path.clone(),
+                        offset: content.len() as u64,
+                    })
+                    .await?;
+                // The chunks only make up the file if it didn't change in between.
+                let (total_len, mtime) = first_response
+                    .get_or_insert_with(|| (response.total_len, response.mtime.clone()));
+                if *total_len != response.total_len || *mtime != response.mtime {
+                    return Err(anyhow!("{path} changed while it was being loaded"));
+                }
+                if response.content.is_empty() {
+                    break;
+                }
+                content.extend_from_slice(&response.content);
+                if content.len() as u64 >= response.total_len {
+                    break;
+                }
+            }
+            Ok(content)
+        })
+    }
+
     /// LanguageServerName is owned, because it is inserted into a map
     pub fn open_local_buffer_via_lsp(
         &mut self,
@@ -2069,8 +2191,17 @@ impl Project {
         self.register_buffer_with_language_servers(buffer, cx);
         self.register_buffer_with_copilot(buffer, cx);
         cx.observe_release(buffer, |this, buffer, cx| {
+            this.buffers_with_large_file_features
+                .remove(&buffer.remote_id());
             if let Some(file) = File::from_dyn(buffer.file()) {
                 if file.is_local() {
+                    let path = file.path.clone();
+                    file.worktree.update(cx, |worktree, cx| {
+                        if let Some(worktree) = worktree.as_local_mut() {
+                            worktree.remove_editorconfig_settings(&path, cx);
+                        }
+                    });
+
                     let uri = lsp::Url::from_file_path(file.abs_path(cx)).unwrap();
                     for server in this.language_servers_for_buffer(buffer, cx) {
                         server
@@ -2098,6 +2229,9 @@ impl Project {
     ) {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
+        if buffer.is_large_file() {
+            return;
+        }
 
         if let Some(file) = File::from_dyn(buffer.file()) {
             if !file.is_local() {
@@ -2217,6 +2351,9 @@ impl Project {
         buffer_handle: &Model<Buffer>,
         cx: &mut ModelContext<Self>,
     ) {
+        if buffer_handle.read(cx).is_large_file() {
+            return;
+        }
         if let Some(copilot) = Copilot::global(cx) {
             copilot.update(cx, |copilot, cx| copilot.register_buffer(buffer_handle, cx));
         }
@@ -2456,7 +2593,12 @@ impl Project {
                     uri: lsp::Url::from_file_path(abs_path).unwrap(),
                 };
 
-                for (_, _, server) in self.language_servers_for_worktree(worktree_id) {
+                let servers = if buffer.read(cx).is_large_file() {
+                    Vec::new()
+                } else {
+                    self.language_servers_for_worktree(worktree_id).collect()
+                };
+                for (_, _, server) in servers {
                     let text = include_text(server.as_ref()).then(|| buffer.read(cx).text());
 
                     server
@@ -2536,6 +2678,11 @@ impl Project {
                     remote_id,
                 );
             }
+            BufferEvent::Reloaded => {
+                if self.is_local() {
+                    self.update_large_file_mode_after_reload(&buffer, cx);
+                }
+            }
             _ => {}
         }
 
@@ -2782,12 +2929,166 @@ impl Project {
         };
         if let Some(file) = buffer_file {
             let worktree = file.worktree.clone();
-            if worktree.read(cx).is_local() {
+            if worktree.read(cx).is_local() && !buffer.read(cx).is_large_file() {
                 self.start_language_servers(&worktree, new_language, cx);
             }
         }
     }
 
+    /// Takes the given buffer out of large-file mode, parsing it and turning its
+    /// language servers and git diff back on. In remote projects, the host does
+    /// the same for its replica of the buffer.
+    pub fn enable_large_file_features(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if !buffer.read(cx).is_large_file() {
+            return Task::ready(Ok(()));
+        }
+
+        if let Some(project_id) = self.remote_id().filter(|_| self.is_remote()) {
+            buffer.update(cx, |buffer, cx| buffer.set_large_file(false, cx));
+            let request = self.client.request(proto::EnableLargeFileFeatures {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+            });
+            return cx.spawn(move |_, _| async move {
+                request.await?;
+                Ok(())
+            });
+        }
+
+        self.buffers_with_large_file_features
+            .insert(buffer.read(cx).remote_id());
+        self.set_large_file_mode(buffer, false, cx)
+    }
+
+    /// Puts a local buffer in or out of large-file mode, turning the features
+    /// that are disabled in that mode off or on, and tells the guests about it.
+    fn set_large_file_mode(
+        &mut self,
+        buffer: &Model<Buffer>,
+        large_file: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if buffer.read(cx).is_large_file() == large_file {
+            return Task::ready(Ok(()));
+        }
+        if let Some(project_id) = self.remote_id() {
+            self.client
+                .send(proto::UpdateBufferLargeFile {
+                    project_id,
+                    buffer_id: buffer.read(cx).remote_id().into(),
+                    large_file,
+                })
+                .log_err();
+        }
+
+        if large_file {
+            if let Some(file) = File::from_dyn(buffer.read(cx).file()).cloned() {
+                self.unregister_buffer_from_language_servers(buffer, &file, cx);
+            }
+            if let Some(copilot) = Copilot::global(cx) {
+                copilot.update(cx, |copilot, _| copilot.remove_buffer(buffer));
+            }
+            buffer.update(cx, |buffer, cx| buffer.set_large_file(true, cx));
+            return Task::ready(Ok(()));
+        }
+
+        buffer.update(cx, |buffer, cx| buffer.set_large_file(false, cx));
+        if let Some(language) = buffer.read(cx).language().cloned() {
+            self.set_language_for_buffer(buffer, language, cx);
+        }
+        self.register_buffer_with_language_servers(buffer, cx);
+        self.register_buffer_with_copilot(buffer, cx);
+        self.reload_diff_base(buffer, cx)
+    }
+
+    /// Checks whether a local buffer that was reloaded from disk crossed the
+    /// large-file threshold, unless the user turned its features back on.
+    fn update_large_file_mode_after_reload(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if self.buffers_with_large_file_features.contains(&buffer_id) {
+            return;
+        }
+        let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
+            return;
+        };
+        let Some(abs_path) = file.as_local().map(|file| file.abs_path(cx)) else {
+            return;
+        };
+        let threshold =
+            ProjectSettings::get(Some((file.worktree_id(cx).to_usize(), Path::new(""))), cx)
+                .large_file_threshold();
+        let fs = self.fs.clone();
+        let buffer = buffer.downgrade();
+        cx.spawn(move |this, mut cx| async move {
+            let Some(metadata) = fs.metadata(&abs_path).await? else {
+                return Ok(());
+            };
+            let Some(buffer) = buffer.upgrade() else {
+                return Ok(());
+            };
+            this.update(&mut cx, |this, cx| {
+                this.set_large_file_mode(&buffer, metadata.len > threshold, cx)
+            })?
+            .await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Loads the diff base of a local buffer from its git repository.
+    fn reload_diff_base(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
+            return Task::ready(Ok(()));
+        };
+        let Some(worktree) = file.worktree.read(cx).as_local() else {
+            return Task::ready(Ok(()));
+        };
+        let snapshot = worktree.snapshot();
+        let diff_base = self.git_diff_base(worktree.id(), cx);
+        let path = file.path.clone();
+        let remote_id = self.remote_id();
+        let client = self.client.clone();
+        let buffer = buffer.clone();
+        cx.spawn(move |_, mut cx| async move {
+            let diff_base = cx
+                .background_executor()
+                .spawn(async move {
+                    let (work_directory, repo) =
+                        snapshot.repository_and_work_directory_for_path(&path)?;
+                    let repo = snapshot.get_local_repo(&repo)?;
+                    let relative_path = path.strip_prefix(&work_directory).ok()?;
+                    repo.load_diff_base_text(relative_path, &diff_base)
+                })
+                .await;
+
+            let buffer_id = buffer.update(&mut cx, |buffer, cx| {
+                buffer.set_diff_base(diff_base.clone(), cx);
+                buffer.remote_id().into()
+            })?;
+            if let Some(project_id) = remote_id {
+                client
+                    .send(proto::UpdateDiffBase {
+                        project_id,
+                        buffer_id,
+                        diff_base,
+                    })
+                    .log_err();
+            }
+            Ok(())
+        })
+    }
+
     fn start_language_servers(
         &mut self,
         worktree: &Model<Worktree>,
@@ -3349,6 +3650,9 @@ impl Project {
         for buffer in self.opened_buffers.values() {
             if let Some(buffer_handle) = buffer.upgrade() {
                 let buffer = buffer_handle.read(cx);
+                if buffer.is_large_file() {
+                    continue;
+                }
                 let file = match File::from_dyn(buffer.file()) {
                     Some(file) => file,
                     None => continue,
@@ -4846,6 +5150,48 @@ impl Project {
         self.document_highlights_impl(buffer, position, cx)
     }
 
+    /// Returns the ranges that should be edited together with the one at the
+    /// given position, such as the names of an opening and a closing tag.
+    ///
+    /// The ranges are requested from the primary language server, and computed
+    /// from the language's linked editing query if the server doesn't provide
+    /// any.
+    pub fn linked_edit<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<LinkedEditRanges>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        let lsp_request = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            LinkedEditingRange { position },
+            cx,
+        );
+        let buffer = buffer.clone();
+        cx.spawn(move |_, mut cx| async move {
+            let ranges = lsp_request.await.log_err().unwrap_or_default();
+            if !ranges.ranges.is_empty() {
+                return Ok(ranges);
+            }
+            buffer.update(&mut cx, |buffer, _| {
+                let snapshot = buffer.snapshot();
+                let ranges = snapshot
+                    .linked_editing_ranges(position)
+                    .into_iter()
+                    .map(|range| {
+                        snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end)
+                    })
+                    .collect();
+                LinkedEditRanges {
+                    ranges,
+                    word_pattern: None,
+                }
+            })
+        })
+    }
+
     pub fn symbols(&self, query: &str, cx: &mut ModelContext<Self>) -> Task<Result<Vec<Symbol>>> {
         if self.is_local() {
             let mut requests = Vec::new();
@@ -6833,6 +7179,7 @@ impl Project {
                     .clear_local_settings(handle_id.as_u64() as usize, cx)
                     .log_err()
             });
+            SpellChecker::set_worktree_words(handle_id.as_u64() as usize, None, cx);
         })
         .detach();
 
@@ -7024,6 +7371,12 @@ impl Project {
         }
     }
 
+    fn git_diff_base(&self, worktree_id: WorktreeId, cx: &AppContext) -> GitDiffBase {
+        ProjectSettings::get(Some((worktree_id.to_usize(), Path::new(""))), cx)
+            .git
+            .diff_base()
+    }
+
     fn update_local_worktree_buffers_git_repos(
         &mut self,
         worktree_handle: Model<Worktree>,
@@ -7062,7 +7415,7 @@ impl Project {
             .filter_map(|buffer| {
                 let buffer = buffer.upgrade()?;
                 let file = File::from_dyn(buffer.read(cx).file())?;
-                if file.worktree != worktree_handle {
+                if file.worktree != worktree_handle || buffer.read(cx).is_large_file() {
                     return None;
                 }
                 let path = file.path();
@@ -7079,6 +7432,7 @@ impl Project {
 
         let remote_id = self.remote_id();
         let client = self.client.clone();
+        let diff_base = self.git_diff_base(worktree_handle.read(cx).id(), cx);
         cx.spawn(move |_, mut cx| async move {
             // Wait for all of the buffers to load.
             let future_buffers = future_buffers.collect::<Vec<_>>().await;
@@ -7098,7 +7452,7 @@ impl Project {
                                 snapshot.repository_and_work_directory_for_path(&path)?;
                             let repo = snapshot.get_local_repo(&repo)?;
                             let relative_path = path.strip_prefix(&work_directory).ok()?;
-                            let base_text = repo.load_index_text(relative_path);
+                            let base_text = repo.load_diff_base_text(relative_path, &diff_base);
                             Some((buffer, base_text))
                         })
                         .collect::<Vec<_>>()
@@ -7141,6 +7495,7 @@ impl Project {
         let remote_worktree_id = worktree.read(cx).id();
 
         let mut settings_contents = Vec::new();
+        let mut editorconfig_contents = Vec::new();
         for (path, _, change) in changes.iter() {
             let removed = change == &PathChange::Removed;
             let abs_path = match worktree.read(cx).absolutize(path) {
@@ -7168,6 +7523,30 @@ impl Project {
                         },
                     )
                 });
+            } else if path.as_ref() == *LOCAL_DICTIONARY_RELATIVE_PATH {
+                let fs = self.fs.clone();
+                let worktree_id = worktree_id.as_u64() as usize;
+                cx.spawn(move |_, cx| async move {
+                    let content = if removed {
+                        None
+                    } else {
+                        fs.load(&abs_path).await.log_err()
+                    };
+                    cx.update(|cx| {
+                        SpellChecker::set_worktree_words(worktree_id, content.as_deref(), cx)
+                    })
+                })
+                .detach();
+            } else if let Some(directory) = editorconfig::editorconfig_dir(path) {
+                let fs = self.fs.clone();
+                editorconfig_contents.push(async move {
+                    let content = if removed {
+                        None
+                    } else {
+                        fs.load(&abs_path).await.log_err()
+                    };
+                    (directory, content)
+                });
             } else if abs_path.ends_with(&*LOCAL_TASKS_RELATIVE_PATH) {
                 self.task_inventory().update(cx, |task_inventory, cx| {
                     if removed {
@@ -7196,6 +7575,24 @@ impl Project {
             }
         }
 
+        if !editorconfig_contents.is_empty() {
+            let worktree = worktree.downgrade();
+            cx.spawn(move |_, mut cx| async move {
+                let editorconfig_contents: Vec<(Arc<Path>, Option<String>)> =
+                    futures::future::join_all(editorconfig_contents).await;
+                worktree
+                    .update(&mut cx, |worktree, cx| {
+                        if let Some(worktree) = worktree.as_local_mut() {
+                            for (directory, content) in editorconfig_contents {
+                                worktree.set_editorconfig(directory, content.as_deref(), cx);
+                            }
+                        }
+                    })
+                    .ok();
+            })
+            .detach();
+        }
+
         if settings_contents.is_empty() {
             return;
         }
@@ -7357,6 +7754,48 @@ impl Project {
             .local_git_repo(&project_path.path)
     }
 
+    /// Returns the paths of all files in visible worktrees that have unresolved merge conflicts.
+    pub fn conflicted_paths(&self, cx: &AppContext) -> Vec<ProjectPath> {
+        self.visible_worktrees(cx)
+            .flat_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let worktree_id = worktree.id();
+                worktree
+                    .files(false, 0)
+                    .filter(|entry| entry.git_status == Some(GitFileStatus::Conflict))
+                    .map(|entry| ProjectPath {
+                        worktree_id,
+                        path: entry.path.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Stages the given file in its repository, marking its merge conflicts as resolved.
+    pub fn mark_conflict_resolved(
+        &self,
+        project_path: &ProjectPath,
+        cx: &AppContext,
+    ) -> Result<()> {
+        let worktree = self
+            .worktree_for_id(project_path.worktree_id, cx)
+            .ok_or_else(|| anyhow!("no worktree found for {project_path:?}"))?;
+        let snapshot = worktree
+            .read(cx)
+            .as_local()
+            .ok_or_else(|| anyhow!("cannot resolve conflicts in a remote worktree"))?
+            .snapshot();
+        let (work_directory, repo) = snapshot
+            .repository_and_work_directory_for_path(&project_path.path)
+            .ok_or_else(|| anyhow!("{:?} is not in a git repository", project_path.path))?;
+        let repo = snapshot
+            .get_local_repo(&repo)
+            .ok_or_else(|| anyhow!("git repository not found for {:?}", project_path.path))?;
+        let relative_path = project_path.path.strip_prefix(&work_directory)?;
+        repo.mark_resolved(relative_path)
+    }
+
     // RPC message handlers
 
     async fn handle_unshare_project(
@@ -7903,6 +8342,33 @@ impl Project {
         })?
     }
 
+    async fn handle_update_buffer_large_file(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateBufferLargeFile>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            if let Some(buffer) = this
+                .opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .or_else(|| {
+                    this.incomplete_remote_buffers
+                        .get(&buffer_id)
+                        .cloned()
+                        .flatten()
+                })
+            {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_large_file(envelope.payload.large_file, cx)
+                });
+            }
+            Ok(())
+        })?
+    }
+
     async fn handle_update_buffer_file(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::UpdateBufferFile>,
@@ -7946,6 +8412,11 @@ impl Project {
         mut cx: AsyncAppContext,
     ) -> Result<proto::BufferSaved> {
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let encoding = envelope
+            .payload
+            .encoding
+            .as_deref()
+            .and_then(language::proto::deserialize_encoding);
         let (project_id, buffer) = this.update(&mut cx, |this, _cx| {
             let project_id = this.remote_id().ok_or_else(|| anyhow!("not connected"))?;
             let buffer = this
@@ -7960,7 +8431,12 @@ impl Project {
                 buffer.wait_for_version(deserialize_version(&envelope.payload.version))
             })?
             .await?;
-        let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id())?;
+        let buffer_id = buffer.update(&mut cx, |buffer, cx| {
+            if let Some(encoding) = encoding {
+                buffer.set_encoding(encoding, cx);
+            }
+            buffer.remote_id()
+        })?;
 
         this.update(&mut cx, |this, cx| this.save_buffer(buffer.clone(), cx))?
             .await?;
@@ -7970,6 +8446,7 @@ impl Project {
             version: serialize_version(buffer.saved_version()),
             mtime: Some(buffer.saved_mtime().into()),
             fingerprint: language::proto::serialize_fingerprint(buffer.saved_version_fingerprint()),
+            encoding: Some(language::proto::serialize_encoding(buffer.encoding())),
         })
     }
 
@@ -8003,6 +8480,25 @@ impl Project {
         })
     }
 
+    async fn handle_enable_large_file_features(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::EnableLargeFileFeatures>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        this.update(&mut cx, |this, cx| {
+            let buffer = this
+                .opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))?;
+            Ok::<_, anyhow::Error>(this.enable_large_file_features(&buffer, cx))
+        })??
+        .await?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_synchronize_buffers(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::SynchronizeBuffers>,
@@ -8068,6 +8564,7 @@ impl Project {
                             line_ending: language::proto::serialize_line_ending(
                                 buffer.line_ending(),
                             ) as i32,
+                            encoding: Some(language::proto::serialize_encoding(buffer.encoding())),
                         })
                         .log_err();
 
@@ -8560,6 +9057,42 @@ impl Project {
         Project::respond_to_open_buffer_request(this, buffer, peer_id, &mut cx)
     }
 
+    async fn handle_load_binary_file(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::LoadBinaryFile>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::LoadBinaryFileResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let path = PathBuf::from(envelope.payload.path);
+        let (fs, abs_path) = this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_id(worktree_id, cx)
+                .ok_or_else(|| anyhow!("no such worktree"))?;
+            let worktree = worktree.read(cx);
+            let is_private = worktree
+                .entry_for_path(&path)
+                .map_or(true, |entry| entry.is_private);
+            if is_private {
+                return Err(anyhow!(ErrorCode::UnsharedItem));
+            }
+            Ok((this.fs.clone(), worktree.absolutize(&path)?))
+        })??;
+
+        let (content, total_len) = fs
+            .load_bytes_range(&abs_path, envelope.payload.offset, BINARY_FILE_CHUNK_LEN)
+            .await?;
+        let mtime = fs
+            .metadata(&abs_path)
+            .await?
+            .map(|metadata| metadata.mtime.into());
+        Ok(proto::LoadBinaryFileResponse {
+            content,
+            total_len,
+            mtime,
+        })
+    }
+
     fn respond_to_open_buffer_request(
         this: Model<Self>,
         buffer: Model<Buffer>,
@@ -8941,6 +9474,11 @@ impl Project {
             .mtime
             .ok_or_else(|| anyhow!("missing mtime"))?
             .into();
+        let encoding = envelope
+            .payload
+            .encoding
+            .as_deref()
+            .and_then(deserialize_encoding);
 
         this.update(&mut cx, |this, cx| {
             let buffer = this
@@ -8954,6 +9492,9 @@ impl Project {
                 });
             if let Some(buffer) = buffer {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_save(version, fingerprint, mtime, cx);
                 });
             }
@@ -8978,6 +9519,7 @@ impl Project {
             .mtime
             .ok_or_else(|| anyhow!("missing mtime"))?
             .into();
+        let encoding = payload.encoding.as_deref().and_then(deserialize_encoding);
         let buffer_id = BufferId::new(payload.buffer_id)?;
         this.update(&mut cx, |this, cx| {
             let buffer = this
@@ -8992,6 +9534,9 @@ impl Project {
                 });
             if let Some(buffer) = buffer {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_reload(version, fingerprint, line_ending, mtime, cx);
                 });
             }
@@ -9218,6 +9763,9 @@ impl Project {
         buffer: &Buffer,
         cx: &AppContext,
     ) -> Vec<LanguageServerId> {
+        if buffer.is_large_file() {
+            return Vec::new();
+        }
         if let Some((file, language)) = File::from_dyn(buffer.file()).zip(buffer.language()) {
             let worktree_id = file.worktree_id(cx);
             self.languages
@@ -9528,6 +10076,14 @@ fn resolve_path(base: &Path, path: &Path) -> PathBuf {
 }
 
 impl Item for Buffer {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        Some(project.update(cx, |project, cx| project.open_buffer(path.clone(), cx)))
+    }
+
     fn entry_id(&self, cx: &AppContext) -> Option<ProjectEntryId> {
         File::from_dyn(self.file()).and_then(|file| file.project_entry_id(cx))
     }
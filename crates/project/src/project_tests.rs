@@ -4,12 +4,13 @@ use futures::{future, StreamExt};
 use gpui::AppContext;
 use language::{
     language_settings::{AllLanguageSettings, LanguageSettingsContent},
-    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
+    tree_sitter_rust, tree_sitter_typescript, Diagnostic, Encoding, FakeLspAdapter, LanguageConfig,
     LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
 use lsp::Url;
 use parking_lot::Mutex;
 use pretty_assertions::assert_eq;
+use project_core::project_settings::{GitDiffBase, ProjectSettings};
 use serde_json::json;
 use std::{os, task::Poll};
 use unindent::Unindent as _;
@@ -185,6 +186,92 @@ async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext)
     });
 }
 
+#[gpui::test]
+async fn test_editorconfig_settings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".editorconfig": "root = true\n[*]\nindent_style = tab\ntab_width = 3\nend_of_line = crlf\n",
+            "a.rs": "fn a() {\n\tA\n}",
+            "b": {
+                ".zed": {
+                    "settings.json": r#"{ "tab_size": 2 }"#,
+                },
+                "b.rs": "fn b() {\n  B\n}"
+            }
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+
+    let buffer_a = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/the-root/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let buffer_b = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/the-root/b/b.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer_a = buffer_a.read(cx);
+        let settings_a = language_settings(None, buffer_a.file(), cx);
+        assert!(settings_a.hard_tabs);
+        assert_eq!(settings_a.tab_size.get(), 3);
+        assert_eq!(buffer_a.line_ending(), LineEnding::Windows);
+
+        // Explicit project settings take precedence over `.editorconfig` files.
+        let settings_b = language_settings(None, buffer_b.read(cx).file(), cx);
+        assert!(settings_b.hard_tabs);
+        assert_eq!(settings_b.tab_size.get(), 2);
+    });
+
+    // The configured line ending is kept when the file is reloaded.
+    fs.save(
+        "/the-root/a.rs".as_ref(),
+        &"fn a() {\n\tB\n}".into(),
+        LineEnding::Unix,
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    buffer_a.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "fn a() {\n\tB\n}");
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+    });
+
+    fs.save(
+        "/the-root/.editorconfig".as_ref(),
+        &"[*]\nindent_size = 6\n".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    cx.update(|cx| {
+        let settings_a = language_settings(None, buffer_a.read(cx).file(), cx);
+        assert!(!settings_a.hard_tabs);
+        assert_eq!(settings_a.tab_size.get(), 6);
+    });
+
+    // The settings of a file are forgotten once its buffer is released.
+    let file_a = buffer_a.read_with(cx, |buffer, _| buffer.file().cloned());
+    drop(buffer_a);
+    cx.executor().run_until_parked();
+    cx.update(|cx| {
+        let settings_a = language_settings(None, file_a.as_ref(), cx);
+        assert_eq!(settings_a.tab_size.get(), 4);
+    });
+}
+
 #[gpui::test]
 async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2623,6 +2710,52 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
+#[gpui::test]
+async fn test_reload_with_encoding(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({})).await;
+    fs.insert_file("/dir/file.txt", b"caf\xe9\n".to_vec()).await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file.txt", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "café\n");
+        assert_eq!(
+            buffer.encoding(),
+            Encoding::from_label("windows-1252").unwrap()
+        );
+    });
+
+    let greek = Encoding::from_label("iso-8859-7").unwrap();
+    buffer
+        .update(cx, |buffer, cx| buffer.reload_with_encoding(greek, cx))
+        .unwrap()
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "cafι\n");
+        assert_eq!(buffer.encoding(), greek);
+        assert!(!buffer.is_dirty());
+    });
+
+    // Reloading would discard unsaved edits, so it is refused.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "x")], None, cx);
+        let error = buffer
+            .reload_with_encoding(Encoding::default(), cx)
+            .err()
+            .expect("reloading a dirty buffer should fail");
+        assert!(error.to_string().contains("unsaved changes"), "{error}");
+        assert_eq!(buffer.encoding(), greek);
+        assert_eq!(buffer.text(), "xcafι\n");
+    });
+}
+
 #[gpui::test(iterations = 30)]
 async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4324,6 +4457,209 @@ async fn search(
         .collect())
 }
 
+#[gpui::test]
+async fn test_git_diff_base_setting(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "a.txt": "working copy\n",
+        }),
+    )
+    .await;
+    let dot_git = Path::new("/dir/.git");
+    fs.set_index_for_repo(dot_git, &[(Path::new("a.txt"), "index\n".into())]);
+    fs.set_revision_text_for_repo(dot_git, "main", &[(Path::new("a.txt"), "main\n".into())]);
+    fs.set_revision_text_for_repo(
+        dot_git,
+        "1234abcd",
+        &[(Path::new("a.txt"), "merge base\n".into())],
+    );
+    fs.set_merge_base_for_repo(dot_git, "main", "1234abcd");
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/a.txt", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.diff_base(), Some("index\n"));
+    });
+
+    for (diff_base, expected_text) in [
+        (GitDiffBase::Branch("main".into()), "main\n"),
+        (GitDiffBase::MergeBase("main".into()), "merge base\n"),
+        (GitDiffBase::Index, "index\n"),
+    ] {
+        cx.update(|cx| {
+            cx.update_global(|settings: &mut SettingsStore, cx| {
+                settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                    settings.git.diff_base = Some(diff_base);
+                });
+            })
+        });
+        cx.executor().run_until_parked();
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.diff_base(), Some(expected_text));
+        });
+    }
+}
+
+#[gpui::test]
+async fn test_large_file_mode(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.large_file_threshold = Some(16);
+            });
+        })
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "large.rs": "const A: i32 = 1;",
+        }),
+    )
+    .await;
+    fs.set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("large.rs"), "const A: i32 = 0;".into())],
+    );
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+    language_registry.add(rust_lang());
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/large.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    // The buffer gets its language, but no language server or git diff.
+    buffer.read_with(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert_eq!(buffer.language().map(|l| l.name()), Some("Rust".into()));
+        assert_eq!(buffer.diff_base(), None);
+    });
+    assert!(fake_servers.try_next().is_err());
+
+    project
+        .update(cx, |project, cx| {
+            project.enable_large_file_features(&buffer, cx)
+        })
+        .await
+        .unwrap();
+    let mut fake_server = fake_servers.next().await.unwrap();
+    assert_eq!(
+        fake_server
+            .receive_notification::<lsp::notification::DidOpenTextDocument>()
+            .await
+            .text_document
+            .uri,
+        lsp::Url::from_file_path("/dir/large.rs").unwrap()
+    );
+    buffer.read_with(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert_eq!(buffer.diff_base(), Some("const A: i32 = 0;"));
+    });
+}
+
+#[gpui::test]
+async fn test_large_file_mode_after_reload(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.large_file_threshold = Some(16);
+            });
+        })
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.txt": "small",
+            "b.txt": "small",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer_a = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/a.txt", cx)
+        })
+        .await
+        .unwrap();
+    let buffer_b = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/b.txt", cx)
+        })
+        .await
+        .unwrap();
+    buffer_a.read_with(cx, |buffer, _| assert!(!buffer.is_large_file()));
+
+    // Growing a file past the threshold switches it to large-file mode on reload.
+    for path in ["/dir/a.txt", "/dir/b.txt"] {
+        fs.save(
+            path.as_ref(),
+            &"this file is now large".into(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    }
+    cx.executor().run_until_parked();
+    buffer_a.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "this file is now large");
+        assert!(buffer.is_large_file());
+    });
+    buffer_b.read_with(cx, |buffer, _| assert!(buffer.is_large_file()));
+
+    // Features that were turned on explicitly stay on across reloads.
+    project
+        .update(cx, |project, cx| {
+            project.enable_large_file_features(&buffer_b, cx)
+        })
+        .await
+        .unwrap();
+    for path in ["/dir/a.txt", "/dir/b.txt"] {
+        fs.save(
+            path.as_ref(),
+            &"this file is still large".into(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    }
+    cx.executor().run_until_parked();
+    buffer_a.read_with(cx, |buffer, _| assert!(buffer.is_large_file()));
+    buffer_b.read_with(cx, |buffer, _| assert!(!buffer.is_large_file()));
+
+    // Shrinking a file below the threshold leaves large-file mode.
+    fs.save("/dir/a.txt".as_ref(), &"small".into(), Default::default())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    buffer_a.read_with(cx, |buffer, _| assert!(!buffer.is_large_file()));
+}
+
 fn init_test(cx: &mut gpui::TestAppContext) {
     if std::env::var("RUST_LOG").is_ok() {
         env_logger::try_init().ok();
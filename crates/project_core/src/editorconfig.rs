@@ -0,0 +1,363 @@
+//! Parsing and resolution of [EditorConfig](https://editorconfig.org) files.
+
+use collections::HashMap;
+use globset::{GlobBuilder, GlobMatcher};
+use serde_json::json;
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+pub const EDITORCONFIG: &str = ".editorconfig";
+
+/// A parsed `.editorconfig` file.
+#[derive(Debug, Default)]
+pub struct EditorConfig {
+    /// Whether the search for `.editorconfig` files should stop at this one.
+    root: bool,
+    sections: Vec<Section>,
+}
+
+#[derive(Debug)]
+struct Section {
+    glob: GlobMatcher,
+    properties: Vec<(String, String)>,
+}
+
+impl EditorConfig {
+    /// Parses the contents of an `.editorconfig` file, skipping any lines or
+    /// section headers that are malformed.
+    pub fn parse(content: &str) -> Self {
+        let mut config = Self::default();
+        let mut in_preamble = true;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(header) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                in_preamble = false;
+                match section_glob(header) {
+                    Some(glob) => config.sections.push(Section {
+                        glob,
+                        properties: Vec::new(),
+                    }),
+                    None => log::warn!("invalid .editorconfig section {header:?}"),
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            if in_preamble {
+                if key == "root" {
+                    config.root = value == "true";
+                }
+            } else if let Some(section) = config.sections.last_mut() {
+                section.properties.push((key, value));
+            }
+        }
+        config
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.root
+    }
+
+    /// Applies the properties of all sections matching `path`, which is
+    /// relative to the directory containing this file.
+    fn apply(&self, path: &Path, properties: &mut HashMap<String, String>) {
+        for section in &self.sections {
+            if section.glob.is_match(path) {
+                for (key, value) in &section.properties {
+                    properties.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Converts a section header into a glob that matches paths relative to the
+/// directory containing the `.editorconfig` file. Headers without a slash
+/// match files with that name in any subdirectory.
+fn section_glob(header: &str) -> Option<GlobMatcher> {
+    let header = header.trim();
+    if header.is_empty() {
+        return None;
+    }
+    let pattern = if let Some(header) = header.strip_prefix('/') {
+        header.to_string()
+    } else if header.contains('/') {
+        header.to_string()
+    } else {
+        format!("**/{header}")
+    };
+    Some(
+        GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .ok()?
+            .compile_matcher(),
+    )
+}
+
+/// Resolves the EditorConfig properties for the file at `path`, given the
+/// `.editorconfig` files in a worktree keyed by the directory containing them.
+///
+/// Files closer to `path` take precedence, and the search stops at the first
+/// file declaring `root = true`. Properties set to `unset` are omitted.
+pub fn properties_for_path(
+    configs: &BTreeMap<Arc<Path>, EditorConfig>,
+    path: &Path,
+) -> HashMap<String, String> {
+    let mut applicable_configs = Vec::new();
+    for directory in path.ancestors().skip(1) {
+        if let Some(config) = configs.get(directory) {
+            applicable_configs.push((directory, config));
+            if config.is_root() {
+                break;
+            }
+        }
+    }
+
+    let mut properties = HashMap::default();
+    for (directory, config) in applicable_configs.into_iter().rev() {
+        let relative_path = path.strip_prefix(directory).unwrap_or(path);
+        config.apply(relative_path, &mut properties);
+    }
+    properties.retain(|_, value| value != "unset");
+    properties
+}
+
+/// Converts EditorConfig properties into the equivalent language settings,
+/// returning `None` if none of the properties are supported.
+pub fn language_settings_json(properties: &HashMap<String, String>) -> Option<serde_json::Value> {
+    let mut settings = serde_json::Map::new();
+    let get = |key: &str| properties.get(key).map(String::as_str);
+    let get_number = |key: &str| get(key).and_then(|value| value.parse::<u32>().ok());
+
+    let hard_tabs = match get("indent_style") {
+        Some("tab") => Some(true),
+        Some("space") => Some(false),
+        _ => None,
+    };
+    if let Some(hard_tabs) = hard_tabs {
+        settings.insert("hard_tabs".into(), json!(hard_tabs));
+    }
+
+    // Zed uses a single width for both indentation and tab characters, so
+    // prefer the tab width when indenting with tabs.
+    let indent_size = match get("indent_size") {
+        Some("tab") => get_number("tab_width"),
+        _ => get_number("indent_size"),
+    };
+    let tab_size = if hard_tabs == Some(true) {
+        get_number("tab_width").or(indent_size)
+    } else {
+        indent_size.or_else(|| get_number("tab_width"))
+    };
+    if let Some(tab_size) = tab_size.filter(|size| *size > 0) {
+        settings.insert("tab_size".into(), json!(tab_size));
+    }
+
+    let line_ending = match get("end_of_line") {
+        Some("lf") => Some("unix"),
+        Some("crlf") => Some("windows"),
+        _ => None,
+    };
+    if let Some(line_ending) = line_ending {
+        settings.insert("line_ending".into(), json!(line_ending));
+    }
+
+    let encoding = match get("charset") {
+        Some("utf-8") => Some("utf-8"),
+        Some("utf-8-bom") => Some("utf-8-bom"),
+        Some("latin1") => Some("iso-8859-1"),
+        Some("utf-16be") => Some("utf-16be"),
+        Some("utf-16le") => Some("utf-16le"),
+        _ => None,
+    };
+    if let Some(encoding) = encoding {
+        settings.insert("encoding".into(), json!(encoding));
+    }
+
+    if let Some(trim) = get("trim_trailing_whitespace") {
+        settings.insert(
+            "remove_trailing_whitespace_on_save".into(),
+            json!(trim == "true"),
+        );
+    }
+    if let Some(insert) = get("insert_final_newline") {
+        settings.insert(
+            "ensure_final_newline_on_save".into(),
+            json!(insert == "true"),
+        );
+    }
+    if let Some(max_line_length) = get_number("max_line_length") {
+        settings.insert("preferred_line_length".into(), json!(max_line_length));
+    }
+
+    (!settings.is_empty()).then_some(serde_json::Value::Object(settings))
+}
+
+/// Returns the directory whose `.editorconfig` file is at `path`.
+pub fn editorconfig_dir(path: &Path) -> Option<Arc<Path>> {
+    if path.file_name()? == EDITORCONFIG {
+        Some(Arc::from(path.parent()?))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configs(files: &[(&str, &str)]) -> BTreeMap<Arc<Path>, EditorConfig> {
+        files
+            .iter()
+            .map(|(dir, content)| (Arc::from(Path::new(dir)), EditorConfig::parse(content)))
+            .collect()
+    }
+
+    #[test]
+    fn test_editorconfig_properties() {
+        let configs = configs(&[
+            (
+                "",
+                "
+                root = true
+
+                [*]
+                indent_style = space
+                indent_size = 4
+                end_of_line = lf
+
+                # Makefiles require tabs.
+                [Makefile]
+                indent_style = tab
+
+                [*.{js,ts}]
+                indent_size = 2
+
+                [/docs/*.md]
+                trim_trailing_whitespace = false
+                ",
+            ),
+            (
+                "web",
+                "
+                [*.ts]
+                indent_size = 8
+                end_of_line = unset
+                ",
+            ),
+        ]);
+
+        let properties = |path: &str| {
+            let mut properties = properties_for_path(&configs, Path::new(path))
+                .into_iter()
+                .collect::<Vec<_>>();
+            properties.sort();
+            properties
+        };
+        let pairs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            properties("src/main.rs"),
+            pairs(&[
+                ("end_of_line", "lf"),
+                ("indent_size", "4"),
+                ("indent_style", "space")
+            ])
+        );
+        assert_eq!(
+            properties("src/Makefile"),
+            pairs(&[
+                ("end_of_line", "lf"),
+                ("indent_size", "4"),
+                ("indent_style", "tab")
+            ])
+        );
+        assert_eq!(
+            properties("web/app.ts"),
+            pairs(&[("indent_size", "8"), ("indent_style", "space")])
+        );
+        assert_eq!(
+            properties("web/app.js"),
+            pairs(&[
+                ("end_of_line", "lf"),
+                ("indent_size", "2"),
+                ("indent_style", "space")
+            ])
+        );
+        assert_eq!(
+            properties("docs/README.md"),
+            pairs(&[
+                ("end_of_line", "lf"),
+                ("indent_size", "4"),
+                ("indent_style", "space"),
+                ("trim_trailing_whitespace", "false")
+            ])
+        );
+        assert_eq!(
+            properties("web/docs/README.md"),
+            pairs(&[
+                ("end_of_line", "lf"),
+                ("indent_size", "4"),
+                ("indent_style", "space")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_editorconfig_language_settings() {
+        let settings = |pairs: &[(&str, &str)]| {
+            language_settings_json(
+                &pairs
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            )
+        };
+
+        assert_eq!(
+            settings(&[
+                ("indent_style", "tab"),
+                ("indent_size", "2"),
+                ("tab_width", "8"),
+                ("end_of_line", "crlf"),
+                ("charset", "utf-8-bom"),
+                ("trim_trailing_whitespace", "true"),
+                ("insert_final_newline", "false"),
+                ("max_line_length", "100"),
+            ]),
+            Some(json!({
+                "hard_tabs": true,
+                "tab_size": 8,
+                "line_ending": "windows",
+                "encoding": "utf-8-bom",
+                "remove_trailing_whitespace_on_save": true,
+                "ensure_final_newline_on_save": false,
+                "preferred_line_length": 100,
+            }))
+        );
+        assert_eq!(
+            settings(&[("indent_size", "tab"), ("tab_width", "3")]),
+            Some(json!({ "tab_size": 3 }))
+        );
+        assert_eq!(
+            settings(&[("max_line_length", "off"), ("charset", "latin2")]),
+            None
+        );
+    }
+}
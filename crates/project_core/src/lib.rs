@@ -7,6 +7,7 @@ use lsp::{DiagnosticSeverity, LanguageServerId};
 use rpc::proto;
 use serde::Serialize;
 
+pub mod editorconfig;
 mod ignore;
 pub mod project_settings;
 pub mod worktree;
@@ -39,15 +39,101 @@ pub struct ProjectSettings {
     /// Treat the files matching these globs as `.env` files.
     /// Default: [ "**/.env*" ]
     pub private_files: Option<Vec<String>>,
+
+    /// Files larger than this many bytes are opened in large-file mode, which
+    /// disables syntax highlighting, language servers, Copilot, git diffs,
+    /// inlay hints and soft wrapping for them.
+    ///
+    /// Default: 20000000
+    pub large_file_threshold: Option<u64>,
 }
 
-#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct GitSettings {
     /// Whether or not to show the git gutter.
     ///
     /// Default: tracked_files
     pub git_gutter: Option<GitGutterSetting>,
     pub gutter_debounce: Option<u64>,
+    /// What the git gutter compares the working copy against.
+    ///
+    /// Default: index
+    pub diff_base: Option<GitDiffBase>,
+    /// Additional git hosting providers, such as self-hosted forges, used
+    /// to build permalinks and links to commits and pull requests.
+    /// These take precedence over the built-in providers.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub hosting_providers: Vec<GitHostingProviderSettings>,
+}
+
+/// A git hosting provider for remotes on hosts matching `host_pattern`.
+///
+/// URLs are either built the same way as for one of the known forges, by setting
+/// `provider`, or from the templates, which may contain the `{base_url}`, `{owner}`,
+/// `{repo}`, `{sha}`, `{path}`, `{start_line}`, `{end_line}` and `{number}` placeholders.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct GitHostingProviderSettings {
+    /// The name of the provider shown in the UI.
+    pub name: Option<String>,
+    /// A glob matched against the host of the remote URL, e.g. `git.example.com` or `*.example.com`.
+    pub host_pattern: String,
+    /// The URL the provider is served from.
+    ///
+    /// Default: https://{host}
+    pub base_url: Option<String>,
+    /// The forge software running on this host.
+    pub provider: Option<GitHostingProviderKind>,
+    /// A template for links to files at a given commit,
+    /// e.g. `{base_url}/{owner}/{repo}/blob/{sha}/{path}`.
+    pub permalink_template: Option<String>,
+    /// A template for the URL fragment selecting lines in a file, e.g. `L{start_line}-L{end_line}`.
+    pub line_fragment_template: Option<String>,
+    /// A template for links to commits, e.g. `{base_url}/{owner}/{repo}/commit/{sha}`.
+    pub commit_template: Option<String>,
+    /// A template for links to pull requests, e.g. `{base_url}/{owner}/{repo}/pull/{number}`.
+    pub pull_request_template: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GitHostingProviderKind {
+    Github,
+    Gitlab,
+    Gitee,
+    Bitbucket,
+    Sourcehut,
+    /// Gitea and its forks, such as Forgejo.
+    Gitea,
+}
+
+impl ProjectSettings {
+    pub fn large_file_threshold(&self) -> u64 {
+        self.large_file_threshold
+            .unwrap_or(DEFAULT_LARGE_FILE_THRESHOLD)
+    }
+}
+
+const DEFAULT_LARGE_FILE_THRESHOLD: u64 = 20_000_000;
+
+impl GitSettings {
+    pub fn diff_base(&self) -> GitDiffBase {
+        self.diff_base.clone().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GitDiffBase {
+    /// Compare against the staged contents of files.
+    #[default]
+    Index,
+    /// Compare against the contents of files at a branch, or any other revision.
+    Branch(String),
+    /// Compare against the contents of files at the merge-base of HEAD and a branch,
+    /// showing the changes made on the current branch since it diverged from it.
+    MergeBase(String),
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema)]
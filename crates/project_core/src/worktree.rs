@@ -1,12 +1,15 @@
 use crate::{
-    ignore::IgnoreStack, project_settings::ProjectSettings, DiagnosticSummary, ProjectEntryId,
+    editorconfig::{self, EditorConfig},
+    ignore::IgnoreStack,
+    project_settings::{GitDiffBase, ProjectSettings},
+    DiagnosticSummary, ProjectEntryId,
 };
 use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
 use anyhow::{anyhow, Context as _, Result};
 use client::{proto, Client};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet, VecDeque};
-use fs::{copy_recursive, RemoveOptions};
+use fs::{copy_recursive, normalize_path, RemoveOptions};
 use fs::{
     repository::{GitFileStatus, GitRepository, RepoPath},
     Fs,
@@ -17,6 +20,7 @@ use futures::{
         oneshot,
     },
     select_biased,
+    stream::SelectAll,
     task::Poll,
     FutureExt as _, Stream, StreamExt,
 };
@@ -28,12 +32,13 @@ use gpui::{
 };
 use itertools::Itertools;
 use language::{
+    language_settings::{AllLanguageSettings, LanguageSettings},
     proto::{
-        deserialize_fingerprint, deserialize_version, serialize_fingerprint, serialize_line_ending,
-        serialize_version,
+        deserialize_encoding, deserialize_fingerprint, deserialize_version, serialize_encoding,
+        serialize_fingerprint, serialize_line_ending, serialize_version,
     },
-    Buffer, Capability, DiagnosticEntry, File as _, LineEnding, PointUtf16, Rope, RopeFingerprint,
-    Unclipped,
+    Buffer, Capability, DiagnosticEntry, Encoding, File as _, LineEnding, PointUtf16, Rope,
+    RopeFingerprint, Unclipped,
 };
 use lsp::LanguageServerId;
 use parking_lot::Mutex;
@@ -47,6 +52,7 @@ use smol::channel::{self, Sender};
 use std::{
     any::Any,
     cmp::{self, Ordering},
+    collections::BTreeMap,
     convert::TryFrom,
     ffi::OsStr,
     fmt,
@@ -96,6 +102,10 @@ pub struct LocalWorktree {
         )>,
     >,
     diagnostic_summaries: HashMap<Arc<Path>, HashMap<LanguageServerId, DiagnosticSummary>>,
+    /// The `.editorconfig` files in the worktree, keyed by their directory.
+    editorconfigs: BTreeMap<Arc<Path>, EditorConfig>,
+    /// The settings derived from `editorconfigs` for each file that has been loaded.
+    editorconfig_settings: HashMap<Arc<Path>, Option<serde_json::Value>>,
     client: Arc<Client>,
     fs: Arc<dyn Fs>,
     fs_case_sensitive: bool,
@@ -147,6 +157,20 @@ pub struct Snapshot {
 pub struct RepositoryEntry {
     pub(crate) work_directory: WorkDirectoryEntry,
     pub(crate) branch: Option<Arc<str>>,
+    pub(crate) kind: RepositoryKind,
+}
+
+/// How a repository's work directory is connected to its git directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RepositoryKind {
+    /// The work directory contains its own `.git` folder.
+    #[default]
+    Standalone,
+    /// A submodule, whose git directory lives inside its superproject's.
+    Submodule,
+    /// A worktree created with `git worktree add`, sharing the git directory
+    /// of another repository.
+    LinkedWorktree,
 }
 
 impl RepositoryEntry {
@@ -154,6 +178,10 @@ impl RepositoryEntry {
         self.branch.clone()
     }
 
+    pub fn kind(&self) -> RepositoryKind {
+        self.kind
+    }
+
     pub fn work_directory_id(&self) -> ProjectEntryId {
         *self.work_directory
     }
@@ -168,6 +196,7 @@ impl RepositoryEntry {
         proto::RepositoryEntry {
             work_directory_id: self.work_directory_id().to_proto(),
             branch: self.branch.as_ref().map(|str| str.to_string()),
+            kind: repository_kind_to_proto(self.kind),
         }
     }
 }
@@ -177,6 +206,7 @@ impl From<&RepositoryEntry> for proto::RepositoryEntry {
         proto::RepositoryEntry {
             work_directory_id: value.work_directory.to_proto(),
             branch: value.branch.as_ref().map(|str| str.to_string()),
+            kind: repository_kind_to_proto(value.kind),
         }
     }
 }
@@ -257,15 +287,48 @@ struct BackgroundScannerState {
 pub struct LocalRepositoryEntry {
     pub(crate) git_dir_scan_id: usize,
     pub(crate) repo_ptr: Arc<Mutex<dyn GitRepository>>,
-    /// Path to the actual .git folder.
+    /// Path to the actual .git folder, relative to the worktree root, or absolute
+    /// if it lives outside of the worktree (e.g. for linked worktrees).
     /// Note: if .git is a file, this points to the folder indicated by the .git file
     pub(crate) git_dir_path: Arc<Path>,
+    /// Path to the .git entry in the work directory, which is either the git
+    /// folder itself or, for submodules and linked worktrees, a file pointing to it.
+    pub(crate) dot_git_path: Arc<Path>,
 }
 
 impl LocalRepositoryEntry {
+    /// Returns the path to the repository's git directory, relative to the
+    /// worktree root or absolute if it lives outside of the worktree.
+    pub fn git_dir_path(&self) -> &Arc<Path> {
+        &self.git_dir_path
+    }
+
     pub fn load_index_text(&self, relative_file_path: &Path) -> Option<String> {
         self.repo_ptr.lock().load_index_text(relative_file_path)
     }
+
+    /// Loads the text that the file's contents should be diffed against.
+    pub fn load_diff_base_text(
+        &self,
+        relative_file_path: &Path,
+        diff_base: &GitDiffBase,
+    ) -> Option<String> {
+        let repo = self.repo_ptr.lock();
+        match diff_base {
+            GitDiffBase::Index => repo.load_index_text(relative_file_path),
+            GitDiffBase::Branch(revision) => repo.load_revision_text(relative_file_path, revision),
+            GitDiffBase::MergeBase(revision) => {
+                let merge_base = repo.merge_base(revision)?;
+                repo.load_revision_text(relative_file_path, &merge_base)
+            }
+        }
+    }
+
+    pub fn mark_resolved(&self, relative_file_path: &Path) -> Result<()> {
+        self.repo_ptr
+            .lock()
+            .mark_resolved(&RepoPath::from(relative_file_path))
+    }
 }
 
 impl Deref for LocalSnapshot {
@@ -452,6 +515,8 @@ impl Worktree {
                 ),
                 diagnostics: Default::default(),
                 diagnostic_summaries: Default::default(),
+                editorconfigs: Default::default(),
+                editorconfig_settings: Default::default(),
                 client,
                 fs,
                 fs_case_sensitive,
@@ -735,26 +800,112 @@ impl LocalWorktree {
         path: &Path,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Model<Buffer>>> {
-        let path = Arc::from(path);
+        let path: Arc<Path> = Arc::from(path);
+        self.update_editorconfig_settings(path.clone(), cx);
+        let line_ending = self
+            .language_settings_for_path(&path, cx)
+            .line_ending
+            .line_ending();
         cx.spawn(move |this, mut cx| async move {
-            let (file, contents, diff_base) = this
+            let (file, contents, encoding, diff_base, large_file) = this
                 .update(&mut cx, |t, cx| t.as_local().unwrap().load(&path, cx))?
                 .await?;
             let text_buffer = cx
                 .background_executor()
-                .spawn(async move { text::Buffer::new(0, id, contents) })
+                .spawn(async move {
+                    let mut buffer = text::Buffer::new(0, id, contents);
+                    if let Some(line_ending) = line_ending {
+                        buffer.set_line_ending(line_ending);
+                    }
+                    buffer
+                })
                 .await;
-            cx.new_model(|_| {
-                Buffer::build(
+            cx.new_model(|cx| {
+                let mut buffer = Buffer::build(
                     text_buffer,
                     diff_base,
                     Some(Arc::new(file)),
                     Capability::ReadWrite,
-                )
+                );
+                buffer.set_encoding(encoding, cx);
+                buffer.set_large_file(large_file, cx);
+                buffer
             })
         })
     }
 
+    /// Returns the language settings that apply to the file at `path`,
+    /// before its language is known.
+    fn language_settings_for_path<'a>(
+        &self,
+        path: &Path,
+        cx: &'a AppContext,
+    ) -> &'a LanguageSettings {
+        AllLanguageSettings::get(Some((self.id().to_usize(), path)), cx).language(None)
+    }
+
+    /// Adds, updates or removes the `.editorconfig` file in the given directory,
+    /// updating the settings of any loaded files it applies to.
+    pub fn set_editorconfig(
+        &mut self,
+        directory: Arc<Path>,
+        content: Option<&str>,
+        cx: &mut ModelContext<Worktree>,
+    ) {
+        if let Some(content) = content {
+            self.editorconfigs
+                .insert(directory.clone(), EditorConfig::parse(content));
+        } else {
+            self.editorconfigs.remove(&directory);
+        }
+
+        let paths = self
+            .editorconfig_settings
+            .keys()
+            .filter(|path| path.starts_with(&directory))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in paths {
+            self.update_editorconfig_settings(path, cx);
+        }
+    }
+
+    fn update_editorconfig_settings(&mut self, path: Arc<Path>, cx: &mut ModelContext<Worktree>) {
+        let settings = editorconfig::language_settings_json(&editorconfig::properties_for_path(
+            &self.editorconfigs,
+            &path,
+        ));
+        let previous_settings = self
+            .editorconfig_settings
+            .insert(path.clone(), settings.clone())
+            .flatten();
+        if previous_settings == settings {
+            return;
+        }
+
+        let root_id = self.id().to_usize();
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store
+                .set_editorconfig_settings(root_id, path, settings, cx)
+                .log_err();
+        });
+    }
+
+    /// Forgets the `.editorconfig` settings of a file once it is no longer open.
+    pub fn remove_editorconfig_settings(&mut self, path: &Path, cx: &mut ModelContext<Worktree>) {
+        let Some(settings) = self.editorconfig_settings.remove(path) else {
+            return;
+        };
+        if settings.is_some() {
+            let root_id = self.id().to_usize();
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store
+                    .set_editorconfig_settings(root_id, path.into(), None, cx)
+                    .log_err();
+            });
+        }
+    }
+
     pub fn diagnostics_for_path(
         &self,
         path: &Path,
@@ -1019,26 +1170,59 @@ impl LocalWorktree {
         &self,
         path: &Path,
         cx: &mut ModelContext<Worktree>,
-    ) -> Task<Result<(File, String, Option<String>)>> {
+    ) -> Task<Result<(File, String, Encoding, Option<String>, bool)>> {
         let path = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
         let entry = self.refresh_entry(path.clone(), None, cx);
+        let configured_encoding = self.language_settings_for_path(&path, cx).file_encoding();
+        let large_file_threshold =
+            ProjectSettings::get(Some((self.id().to_usize(), Path::new(""))), cx)
+                .large_file_threshold();
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let large_file = fs
+                .metadata(&abs_path)
+                .await?
+                .map_or(false, |metadata| metadata.len > large_file_threshold);
+
+            // Fall back to detecting the encoding if the file isn't valid in the
+            // configured one.
+            let loaded = match configured_encoding {
+                Some(encoding) => fs
+                    .load_with_encoding(&abs_path, Some(encoding))
+                    .await
+                    .log_err(),
+                None => None,
+            };
+            let (text, encoding) = match loaded {
+                Some(loaded) => loaded,
+                // Only the start of large files is inspected to detect their encoding.
+                None if large_file => {
+                    Encoding::detect_and_decode_from_prefix(fs.load_bytes(&abs_path).await?)?
+                }
+                None => fs.load_with_encoding(&abs_path, None).await?,
+            };
             let mut index_task = None;
-            let snapshot = this.update(&mut cx, |this, _| this.as_local().unwrap().snapshot())?;
-            if let Some(repo) = snapshot.repository_for_path(&path) {
+            let (snapshot, diff_base) = this.update(&mut cx, |this, cx| {
+                let diff_base =
+                    ProjectSettings::get(Some((this.id().to_usize(), Path::new(""))), cx)
+                        .git
+                        .diff_base();
+                (this.as_local().unwrap().snapshot(), diff_base)
+            })?;
+            // Large files are opened without their git diff, see `Buffer::set_large_file`.
+            if large_file {
+                log::info!("opening {abs_path:?} in large-file mode");
+            } else if let Some(repo) = snapshot.repository_for_path(&path) {
                 if let Some(repo_path) = repo.work_directory.relativize(&snapshot, &path).log_err()
                 {
                     if let Some(git_repo) = snapshot.git_repositories.get(&*repo.work_directory) {
-                        let git_repo = git_repo.repo_ptr.clone();
-                        index_task = Some(
-                            cx.background_executor()
-                                .spawn(async move { git_repo.lock().load_index_text(&repo_path) }),
-                        );
+                        let git_repo = git_repo.clone();
+                        index_task = Some(cx.background_executor().spawn(async move {
+                            git_repo.load_diff_base_text(&repo_path, &diff_base)
+                        }));
                     }
                 }
             }
@@ -1064,7 +1248,9 @@ impl LocalWorktree {
                         is_private: entry.is_private,
                     },
                     text,
+                    encoding,
                     diff_base,
+                    large_file,
                 )),
                 None => {
                     let metadata = fs
@@ -1088,7 +1274,9 @@ impl LocalWorktree {
                             is_private,
                         },
                         text,
+                        encoding,
                         diff_base,
+                        large_file,
                     ))
                 }
             }
@@ -1111,7 +1299,9 @@ impl LocalWorktree {
         let text = buffer.as_rope().clone();
         let fingerprint = text.fingerprint();
         let version = buffer.version();
-        let save = self.write_file(path.as_ref(), text, buffer.line_ending(), cx);
+        let encoding = buffer.encoding();
+        let save =
+            self.write_file_with_encoding(path.as_ref(), text, buffer.line_ending(), encoding, cx);
         let fs = Arc::clone(&self.fs);
         let abs_path = self.absolutize(&path);
         let is_private = self.snapshot.is_path_private(&path);
@@ -1161,7 +1351,12 @@ impl LocalWorktree {
 
                 buffer_handle.update(&mut cx, |buffer, cx| {
                     if has_changed_file {
-                        buffer.file_updated(new_file, cx);
+                        buffer.file_updated(new_file.clone(), cx);
+                    }
+                })?;
+                new_file.worktree.update(&mut cx, |worktree, cx| {
+                    if let Some(worktree) = worktree.as_local_mut() {
+                        worktree.update_editorconfig_settings(new_file.path.clone(), cx);
                     }
                 })?;
             }
@@ -1173,6 +1368,7 @@ impl LocalWorktree {
                     version: serialize_version(&version),
                     mtime: Some(mtime.into()),
                     fingerprint: serialize_fingerprint(fingerprint),
+                    encoding: Some(serialize_encoding(encoding)),
                 })?;
             }
 
@@ -1252,13 +1448,25 @@ impl LocalWorktree {
         text: Rope,
         line_ending: LineEnding,
         cx: &mut ModelContext<Worktree>,
+    ) -> Task<Result<Option<Entry>>> {
+        self.write_file_with_encoding(path, text, line_ending, Encoding::default(), cx)
+    }
+
+    pub(crate) fn write_file_with_encoding(
+        &self,
+        path: impl Into<Arc<Path>>,
+        text: Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+        cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Option<Entry>>> {
         let path: Arc<Path> = path.into();
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
-        let write = cx
-            .background_executor()
-            .spawn(async move { fs.save(&abs_path?, &text, line_ending).await });
+        let write = cx.background_executor().spawn(async move {
+            fs.save_with_encoding(&abs_path?, &text, line_ending, encoding)
+                .await
+        });
 
         cx.spawn(|this, mut cx| async move {
             write.await?;
@@ -1567,6 +1775,7 @@ impl RemoteWorktree {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id().into();
         let version = buffer.version();
+        let encoding = buffer.encoding();
         let rpc = self.client.clone();
         let project_id = self.project_id;
         cx.spawn(move |_, mut cx| async move {
@@ -1575,6 +1784,7 @@ impl RemoteWorktree {
                     project_id,
                     buffer_id,
                     version: serialize_version(&version),
+                    encoding: Some(serialize_encoding(encoding)),
                 })
                 .await?;
             let version = deserialize_version(&response.version);
@@ -1585,6 +1795,10 @@ impl RemoteWorktree {
                 .into();
 
             buffer_handle.update(&mut cx, |buffer, cx| {
+                if let Some(encoding) = response.encoding.as_deref().and_then(deserialize_encoding)
+                {
+                    buffer.set_encoding(encoding, cx);
+                }
                 buffer.did_save(version.clone(), fingerprint, mtime, cx);
             })?;
 
@@ -1819,6 +2033,7 @@ impl Snapshot {
                 if self.repository_entries.get(&work_directory).is_some() {
                     self.repository_entries.update(&work_directory, |repo| {
                         repo.branch = repository.branch.map(Into::into);
+                        repo.kind = repository_kind_from_proto(repository.kind);
                     });
                 } else {
                     self.repository_entries.insert(
@@ -1826,6 +2041,7 @@ impl Snapshot {
                         RepositoryEntry {
                             work_directory: work_directory_entry,
                             branch: repository.branch.map(Into::into),
+                            kind: repository_kind_from_proto(repository.kind),
                         },
                     )
                 }
@@ -2276,7 +2492,7 @@ impl LocalSnapshot {
                     new_ignores.push((ancestor, None));
                 }
             }
-            if ancestor.join(&*DOT_GIT).is_dir() {
+            if ancestor.join(&*DOT_GIT).exists() {
                 break;
             }
         }
@@ -2589,7 +2805,9 @@ impl BackgroundScannerState {
                 .git_repositories
                 .iter()
                 .find_map(|(entry_id, repo)| {
-                    (repo.git_dir_path.as_ref() == dot_git_dir).then(|| (*entry_id, repo.clone()))
+                    (repo.git_dir_path.as_ref() == dot_git_dir
+                        || repo.dot_git_path.as_ref() == dot_git_dir)
+                        .then(|| (*entry_id, repo.clone()))
                 });
             match repository {
                 None => {
@@ -2637,10 +2855,10 @@ impl BackgroundScannerState {
             if exists_in_snapshot {
                 ids_to_preserve.insert(work_directory_id);
             } else {
-                let git_dir_abs_path = snapshot.abs_path().join(&entry.git_dir_path);
-                let git_dir_excluded = snapshot.is_path_excluded(entry.git_dir_path.to_path_buf());
-                if git_dir_excluded
-                    && !matches!(smol::block_on(fs.metadata(&git_dir_abs_path)), Ok(None))
+                let dot_git_abs_path = snapshot.abs_path().join(&entry.dot_git_path);
+                let dot_git_excluded = snapshot.is_path_excluded(entry.dot_git_path.to_path_buf());
+                if dot_git_excluded
+                    && !matches!(smol::block_on(fs.metadata(&dot_git_abs_path)), Ok(None))
                 {
                     ids_to_preserve.insert(work_directory_id);
                 }
@@ -2682,7 +2900,12 @@ impl BackgroundScannerState {
         }
 
         let abs_path = self.snapshot.abs_path.join(&dot_git_path);
-        let repository = fs.open_repo(abs_path.as_path())?;
+        let (git_dir_abs_path, kind) = resolve_git_dir(&abs_path, fs)?;
+        let repository = fs.open_repo(git_dir_abs_path.as_path())?;
+        let git_dir_path: Arc<Path> = match git_dir_abs_path.strip_prefix(&self.snapshot.abs_path) {
+            Ok(path) => path.into(),
+            Err(_) => git_dir_abs_path.as_path().into(),
+        };
         let work_directory = RepositoryWorkDirectory(work_dir_path.clone());
 
         let repo_lock = repository.lock();
@@ -2691,6 +2914,7 @@ impl BackgroundScannerState {
             RepositoryEntry {
                 work_directory: work_dir_id.into(),
                 branch: repo_lock.branch_name().map(Into::into),
+                kind,
             },
         );
 
@@ -2702,7 +2926,8 @@ impl BackgroundScannerState {
             LocalRepositoryEntry {
                 git_dir_scan_id: 0,
                 repo_ptr: repository.clone(),
-                git_dir_path: dot_git_path.clone(),
+                git_dir_path,
+                dot_git_path,
             },
         );
 
@@ -2716,6 +2941,16 @@ impl BackgroundScannerState {
     ) -> TreeMap<RepoPath, GitFileStatus> {
         let staged_statuses = repo.staged_statuses(Path::new(""));
 
+        // Entries inside nested repositories, such as submodules, get their
+        // statuses from those repositories instead.
+        let nested_work_directories = self
+            .snapshot
+            .repository_entries
+            .iter()
+            .map(|(nested_work_directory, _)| nested_work_directory.0.clone())
+            .filter(|path| path.starts_with(&work_directory.0) && *path != work_directory.0)
+            .collect::<Vec<_>>();
+
         let mut changes = vec![];
         let mut edits = vec![];
 
@@ -2724,6 +2959,12 @@ impl BackgroundScannerState {
             .descendent_entries(false, false, &work_directory.0)
             .cloned()
         {
+            if nested_work_directories
+                .iter()
+                .any(|path| entry.path.starts_with(path))
+            {
+                continue;
+            }
             let Ok(repo_path) = entry.path.strip_prefix(&work_directory.0) else {
                 continue;
             };
@@ -2745,6 +2986,48 @@ impl BackgroundScannerState {
     }
 }
 
+/// Returns the absolute path of the git directory that a `.git` entry refers to,
+/// along with the kind of repository it belongs to.
+///
+/// Submodules and linked worktrees have a `.git` file rather than a directory,
+/// containing a `gitdir: <path>` line that points to their actual git directory.
+/// Git keeps the directories of linked worktrees in `<git dir>/worktrees/<name>`,
+/// while those of submodules live in `<git dir>/modules/<name>`.
+fn resolve_git_dir(dot_git_abs_path: &Path, fs: &dyn Fs) -> Option<(PathBuf, RepositoryKind)> {
+    let metadata = smol::block_on(fs.metadata(dot_git_abs_path)).log_err()??;
+    if metadata.is_dir {
+        return Some((dot_git_abs_path.to_path_buf(), RepositoryKind::Standalone));
+    }
+
+    let contents = smol::block_on(fs.load(dot_git_abs_path)).log_err()?;
+    let Some(git_dir) = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("gitdir:"))
+        .map(str::trim)
+    else {
+        log::error!("invalid .git file {dot_git_abs_path:?}");
+        return None;
+    };
+    let git_dir = dot_git_abs_path.parent()?.join(git_dir);
+    let git_dir = normalize_path(&git_dir);
+    let kind = if git_dir
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .map_or(false, |name| name == "worktrees")
+    {
+        RepositoryKind::LinkedWorktree
+    } else {
+        RepositoryKind::Submodule
+    };
+    match smol::block_on(fs.metadata(&git_dir)) {
+        Ok(Some(metadata)) if metadata.is_dir => Some((git_dir, kind)),
+        _ => {
+            log::error!("git directory {git_dir:?} referenced by {dot_git_abs_path:?} is missing");
+            None
+        }
+    }
+}
+
 async fn build_gitignore(abs_path: &Path, fs: &dyn Fs) -> Result<Gitignore> {
     let contents = fs.load(abs_path).await?;
     let parent = abs_path.parent().unwrap_or_else(|| Path::new("/"));
@@ -2937,12 +3220,14 @@ impl language::LocalFile for File {
         }
     }
 
-    fn load(&self, cx: &AppContext) -> Task<Result<String>> {
+    fn load(&self, encoding: Encoding, cx: &AppContext) -> Task<Result<String>> {
         let worktree = self.worktree.read(cx).as_local().unwrap();
         let abs_path = worktree.absolutize(&self.path);
         let fs = worktree.fs.clone();
-        cx.background_executor()
-            .spawn(async move { fs.load(&abs_path?).await })
+        cx.background_executor().spawn(async move {
+            let (text, _) = fs.load_with_encoding(&abs_path?, Some(encoding)).await?;
+            Ok(text)
+        })
     }
 
     fn buffer_reloaded(
@@ -2951,6 +3236,7 @@ impl language::LocalFile for File {
         version: &clock::Global,
         fingerprint: RopeFingerprint,
         line_ending: LineEnding,
+        encoding: Encoding,
         mtime: SystemTime,
         cx: &mut AppContext,
     ) {
@@ -2965,6 +3251,7 @@ impl language::LocalFile for File {
                     mtime: Some(mtime.into()),
                     fingerprint: serialize_fingerprint(fingerprint),
                     line_ending: serialize_line_ending(line_ending) as i32,
+                    encoding: Some(serialize_encoding(encoding)),
                 })
                 .log_err();
         }
@@ -3294,6 +3581,8 @@ struct BackgroundScanner {
     path_prefixes_to_scan_rx: channel::Receiver<Arc<Path>>,
     next_entry_id: Arc<AtomicUsize>,
     phase: BackgroundScannerPhase,
+    /// Git directories outside of the worktree that are being watched for changes.
+    watched_external_git_dirs: HashSet<Arc<Path>>,
 }
 
 #[derive(PartialEq)]
@@ -3333,12 +3622,15 @@ impl BackgroundScanner {
                 changed_paths: Default::default(),
             }),
             phase: BackgroundScannerPhase::InitialScan,
+            watched_external_git_dirs: Default::default(),
         }
     }
 
-    async fn run(&mut self, mut fs_events_rx: Pin<Box<dyn Send + Stream<Item = Vec<PathBuf>>>>) {
+    async fn run(&mut self, fs_events_rx: Pin<Box<dyn Send + Stream<Item = Vec<PathBuf>>>>) {
         use futures::FutureExt as _;
 
+        let mut fs_events_rx = futures::stream::select_all([fs_events_rx]);
+
         // Populate ignores above the root.
         let root_abs_path = self.state.lock().snapshot.abs_path.clone();
         for (index, ancestor) in root_abs_path.ancestors().enumerate() {
@@ -3353,7 +3645,7 @@ impl BackgroundScanner {
                         .insert(ancestor.into(), (ignore.into(), false));
                 }
             }
-            if ancestor.join(&*DOT_GIT).is_dir() {
+            if ancestor.join(&*DOT_GIT).exists() {
                 // Reached root of git repository.
                 break;
             }
@@ -3384,6 +3676,7 @@ impl BackgroundScanner {
         }
 
         self.send_status_update(false, None);
+        self.watch_external_git_dirs(&mut fs_events_rx).await;
 
         // Process any any FS events that occurred while performing the initial scan.
         // For these events, update events cannot be as precise, because we didn't
@@ -3436,6 +3729,33 @@ impl BackgroundScanner {
                     self.process_events(paths.clone()).await;
                 }
             }
+
+            self.watch_external_git_dirs(&mut fs_events_rx).await;
+        }
+    }
+
+    /// Starts watching the git directories of repositories that live outside of the
+    /// worktree, such as those of linked worktrees, so that changes to their HEAD
+    /// and index are picked up.
+    async fn watch_external_git_dirs(
+        &mut self,
+        fs_events_rx: &mut SelectAll<Pin<Box<dyn Send + Stream<Item = Vec<PathBuf>>>>>,
+    ) {
+        let git_dirs_to_watch = self
+            .state
+            .lock()
+            .snapshot
+            .git_repositories
+            .values()
+            .map(|repository| repository.git_dir_path.clone())
+            .filter(|git_dir| {
+                git_dir.is_absolute() && !self.watched_external_git_dirs.contains(git_dir)
+            })
+            .collect::<Vec<_>>();
+        for git_dir in git_dirs_to_watch {
+            log::debug!("watching external git directory {git_dir:?}");
+            fs_events_rx.push(self.fs.watch(&git_dir, FS_WATCH_LATENCY).await);
+            self.watched_external_git_dirs.insert(git_dir);
         }
     }
 
@@ -3494,7 +3814,24 @@ impl BackgroundScanner {
             let snapshot = &self.state.lock().snapshot;
             {
                 let mut is_git_related = false;
-                if let Some(dot_git_dir) = abs_path
+
+                // Git directories of submodules and linked worktrees aren't necessarily
+                // named `.git`, so look for the repository whose git directory contains
+                // this path first.
+                let repository_git_dir = snapshot
+                    .git_repositories
+                    .values()
+                    .filter_map(|repository| {
+                        let git_dir_abs_path = root_canonical_path.join(&repository.git_dir_path);
+                        abs_path
+                            .starts_with(&git_dir_abs_path)
+                            .then(|| (git_dir_abs_path, &repository.git_dir_path))
+                    })
+                    .max_by_key(|(git_dir_abs_path, _)| git_dir_abs_path.components().count());
+                if let Some((_, git_dir)) = repository_git_dir {
+                    dot_git_paths_to_reload.insert(git_dir.to_path_buf());
+                    is_git_related = true;
+                } else if let Some(dot_git_dir) = abs_path
                     .ancestors()
                     .find(|ancestor| ancestor.file_name() == Some(*DOT_GIT))
                 {
@@ -3510,6 +3847,10 @@ impl BackgroundScanner {
                 let relative_path: Arc<Path> =
                     if let Ok(path) = abs_path.strip_prefix(&root_canonical_path) {
                         path.into()
+                    } else if is_git_related {
+                        // Events within git directories outside of the worktree only
+                        // cause the corresponding repository to be reloaded.
+                        return false;
                     } else {
                         log::error!(
                         "ignoring event {abs_path:?} outside of root path {root_canonical_path:?}",
@@ -4737,3 +5078,19 @@ fn git_status_to_proto(status: GitFileStatus) -> i32 {
         GitFileStatus::Conflict => proto::GitStatus::Conflict as i32,
     }
 }
+
+fn repository_kind_from_proto(kind: i32) -> RepositoryKind {
+    match proto::RepositoryKind::from_i32(kind) {
+        Some(proto::RepositoryKind::Submodule) => RepositoryKind::Submodule,
+        Some(proto::RepositoryKind::LinkedWorktree) => RepositoryKind::LinkedWorktree,
+        Some(proto::RepositoryKind::Standalone) | None => RepositoryKind::Standalone,
+    }
+}
+
+fn repository_kind_to_proto(kind: RepositoryKind) -> i32 {
+    match kind {
+        RepositoryKind::Standalone => proto::RepositoryKind::Standalone as i32,
+        RepositoryKind::Submodule => proto::RepositoryKind::Submodule as i32,
+        RepositoryKind::LinkedWorktree => proto::RepositoryKind::LinkedWorktree as i32,
+    }
+}
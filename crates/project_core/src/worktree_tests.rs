@@ -1,6 +1,6 @@
 use crate::{
     project_settings::ProjectSettings,
-    worktree::{Entry, EntryKind, PathChange, Worktree},
+    worktree::{Entry, EntryKind, PathChange, RepositoryKind, Worktree},
     worktree::{Event, Snapshot, WorktreeModelHandle},
 };
 use anyhow::Result;
@@ -22,7 +22,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use text::BufferId;
+use text::{BufferId, Encoding};
 use util::{http::FakeHttpClient, test::temp_tree, ResultExt};
 
 #[gpui::test]
@@ -966,6 +966,143 @@ async fn test_write_file(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_load_and_save_file_encodings(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree("/root", json!({})).await;
+    fs.insert_file("/root/latin1.txt", b"caf\xe9 cr\xe8me\n".to_vec())
+        .await;
+    fs.insert_file("/root/utf16.txt", b"\xFF\xFEh\0i\0\n\0".to_vec())
+        .await;
+    fs.insert_file("/root/image.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec())
+        .await;
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    for (id, path, text, encoding, edited_text) in [
+        (
+            1,
+            "latin1.txt",
+            "café crème\n",
+            Encoding::from_label("windows-1252").unwrap(),
+            "café crème\nà\n",
+        ),
+        (
+            2,
+            "utf16.txt",
+            "hi\n",
+            Encoding::from_label("utf-16le").unwrap().with_bom(true),
+            "hi\nà\n",
+        ),
+    ] {
+        let buffer = tree
+            .update(cx, |tree, cx| {
+                tree.as_local_mut().unwrap().load_buffer(
+                    BufferId::new(id).unwrap(),
+                    path.as_ref(),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            assert_eq!(buffer.text(), text);
+            assert_eq!(buffer.encoding(), encoding);
+            let len = buffer.len();
+            buffer.edit([(len..len, "à\n")], None, cx);
+        });
+
+        tree.update(cx, |tree, cx| {
+            tree.as_local()
+                .unwrap()
+                .save_buffer(buffer.clone(), Path::new(path).into(), false, cx)
+        })
+        .await
+        .unwrap();
+        // The file is saved in its original encoding, which is detected again.
+        assert_eq!(
+            fs.load_with_encoding(&Path::new("/root").join(path), None)
+                .await
+                .unwrap(),
+            (edited_text.to_string(), encoding)
+        );
+    }
+
+    // Binary files aren't decoded as text.
+    let result = tree
+        .update(cx, |tree, cx| {
+            tree.as_local_mut().unwrap().load_buffer(
+                BufferId::new(3).unwrap(),
+                "image.png".as_ref(),
+                cx,
+            )
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[gpui::test]
+async fn test_load_large_file_with_late_non_ascii_character(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |project_settings| {
+                project_settings.large_file_threshold = Some(1024);
+            });
+        });
+    });
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree("/root", json!({})).await;
+    // Only the first megabyte is inspected to guess the encoding, and it's all ASCII.
+    let mut contents = "log line\n".repeat(256 * 1024).into_bytes();
+    contents.extend_from_slice(b"caf\xe9\n");
+    fs.insert_file("/root/latin1.log", contents).await;
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    let buffer = tree
+        .update(cx, |tree, cx| {
+            tree.as_local_mut().unwrap().load_buffer(
+                BufferId::new(1).unwrap(),
+                "latin1.log".as_ref(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert_eq!(
+            buffer.encoding(),
+            Encoding::from_label("windows-1252").unwrap()
+        );
+        assert!(buffer.text().ends_with("log line\ncafé\n"));
+    });
+}
+
 #[gpui::test]
 async fn test_file_scan_exclusions(cx: &mut TestAppContext) {
     init_test(cx);
@@ -2294,6 +2431,164 @@ async fn test_git_status(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_git_submodules(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".git": {
+                "modules": {
+                    "sub": {}
+                }
+            },
+            "a.txt": "",
+            "sub": {
+                ".git": "gitdir: ../.git/modules/sub\n",
+                "b.txt": "",
+                "c.txt": "",
+            },
+        }),
+    )
+    .await;
+
+    let super_git_dir = Path::new("/root/.git");
+    let sub_git_dir = Path::new("/root/.git/modules/sub");
+    fs.set_branch_name(super_git_dir, Some("main"));
+    fs.set_branch_name(sub_git_dir, Some("detached"));
+    fs.set_status_for_repo_via_git_operation(
+        super_git_dir,
+        &[(Path::new("a.txt"), GitFileStatus::Modified)],
+    );
+    fs.set_status_for_repo_via_git_operation(
+        sub_git_dir,
+        &[(Path::new("b.txt"), GitFileStatus::Added)],
+    );
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _| {
+        let repo = tree.repository_for_path("a.txt".as_ref()).unwrap();
+        assert_eq!(repo.branch().as_deref(), Some("main"));
+        let repo = tree.repository_for_path("sub/b.txt".as_ref()).unwrap();
+        assert_eq!(
+            repo.work_directory(tree).unwrap().as_ref(),
+            Path::new("sub")
+        );
+        assert_eq!(repo.branch().as_deref(), Some("detached"));
+        assert_eq!(repo.kind(), RepositoryKind::Submodule);
+
+        let status = |path: &str| tree.status_for_file(Path::new(path));
+        assert_eq!(status("a.txt"), Some(GitFileStatus::Modified));
+        assert_eq!(status("sub/b.txt"), Some(GitFileStatus::Added));
+        assert_eq!(status("sub/c.txt"), None);
+    });
+
+    // Changes to the submodule's git directory are picked up, even though it
+    // lives inside the superproject's `.git` directory.
+    fs.set_branch_name(sub_git_dir, Some("feature"));
+    fs.set_status_for_repo_via_git_operation(
+        sub_git_dir,
+        &[(Path::new("c.txt"), GitFileStatus::Modified)],
+    );
+    cx.executor().run_until_parked();
+
+    // Reloading the superproject doesn't clobber the submodule's statuses.
+    fs.set_status_for_repo_via_git_operation(super_git_dir, &[]);
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _| {
+        let repo = tree.repository_for_path("sub/c.txt".as_ref()).unwrap();
+        assert_eq!(repo.branch().as_deref(), Some("feature"));
+
+        let status = |path: &str| tree.status_for_file(Path::new(path));
+        assert_eq!(status("a.txt"), None);
+        assert_eq!(status("sub/b.txt"), None);
+        assert_eq!(status("sub/c.txt"), Some(GitFileStatus::Modified));
+    });
+}
+
+#[gpui::test]
+async fn test_git_linked_worktree(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/main",
+        json!({
+            ".git": {
+                "worktrees": {
+                    "feature": {}
+                }
+            },
+            "a.txt": "",
+        }),
+    )
+    .await;
+    fs.insert_tree(
+        "/feature",
+        json!({
+            ".git": "gitdir: /main/.git/worktrees/feature\n",
+            "a.txt": "",
+        }),
+    )
+    .await;
+
+    let git_dir = Path::new("/main/.git/worktrees/feature");
+    fs.set_branch_name(git_dir, Some("feature"));
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/feature"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _| {
+        let repo = tree.root_git_entry().unwrap();
+        assert_eq!(repo.branch().as_deref(), Some("feature"));
+        assert_eq!(repo.kind(), RepositoryKind::LinkedWorktree);
+        assert_eq!(tree.status_for_file(Path::new("a.txt")), None);
+    });
+
+    // The worktree's git directory is outside of the worktree, but it is
+    // still watched for changes.
+    fs.set_branch_name(git_dir, Some("feature-2"));
+    fs.set_status_for_repo_via_git_operation(
+        git_dir,
+        &[(Path::new("a.txt"), GitFileStatus::Modified)],
+    );
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _| {
+        let repo = tree.root_git_entry().unwrap();
+        assert_eq!(repo.branch().as_deref(), Some("feature-2"));
+        assert_eq!(
+            tree.status_for_file(Path::new("a.txt")),
+            Some(GitFileStatus::Modified)
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_propagate_git_statuses(cx: &mut TestAppContext) {
     init_test(cx);
@@ -2541,6 +2836,7 @@ fn init_test(cx: &mut gpui::TestAppContext) {
     cx.update(|cx| {
         let settings_store = SettingsStore::test(cx);
         cx.set_global(settings_store);
+        language::init(cx);
         ProjectSettings::register(cx);
     });
 }
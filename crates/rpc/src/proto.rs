@@ -160,6 +160,7 @@ messages!(
     (DeleteChannel, Foreground),
     (DeleteNotification, Foreground),
     (DeleteProjectEntry, Foreground),
+    (EnableLargeFileFeatures, Foreground),
     (Error, Foreground),
     (ExpandProjectEntry, Foreground),
     (ExpandProjectEntryResponse, Foreground),
@@ -213,8 +214,12 @@ messages!(
     (JoinRoomResponse, Foreground),
     (LeaveChannelBuffer, Background),
     (LeaveChannelChat, Foreground),
+    (LoadBinaryFile, Background),
+    (LoadBinaryFileResponse, Background),
     (LeaveProject, Foreground),
     (LeaveRoom, Foreground),
+    (LinkedEditingRange, Background),
+    (LinkedEditingRangeResponse, Background),
     (MarkNotificationRead, Foreground),
     (MoveChannel, Foreground),
     (OnTypeFormatting, Background),
@@ -270,6 +275,7 @@ messages!(
     (UnshareProject, Foreground),
     (UpdateBuffer, Foreground),
     (UpdateBufferFile, Foreground),
+    (UpdateBufferLargeFile, Foreground),
     (UpdateChannelBuffer, Foreground),
     (UpdateChannelBufferCollaborators, Foreground),
     (UpdateChannels, Foreground),
@@ -306,6 +312,7 @@ request_messages!(
     (DeclineCall, Ack),
     (DeleteChannel, Ack),
     (DeleteProjectEntry, ProjectEntryResponse),
+    (EnableLargeFileFeatures, Ack),
     (ExpandProjectEntry, ExpandProjectEntryResponse),
     (Follow, FollowResponse),
     (FormatBuffers, FormatBuffersResponse),
@@ -336,6 +343,8 @@ request_messages!(
     (JoinRoom, JoinRoomResponse),
     (LeaveChannelBuffer, Ack),
     (LeaveRoom, Ack),
+    (LinkedEditingRange, LinkedEditingRangeResponse),
+    (LoadBinaryFile, LoadBinaryFileResponse),
     (MarkNotificationRead, Ack),
     (MoveChannel, Ack),
     (OnTypeFormatting, OnTypeFormattingResponse),
@@ -389,6 +398,7 @@ entity_messages!(
     CreateBufferForPeer,
     CreateProjectEntry,
     DeleteProjectEntry,
+    EnableLargeFileFeatures,
     ExpandProjectEntry,
     FormatBuffers,
     GetCodeActions,
@@ -403,6 +413,8 @@ entity_messages!(
     InlayHints,
     JoinProject,
     LeaveProject,
+    LinkedEditingRange,
+    LoadBinaryFile,
     OnTypeFormatting,
     OpenBufferById,
     OpenBufferByPath,
@@ -422,6 +434,7 @@ entity_messages!(
     UnshareProject,
     UpdateBuffer,
     UpdateBufferFile,
+    UpdateBufferLargeFile,
     UpdateDiagnosticSummary,
     UpdateDiffBase,
     UpdateLanguageServer,
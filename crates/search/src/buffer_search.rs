@@ -741,6 +741,16 @@ impl BufferSearchBar {
         cx.notify();
     }
 
+    pub fn has_search_option(&self, option: SearchOptions) -> bool {
+        self.search_options.contains(option)
+    }
+
+    /// Adds a query to the history navigated with up and down, for searches made
+    /// with a query other than the one that was searched for.
+    pub fn add_to_history(&mut self, query: String) {
+        self.search_history.add(query);
+    }
+
     fn select_next_match(&mut self, _: &SelectNextMatch, cx: &mut ViewContext<Self>) {
         self.select_match(Direction::Next, 1, cx);
     }
@@ -7,7 +7,7 @@ use schemars::{
     schema::{InstanceType, Schema, SchemaObject, SingleOrVec, SubschemaValidation},
     JsonSchema,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use util::{asset_str, ResultExt};
 
@@ -22,10 +22,35 @@ pub struct KeymapBlock {
     bindings: BTreeMap<String, KeymapAction>,
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
 #[serde(transparent)]
 pub struct KeymapAction(Value);
 
+impl KeymapAction {
+    /// Builds the action this value names, either as `"name"` or as `["name", data]`.
+    pub fn build(&self, cx: &AppContext) -> Result<Box<dyn Action>> {
+        // This is a workaround for a limitation in serde: serde-rs/json#497
+        // We want to deserialize the action data as a `RawValue` so that we can
+        // deserialize the action itself dynamically directly from the JSON
+        // string. But `RawValue` currently does not work inside of an untagged enum.
+        match &self.0 {
+            Value::Array(items) => {
+                let Ok([name, data]): Result<[serde_json::Value; 2], _> = items.clone().try_into()
+                else {
+                    return Err(anyhow!("Expected array of length 2"));
+                };
+                let serde_json::Value::String(name) = name else {
+                    return Err(anyhow!("Expected first item in array to be a string."));
+                };
+                cx.build_action(&name, Some(data))
+            }
+            Value::String(name) => cx.build_action(name, None),
+            Value::Null => Ok(no_action()),
+            action => Err(anyhow!("Expected two-element array, got {action:?}")),
+        }
+    }
+}
+
 impl JsonSchema for KeymapAction {
     fn schema_name() -> String {
         "KeymapAction".into()
@@ -55,39 +80,15 @@ impl KeymapFile {
             let bindings = bindings
                 .into_iter()
                 .filter_map(|(keystroke, action)| {
-                    let action = action.0;
-
-                    // This is a workaround for a limitation in serde: serde-rs/json#497
-                    // We want to deserialize the action data as a `RawValue` so that we can
-                    // deserialize the action itself dynamically directly from the JSON
-                    // string. But `RawValue` currently does not work inside of an untagged enum.
-                    match action {
-                        Value::Array(items) => {
-                            let Ok([name, data]): Result<[serde_json::Value; 2], _> =
-                                items.try_into()
-                            else {
-                                return Some(Err(anyhow!("Expected array of length 2")));
-                            };
-                            let serde_json::Value::String(name) = name else {
-                                return Some(Err(anyhow!(
-                                    "Expected first item in array to be a string."
-                                )));
-                            };
-                            cx.build_action(&name, Some(data))
-                        }
-                        Value::String(name) => cx.build_action(&name, None),
-                        Value::Null => Ok(no_action()),
-                        _ => {
-                            return Some(Err(anyhow!("Expected two-element array, got {action:?}")))
-                        }
-                    }
-                    .with_context(|| {
-                        format!(
-                            "invalid binding value for keystroke {keystroke}, context {context:?}"
-                        )
-                    })
-                    .log_err()
-                    .map(|action| KeyBinding::load(&keystroke, action, context.as_deref()))
+                    action
+                        .build(cx)
+                        .with_context(|| {
+                            format!(
+                                "invalid binding value for keystroke {keystroke}, context {context:?}"
+                            )
+                        })
+                        .log_err()
+                        .map(|action| KeyBinding::load(&keystroke, action, context.as_deref()))
                 })
                 .collect::<Result<Vec<_>>>()?;
 
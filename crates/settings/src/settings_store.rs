@@ -132,6 +132,7 @@ pub struct SettingsStore {
     raw_default_settings: serde_json::Value,
     raw_user_settings: serde_json::Value,
     raw_local_settings: BTreeMap<(usize, Arc<Path>), serde_json::Value>,
+    raw_editorconfig_settings: BTreeMap<(usize, Arc<Path>), serde_json::Value>,
     tab_size_callback: Option<(
         TypeId,
         Box<dyn Fn(&dyn Any) -> Option<usize> + Send + Sync + 'static>,
@@ -147,6 +148,7 @@ impl Default for SettingsStore {
             raw_default_settings: serde_json::json!({}),
             raw_user_settings: serde_json::json!({}),
             raw_local_settings: Default::default(),
+            raw_editorconfig_settings: Default::default(),
             tab_size_callback: Default::default(),
         }
     }
@@ -171,6 +173,7 @@ trait AnySettingValue: 'static + Send + Sync {
     fn value_for_path(&self, path: Option<(usize, &Path)>) -> &dyn Any;
     fn set_global_value(&mut self, value: Box<dyn Any>);
     fn set_local_value(&mut self, root_id: usize, path: Arc<Path>, value: Box<dyn Any>);
+    fn remove_local_value(&mut self, root_id: usize, path: &Path);
     fn json_schema(
         &self,
         generator: &mut SchemaGenerator,
@@ -420,9 +423,37 @@ impl SettingsStore {
         Ok(())
     }
 
+    /// Add or remove the settings derived from `.editorconfig` files for a single file.
+    ///
+    /// These settings take precedence over the user's settings, but not over any
+    /// local settings files that apply to the file.
+    pub fn set_editorconfig_settings(
+        &mut self,
+        root_id: usize,
+        path: Arc<Path>,
+        settings: Option<serde_json::Value>,
+        cx: &mut AppContext,
+    ) -> Result<()> {
+        if let Some(settings) = settings {
+            self.raw_editorconfig_settings
+                .insert((root_id, path.clone()), settings);
+        } else if self
+            .raw_editorconfig_settings
+            .remove(&(root_id, path.clone()))
+            .is_some()
+        {
+            for setting_value in self.setting_values.values_mut() {
+                setting_value.remove_local_value(root_id, &path);
+            }
+        }
+        self.recompute_values(Some((root_id, &path)), cx)?;
+        Ok(())
+    }
+
     /// Add or remove a set of local settings via a JSON string.
     pub fn clear_local_settings(&mut self, root_id: usize, cx: &mut AppContext) -> Result<()> {
         self.raw_local_settings.retain(|k, _| k.0 != root_id);
+        self.raw_editorconfig_settings.retain(|k, _| k.0 != root_id);
         self.recompute_values(Some((root_id, "".as_ref())), cx)?;
         Ok(())
     }
@@ -554,6 +585,8 @@ impl SettingsStore {
                 }
             }
 
+            let global_stack_len = user_settings_stack.len();
+
             // If the global settings file changed, reload the global value for the field.
             if changed_local_path.is_none() {
                 if let Some(value) = setting_value
@@ -600,6 +633,40 @@ impl SettingsStore {
                     }
                 }
             }
+
+            // Reload the values for files with `.editorconfig` settings, which sit
+            // between the global settings and any local settings files.
+            for ((root_id, path), editorconfig_settings) in &self.raw_editorconfig_settings {
+                if changed_local_path.map_or(false, |(changed_root_id, changed_local_path)| {
+                    *root_id != changed_root_id || !path.starts_with(changed_local_path)
+                }) {
+                    continue;
+                }
+
+                user_settings_stack.truncate(global_stack_len);
+                if let Some(editorconfig_settings) = setting_value
+                    .deserialize_setting(editorconfig_settings)
+                    .log_err()
+                {
+                    user_settings_stack.push(editorconfig_settings);
+                }
+                for ((local_root_id, local_path), local_settings) in &self.raw_local_settings {
+                    if local_root_id == root_id && path.starts_with(local_path) {
+                        if let Some(local_settings) =
+                            setting_value.deserialize_setting(local_settings).log_err()
+                        {
+                            user_settings_stack.push(local_settings);
+                        }
+                    }
+                }
+
+                if let Some(value) = setting_value
+                    .load_setting(&default_settings, &user_settings_stack, cx)
+                    .log_err()
+                {
+                    setting_value.set_local_value(*root_id, path.clone(), value);
+                }
+            }
         }
         Ok(())
     }
@@ -687,6 +754,13 @@ impl<T: Settings> AnySettingValue for SettingValue<T> {
         }
     }
 
+    fn remove_local_value(&mut self, root_id: usize, path: &Path) {
+        self.local_values
+            .retain(|(settings_root_id, settings_path, _)| {
+                *settings_root_id != root_id || settings_path.as_ref() != path
+            });
+    }
+
     fn json_schema(
         &self,
         generator: &mut SchemaGenerator,
@@ -1042,6 +1116,100 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_settings_store_editorconfig(cx: &mut AppContext) {
+        let mut store = SettingsStore::default();
+        store.register_setting::<UserSettings>(cx);
+        store
+            .set_default_settings(
+                r#"{
+                    "user": {
+                        "name": "John Doe",
+                        "age": 30,
+                        "staff": false
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+        store
+            .set_user_settings(r#"{ "user": { "age": 31 } }"#, cx)
+            .unwrap();
+        store
+            .set_local_settings(
+                1,
+                Path::new("/root/subdir").into(),
+                Some(r#"{ "user": { "name": "Jane Doe" } }"#),
+                cx,
+            )
+            .unwrap();
+
+        // Editorconfig settings override user settings, but not local settings.
+        let editorconfig = serde_json::json!({ "user": { "name": "Jim Doe", "age": 40 } });
+        store
+            .set_editorconfig_settings(
+                1,
+                Path::new("/root/a.txt").into(),
+                Some(editorconfig.clone()),
+                cx,
+            )
+            .unwrap();
+        store
+            .set_editorconfig_settings(
+                1,
+                Path::new("/root/subdir/b.txt").into(),
+                Some(editorconfig),
+                cx,
+            )
+            .unwrap();
+        assert_eq!(
+            store.get::<UserSettings>(Some((1, Path::new("/root/a.txt")))),
+            &UserSettings {
+                name: "Jim Doe".to_string(),
+                age: 40,
+                staff: false
+            }
+        );
+        assert_eq!(
+            store.get::<UserSettings>(Some((1, Path::new("/root/subdir/b.txt")))),
+            &UserSettings {
+                name: "Jane Doe".to_string(),
+                age: 40,
+                staff: false
+            }
+        );
+
+        // Changes to local settings are reflected in the editorconfig layers.
+        store
+            .set_local_settings(
+                1,
+                Path::new("/root").into(),
+                Some(r#"{ "user": { "staff": true } }"#),
+                cx,
+            )
+            .unwrap();
+        assert_eq!(
+            store.get::<UserSettings>(Some((1, Path::new("/root/a.txt")))),
+            &UserSettings {
+                name: "Jim Doe".to_string(),
+                age: 40,
+                staff: true
+            }
+        );
+
+        store
+            .set_editorconfig_settings(1, Path::new("/root/a.txt").into(), None, cx)
+            .unwrap();
+        assert_eq!(
+            store.get::<UserSettings>(Some((1, Path::new("/root/a.txt")))),
+            &UserSettings {
+                name: "John Doe".to_string(),
+                age: 31,
+                staff: true
+            }
+        );
+    }
+
     #[gpui::test]
     fn test_setting_store_assign_json_before_register(cx: &mut AppContext) {
         let mut store = SettingsStore::default();
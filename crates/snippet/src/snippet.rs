@@ -1,50 +1,173 @@
+mod transform;
+mod user_snippets;
+
 use anyhow::{anyhow, Context, Result};
 use smallvec::SmallVec;
-use std::{collections::BTreeMap, ops::Range};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
+use transform::Transform;
+
+pub use user_snippets::{parse_snippet_file, UserSnippet};
 
 #[derive(Default)]
 pub struct Snippet {
     pub text: String,
     pub tabstops: Vec<TabStop>,
+    /// The values to choose from at each tabstop, in the same order as
+    /// `tabstops`. Empty for tabstops without a choice.
+    pub choices: Vec<Vec<String>>,
+    /// Text that is derived from the value of a tabstop, and must be updated
+    /// when that tabstop is left.
+    pub transformations: Vec<Transformation>,
 }
 
 type TabStop = SmallVec<[Range<isize>; 2]>;
 
+/// A range of the snippet text containing the transformed value of a tabstop,
+/// such as `${1/(.*)/${1:/upcase}/}`.
+#[derive(Clone, Debug)]
+pub struct Transformation {
+    /// The index in [`Snippet::tabstops`] of the tabstop whose value is transformed.
+    pub tabstop: usize,
+    pub range: Range<isize>,
+    transform: Transform,
+}
+
+impl Transformation {
+    /// Returns the text to show for the given value of the tabstop.
+    pub fn apply(&self, value: &str) -> String {
+        self.transform.apply(value)
+    }
+}
+
 impl Snippet {
+    /// Parses a snippet, inserting the names of any variables as placeholders.
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
-            .context("failed to parse snippet")?;
+        Self::parse_with_variables(source, &|_| None)
+    }
+
+    /// Parses a snippet, using `resolve_variable` to look up the values of
+    /// variables such as `$TM_FILENAME`. Variables that can't be resolved and
+    /// have no default are inserted as placeholders containing their name.
+    pub fn parse_with_variables(
+        source: &str,
+        resolve_variable: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let (nodes, _) = parse_nodes(source, false).context("failed to parse snippet")?;
 
+        let mut placeholders = HashMap::default();
+        collect_placeholders(&nodes, &mut placeholders);
+        let mut renderer = Renderer {
+            resolve_variable,
+            placeholders,
+            text: String::with_capacity(source.len()),
+            tabstops: BTreeMap::new(),
+            choices: BTreeMap::new(),
+            variable_tabstops: Vec::new(),
+            transformations: Vec::new(),
+        };
+        renderer.render(&nodes, &mut Vec::new());
+
+        let Renderer {
+            text,
+            mut tabstops,
+            mut choices,
+            variable_tabstops,
+            transformations,
+            ..
+        } = renderer;
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
+        let final_choices = choices.remove(&0).unwrap_or_default();
+        let mut tabstop_positions = tabstops
+            .keys()
+            .enumerate()
+            .map(|(position, index)| (*index, position))
+            .collect::<HashMap<_, _>>();
+        let mut tabstop_choices = tabstops
+            .keys()
+            .map(|index| choices.remove(index).unwrap_or_default())
+            .collect::<Vec<_>>();
         let mut tabstops = tabstops.into_values().collect::<Vec<_>>();
+        tabstop_choices.extend(variable_tabstops.iter().map(|_| Vec::new()));
+        tabstops.extend(variable_tabstops);
 
         if let Some(final_tabstop) = final_tabstop {
+            tabstop_positions.insert(0, tabstops.len());
             tabstops.push(final_tabstop);
+            tabstop_choices.push(final_choices);
         } else {
             let end_tabstop = [len..len].into_iter().collect();
             if !tabstops.last().map_or(false, |t| *t == end_tabstop) {
                 tabstops.push(end_tabstop);
+                tabstop_choices.push(Vec::new());
             }
         }
 
-        Ok(Snippet { text, tabstops })
+        // Transformations of tabstops that never appear untransformed can't
+        // change, so they are left as plain text.
+        let transformations = transformations
+            .into_iter()
+            .filter_map(|(index, range, transform)| {
+                Some(Transformation {
+                    tabstop: *tabstop_positions.get(&index)?,
+                    range,
+                    transform: transform.clone(),
+                })
+            })
+            .collect();
+
+        Ok(Snippet {
+            text,
+            tabstops,
+            choices: tabstop_choices,
+            transformations,
+        })
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    /// A tabstop, with the placeholder text it contains. Tabstops with a
+    /// transform are replaced by the transformed value of the tabstop.
+    Tabstop {
+        index: usize,
+        content: Vec<Node>,
+        transform: Option<Transform>,
+    },
+    Choice {
+        index: usize,
+        options: Vec<String>,
+    },
+    Variable {
+        name: String,
+        default: Option<Vec<Node>>,
+        transform: Option<Transform>,
+    },
+}
+
+/// Parses snippet nodes until the end of the source or, if `nested`, until an
+/// unescaped closing brace, which is not consumed.
+fn parse_nodes(mut source: &str, nested: bool) -> Result<(Vec<Node>, &str)> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
     loop {
         match source.chars().next() {
-            None => return Ok(""),
+            None => break,
             Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+                if let Some((node, rest)) = parse_dollar(&source[1..])? {
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+                    nodes.push(node);
+                    source = rest;
+                } else {
+                    text.push('$');
+                    source = &source[1..];
+                }
             }
             Some('\\') => {
                 source = &source[1..];
@@ -55,7 +178,7 @@ fn parse_snippet<'a>(
             }
             Some('}') => {
                 if nested {
-                    return Ok(source);
+                    break;
                 } else {
                     text.push('}');
                     source = &source[1..];
@@ -69,40 +192,124 @@ fn parse_snippet<'a>(
             }
         }
     }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    Ok((nodes, source))
 }
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
-    let tabstop_index;
-    if source.starts_with('{') {
-        let (index, rest) = parse_int(&source[1..])?;
-        tabstop_index = index;
-        source = rest;
-
-        if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
-        }
+/// Parses the construct following a `$`, or returns `None` if the dollar sign
+/// should be treated as text.
+fn parse_dollar(source: &str) -> Result<Option<(Node, &str)>> {
+    if source.starts_with(|c: char| c.is_ascii_digit()) {
+        let (index, rest) = parse_int(source)?;
+        return Ok(Some((tabstop(index, Vec::new(), None), rest)));
+    }
+    if let Some((name, rest)) = parse_variable_name(source) {
+        let node = Node::Variable {
+            name: name.to_string(),
+            default: None,
+            transform: None,
+        };
+        return Ok(Some((node, rest)));
+    }
+    let Some(source) = source.strip_prefix('{') else {
+        return Ok(None);
+    };
 
-        if source.starts_with('}') {
-            source = &source[1..];
+    if source.starts_with(|c: char| c.is_ascii_digit()) {
+        let (index, mut source) = parse_int(source)?;
+        let node = if let Some(rest) = source.strip_prefix(':') {
+            let (content, rest) = parse_nodes(rest, true)?;
+            source = rest;
+            tabstop(index, content, None)
+        } else if let Some(rest) = source.strip_prefix('|') {
+            let (options, rest) = parse_choice(rest)?;
+            source = rest;
+            Node::Choice { index, options }
+        } else if let Some(rest) = source.strip_prefix('/') {
+            let (transform, rest) = Transform::parse(rest)?;
+            source = rest;
+            tabstop(index, Vec::new(), Some(transform))
         } else {
-            return Err(anyhow!("expected a closing brace"));
-        }
-    } else {
-        let (index, rest) = parse_int(source)?;
-        tabstop_index = index;
+            tabstop(index, Vec::new(), None)
+        };
+        let source = source
+            .strip_prefix('}')
+            .ok_or_else(|| anyhow!("expected a closing brace"))?;
+        return Ok(Some((node, source)));
+    }
+
+    let (name, mut source) =
+        parse_variable_name(source).ok_or_else(|| anyhow!("expected an integer or a variable"))?;
+    let mut default = None;
+    let mut transform = None;
+    if let Some(rest) = source.strip_prefix(':') {
+        let (content, rest) = parse_nodes(rest, true)?;
+        default = Some(content);
         source = rest;
+    } else if let Some(rest) = source.strip_prefix('/') {
+        let (parsed_transform, rest) = Transform::parse(rest)?;
+        transform = Some(parsed_transform);
+        source = rest;
+    }
+    let source = source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))?;
+    let node = Node::Variable {
+        name: name.to_string(),
+        default,
+        transform,
+    };
+    Ok(Some((node, source)))
+}
+
+fn tabstop(index: usize, content: Vec<Node>, transform: Option<Transform>) -> Node {
+    Node::Tabstop {
+        index,
+        content,
+        transform,
+    }
+}
+
+fn parse_variable_name(source: &str) -> Option<(&str, &str)> {
+    if !source.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+        return None;
     }
+    let len = source
+        .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+        .unwrap_or(source.len());
+    Some(source.split_at(len))
+}
 
-    tabstops
-        .entry(tabstop_index)
-        .or_default()
-        .push(tabstop_start as isize..text.len() as isize);
-    Ok(source)
+/// Parses the options of a choice, up to and including the closing `|`.
+fn parse_choice(mut source: &str) -> Result<(Vec<String>, &str)> {
+    let mut options = Vec::new();
+    let mut option = String::new();
+    loop {
+        let c = source
+            .chars()
+            .next()
+            .ok_or_else(|| anyhow!("unterminated choice"))?;
+        source = &source[c.len_utf8()..];
+        match c {
+            '\\' => {
+                if let Some(c) = source.chars().next() {
+                    if !matches!(c, ',' | '|' | '\\') {
+                        option.push('\\');
+                    }
+                    option.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            ',' => options.push(std::mem::take(&mut option)),
+            '|' => {
+                options.push(option);
+                return Ok((options, source));
+            }
+            c => option.push(c),
+        }
+    }
 }
 
 fn parse_int(source: &str) -> Result<(usize, &str)> {
@@ -116,6 +323,147 @@ fn parse_int(source: &str) -> Result<(usize, &str)> {
     Ok((prefix.parse()?, suffix))
 }
 
+/// Finds the first placeholder for each tabstop, whose text is mirrored by
+/// the tabstop's other occurrences.
+fn collect_placeholders<'a>(nodes: &'a [Node], placeholders: &mut HashMap<usize, &'a Node>) {
+    for node in nodes {
+        match node {
+            Node::Tabstop { index, content, .. } => {
+                if !content.is_empty() {
+                    placeholders.entry(*index).or_insert(node);
+                }
+                collect_placeholders(content, placeholders);
+            }
+            Node::Choice { index, .. } => {
+                placeholders.entry(*index).or_insert(node);
+            }
+            Node::Variable {
+                default: Some(default),
+                ..
+            } => collect_placeholders(default, placeholders),
+            Node::Text(_) | Node::Variable { .. } => {}
+        }
+    }
+}
+
+struct Renderer<'a> {
+    resolve_variable: &'a dyn Fn(&str) -> Option<String>,
+    placeholders: HashMap<usize, &'a Node>,
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    choices: BTreeMap<usize, Vec<String>>,
+    /// Placeholders for variables that couldn't be resolved.
+    variable_tabstops: Vec<TabStop>,
+    /// The ranges containing transformed tabstop values, with the index of
+    /// the tabstop and the transform.
+    transformations: Vec<(usize, Range<isize>, &'a Transform)>,
+}
+
+impl<'a> Renderer<'a> {
+    /// Renders the nodes into the snippet text, recording tabstop ranges.
+    /// `resolving` holds the tabstops whose values are being computed, to
+    /// avoid infinite recursion in self-referential snippets.
+    fn render(&mut self, nodes: &'a [Node], resolving: &mut Vec<usize>) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.text.push_str(text),
+                Node::Tabstop {
+                    index,
+                    transform: Some(transform),
+                    ..
+                } => {
+                    let start = self.text.len();
+                    let value = self.tabstop_value(*index, resolving);
+                    self.text.push_str(&transform.apply(&value));
+                    self.transformations.push((
+                        *index,
+                        start as isize..self.text.len() as isize,
+                        transform,
+                    ));
+                }
+                Node::Tabstop {
+                    index,
+                    content,
+                    transform: None,
+                } => {
+                    let start = self.text.len();
+                    if content.is_empty() {
+                        let value = self.tabstop_value(*index, resolving);
+                        self.text.push_str(&value);
+                    } else {
+                        self.render(content, resolving);
+                    }
+                    self.tabstops
+                        .entry(*index)
+                        .or_default()
+                        .push(start as isize..self.text.len() as isize);
+                }
+                Node::Choice { index, options } => {
+                    let start = self.text.len();
+                    self.text
+                        .push_str(options.first().map_or("", String::as_str));
+                    self.tabstops
+                        .entry(*index)
+                        .or_default()
+                        .push(start as isize..self.text.len() as isize);
+                    self.choices
+                        .entry(*index)
+                        .or_insert_with(|| options.clone());
+                }
+                Node::Variable {
+                    name,
+                    default,
+                    transform,
+                } => match ((self.resolve_variable)(name), default) {
+                    (Some(value), default) if !value.is_empty() || default.is_none() => {
+                        let value = match transform {
+                            Some(transform) => transform.apply(&value),
+                            None => value,
+                        };
+                        self.text.push_str(&value);
+                    }
+                    (_, Some(default)) => self.render(default, resolving),
+                    (None, None) => {
+                        let start = self.text.len();
+                        self.text.push_str(name);
+                        self.variable_tabstops.push(
+                            [start as isize..self.text.len() as isize]
+                                .into_iter()
+                                .collect(),
+                        );
+                    }
+                },
+            }
+        }
+    }
+
+    /// Returns the text of the placeholder for the given tabstop.
+    fn tabstop_value(&self, index: usize, resolving: &mut Vec<usize>) -> String {
+        if resolving.contains(&index) {
+            return String::new();
+        }
+        match self.placeholders.get(&index) {
+            Some(Node::Tabstop { content, .. }) => {
+                let mut renderer = Renderer {
+                    resolve_variable: self.resolve_variable,
+                    placeholders: self.placeholders.clone(),
+                    text: String::new(),
+                    tabstops: BTreeMap::new(),
+                    choices: BTreeMap::new(),
+                    variable_tabstops: Vec::new(),
+                    transformations: Vec::new(),
+                };
+                resolving.push(index);
+                renderer.render(content, resolving);
+                resolving.pop();
+                renderer.text
+            }
+            Some(Node::Choice { options, .. }) => options.first().cloned().unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,6 +547,91 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![3..3]]);
     }
 
+    #[test]
+    fn test_snippet_with_mirrored_tabstops() {
+        let snippet = Snippet::parse("<${1:div}>$0</$1>").unwrap();
+        assert_eq!(snippet.text, "<div></div>");
+        assert_eq!(tabstops(&snippet), &[vec![1..4, 7..10], vec![5..5]]);
+    }
+
+    #[test]
+    fn test_snippet_with_choices() {
+        let snippet = Snippet::parse("let ${1|a,b\\,c,d|} = $2;").unwrap();
+        assert_eq!(snippet.text, "let a = ;");
+        assert_eq!(tabstops(&snippet), &[vec![4..5], vec![8..8], vec![9..9]]);
+        assert_eq!(
+            snippet.choices,
+            &[
+                vec!["a".to_string(), "b,c".into(), "d".into()],
+                vec![],
+                vec![]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snippet_with_variables() {
+        let resolve = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+
+        let snippet =
+            Snippet::parse_with_variables("// $TM_FILENAME ${TM_FILENAME}$1", &resolve).unwrap();
+        assert_eq!(snippet.text, "// main.rs main.rs");
+        assert_eq!(tabstops(&snippet), &[vec![18..18]]);
+
+        // Empty and unknown variables use their default.
+        let snippet = Snippet::parse_with_variables(
+            "${TM_SELECTED_TEXT:${1:a}} ${UNKNOWN:b} $TM_SELECTED_TEXT.",
+            &resolve,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "a b .");
+        assert_eq!(tabstops(&snippet), &[vec![0..1], vec![5..5]]);
+
+        // Unknown variables without a default become placeholders.
+        let snippet = Snippet::parse_with_variables("$1 $UNKNOWN $0", &resolve).unwrap();
+        assert_eq!(snippet.text, " UNKNOWN ");
+        assert_eq!(tabstops(&snippet), &[vec![0..0], vec![1..8], vec![9..9]]);
+
+        // A lone dollar sign is treated as text.
+        let snippet = Snippet::parse("cost: $ 5").unwrap();
+        assert_eq!(snippet.text, "cost: $ 5");
+    }
+
+    #[test]
+    fn test_snippet_with_transforms() {
+        let resolve = |name: &str| match name {
+            "TM_FILENAME" => Some("my_module.rs".to_string()),
+            _ => None,
+        };
+
+        let snippet = Snippet::parse_with_variables(
+            "mod ${TM_FILENAME/(.*)\\..+$/$1/}; ${TM_FILENAME/(\\w+)_(\\w+).*/${1:/capitalize}${2:/upcase}/}",
+            &resolve,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "mod my_module; MyMODULE");
+
+        let snippet = Snippet::parse("${1:some_name} ${1/_(.)/${1:/upcase}/g}").unwrap();
+        assert_eq!(snippet.text, "some_name someName");
+        assert_eq!(tabstops(&snippet), &[vec![0..9], vec![18..18]]);
+        assert_eq!(snippet.transformations.len(), 1);
+        assert_eq!(snippet.transformations[0].tabstop, 0);
+        assert_eq!(snippet.transformations[0].range, 10..18);
+        assert_eq!(snippet.transformations[0].apply("other_name"), "otherName");
+
+        let snippet =
+            Snippet::parse("${1:x}: ${1/(y)|(x)/${1:?why:ex}${2:+!}/}${2/(.*)/${1:-none}/}")
+                .unwrap();
+        assert_eq!(snippet.text, "x: ex!none");
+
+        assert!(Snippet::parse("${1/(unclosed/$1/}").is_err());
+        assert!(Snippet::parse("${1/a/b}").is_err());
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.to_vec()).collect()
     }
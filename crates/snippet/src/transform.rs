@@ -0,0 +1,270 @@
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex, RegexBuilder};
+
+/// A regular expression replacement applied to the value of a tabstop or a
+/// variable, written as `/regex/format/options`.
+#[derive(Clone, Debug)]
+pub(crate) struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+#[derive(Clone, Debug)]
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    Case(usize, Case),
+    /// Inserts the first text if the group matched, and the second otherwise.
+    Conditional(usize, String, String),
+}
+
+#[derive(Clone, Debug)]
+enum Case {
+    Upcase,
+    Downcase,
+    Capitalize,
+    Camelcase,
+    Pascalcase,
+}
+
+impl Transform {
+    /// Parses a transform following its opening slash, up to but not
+    /// including the closing brace.
+    pub(crate) fn parse(source: &str) -> Result<(Self, &str)> {
+        let (pattern, source) = parse_regex(source)?;
+        let (format, source) = parse_format(source)?;
+        let options_len = source
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(source.len());
+        let (options, source) = source.split_at(options_len);
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(options.contains('i'))
+            .multi_line(options.contains('m'))
+            .dot_matches_new_line(options.contains('s'))
+            .build()?;
+        let transform = Self {
+            regex,
+            format,
+            global: options.contains('g'),
+        };
+        Ok((transform, source))
+    }
+
+    pub(crate) fn apply(&self, value: &str) -> String {
+        let replace = |captures: &Captures| self.format(captures);
+        if self.global {
+            self.regex.replace_all(value, replace).into_owned()
+        } else {
+            self.regex.replace(value, replace).into_owned()
+        }
+    }
+
+    fn format(&self, captures: &Captures) -> String {
+        let group = |index: usize| {
+            captures
+                .get(index)
+                .map_or("", |group| group.as_str())
+                .to_string()
+        };
+        let mut result = String::new();
+        for item in &self.format {
+            match item {
+                FormatItem::Text(text) => result.push_str(text),
+                FormatItem::Group(index) => result.push_str(&group(*index)),
+                FormatItem::Case(index, case) => result.push_str(&case.apply(&group(*index))),
+                FormatItem::Conditional(index, if_text, else_text) => {
+                    if group(*index).is_empty() {
+                        result.push_str(else_text);
+                    } else {
+                        result.push_str(if_text);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Case {
+    fn apply(&self, text: &str) -> String {
+        match self {
+            Case::Upcase => text.to_uppercase(),
+            Case::Downcase => text.to_lowercase(),
+            Case::Capitalize => capitalize(text),
+            Case::Pascalcase => words(text).map(|word| capitalize(&word)).collect(),
+            Case::Camelcase => words(text)
+                .enumerate()
+                .map(|(ix, word)| {
+                    if ix == 0 {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(&word)
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+}
+
+/// Parses a regular expression up to and including its closing slash.
+fn parse_regex(mut source: &str) -> Result<(String, &str)> {
+    let mut pattern = String::new();
+    loop {
+        let c = source
+            .chars()
+            .next()
+            .ok_or_else(|| anyhow!("unterminated transform"))?;
+        source = &source[c.len_utf8()..];
+        match c {
+            '/' => return Ok((pattern, source)),
+            '\\' if source.starts_with('/') => {
+                pattern.push('/');
+                source = &source[1..];
+            }
+            '\\' => {
+                pattern.push('\\');
+                if let Some(c) = source.chars().next() {
+                    pattern.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            c => pattern.push(c),
+        }
+    }
+}
+
+/// Parses a format string up to and including its closing slash.
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    let mut text = String::new();
+    loop {
+        let c = source
+            .chars()
+            .next()
+            .ok_or_else(|| anyhow!("unterminated transform"))?;
+        source = &source[c.len_utf8()..];
+        match c {
+            '/' => break,
+            '\\' => {
+                if let Some(c) = source.chars().next() {
+                    text.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            '$' => {
+                let Some((item, rest)) = parse_format_item(source)? else {
+                    text.push('$');
+                    continue;
+                };
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(std::mem::take(&mut text)));
+                }
+                items.push(item);
+                source = rest;
+            }
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        items.push(FormatItem::Text(text));
+    }
+    Ok((items, source))
+}
+
+/// Parses a group reference following a `$` in a format string.
+fn parse_format_item(source: &str) -> Result<Option<(FormatItem, &str)>> {
+    if let Some((index, rest)) = parse_group(source) {
+        return Ok(Some((FormatItem::Group(index), rest)));
+    }
+    let Some(source) = source.strip_prefix('{') else {
+        return Ok(None);
+    };
+    let (index, source) = parse_group(source).ok_or_else(|| anyhow!("expected a group number"))?;
+    if let Some(rest) = source.strip_prefix('}') {
+        return Ok(Some((FormatItem::Group(index), rest)));
+    }
+    let source = source
+        .strip_prefix(':')
+        .ok_or_else(|| anyhow!("expected a closing brace"))?;
+
+    let (item, source) = if let Some(rest) = source.strip_prefix('/') {
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (name, rest) = rest.split_at(name_len);
+        let case = match name {
+            "upcase" => Case::Upcase,
+            "downcase" => Case::Downcase,
+            "capitalize" => Case::Capitalize,
+            "camelcase" => Case::Camelcase,
+            "pascalcase" => Case::Pascalcase,
+            _ => return Err(anyhow!("unknown case modifier {name:?}")),
+        };
+        (FormatItem::Case(index, case), rest)
+    } else if let Some(rest) = source.strip_prefix('+') {
+        let (if_text, rest) = parse_format_text(rest, &['}']);
+        (FormatItem::Conditional(index, if_text, String::new()), rest)
+    } else if let Some(rest) = source.strip_prefix('?') {
+        let (if_text, rest) = parse_format_text(rest, &[':']);
+        let rest = rest
+            .strip_prefix(':')
+            .ok_or_else(|| anyhow!("expected a colon"))?;
+        let (else_text, rest) = parse_format_text(rest, &['}']);
+        (FormatItem::Conditional(index, if_text, else_text), rest)
+    } else {
+        let rest = source.strip_prefix('-').unwrap_or(source);
+        let (else_text, rest) = parse_format_text(rest, &['}']);
+        (
+            FormatItem::Conditional(index, String::new(), else_text),
+            rest,
+        )
+    };
+    let source = source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))?;
+    Ok(Some((item, source)))
+}
+
+fn parse_group(source: &str) -> Option<(usize, &str)> {
+    let len = source
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(source.len());
+    let index = source[..len].parse().ok()?;
+    Some((index, &source[len..]))
+}
+
+/// Parses literal text in a conditional, up to one of the given terminators.
+fn parse_format_text<'a>(mut source: &'a str, terminators: &[char]) -> (String, &'a str) {
+    let mut text = String::new();
+    while let Some(c) = source.chars().next() {
+        if terminators.contains(&c) {
+            break;
+        }
+        source = &source[c.len_utf8()..];
+        if c == '\\' {
+            if let Some(c) = source.chars().next() {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        } else {
+            text.push(c);
+        }
+    }
+    (text, source)
+}
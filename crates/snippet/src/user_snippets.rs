@@ -0,0 +1,127 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A snippet defined by the user, in the format used by VS Code snippet files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserSnippet {
+    pub name: String,
+    /// The words that trigger this snippet in completions.
+    pub prefixes: Vec<String>,
+    pub body: String,
+    pub description: Option<String>,
+    /// The languages this snippet applies to, if it's defined in a global
+    /// snippet file and restricted to some languages.
+    pub scope: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct SnippetDefinition {
+    #[serde(default)]
+    prefix: Option<OneOrMany>,
+    body: OneOrMany,
+    #[serde(default)]
+    description: Option<OneOrMany>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+/// Parses the contents of a VS Code snippet file, which maps snippet names to
+/// their definitions. Bodies given as arrays are joined into lines, and
+/// snippets without a prefix are skipped, since they can't be completed.
+pub fn parse_snippet_file(content: &str) -> Result<Vec<UserSnippet>> {
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let definitions: BTreeMap<String, SnippetDefinition> = serde_json_lenient::from_str(content)?;
+    Ok(definitions
+        .into_iter()
+        .filter_map(|(name, definition)| {
+            let prefixes = definition.prefix?.into_vec();
+            let scope = definition.scope.map(|scope| {
+                scope
+                    .split(',')
+                    .map(|language| language.trim().to_string())
+                    .filter(|language| !language.is_empty())
+                    .collect()
+            });
+            Some(UserSnippet {
+                name,
+                prefixes,
+                body: definition.body.into_vec().join("\n"),
+                description: definition
+                    .description
+                    .map(|description| description.into_vec().join("\n")),
+                scope,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snippet_file() {
+        let snippets = parse_snippet_file(
+            r#"{
+                // Comments and trailing commas are allowed.
+                "Print to console": {
+                    "prefix": ["log", "print"],
+                    "body": ["console.log('$1');", "$0"],
+                    "description": "Log output to console",
+                },
+                "Header": {
+                    "prefix": "header",
+                    "body": "// ${TM_FILENAME} (c) ${CURRENT_YEAR}",
+                    "scope": "rust, javascript"
+                },
+                "No prefix": {
+                    "body": "unreachable"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            snippets,
+            vec![
+                UserSnippet {
+                    name: "Header".into(),
+                    prefixes: vec!["header".into()],
+                    body: "// ${TM_FILENAME} (c) ${CURRENT_YEAR}".into(),
+                    description: None,
+                    scope: Some(vec!["rust".into(), "javascript".into()]),
+                },
+                UserSnippet {
+                    name: "Print to console".into(),
+                    prefixes: vec!["log".into(), "print".into()],
+                    body: "console.log('$1');\n$0".into(),
+                    description: Some("Log output to console".into()),
+                    scope: None,
+                },
+            ]
+        );
+
+        assert_eq!(parse_snippet_file("").unwrap(), vec![]);
+        assert!(parse_snippet_file("{\"a\": {\"prefix\": 1}}").is_err());
+    }
+}
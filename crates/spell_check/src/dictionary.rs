@@ -0,0 +1,553 @@
+use anyhow::{anyhow, Result};
+use collections::{HashMap, HashSet};
+use std::borrow::Cow;
+
+type Flag = u32;
+
+/// A dictionary in the Hunspell format, made of an affix file (`.aff`)
+/// describing how words can be inflected, and a word list (`.dic`) giving each
+/// stem and the affixes it accepts.
+///
+/// This supports the subset of the format that's needed to check words in most
+/// dictionaries: prefixes and suffixes with their conditions, cross products,
+/// `NEEDAFFIX`, `FORBIDDENWORD` and `NOSUGGEST`, and the `TRY` and `REP`
+/// suggestion hints. Compound words are not supported.
+pub struct Dictionary {
+    words: HashMap<String, Vec<Vec<Flag>>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+    need_affix: Option<Flag>,
+    forbidden: Option<Flag>,
+    no_suggest: Option<Flag>,
+}
+
+#[derive(Clone, Copy)]
+enum FlagFormat {
+    Char,
+    Long,
+    Num,
+}
+
+impl FlagFormat {
+    fn parse(self, flags: &str) -> Vec<Flag> {
+        match self {
+            FlagFormat::Char => flags.chars().map(|c| c as Flag).collect(),
+            FlagFormat::Long => flags
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| pair.iter().fold(0, |flag, c| (flag << 16) | *c as Flag))
+                .collect(),
+            FlagFormat::Num => flags
+                .split(',')
+                .filter_map(|flag| flag.trim().parse().ok())
+                .collect(),
+        }
+    }
+
+    fn parse_one(self, flag: Option<&str>) -> Option<Flag> {
+        self.parse(flag?).first().copied()
+    }
+}
+
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Vec<CharClass>,
+}
+
+enum CharClass {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::Char(expected) => c == *expected,
+            CharClass::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+
+    /// Parses a condition such as `[^aeiou]y`, which is a simplified regular
+    /// expression made of characters, character sets and dots.
+    fn parse_condition(condition: &str) -> Vec<Self> {
+        let mut classes = Vec::new();
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => classes.push(CharClass::Any),
+                '[' => {
+                    let mut set = Vec::new();
+                    let mut negated = false;
+                    for c in chars.by_ref() {
+                        match c {
+                            ']' => break,
+                            '^' if set.is_empty() && !negated => negated = true,
+                            c => set.push(c),
+                        }
+                    }
+                    classes.push(CharClass::Set {
+                        chars: set,
+                        negated,
+                    });
+                }
+                c => classes.push(CharClass::Char(c)),
+            }
+        }
+        if classes.iter().all(|class| matches!(class, CharClass::Any)) {
+            classes.clear();
+        }
+        classes
+    }
+}
+
+impl Affix {
+    /// Returns the stem this suffix could have been added to, to form `word`.
+    fn remove_suffix(&self, word: &str) -> Option<String> {
+        let base = word.strip_suffix(self.add.as_str())?;
+        if base.is_empty() {
+            return None;
+        }
+        let stem = format!("{base}{}", self.strip);
+        let mut chars = stem.chars().rev();
+        let matches = self
+            .condition
+            .iter()
+            .rev()
+            .all(|class| chars.next().map_or(false, |c| class.matches(c)));
+        matches.then_some(stem)
+    }
+
+    /// Returns the stem this prefix could have been added to, to form `word`.
+    fn remove_prefix(&self, word: &str) -> Option<String> {
+        let base = word.strip_prefix(self.add.as_str())?;
+        if base.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{base}", self.strip);
+        let mut chars = stem.chars();
+        let matches = self
+            .condition
+            .iter()
+            .all(|class| chars.next().map_or(false, |c| class.matches(c)));
+        matches.then_some(stem)
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum Casing {
+    Lower,
+    Title,
+    Upper,
+    Mixed,
+}
+
+fn casing(word: &str) -> Casing {
+    let mut chars = word.chars().filter(|c| c.is_alphabetic());
+    let Some(first) = chars.next() else {
+        return Casing::Lower;
+    };
+    let (mut upper, mut lower) = (0, 0);
+    for c in chars {
+        if c.is_uppercase() {
+            upper += 1;
+        } else {
+            lower += 1;
+        }
+    }
+    match (first.is_uppercase(), upper, lower) {
+        (false, 0, _) => Casing::Lower,
+        (true, 0, _) => Casing::Title,
+        (true, _, 0) => Casing::Upper,
+        _ => Casing::Mixed,
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Decodes the contents of a dictionary file in the encoding named by the
+/// affix file's `SET` directive.
+fn decode<'a>(bytes: &'a [u8], encoding: &str) -> Cow<'a, str> {
+    match encoding.to_ascii_uppercase().as_str() {
+        "ISO8859-1" | "ISO-8859-1" | "LATIN1" => {
+            Cow::Owned(bytes.iter().map(|byte| *byte as char).collect())
+        }
+        _ => String::from_utf8_lossy(bytes),
+    }
+}
+
+impl Dictionary {
+    /// Parses a dictionary from the contents of its affix and word list files.
+    pub fn new(aff: &[u8], dic: &[u8]) -> Result<Self> {
+        let encoding = String::from_utf8_lossy(aff)
+            .lines()
+            .find_map(|line| Some(line.strip_prefix("SET")?.trim().to_string()))
+            .unwrap_or_else(|| "UTF-8".into());
+        let aff = decode(aff, &encoding);
+        let dic = decode(dic, &encoding);
+
+        let mut dictionary = Self {
+            words: HashMap::default(),
+            prefixes: Vec::new(),
+            suffixes: Vec::new(),
+            try_chars: Vec::new(),
+            replacements: Vec::new(),
+            need_affix: None,
+            forbidden: None,
+            no_suggest: None,
+        };
+
+        let mut flag_format = FlagFormat::Char;
+        let mut affix_headers = HashMap::default();
+        for line in aff.lines() {
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("FLAG") => {
+                    flag_format = match fields.next() {
+                        Some("long") => FlagFormat::Long,
+                        Some("num") => FlagFormat::Num,
+                        _ => FlagFormat::Char,
+                    }
+                }
+                Some("TRY") => {
+                    dictionary.try_chars = fields.next().unwrap_or_default().chars().collect()
+                }
+                Some("NEEDAFFIX") => dictionary.need_affix = flag_format.parse_one(fields.next()),
+                Some("FORBIDDENWORD") => {
+                    dictionary.forbidden = flag_format.parse_one(fields.next())
+                }
+                Some("NOSUGGEST") => dictionary.no_suggest = flag_format.parse_one(fields.next()),
+                Some("REP") => {
+                    if let (Some(from), Some(to)) = (fields.next(), fields.next()) {
+                        dictionary
+                            .replacements
+                            .push((from.replace('_', " "), to.replace('_', " ")));
+                    }
+                }
+                Some(kind @ ("PFX" | "SFX")) => {
+                    let Some(flag) = flag_format.parse_one(fields.next()) else {
+                        continue;
+                    };
+                    let (Some(strip), Some(add)) = (fields.next(), fields.next()) else {
+                        continue;
+                    };
+
+                    // The first line for each flag is a header saying whether
+                    // the affix can be combined with affixes of the other kind.
+                    let Some(&cross_product) = affix_headers.get(&(kind, flag)) else {
+                        affix_headers.insert((kind, flag), strip == "Y");
+                        continue;
+                    };
+
+                    let add = add.split('/').next().unwrap_or_default();
+                    let affix = Affix {
+                        flag,
+                        cross_product,
+                        strip: if strip == "0" { "" } else { strip }.to_string(),
+                        add: if add == "0" { "" } else { add }.to_string(),
+                        condition: CharClass::parse_condition(fields.next().unwrap_or(".")),
+                    };
+                    if kind == "PFX" {
+                        dictionary.prefixes.push(affix);
+                    } else {
+                        dictionary.suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut lines = dic.lines();
+        let first_line = lines.next().unwrap_or_default();
+        if first_line.trim().parse::<usize>().is_err() {
+            return Err(anyhow!("dictionary doesn't start with a word count"));
+        }
+        for line in lines {
+            if line.starts_with(['\t', '#']) {
+                continue;
+            }
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            let (word, flags) = split_entry(entry);
+            dictionary
+                .words
+                .entry(word)
+                .or_default()
+                .push(flag_format.parse(flags));
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Returns whether the word is spelled correctly. Words that are
+    /// capitalized or in upper case are accepted if their lowercase form is.
+    pub fn check(&self, word: &str) -> bool {
+        if self.check_exact(word) {
+            return true;
+        }
+        match casing(word) {
+            Casing::Title => self.check_exact(&word.to_lowercase()),
+            Casing::Upper => {
+                let lowercase = word.to_lowercase();
+                self.check_exact(&lowercase) || self.check_exact(&capitalize(&lowercase))
+            }
+            Casing::Lower | Casing::Mixed => false,
+        }
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        if let Some(homonyms) = self.words.get(word) {
+            if homonyms.iter().any(|flags| has_flag(flags, self.forbidden)) {
+                return false;
+            }
+            if homonyms
+                .iter()
+                .any(|flags| !has_flag(flags, self.need_affix))
+            {
+                return true;
+            }
+        }
+        self.check_affixed(word)
+    }
+
+    fn check_affixed(&self, word: &str) -> bool {
+        for suffix in &self.suffixes {
+            if let Some(stem) = suffix.remove_suffix(word) {
+                if self.stem_has_flags(&stem, &[suffix.flag]) {
+                    return true;
+                }
+            }
+        }
+
+        for prefix in &self.prefixes {
+            let Some(stem) = prefix.remove_prefix(word) else {
+                continue;
+            };
+            if self.stem_has_flags(&stem, &[prefix.flag]) {
+                return true;
+            }
+            if prefix.cross_product {
+                for suffix in self.suffixes.iter().filter(|suffix| suffix.cross_product) {
+                    if let Some(stem) = suffix.remove_suffix(&stem) {
+                        if self.stem_has_flags(&stem, &[prefix.flag, suffix.flag]) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    fn stem_has_flags(&self, stem: &str, required_flags: &[Flag]) -> bool {
+        self.words.get(stem).map_or(false, |homonyms| {
+            homonyms.iter().any(|flags| {
+                required_flags.iter().all(|flag| flags.contains(flag))
+                    && !has_flag(flags, self.forbidden)
+            })
+        })
+    }
+
+    /// Returns up to `limit` correctly spelled words that the given word may be
+    /// a misspelling of, from the most to the least likely.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let casing = casing(word);
+        let chars = word.chars().collect::<Vec<_>>();
+        let try_chars = if self.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.try_chars.clone()
+        };
+
+        let mut candidates = Vec::new();
+        for (from, to) in &self.replacements {
+            for (ix, _) in word.match_indices(from.as_str()) {
+                candidates.push(format!("{}{to}{}", &word[..ix], &word[ix + from.len()..]));
+            }
+        }
+        for ix in 1..chars.len() {
+            let mut transposed = chars.clone();
+            transposed.swap(ix - 1, ix);
+            candidates.push(transposed.into_iter().collect());
+        }
+        for ix in 0..chars.len() {
+            for &c in &try_chars {
+                if c != chars[ix] {
+                    let mut replaced = chars.clone();
+                    replaced[ix] = c;
+                    candidates.push(replaced.into_iter().collect());
+                }
+            }
+        }
+        for ix in 0..chars.len() {
+            let mut removed = chars.clone();
+            removed.remove(ix);
+            candidates.push(removed.into_iter().collect());
+        }
+        for ix in 0..=chars.len() {
+            for &c in &try_chars {
+                let mut inserted = chars.clone();
+                inserted.insert(ix, c);
+                candidates.push(inserted.into_iter().collect());
+            }
+        }
+        for ix in 1..chars.len() {
+            let first = chars[..ix].iter().collect::<String>();
+            let second = chars[ix..].iter().collect::<String>();
+            if first.chars().count() > 1 && self.check(&first) && self.check(&second) {
+                candidates.push(format!("{first} {second}"));
+            }
+        }
+
+        let mut seen = HashSet::default();
+        let mut suggestions = Vec::new();
+        for candidate in candidates {
+            if suggestions.len() >= limit {
+                break;
+            }
+            let candidate = match casing {
+                Casing::Title => capitalize(&candidate),
+                Casing::Upper => candidate.to_uppercase(),
+                Casing::Lower | Casing::Mixed => candidate,
+            };
+            if candidate.is_empty() || candidate == word || !seen.insert(candidate.clone()) {
+                continue;
+            }
+            let is_correct = if candidate.contains(' ') {
+                candidate.split(' ').all(|part| self.check(part))
+            } else {
+                self.check(&candidate) && !self.is_unsuggestable(&candidate)
+            };
+            if is_correct {
+                suggestions.push(candidate);
+            }
+        }
+        suggestions
+    }
+
+    fn is_unsuggestable(&self, word: &str) -> bool {
+        self.no_suggest.is_some()
+            && self.words.get(word).map_or(false, |homonyms| {
+                homonyms
+                    .iter()
+                    .all(|flags| has_flag(flags, self.no_suggest))
+            })
+    }
+}
+
+fn has_flag(flags: &[Flag], flag: Option<Flag>) -> bool {
+    flag.map_or(false, |flag| flags.contains(&flag))
+}
+
+/// Splits a word list entry into the word and its flags, which follow the
+/// first slash that isn't escaped with a backslash.
+fn split_entry(entry: &str) -> (String, &str) {
+    let mut word = String::new();
+    let mut chars = entry.char_indices();
+    while let Some((ix, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, c)) = chars.next() {
+                    word.push(c);
+                }
+            }
+            '/' => return (word, &entry[ix + 1..]),
+            c => word.push(c),
+        }
+    }
+    (word, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "
+        SET UTF-8
+        TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+        NEEDAFFIX X
+        FORBIDDENWORD F
+
+        REP 1
+        REP f ph
+
+        PFX U Y 1
+        PFX U 0 un .
+
+        SFX S Y 3
+        SFX S y ies [^aeiou]y
+        SFX S 0 s [aeiou]y
+        SFX S 0 s [^y]
+
+        SFX D Y 4
+        SFX D y ied [^aeiou]y
+        SFX D 0 ed [aeiou]y
+        SFX D 0 ed [^ey]
+        SFX D 0 d e
+    ";
+
+    const DIC: &str = "8
+        hello
+        world/S
+        try/SD
+        play/SD
+        lock/UD
+        bake/D
+        phone/S
+        foo/X
+        bakeed/F
+    ";
+
+    fn dictionary() -> Dictionary {
+        let aff = AFF.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+        let dic = DIC.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+        Dictionary::new(aff.as_bytes(), dic.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_check() {
+        let dictionary = dictionary();
+        for word in [
+            "hello", "Hello", "HELLO", "worlds", "tries", "tried", "plays", "played", "locked",
+            "unlocked", "unlock", "baked",
+        ] {
+            assert!(dictionary.check(word), "{word:?} should be correct");
+        }
+        for word in [
+            "helo", "trys", "tryed", "playies", "unbake", "foo", "bakeed", "hEllo", "unworld",
+        ] {
+            assert!(!dictionary.check(word), "{word:?} should be incorrect");
+        }
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggest("helo", 3), vec!["hello"]);
+        assert_eq!(dictionary.suggest("Wrold", 3), vec!["World"]);
+        assert_eq!(dictionary.suggest("fone", 3), vec!["phone"]);
+        assert_eq!(dictionary.suggest("helloworld", 3), vec!["hello world"]);
+    }
+
+    #[test]
+    fn test_split_entry() {
+        assert_eq!(split_entry("word/AB"), ("word".to_string(), "AB"));
+        assert_eq!(split_entry("and\\/or/X"), ("and/or".to_string(), "X"));
+        assert_eq!(split_entry("plain"), ("plain".to_string(), ""));
+    }
+}
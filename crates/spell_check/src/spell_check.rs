@@ -0,0 +1,445 @@
+mod dictionary;
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+use fs::Fs;
+use futures::{lock::Mutex, StreamExt};
+use gpui::{AppContext, Global, SharedString, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::{paths, ResultExt};
+
+pub use dictionary::Dictionary;
+
+/// The maximum number of suggestions offered for a misspelled word.
+pub const MAX_SUGGESTIONS: usize = 5;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SpellCheckSettings {
+    /// The name of the Hunspell dictionary to check spelling with, such as
+    /// `en_US`. Dictionaries are looked up in the `dictionaries` directory of
+    /// the Zed config directory, and then in the system's Hunspell directories.
+    ///
+    /// Default: en_US
+    pub spell_check_dictionary: Option<String>,
+}
+
+impl Settings for SpellCheckSettings {
+    const KEY: Option<&'static str> = None;
+
+    type FileContent = Self;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
+
+/// The dictionary and word lists used to check spelling.
+#[derive(Default)]
+pub struct SpellChecker {
+    dictionary_name: Option<String>,
+    dictionary: Option<Arc<Dictionary>>,
+    dictionary_error: Option<SharedString>,
+    user_words: Arc<HashSet<String>>,
+    worktree_words: HashMap<usize, Arc<HashSet<String>>>,
+    /// Held while a word list is being rewritten, so that words added in quick
+    /// succession don't overwrite each other.
+    word_list_lock: Arc<Mutex<()>>,
+}
+
+impl Global for SpellChecker {}
+
+/// A snapshot of the words accepted in a particular worktree, which can be used
+/// on a background thread.
+#[derive(Clone)]
+pub struct WordChecker {
+    dictionary: Arc<Dictionary>,
+    user_words: Arc<HashSet<String>>,
+    worktree_words: Option<Arc<HashSet<String>>>,
+}
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+    SpellCheckSettings::register(cx);
+    cx.set_global(SpellChecker::default());
+
+    load_dictionary_if_changed(fs.clone(), cx);
+    cx.observe_global::<SettingsStore>({
+        let fs = fs.clone();
+        move |cx| load_dictionary_if_changed(fs.clone(), cx)
+    })
+    .detach();
+
+    let mut user_dictionary_rx =
+        settings::watch_config_file(cx.background_executor(), fs, paths::USER_DICTIONARY.clone());
+    cx.spawn(|mut cx| async move {
+        while let Some(content) = user_dictionary_rx.next().await {
+            cx.update_global::<SpellChecker, _>(|checker, _| {
+                checker.user_words = Arc::new(parse_word_list(&content));
+            })
+            .ok();
+        }
+    })
+    .detach();
+}
+
+fn load_dictionary_if_changed(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+    let name = SpellCheckSettings::get_global(cx)
+        .spell_check_dictionary
+        .clone()
+        .unwrap_or_else(|| "en_US".into());
+    if SpellChecker::global(cx).dictionary_name.as_ref() == Some(&name) {
+        return;
+    }
+    cx.global_mut::<SpellChecker>().dictionary_name = Some(name.clone());
+
+    let load_dictionary = cx.background_executor().spawn({
+        let name = name.clone();
+        async move { load_dictionary(&name, fs.as_ref()).await }
+    });
+    cx.spawn(|mut cx| async move {
+        let dictionary = load_dictionary.await;
+        cx.update_global::<SpellChecker, _>(|checker, _| {
+            if checker.dictionary_name.as_ref() == Some(&name) {
+                match dictionary {
+                    Ok(dictionary) => {
+                        checker.dictionary = Some(Arc::new(dictionary));
+                        checker.dictionary_error = None;
+                    }
+                    Err(error) => {
+                        log::error!("{error:?}");
+                        checker.dictionary = None;
+                        checker.dictionary_error = Some(format!("{error:#}").into());
+                    }
+                }
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![paths::DICTIONARIES_DIR.clone()];
+    if cfg!(target_os = "macos") {
+        dirs.push(paths::HOME.join("Library/Spelling"));
+        dirs.push("/Library/Spelling".into());
+    } else {
+        dirs.push(paths::HOME.join(".local/share/hunspell"));
+        dirs.push("/usr/share/hunspell".into());
+        dirs.push("/usr/share/myspell".into());
+        dirs.push("/usr/share/myspell/dicts".into());
+    }
+    dirs
+}
+
+async fn load_dictionary(name: &str, fs: &dyn Fs) -> Result<Dictionary> {
+    for dir in dictionary_dirs() {
+        let aff_path = dir.join(format!("{name}.aff"));
+        let dic_path = dir.join(format!("{name}.dic"));
+        if !fs.is_file(&aff_path).await || !fs.is_file(&dic_path).await {
+            continue;
+        }
+
+        let aff = fs.load_bytes(&aff_path).await?;
+        let dic = fs.load_bytes(&dic_path).await?;
+        return Dictionary::new(&aff, &dic)
+            .with_context(|| format!("failed to parse dictionary {dic_path:?}"));
+    }
+    Err(anyhow!(
+        "no dictionary named {name:?} was found. Add {name}.aff and {name}.dic to {:?}",
+        paths::DICTIONARIES_DIR.as_path()
+    ))
+}
+
+/// Parses a word list, which contains one word per line.
+fn parse_word_list(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+impl SpellChecker {
+    pub fn global(cx: &AppContext) -> &Self {
+        cx.global::<Self>()
+    }
+
+    pub fn try_global(cx: &AppContext) -> Option<&Self> {
+        cx.try_global::<Self>()
+    }
+
+    /// Returns why the dictionary set in the settings couldn't be loaded, if it
+    /// couldn't.
+    pub fn dictionary_error(&self) -> Option<&SharedString> {
+        self.dictionary_error.as_ref()
+    }
+
+    /// Returns a checker for the words in the given worktree, or `None` if no
+    /// dictionary is loaded.
+    pub fn word_checker(&self, worktree_id: Option<usize>) -> Option<WordChecker> {
+        Some(WordChecker {
+            dictionary: self.dictionary.clone()?,
+            user_words: self.user_words.clone(),
+            worktree_words: worktree_id.and_then(|id| self.worktree_words.get(&id).cloned()),
+        })
+    }
+
+    /// Sets the words accepted in a worktree from the contents of its
+    /// `.zed/dictionary.txt` file, or clears them if `content` is `None`.
+    pub fn set_worktree_words(worktree_id: usize, content: Option<&str>, cx: &mut AppContext) {
+        if !cx.has_global::<Self>() {
+            return;
+        }
+        cx.update_global::<Self, _>(|checker, _| match content {
+            Some(content) => {
+                checker
+                    .worktree_words
+                    .insert(worktree_id, Arc::new(parse_word_list(content)));
+            }
+            None => {
+                checker.worktree_words.remove(&worktree_id);
+            }
+        });
+    }
+
+    /// Adds a word to the user's dictionary, which applies to every project.
+    pub fn add_user_word(word: String, fs: Arc<dyn Fs>, cx: &mut AppContext) -> Task<Result<()>> {
+        let lock = cx.update_global::<Self, _>(|checker, _| {
+            Arc::make_mut(&mut checker.user_words).insert(word.clone());
+            checker.word_list_lock.clone()
+        });
+        cx.background_executor().spawn(async move {
+            let _guard = lock.lock().await;
+            append_word(fs.as_ref(), &paths::USER_DICTIONARY, &word).await
+        })
+    }
+
+    /// Adds a word to the dictionary of the worktree with the given root path.
+    pub fn add_worktree_word(
+        word: String,
+        worktree_id: usize,
+        worktree_abs_path: &Path,
+        fs: Arc<dyn Fs>,
+        cx: &mut AppContext,
+    ) -> Task<Result<()>> {
+        let lock = cx.update_global::<Self, _>(|checker, _| {
+            let words = checker.worktree_words.entry(worktree_id).or_default();
+            Arc::make_mut(words).insert(word.clone());
+            checker.word_list_lock.clone()
+        });
+        let path = worktree_abs_path.join(*paths::LOCAL_DICTIONARY_RELATIVE_PATH);
+        cx.background_executor().spawn(async move {
+            let _guard = lock.lock().await;
+            append_word(fs.as_ref(), &path, &word).await
+        })
+    }
+}
+
+async fn append_word(fs: &dyn Fs, path: &Path, word: &str) -> Result<()> {
+    let mut content = if fs.is_file(path).await {
+        fs.load(path).await?
+    } else {
+        String::new()
+    };
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(word);
+    content.push('\n');
+
+    if let Some(dir) = path.parent() {
+        fs.create_dir(dir).await?;
+    }
+    fs.atomic_write(path.to_path_buf(), content)
+        .await
+        .with_context(|| format!("failed to write dictionary {path:?}"))
+}
+
+impl WordChecker {
+    /// Returns whether the word is spelled correctly, either because it's in
+    /// the dictionary or in one of the user's word lists.
+    pub fn is_correct(&self, word: &str) -> bool {
+        self.is_listed(word) || self.dictionary.check(word)
+    }
+
+    fn is_listed(&self, word: &str) -> bool {
+        let lowercase = word.to_lowercase();
+        [Some(&self.user_words), self.worktree_words.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|words| words.contains(word) || words.contains(&lowercase))
+    }
+
+    /// Returns the most likely corrections for a misspelled word.
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        self.dictionary.suggest(word, MAX_SUGGESTIONS)
+    }
+}
+
+/// Returns the ranges of the words in `text` that should be spell checked.
+///
+/// Text is split on whitespace and escape sequences, and tokens that look like
+/// code, such as paths, URLs, identifiers and words in backticks, are skipped,
+/// as are acronyms and words of a single letter.
+pub fn words_to_check(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut token_start = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((ix, c)) = chars.next() {
+        let is_separator = c.is_whitespace() || c == '\\';
+        if is_separator {
+            if let Some(start) = token_start.take() {
+                push_token_words(text, start..ix, &mut words);
+            }
+            if c == '\\' {
+                chars.next();
+            }
+        } else if token_start.is_none() {
+            token_start = Some(ix);
+        }
+    }
+    if let Some(start) = token_start {
+        push_token_words(text, start..text.len(), &mut words);
+    }
+    words
+}
+
+fn push_token_words(text: &str, token_range: Range<usize>, words: &mut Vec<Range<usize>>) {
+    let token = &text[token_range.clone()];
+    let looks_like_code = token.contains("://")
+        || token.contains("::")
+        || token
+            .chars()
+            .any(|c| matches!(c, '/' | '@' | '_' | '`' | '=' | '<' | '>' | '#') || c.is_numeric())
+        || token
+            .trim_end_matches(|c: char| !c.is_alphabetic())
+            .contains(|c: char| c == '.' || c == '(');
+    if looks_like_code {
+        return;
+    }
+
+    let is_word_char = |c: char| c.is_alphabetic() || c == '\'' || c == '’';
+    let mut offset = 0;
+    for part in token.split(|c: char| !is_word_char(c)) {
+        let start = offset;
+        offset += part.len();
+        offset += token[offset..].chars().next().map_or(0, |c| c.len_utf8());
+
+        let trimmed = part.trim_start_matches(['\'', '’']);
+        let start = start + part.len() - trimmed.len();
+        let word = trimmed.trim_end_matches(['\'', '’']);
+        let is_single_letter = word.chars().nth(1).is_none();
+        let is_mixed_case = word.chars().skip(1).any(char::is_uppercase);
+        let is_acronym = word.chars().all(|c| !c.is_lowercase());
+        if is_single_letter || is_mixed_case || is_acronym {
+            continue;
+        }
+
+        let start = token_range.start + start;
+        words.push(start..start + word.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+
+    fn init_test(fs: Arc<FakeFs>, cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            init(fs, cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_missing_dictionary(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        init_test(fs.clone(), cx);
+        cx.executor().run_until_parked();
+
+        cx.read(|cx| {
+            let checker = SpellChecker::global(cx);
+            assert!(checker.word_checker(None).is_none());
+            let error = checker.dictionary_error().unwrap();
+            assert!(error.contains("no dictionary named \"en_US\" was found"));
+        });
+
+        fs.insert_tree(
+            paths::DICTIONARIES_DIR.as_path(),
+            serde_json::json!({ "fr.aff": "", "fr.dic": "1\nbonjour\n" }),
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SpellCheckSettings>(cx, |settings| {
+                    settings.spell_check_dictionary = Some("fr".into())
+                });
+            });
+        });
+        cx.executor().run_until_parked();
+
+        cx.read(|cx| {
+            let checker = SpellChecker::global(cx);
+            assert!(checker.dictionary_error().is_none());
+            assert!(checker.word_checker(None).unwrap().is_correct("bonjour"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_add_words_concurrently(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", serde_json::json!({})).await;
+        init_test(fs.clone(), cx);
+
+        let tasks = cx.update(|cx| {
+            ["one", "two", "three"]
+                .into_iter()
+                .map(|word| {
+                    SpellChecker::add_worktree_word(
+                        word.into(),
+                        1,
+                        "/root".as_ref(),
+                        fs.clone(),
+                        cx,
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let content = fs.load("/root/.zed/dictionary.txt".as_ref()).await.unwrap();
+        let mut words = content.lines().collect::<Vec<_>>();
+        words.sort_unstable();
+        assert_eq!(words, ["one", "three", "two"]);
+    }
+
+    #[test]
+    fn test_words_to_check() {
+        let text = "// Teh quick brown fox's `jumps` over foo_bar, https://zed.dev \
+            and NASA's thing.method() at src/main.rs\\nnext HTTPServer camelCase 'quoted' a";
+        let words = words_to_check(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            words,
+            vec!["Teh", "quick", "brown", "fox's", "over", "and", "at", "next", "quoted"]
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use anyhow::{anyhow, Result};
+use std::borrow::Cow;
+
+/// The character encoding of a file on disk.
+///
+/// Buffers always hold UTF-8 text; the encoding is used to decode files when
+/// loading them and to encode the text again when saving.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    has_bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::new(encoding_rs::UTF_8)
+    }
+}
+
+const UTF_8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF_16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF_16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// How many bytes to inspect when guessing whether a file is UTF-16.
+const UTF_16_SAMPLE_LEN: usize = 1024;
+
+/// How many bytes to inspect when guessing whether a file is binary.
+const BINARY_SAMPLE_LEN: usize = 8192;
+
+/// How many bytes to inspect when guessing the encoding of a large file.
+const PREFIX_SAMPLE_LEN: usize = 1024 * 1024;
+
+impl Encoding {
+    fn new(encoding: &'static encoding_rs::Encoding) -> Self {
+        Self {
+            encoding,
+            has_bom: false,
+        }
+    }
+
+    /// Returns the encoding with the given label, as understood by the
+    /// WHATWG Encoding Standard (e.g. "utf-8", "latin1", "shift_jis").
+    pub fn from_label(label: &str) -> Option<Self> {
+        encoding_rs::Encoding::for_label(label.trim().as_bytes()).map(Self::new)
+    }
+
+    /// Returns the encodings that can be picked when reopening or saving a file.
+    pub fn all() -> Vec<Self> {
+        let mut encodings = vec![
+            Self::default(),
+            Self::default().with_bom(true),
+            Self::new(encoding_rs::UTF_16LE).with_bom(true),
+            Self::new(encoding_rs::UTF_16BE).with_bom(true),
+        ];
+        encodings.extend(
+            [
+                encoding_rs::WINDOWS_1252,
+                encoding_rs::ISO_8859_2,
+                encoding_rs::ISO_8859_15,
+                encoding_rs::WINDOWS_1250,
+                encoding_rs::WINDOWS_1251,
+                encoding_rs::KOI8_R,
+                encoding_rs::ISO_8859_7,
+                encoding_rs::WINDOWS_1254,
+                encoding_rs::SHIFT_JIS,
+                encoding_rs::EUC_JP,
+                encoding_rs::ISO_2022_JP,
+                encoding_rs::GBK,
+                encoding_rs::GB18030,
+                encoding_rs::BIG5,
+                encoding_rs::EUC_KR,
+            ]
+            .into_iter()
+            .map(Self::new),
+        );
+        encodings
+    }
+
+    /// Guesses the encoding of the given file contents.
+    ///
+    /// A byte order mark always wins. Otherwise, text with many NUL bytes in
+    /// alternating positions is assumed to be UTF-16, valid UTF-8 is assumed
+    /// to be UTF-8, and Japanese text is recognized as Shift_JIS. Anything else
+    /// that doesn't look like binary data is treated as Windows-1252, which is
+    /// a superset of Latin-1 and can decode any sequence of bytes.
+    pub fn detect(bytes: &[u8]) -> Result<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Ok(Self::new(encoding).with_bom(true));
+        }
+        if let Some(encoding) = detect_utf_16(bytes) {
+            return Ok(Self::new(encoding));
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return Ok(Self::default());
+        }
+        if is_binary(bytes) {
+            return Err(anyhow!("file appears to be binary"));
+        }
+        if is_shift_jis(bytes) {
+            return Ok(Self::new(encoding_rs::SHIFT_JIS));
+        }
+        Ok(Self::new(encoding_rs::WINDOWS_1252))
+    }
+
+    /// Guesses the encoding of a file from its first bytes only, like
+    /// [Encoding::detect], so that large files aren't scanned in full.
+    pub fn detect_from_prefix(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() <= PREFIX_SAMPLE_LEN {
+            return Self::detect(bytes);
+        }
+        let mut prefix = &bytes[..PREFIX_SAMPLE_LEN];
+        // Don't mistake a UTF-8 character cut at the end of the prefix for
+        // invalid UTF-8.
+        if let Err(error) = std::str::from_utf8(prefix) {
+            if error.error_len().is_none() {
+                prefix = &prefix[..error.valid_up_to()];
+            }
+        }
+        Self::detect(prefix)
+    }
+
+    /// Guesses the encoding of a large file from its first bytes and decodes it,
+    /// guessing again from all of its contents if they aren't valid in the
+    /// encoding of the first bytes, such as a Latin-1 file whose first
+    /// non-ASCII character comes late.
+    pub fn detect_and_decode_from_prefix(bytes: Vec<u8>) -> Result<(String, Self)> {
+        let encoding = Self::detect_from_prefix(&bytes)?;
+        match encoding.try_decode(bytes) {
+            Ok(text) => Ok((text, encoding)),
+            Err(bytes) => {
+                let encoding = Self::detect(&bytes)?;
+                Ok((encoding.decode(bytes)?, encoding))
+            }
+        }
+    }
+
+    /// Returns this encoding, writing a byte order mark on save if `has_bom` is true.
+    /// Only UTF-8 and UTF-16 have byte order marks.
+    pub fn with_bom(mut self, has_bom: bool) -> Self {
+        self.has_bom = has_bom && !self.bom_bytes().is_empty();
+        self
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.has_bom
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.encoding == encoding_rs::UTF_8
+    }
+
+    /// The canonical name of the encoding, e.g. "UTF-8" or "windows-1252".
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// A name suitable for displaying to the user, e.g. "UTF-8 with BOM".
+    pub fn display_name(&self) -> Cow<'static, str> {
+        if self.has_bom {
+            format!("{} with BOM", self.name()).into()
+        } else {
+            self.name().into()
+        }
+    }
+
+    fn bom_bytes(&self) -> &'static [u8] {
+        if self.encoding == encoding_rs::UTF_8 {
+            UTF_8_BOM
+        } else if self.encoding == encoding_rs::UTF_16LE {
+            UTF_16LE_BOM
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            UTF_16BE_BOM
+        } else {
+            &[]
+        }
+    }
+
+    /// Decodes the given file contents, failing if they aren't valid in this encoding.
+    pub fn decode(&self, bytes: Vec<u8>) -> Result<String> {
+        self.try_decode(bytes)
+            .map_err(|_| anyhow!("file is not valid {}", self.name()))
+    }
+
+    /// Decodes the given file contents, giving them back without their byte
+    /// order mark if they aren't valid in this encoding.
+    fn try_decode(&self, mut bytes: Vec<u8>) -> Result<String, Vec<u8>> {
+        let bom = self.bom_bytes();
+        if !bom.is_empty() && bytes.starts_with(bom) {
+            bytes.drain(..bom.len());
+        }
+
+        if self.is_utf8() {
+            return String::from_utf8(bytes).map_err(|error| error.into_bytes());
+        }
+
+        match self
+            .encoding
+            .decode_without_bom_handling_and_without_replacement(&bytes)
+        {
+            Some(text) => Ok(text.into_owned()),
+            None => Err(bytes),
+        }
+    }
+
+    /// Encodes the given text, failing if it contains characters that can't
+    /// be represented in this encoding.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        let bom = if self.has_bom { self.bom_bytes() } else { &[] };
+        if self.is_utf8() {
+            return Ok(if bom.is_empty() {
+                Cow::Borrowed(text.as_bytes())
+            } else {
+                Cow::Owned([bom, text.as_bytes()].concat())
+            });
+        }
+
+        // The WHATWG standard only decodes UTF-16, so encode it by hand.
+        let is_utf_16le = self.encoding == encoding_rs::UTF_16LE;
+        if is_utf_16le || self.encoding == encoding_rs::UTF_16BE {
+            let mut bytes = Vec::with_capacity(bom.len() + text.len() * 2);
+            bytes.extend_from_slice(bom);
+            for unit in text.encode_utf16() {
+                if is_utf_16le {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            return Ok(Cow::Owned(bytes));
+        }
+
+        let (bytes, _, had_unmappable_characters) = self.encoding.encode(text);
+        if had_unmappable_characters {
+            Err(anyhow!(
+                "text contains characters that can't be represented in {}",
+                self.name()
+            ))
+        } else {
+            Ok(bytes)
+        }
+    }
+}
+
+fn detect_utf_16(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    let sample = &bytes[..bytes.len().min(UTF_16_SAMPLE_LEN) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+
+    let pairs = sample.len() / 2;
+    let even_nuls = sample.iter().step_by(2).filter(|byte| **byte == 0).count();
+    let odd_nuls = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|byte| **byte == 0)
+        .count();
+
+    // ASCII characters encoded as UTF-16 have a zero high byte.
+    if odd_nuls * 5 > pairs * 2 && even_nuls * 10 < pairs {
+        Some(encoding_rs::UTF_16LE)
+    } else if even_nuls * 5 > pairs * 2 && odd_nuls * 10 < pairs {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    // Text in single-byte and multi-byte legacy encodings never contains NUL
+    // bytes and rarely contains control characters other than whitespace.
+    let sample = &bytes[..bytes.len().min(BINARY_SAMPLE_LEN)];
+    let mut control_bytes = 0;
+    for byte in sample {
+        match byte {
+            0 => return true,
+            b'\t' | b'\n' | b'\r' | 0x0C | 0x1B => {}
+            0x01..=0x1F | 0x7F => control_bytes += 1,
+            _ => {}
+        }
+    }
+    control_bytes * 10 > sample.len()
+}
+
+fn is_shift_jis(bytes: &[u8]) -> bool {
+    // Many Latin-1 byte sequences are also valid Shift_JIS, so require some
+    // kana, which is present in virtually all Japanese text but very unlikely
+    // to come out of decoding anything else.
+    encoding_rs::SHIFT_JIS
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map_or(false, |text| {
+            text.chars().any(|c| matches!(c, '\u{3040}'..='\u{30FF}'))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(Encoding::detect(b"hello").unwrap(), Encoding::default());
+        assert_eq!(
+            Encoding::detect(b"\xEF\xBB\xBFhello").unwrap(),
+            Encoding::default().with_bom(true)
+        );
+        assert_eq!(
+            Encoding::detect(b"\xFF\xFEh\0i\0").unwrap(),
+            Encoding::from_label("utf-16le").unwrap().with_bom(true)
+        );
+        assert_eq!(
+            Encoding::detect(b"h\0e\0l\0l\0o\0").unwrap(),
+            Encoding::from_label("utf-16le").unwrap()
+        );
+        assert_eq!(
+            Encoding::detect(b"\0h\0e\0l\0l\0o").unwrap(),
+            Encoding::from_label("utf-16be").unwrap()
+        );
+        assert_eq!(
+            Encoding::detect("こんにちは".as_bytes()).unwrap(),
+            Encoding::default()
+        );
+        assert_eq!(
+            Encoding::detect(b"\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd").unwrap(),
+            Encoding::from_label("shift_jis").unwrap()
+        );
+        assert_eq!(
+            Encoding::detect(b"caf\xe9 cr\xe8me").unwrap(),
+            Encoding::from_label("windows-1252").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_detect_binary() {
+        assert!(Encoding::detect(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\xff").is_err());
+        assert!(Encoding::detect(b"\xff\x01\x02\x03\x04\x05\x06\x07\x08caf\xe9").is_err());
+        assert!(Encoding::detect(b"caf\xe9\tcr\xe8me\r\n\x0c").is_ok());
+    }
+
+    #[test]
+    fn test_detect_encoding_from_prefix() {
+        // The prefix ends in the middle of a character.
+        let mut bytes = format!("x{}", "é".repeat(PREFIX_SAMPLE_LEN)).into_bytes();
+        assert_eq!(
+            Encoding::detect_from_prefix(&bytes).unwrap(),
+            Encoding::default()
+        );
+
+        // Bytes past the prefix aren't inspected.
+        bytes.extend_from_slice(b"caf\xe9");
+        assert_eq!(
+            Encoding::detect_from_prefix(&bytes).unwrap(),
+            Encoding::default()
+        );
+        assert_eq!(
+            Encoding::detect(&bytes).unwrap(),
+            Encoding::from_label("windows-1252").unwrap()
+        );
+
+        // The whole file is inspected when it isn't valid in the prefix's encoding.
+        let (text, encoding) = Encoding::detect_and_decode_from_prefix(bytes).unwrap();
+        assert_eq!(encoding, Encoding::from_label("windows-1252").unwrap());
+        assert!(text.ends_with("Ã©café"));
+    }
+
+    #[test]
+    fn test_encoding_round_trip() {
+        for (encoding, bytes) in [
+            (Encoding::default(), b"caf\xc3\xa9".to_vec()),
+            (
+                Encoding::default().with_bom(true),
+                b"\xEF\xBB\xBFcaf\xc3\xa9".to_vec(),
+            ),
+            (Encoding::from_label("latin1").unwrap(), b"caf\xe9".to_vec()),
+            (
+                Encoding::from_label("utf-16le").unwrap().with_bom(true),
+                b"\xFF\xFEc\0a\0f\0\xe9\0".to_vec(),
+            ),
+            (
+                Encoding::from_label("utf-16be").unwrap(),
+                b"\0c\0a\0f\0\xe9".to_vec(),
+            ),
+        ] {
+            let text = encoding.decode(bytes.clone()).unwrap();
+            assert_eq!(text, "café", "decoding {}", encoding.display_name());
+            assert_eq!(
+                encoding.encode(&text).unwrap().as_ref(),
+                bytes.as_slice(),
+                "encoding {}",
+                encoding.display_name()
+            );
+        }
+
+        assert!(Encoding::from_label("latin1")
+            .unwrap()
+            .encode("こんにちは")
+            .is_err());
+        assert!(Encoding::default().decode(b"caf\xe9".to_vec()).is_err());
+    }
+}
@@ -74,8 +74,13 @@ impl ThemeColors {
             editor_invisible: neutral().light().step_10(),
             editor_wrap_guide: neutral().light_alpha().step_7(),
             editor_active_wrap_guide: neutral().light_alpha().step_8(),
+            editor_indent_guide: neutral().light_alpha().step_5(),
+            editor_indent_guide_active: neutral().light_alpha().step_8(),
             editor_document_highlight_read_background: neutral().light_alpha().step_3(),
             editor_document_highlight_write_background: neutral().light_alpha().step_4(),
+            editor_conflict_ours_background: grass().light_alpha().step_3(),
+            editor_conflict_theirs_background: blue().light_alpha().step_3(),
+            editor_conflict_base_background: neutral().light_alpha().step_3(),
             terminal_background: neutral().light().step_1(),
             terminal_foreground: black().light().step_12(),
             terminal_bright_foreground: black().light().step_11(),
@@ -168,8 +173,13 @@ impl ThemeColors {
             editor_invisible: neutral().dark_alpha().step_4(),
             editor_wrap_guide: neutral().dark_alpha().step_4(),
             editor_active_wrap_guide: neutral().dark_alpha().step_4(),
+            editor_indent_guide: neutral().dark_alpha().step_4(),
+            editor_indent_guide_active: neutral().dark_alpha().step_6(),
             editor_document_highlight_read_background: neutral().dark_alpha().step_4(),
             editor_document_highlight_write_background: neutral().dark_alpha().step_4(),
+            editor_conflict_ours_background: grass().dark_alpha().step_3(),
+            editor_conflict_theirs_background: blue().dark_alpha().step_3(),
+            editor_conflict_base_background: neutral().dark_alpha().step_3(),
             terminal_background: neutral().dark().step_1(),
             terminal_foreground: white().dark().step_12(),
             terminal_bright_foreground: white().dark().step_11(),
@@ -91,6 +91,8 @@ pub(crate) fn one_dark() -> Theme {
                 editor_invisible: hsla(222.0 / 360., 11.5 / 100., 34.1 / 100., 1.0),
                 editor_wrap_guide: hsla(228. / 360., 8. / 100., 25. / 100., 1.),
                 editor_active_wrap_guide: hsla(228. / 360., 8. / 100., 25. / 100., 1.),
+                editor_indent_guide: hsla(228. / 360., 8. / 100., 20. / 100., 1.),
+                editor_indent_guide_active: hsla(228. / 360., 8. / 100., 30. / 100., 1.),
                 editor_document_highlight_read_background: hsla(
                     207.8 / 360.,
                     81. / 100.,
@@ -98,6 +100,9 @@ pub(crate) fn one_dark() -> Theme {
                     0.2,
                 ),
                 editor_document_highlight_write_background: gpui::red(),
+                editor_conflict_ours_background: hsla(95. / 360., 38. / 100., 62. / 100., 0.15),
+                editor_conflict_theirs_background: hsla(207.8 / 360., 81. / 100., 66. / 100., 0.15),
+                editor_conflict_base_background: hsla(218.8 / 360., 10. / 100., 40. / 100., 0.15),
 
                 terminal_background: bg,
                 // todo("Use one colors for terminal")
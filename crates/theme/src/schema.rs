@@ -350,6 +350,13 @@ pub struct ThemeColorsContent {
     #[serde(rename = "editor.active_wrap_guide")]
     pub editor_active_wrap_guide: Option<String>,
 
+    #[serde(rename = "editor.indent_guide")]
+    pub editor_indent_guide: Option<String>,
+
+    /// Indent guide of the scope containing the cursor.
+    #[serde(rename = "editor.indent_guide_active")]
+    pub editor_indent_guide_active: Option<String>,
+
     /// Read-access of a symbol, like reading a variable.
     ///
     /// A document highlight is a range inside a text document which deserves
@@ -366,6 +373,18 @@ pub struct ThemeColorsContent {
     #[serde(rename = "editor.document_highlight.write_background")]
     pub editor_document_highlight_write_background: Option<String>,
 
+    /// Background of the "ours" side of a merge conflict.
+    #[serde(rename = "editor.conflict.ours_background")]
+    pub editor_conflict_ours_background: Option<String>,
+
+    /// Background of the "theirs" side of a merge conflict.
+    #[serde(rename = "editor.conflict.theirs_background")]
+    pub editor_conflict_theirs_background: Option<String>,
+
+    /// Background of the common ancestor section of a diff3-style merge conflict.
+    #[serde(rename = "editor.conflict.base_background")]
+    pub editor_conflict_base_background: Option<String>,
+
     /// Terminal background color.
     #[serde(rename = "terminal.background")]
     pub terminal_background: Option<String>,
@@ -710,6 +729,14 @@ impl ThemeColorsContent {
                 .editor_active_wrap_guide
                 .as_ref()
                 .and_then(|color| try_parse_color(color).ok()),
+            editor_indent_guide: self
+                .editor_indent_guide
+                .as_ref()
+                .and_then(|color| try_parse_color(color).ok()),
+            editor_indent_guide_active: self
+                .editor_indent_guide_active
+                .as_ref()
+                .and_then(|color| try_parse_color(color).ok()),
             editor_document_highlight_read_background: self
                 .editor_document_highlight_read_background
                 .as_ref()
@@ -718,6 +745,18 @@ impl ThemeColorsContent {
                 .editor_document_highlight_write_background
                 .as_ref()
                 .and_then(|color| try_parse_color(color).ok()),
+            editor_conflict_ours_background: self
+                .editor_conflict_ours_background
+                .as_ref()
+                .and_then(|color| try_parse_color(color).ok()),
+            editor_conflict_theirs_background: self
+                .editor_conflict_theirs_background
+                .as_ref()
+                .and_then(|color| try_parse_color(color).ok()),
+            editor_conflict_base_background: self
+                .editor_conflict_base_background
+                .as_ref()
+                .and_then(|color| try_parse_color(color).ok()),
             terminal_background: self
                 .terminal_background
                 .as_ref()
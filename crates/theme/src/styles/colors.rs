@@ -153,6 +153,9 @@ pub struct ThemeColors {
     pub editor_invisible: Hsla,
     pub editor_wrap_guide: Hsla,
     pub editor_active_wrap_guide: Hsla,
+    pub editor_indent_guide: Hsla,
+    /// Indent guide of the scope containing the cursor.
+    pub editor_indent_guide_active: Hsla,
     /// Read-access of a symbol, like reading a variable.
     ///
     /// A document highlight is a range inside a text document which deserves
@@ -165,6 +168,12 @@ pub struct ThemeColors {
     /// special attention. Usually a document highlight is visualized by changing
     /// the background color of its range.
     pub editor_document_highlight_write_background: Hsla,
+    /// Background of the "ours" side of a merge conflict, including its `<<<<<<<` marker.
+    pub editor_conflict_ours_background: Hsla,
+    /// Background of the "theirs" side of a merge conflict, including its `=======` and `>>>>>>>` markers.
+    pub editor_conflict_theirs_background: Hsla,
+    /// Background of the common ancestor section of a diff3-style merge conflict.
+    pub editor_conflict_base_background: Hsla,
 
     // ===
     // Terminal
@@ -0,0 +1,311 @@
+use anyhow::{anyhow, Result};
+use fs::repository::{
+    CliGitRemoteTransport, GitRemoteTransport, GitRepository, PullStrategy, RemoteProgress,
+};
+use futures::{channel::mpsc, StreamExt};
+use gpui::{actions, AppContext, ViewContext};
+use parking_lot::Mutex;
+use std::{path::Path, sync::Arc};
+use workspace::{Toast, Workspace};
+
+use crate::GIT_JOB_TOAST_ID;
+
+actions!(git, [Fetch, Pull, PullRebase, Push, Stash, StashPop]);
+
+const DEFAULT_REMOTE: &str = "origin";
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &Fetch, cx| run_git_job(workspace, GitJob::Fetch, cx))
+            .register_action(|workspace, _: &Pull, cx| {
+                run_git_job(workspace, GitJob::Pull(PullStrategy::FastForward), cx)
+            })
+            .register_action(|workspace, _: &PullRebase, cx| {
+                run_git_job(workspace, GitJob::Pull(PullStrategy::Rebase), cx)
+            })
+            .register_action(|workspace, _: &Push, cx| run_git_job(workspace, GitJob::Push, cx))
+            .register_action(|workspace, _: &Stash, cx| run_git_job(workspace, GitJob::Stash, cx))
+            .register_action(|workspace, _: &StashPop, cx| {
+                run_git_job(workspace, GitJob::StashPop, cx)
+            });
+    })
+    .detach();
+}
+
+#[derive(Clone, Copy, Debug)]
+enum GitJob {
+    Fetch,
+    Pull(PullStrategy),
+    Push,
+    Stash,
+    StashPop,
+}
+
+impl GitJob {
+    fn title(&self) -> &'static str {
+        match self {
+            GitJob::Fetch => "Fetching",
+            GitJob::Pull(_) => "Pulling",
+            GitJob::Push => "Pushing",
+            GitJob::Stash => "Stashing changes",
+            GitJob::StashPop => "Popping stash",
+        }
+    }
+
+    /// Runs the job on a background thread, returning a message describing its outcome.
+    /// The repository is only locked while it is accessed, not while talking to remotes.
+    fn run(
+        self,
+        repo: &Mutex<dyn GitRepository>,
+        transport: &dyn GitRemoteTransport,
+        progress: &mut dyn FnMut(RemoteProgress),
+    ) -> Result<String> {
+        match self {
+            GitJob::Fetch => {
+                let git_dir = repo.lock().git_dir();
+                transport.fetch(&git_dir, DEFAULT_REMOTE, &[], progress)?;
+                Ok(format!("Fetched from {DEFAULT_REMOTE}"))
+            }
+            GitJob::Pull(strategy) => {
+                let (git_dir, upstream) = {
+                    let repo = repo.lock();
+                    (repo.git_dir(), repo.upstream()?)
+                };
+                transport.fetch(&git_dir, &upstream.remote, &[], progress)?;
+                repo.lock().integrate_upstream(&upstream, strategy)?;
+                Ok("Pulled from upstream".to_string())
+            }
+            GitJob::Push => {
+                let (git_dir, target) = {
+                    let repo = repo.lock();
+                    (repo.git_dir(), repo.push_target(DEFAULT_REMOTE)?)
+                };
+                transport.push(
+                    &git_dir,
+                    &target.remote,
+                    &[target.refspec.clone()],
+                    progress,
+                )?;
+                repo.lock().set_pushed_upstream(&target)?;
+                Ok("Pushed to upstream".to_string())
+            }
+            GitJob::Stash => {
+                repo.lock().stash_push(None)?;
+                Ok("Stashed changes".to_string())
+            }
+            GitJob::StashPop => {
+                let mut repo = repo.lock();
+                let stash = repo
+                    .stash_list()?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow!("there are no stashed changes"))?;
+                repo.stash_pop(stash.index)?;
+                Ok(format!("Popped stash: {}", stash.message))
+            }
+        }
+    }
+}
+
+/// Opens a new handle to the repository containing the active file, or the root
+/// of the first visible worktree, so that long-running jobs don't block the
+/// worktree's own access to the repository.
+fn repository_for_workspace(
+    workspace: &Workspace,
+    cx: &AppContext,
+) -> Result<Arc<Mutex<dyn GitRepository>>> {
+    let project = workspace.project().read(cx);
+    let (worktree_id, path) = match workspace
+        .active_item(cx)
+        .and_then(|item| item.project_path(cx))
+    {
+        Some(project_path) => (project_path.worktree_id, project_path.path),
+        None => {
+            let worktree = project
+                .visible_worktrees(cx)
+                .next()
+                .ok_or_else(|| anyhow!("There are no visible worktrees."))?;
+            (worktree.read(cx).id(), Arc::from(Path::new("")))
+        }
+    };
+    let worktree = project
+        .worktree_for_id(worktree_id, cx)
+        .ok_or_else(|| anyhow!("The worktree was closed."))?;
+    let snapshot = worktree
+        .read(cx)
+        .as_local()
+        .ok_or_else(|| anyhow!("Git operations aren't supported in remote projects."))?
+        .snapshot();
+    let repo = snapshot
+        .repository_for_path(&path)
+        .and_then(|repo| snapshot.get_local_repo(&repo))
+        .ok_or_else(|| anyhow!("`{}` is not in a git repository.", path.to_string_lossy()))?;
+    let git_dir = snapshot.abs_path().join(repo.git_dir_path());
+    project.fs().open_repo(&git_dir).ok_or_else(|| {
+        anyhow!(
+            "Could not open repository at path `{}`",
+            git_dir.to_string_lossy()
+        )
+    })
+}
+
+fn run_git_job(workspace: &mut Workspace, job: GitJob, cx: &mut ViewContext<Workspace>) {
+    let title = job.title();
+    let repo = match repository_for_workspace(workspace, cx) {
+        Ok(repo) => repo,
+        Err(error) => {
+            workspace.show_toast(
+                Toast::new(GIT_JOB_TOAST_ID, format!("{title} failed: {error}")),
+                cx,
+            );
+            return;
+        }
+    };
+
+    workspace.show_toast(Toast::new(GIT_JOB_TOAST_ID, format!("{title}…")), cx);
+    let (progress_tx, mut progress_rx) = mpsc::unbounded();
+    let job_task = cx.background_executor().spawn(async move {
+        job.run(&repo, &CliGitRemoteTransport, &mut |progress| {
+            progress_tx.unbounded_send(progress).ok();
+        })
+    });
+
+    cx.spawn(|workspace, mut cx| async move {
+        let mut last_percentage = None;
+        while let Some(progress) = progress_rx.next().await {
+            if progress.total_objects == 0 {
+                continue;
+            }
+            let percentage = progress.completed_objects * 100 / progress.total_objects;
+            if last_percentage == Some(percentage) {
+                continue;
+            }
+            last_percentage = Some(percentage);
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.show_toast(
+                    Toast::new(GIT_JOB_TOAST_ID, format!("{title}… {percentage}%")),
+                    cx,
+                )
+            })?;
+        }
+
+        let message = match job_task.await {
+            Ok(message) => message,
+            Err(error) => {
+                log::error!("git job failed: {error:?}");
+                format!("{title} failed: {error:#}")
+            }
+        };
+        workspace.update(&mut cx, |workspace, cx| {
+            workspace.show_toast(Toast::new(GIT_JOB_TOAST_ID, message), cx)
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::{repository::GitFileStatus, FakeFs};
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Project;
+    use serde_json::json;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_stash_and_pop(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "a",
+            }),
+        )
+        .await;
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/dir/.git"),
+            &[(Path::new("a.txt"), GitFileStatus::Modified)],
+        );
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        cx.run_until_parked();
+
+        cx.dispatch_action(Stash);
+        assert_eq!(
+            toast_message(&workspace, cx).as_deref(),
+            Some("Stashing changes…")
+        );
+        cx.run_until_parked();
+        assert_eq!(
+            toast_message(&workspace, cx).as_deref(),
+            Some("Stashed changes")
+        );
+        fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+            assert!(state.worktree_statuses.is_empty());
+            assert_eq!(state.stashes.len(), 1);
+        });
+
+        cx.dispatch_action(StashPop);
+        cx.run_until_parked();
+        assert_eq!(
+            toast_message(&workspace, cx).as_deref(),
+            Some("Popped stash: WIP")
+        );
+        fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+            assert_eq!(
+                state.worktree_statuses.get(&Path::new("a.txt").into()),
+                Some(&GitFileStatus::Modified)
+            );
+            assert!(state.stashes.is_empty());
+        });
+
+        // Failures are reported in the same toast.
+        cx.dispatch_action(StashPop);
+        cx.run_until_parked();
+        assert_eq!(
+            toast_message(&workspace, cx).as_deref(),
+            Some("Popping stash failed: there are no stashed changes")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_git_job_outside_of_repository(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.txt": "a" })).await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        cx.run_until_parked();
+
+        cx.dispatch_action(Fetch);
+        let message = toast_message(&workspace, cx).unwrap();
+        assert!(
+            message.starts_with("Fetching failed:") && message.contains("not in a git repository"),
+            "{message}"
+        );
+    }
+
+    fn toast_message(
+        workspace: &gpui::View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Option<String> {
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .toast_message(GIT_JOB_TOAST_ID, cx)
+                .map(|message| message.to_string())
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+}
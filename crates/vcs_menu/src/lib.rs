@@ -1,3 +1,5 @@
+mod git_commands;
+
 use anyhow::{anyhow, bail, Result};
 use fs::repository::Branch;
 use fuzzy::{StringMatch, StringMatchCandidate};
@@ -17,7 +19,11 @@ use workspace::{ModalView, Toast, Workspace};
 
 actions!(branches, [OpenRecent]);
 
+const GIT_CHECKOUT_FAILURE_ID: usize = 2048;
+const GIT_JOB_TOAST_ID: usize = GIT_CHECKOUT_FAILURE_ID + 1;
+
 pub fn init(cx: &mut AppContext) {
+    git_commands::init(cx);
     cx.observe_new_views(|workspace: &mut Workspace, _| {
         workspace.register_action(|workspace, action, cx| {
             BranchList::toggle_modal(workspace, action, cx).log_err();
@@ -125,7 +131,6 @@ impl BranchListDelegate {
     }
 
     fn display_error_toast(&self, message: String, cx: &mut WindowContext<'_>) {
-        const GIT_CHECKOUT_FAILURE_ID: usize = 2048;
         self.workspace.update(cx, |model, ctx| {
             model.show_toast(Toast::new(GIT_CHECKOUT_FAILURE_ID, message), ctx)
         });
@@ -1,50 +1,1055 @@
+use std::ops::{Range, RangeInclusive};
+
+use anyhow::{anyhow, bail, Result};
+use command_palette::CommandPalette;
 use command_palette_hooks::CommandInterceptResult;
-use editor::actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive};
-use gpui::{impl_actions, Action, AppContext, ViewContext};
+use editor::{
+    actions::{Indent, Outdent},
+    scroll::Autoscroll,
+    Anchor, Editor, MultiBuffer, MultiBufferSnapshot, ToPoint,
+};
+use gpui::{
+    actions, impl_actions, Action, AppContext, Context, Keystroke, ViewContext, VisualContext,
+    WindowContext,
+};
+use language::Point;
 use serde_derive::Deserialize;
+use util::ResultExt;
 use workspace::{SaveIntent, Workspace};
 
 use crate::{
-    motion::{EndOfDocument, Motion, StartOfDocument},
+    motion::Motion,
     normal::{
+        mark::{mark_anchor, set_previous_context_mark, ShowMarks},
         move_cursor,
         search::{FindCommand, ReplaceCommand},
-        JoinLines,
     },
-    state::Mode,
+    options::SetCommand,
+    pattern,
+    state::{Mode, Register},
+    utils::{read_register, write_registers},
     Vim,
 };
 
+/// A line address in an ex command, such as `12`, `.`, `$`, `'a` or `/pattern/`,
+/// followed by any `+N` and `-N` offsets.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Position {
+    pub address: Address,
+    pub offset: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Address {
+    /// A 1-based line number; `0` addresses the position before the first line.
+    Line(u32),
+    Mark(char),
+    CurrentLine,
+    LastLine,
+    Pattern {
+        query: String,
+        backwards: bool,
+    },
+}
+
+impl Position {
+    fn parse(input: &str) -> Option<(Self, &str)> {
+        let (address, rest) = if let Some(rest) = input.strip_prefix('.') {
+            (Address::CurrentLine, rest)
+        } else if let Some(rest) = input.strip_prefix('$') {
+            (Address::LastLine, rest)
+        } else if let Some(rest) = input.strip_prefix('\'') {
+            let mut chars = rest.chars();
+            let name = chars.next()?;
+            (Address::Mark(name), chars.as_str())
+        } else if input.starts_with(['/', '?']) {
+            let delimiter = input.chars().next()?;
+            let (query, rest) = split_pattern(&input[1..], delimiter);
+            let backwards = delimiter == '?';
+            (Address::Pattern { query, backwards }, rest.unwrap_or(""))
+        } else if input.starts_with(|c: char| c.is_ascii_digit()) {
+            let end = input
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(input.len());
+            (Address::Line(input[..end].parse().ok()?), &input[end..])
+        } else if input.starts_with(['+', '-']) {
+            (Address::CurrentLine, input)
+        } else {
+            return None;
+        };
+
+        let mut offset = 0;
+        let mut rest = rest;
+        while let Some(sign) = rest.chars().next().filter(|c| *c == '+' || *c == '-') {
+            rest = &rest[1..];
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let amount = rest[..end].parse::<i32>().unwrap_or(1);
+            offset += if sign == '+' { amount } else { -amount };
+            rest = &rest[end..];
+        }
+
+        Some((Position { address, offset }, rest))
+    }
+
+    fn current_line() -> Self {
+        Position {
+            address: Address::CurrentLine,
+            offset: 0,
+        }
+    }
+
+    /// Whether this addresses the position before the first line, as in `:m0`.
+    fn is_before_first_line(&self) -> bool {
+        self.address == Address::Line(0) && self.offset == 0
+    }
+
+    /// Resolves this position to a buffer row, relative to the cursor on `cursor_row`.
+    fn buffer_row(
+        &self,
+        vim: &Vim,
+        snapshot: &MultiBufferSnapshot,
+        cursor_row: u32,
+    ) -> Result<u32> {
+        let max_row = snapshot.max_buffer_row();
+        let row = match &self.address {
+            Address::Line(row) => row.saturating_sub(1),
+            Address::Mark(name) => {
+                let name = if *name == '`' { '\'' } else { *name };
+                mark_anchor(vim, name, snapshot)
+                    .ok_or_else(|| anyhow!("E20: Mark not set"))?
+                    .to_point(snapshot)
+                    .row
+            }
+            Address::CurrentLine => cursor_row,
+            Address::LastLine => max_row,
+            Address::Pattern { query, backwards } => {
+                let query = if query.is_empty() {
+                    vim.workspace_state
+                        .registers
+                        .get(&'/')
+                        .map(|register| register.text.clone())
+                        .ok_or_else(|| anyhow!("E35: No previous regular expression"))?
+                } else {
+                    query.clone()
+                };
+                let regex = pattern::translate(&query)?.build(true)?;
+                let mut rows: Box<dyn Iterator<Item = u32>> = if *backwards {
+                    Box::new((0..cursor_row).rev().chain((cursor_row..=max_row).rev()))
+                } else {
+                    Box::new((cursor_row + 1..=max_row).chain(0..=cursor_row))
+                };
+                rows.find(|row| regex.is_match(&line_text(snapshot, *row)))
+                    .ok_or_else(|| anyhow!("E486: Pattern not found: {}", query))?
+            }
+        };
+
+        let row = row as i64 + self.offset as i64;
+        if row < 0 || row > max_row as i64 {
+            bail!("E16: Invalid range");
+        }
+        Ok(row as u32)
+    }
+}
+
+/// The lines an ex command applies to, such as `%`, `'<,'>` or `.,+3`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CommandRange {
+    pub start: Position,
+    pub end: Option<Position>,
+}
+
+impl CommandRange {
+    fn parse(input: &str) -> (Option<Self>, &str) {
+        if let Some(rest) = input.strip_prefix('%') {
+            let range = CommandRange {
+                start: Position {
+                    address: Address::Line(1),
+                    offset: 0,
+                },
+                end: Some(Position {
+                    address: Address::LastLine,
+                    offset: 0,
+                }),
+            };
+            return (Some(range), rest);
+        }
+
+        let (start, rest) = match Position::parse(input) {
+            Some((start, rest)) => (Some(start), rest),
+            None => (None, input),
+        };
+        let Some(rest) = rest.strip_prefix([',', ';']) else {
+            return (start.map(|start| CommandRange { start, end: None }), rest);
+        };
+        let (end, rest) = match Position::parse(rest) {
+            Some((end, rest)) => (end, rest),
+            None => (Position::current_line(), rest),
+        };
+        let range = CommandRange {
+            start: start.unwrap_or_else(Position::current_line),
+            end: Some(end),
+        };
+        (Some(range), rest)
+    }
+
+    /// The last position of the range, which is where `:{range}` moves the cursor.
+    fn head(&self) -> &Position {
+        self.end.as_ref().unwrap_or(&self.start)
+    }
+
+    fn buffer_rows(
+        &self,
+        vim: &Vim,
+        snapshot: &MultiBufferSnapshot,
+        cursor_row: u32,
+    ) -> Result<RangeInclusive<u32>> {
+        let start = self.start.buffer_row(vim, snapshot, cursor_row)?;
+        let end = match &self.end {
+            Some(end) => end.buffer_row(vim, snapshot, cursor_row)?,
+            None => start,
+        };
+        Ok(start.min(end)..=start.max(end))
+    }
+}
+
+/// Resolves `range` relative to every cursor in the editor, returning the
+/// sorted, merged rows to operate on. Without a range, each cursor's line is used.
+pub(crate) fn buffer_rows(
+    vim: &Vim,
+    editor: &Editor,
+    range: Option<&CommandRange>,
+    cx: &AppContext,
+) -> Result<Vec<RangeInclusive<u32>>> {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let mut rows = Vec::new();
+    for selection in editor.selections.all::<Point>(cx) {
+        let cursor_row = selection.head().row;
+        rows.push(match range {
+            Some(range) => range.buffer_rows(vim, &snapshot, cursor_row)?,
+            None => cursor_row..=cursor_row,
+        });
+    }
+    rows.sort_by_key(|rows| *rows.start());
+
+    let mut merged: Vec<RangeInclusive<u32>> = Vec::new();
+    for rows in rows {
+        match merged.last_mut() {
+            Some(last) if *rows.start() <= last.end() + 1 => {
+                *last = *last.start()..=*last.end().max(rows.end());
+            }
+            _ => merged.push(rows),
+        }
+    }
+    Ok(merged)
+}
+
+pub(crate) fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect()
+}
+
+/// Splits `input` at the first unescaped `delimiter`, unescaping it within the
+/// pattern. The rest is `None` if the pattern was not terminated.
+pub(crate) fn split_pattern(input: &str, delimiter: char) -> (String, Option<&str>) {
+    let mut pattern = String::new();
+    let mut escaped = false;
+    for (ix, c) in input.char_indices() {
+        if escaped {
+            escaped = false;
+            if c != delimiter {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            return (pattern, Some(&input[ix + c.len_utf8()..]));
+        } else {
+            pattern.push(c);
+        }
+    }
+    if escaped {
+        pattern.push('\\');
+    }
+    (pattern, None)
+}
+
+fn first_non_blank(snapshot: &MultiBufferSnapshot, row: u32) -> Point {
+    Point::new(row, snapshot.indent_size_for_line(row).len)
+}
+
+/// The text of the given rows as a linewise register, one clipboard selection per range.
+fn lines_register(snapshot: &MultiBufferSnapshot, rows: &[RangeInclusive<u32>]) -> Register {
+    let mut text = String::new();
+    let mut clipboard_selections = Vec::new();
+    for rows in rows {
+        let start = text.len();
+        for row in rows.clone() {
+            text.push_str(&line_text(snapshot, row));
+            text.push('\n');
+        }
+        clipboard_selections.push(editor::ClipboardSelection {
+            len: text.len() - start,
+            is_entire_line: true,
+            first_line_indent: snapshot.indent_size_for_line(*rows.start()).len,
+        });
+    }
+    Register {
+        text,
+        clipboard_selections: Some(clipboard_selections),
+    }
+}
+
+/// The range to remove to delete the given rows entirely, including one newline.
+fn line_deletion_range(snapshot: &MultiBufferSnapshot, rows: &RangeInclusive<u32>) -> Range<Point> {
+    let (start, end) = (*rows.start(), *rows.end());
+    if end < snapshot.max_buffer_row() {
+        Point::new(start, 0)..Point::new(end + 1, 0)
+    } else if start > 0 {
+        Point::new(start - 1, snapshot.line_len(start - 1))..snapshot.max_point()
+    } else {
+        Point::zero()..snapshot.max_point()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct GoToLine {
-    pub line: u32,
+    /// Keymaps written for the older `{"line": N}` form keep working.
+    #[serde(alias = "line", deserialize_with = "line_or_range")]
+    pub range: CommandRange,
 }
 
-impl_actions!(vim, [GoToLine]);
+fn line_or_range<'de, D>(deserializer: D) -> std::result::Result<CommandRange, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum LineOrRange {
+        Line(u32),
+        Range(CommandRange),
+    }
+
+    Ok(
+        match <LineOrRange as serde::Deserialize>::deserialize(deserializer)? {
+            LineOrRange::Line(line) => CommandRange {
+                start: Position {
+                    address: Address::Line(line),
+                    offset: 0,
+                },
+                end: None,
+            },
+            LineOrRange::Range(range) => range,
+        },
+    )
+}
+
+/// `:[range]d [x]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DeleteCommand {
+    pub range: Option<CommandRange>,
+    pub register: Option<char>,
+}
+
+/// `:[range]y [x]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct YankCommand {
+    pub range: Option<CommandRange>,
+    pub register: Option<char>,
+}
+
+/// `:[range]m {address}`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MoveCommand {
+    pub range: Option<CommandRange>,
+    pub address: Position,
+}
+
+/// `:[range]t {address}`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CopyCommand {
+    pub range: Option<CommandRange>,
+    pub address: Position,
+}
+
+/// `:[range]>` and `:[range]<`, shifting once per `>` or `<`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ShiftCommand {
+    pub range: Option<CommandRange>,
+    pub count: usize,
+    pub outdent: bool,
+}
+
+/// `:[range]j`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct JoinCommand {
+    pub range: Option<CommandRange>,
+}
+
+/// `:[range]sor[!] [i][u]`, sorting the whole buffer without a range.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SortCommand {
+    pub range: Option<CommandRange>,
+    pub reverse: bool,
+    pub ignore_case: bool,
+    pub unique: bool,
+}
+
+/// `:[range]ret[ab][!] [N]`, rewriting the whitespace in the lines (the whole
+/// buffer without a range) for the tab settings, with tabs `N` columns wide. Only
+/// whitespace containing a tab is changed, unless `!` is given.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RetabCommand {
+    pub range: Option<CommandRange>,
+    pub all: bool,
+    pub tab_size: Option<u32>,
+}
+
+/// `:[range]norm {keys}`, typing `keys` in normal mode at the start of each line.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NormalCommand {
+    pub range: Option<CommandRange>,
+    pub keys: String,
+}
+
+/// `:[range]g/pattern/cmd` and `:[range]v/pattern/cmd`. Runs `command` with a
+/// cursor on every (non-)matching line, or just leaves the cursors there if it is empty.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OnMatchingLines {
+    pub range: Option<CommandRange>,
+    pub search: String,
+    pub invert: bool,
+    pub command: String,
+}
+
+impl_actions!(
+    vim,
+    [
+        GoToLine,
+        DeleteCommand,
+        YankCommand,
+        MoveCommand,
+        CopyCommand,
+        ShiftCommand,
+        JoinCommand,
+        SortCommand,
+        RetabCommand,
+        NormalCommand,
+        OnMatchingLines
+    ]
+);
+
+actions!(vim, [ShowRegisters, VisualCommand, CountCommand]);
 
 pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
-    workspace.register_action(|_: &mut Workspace, action: &GoToLine, cx| {
-        Vim::update(cx, |vim, cx| {
+    workspace.register_action(go_to_line);
+    workspace.register_action(|workspace, action: &DeleteCommand, cx| {
+        run_on_active_editor(workspace, cx, |vim, editor, cx| {
+            delete_lines(vim, editor, action, cx)
+        });
+    });
+    workspace.register_action(|workspace, action: &YankCommand, cx| {
+        run_on_active_editor(workspace, cx, |vim, editor, cx| {
+            let rows = buffer_rows(vim, editor, action.range.as_ref(), cx)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let register = lines_register(&snapshot, &rows);
+            write_registers(vim, register, action.register, true, true, cx);
+            Ok(())
+        });
+    });
+    workspace.register_action(|workspace, action: &MoveCommand, cx| {
+        run_on_active_editor(workspace, cx, |vim, editor, cx| {
+            move_or_copy_lines(
+                vim,
+                editor,
+                action.range.as_ref(),
+                &action.address,
+                true,
+                cx,
+            )
+        });
+    });
+    workspace.register_action(|workspace, action: &CopyCommand, cx| {
+        run_on_active_editor(workspace, cx, |vim, editor, cx| {
+            move_or_copy_lines(
+                vim,
+                editor,
+                action.range.as_ref(),
+                &action.address,
+                false,
+                cx,
+            )
+        });
+    });
+    workspace.register_action(|workspace, action: &ShiftCommand, cx| {
+        run_on_active_editor(workspace, cx, |vim, editor, cx| {
+            shift_lines(vim, editor, action, cx)
+        });
+    });
+    workspace.register_action(|workspace, action: &JoinCommand, cx| {
+        run_on_active_editor(workspace, cx, |vim, editor, cx| {
+            let rows = buffer_rows(vim, editor, action.range.as_ref(), cx)?;
+            editor.change_selections(None, cx, |s| {
+                s.select_ranges(
+                    rows.into_iter()
+                        .map(|rows| Point::new(*rows.start(), 0)..Point::new(*rows.end(), 0)),
+                )
+            });
+            editor.join_lines(&editor::actions::JoinLines, cx);
+            Ok(())
+        });
+    });
+    workspace.register_action(|workspace, action: &SortCommand, cx| {
+        run_on_active_editor(workspace, cx, |vim, editor, cx| {
+            sort_lines(vim, editor, action, cx)
+        });
+    });
+    workspace.register_action(|workspace, action: &RetabCommand, cx| {
+        run_on_active_editor(workspace, cx, |vim, editor, cx| {
+            retab(vim, editor, action, cx)
+        });
+    });
+    workspace.register_action(normal_command);
+    workspace.register_action(on_matching_lines);
+    workspace.register_action(|workspace, _: &VisualCommand, cx| {
+        Vim::update(cx, |vim, cx| vim.switch_mode(Mode::Normal, false, cx));
+        CommandPalette::toggle(workspace, "'<,'>", cx);
+    });
+    workspace.register_action(|workspace, _: &CountCommand, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1);
+        let query = if count > 1 {
+            format!(".,.+{}", count - 1)
+        } else {
+            ".".to_string()
+        };
+        CommandPalette::toggle(workspace, &query, cx);
+    });
+    workspace.register_action(show_registers);
+}
+
+/// Runs an ex command against the active editor in normal mode as a single
+/// transaction, showing any error. Returns whether the command succeeded.
+fn run_on_active_editor(
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+    f: impl FnOnce(&mut Vim, &mut Editor, &mut ViewContext<Editor>) -> Result<()>,
+) -> bool {
+    let result = Vim::update(cx, |vim, cx| {
+        if vim.state().mode != Mode::Normal {
             vim.switch_mode(Mode::Normal, false, cx);
-            move_cursor(vim, Motion::StartOfDocument, Some(action.line as usize), cx);
+        }
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let mut result = Ok(());
+            editor.transact(cx, |editor, cx| result = f(vim, editor, cx));
+            result
+        })
+    });
+    match result {
+        Some(Ok(())) => true,
+        Some(Err(err)) => {
+            workspace.show_error(&err, cx);
+            false
+        }
+        None => false,
+    }
+}
+
+fn go_to_line(workspace: &mut Workspace, action: &GoToLine, cx: &mut ViewContext<Workspace>) {
+    let result = Vim::update(cx, |vim, cx| {
+        vim.switch_mode(Mode::Normal, false, cx);
+        let row = vim
+            .update_active_editor(cx, |vim, editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let cursor_row = editor.selections.newest::<Point>(cx).head().row;
+                action.range.head().buffer_row(vim, &snapshot, cursor_row)
+            })
+            .transpose()?;
+        if let Some(row) = row {
+            set_previous_context_mark(vim, cx);
+            move_cursor(vim, Motion::StartOfDocument, Some(row as usize + 1), cx);
+        }
+        anyhow::Ok(())
+    });
+    if let Err(err) = result {
+        workspace.show_error(&err, cx);
+    }
+}
+
+fn delete_lines(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    action: &DeleteCommand,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let rows = buffer_rows(vim, editor, action.range.as_ref(), cx)?;
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let register = lines_register(&snapshot, &rows);
+    write_registers(vim, register, action.register, false, true, cx);
+
+    let mut deleted = 0;
+    let mut cursor_rows = Vec::new();
+    for rows in &rows {
+        cursor_rows.push(rows.start() - deleted);
+        deleted += rows.end() - rows.start() + 1;
+    }
+    editor.edit(
+        rows.iter()
+            .map(|rows| (line_deletion_range(&snapshot, rows), "")),
+        cx,
+    );
+
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let max_row = snapshot.max_buffer_row();
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges(cursor_rows.into_iter().map(|row| {
+            let point = first_non_blank(&snapshot, row.min(max_row));
+            point..point
+        }))
+    });
+    Ok(())
+}
+
+fn move_or_copy_lines(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    range: Option<&CommandRange>,
+    address: &Position,
+    is_move: bool,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    // Resolve every cursor's lines and destination up front, as anchors, so
+    // that moving one block does not change where the others end up.
+    let mut operations = Vec::new();
+    for selection in editor.selections.all::<Point>(cx) {
+        let cursor_row = selection.head().row;
+        let rows = match range {
+            Some(range) => range.buffer_rows(vim, &snapshot, cursor_row)?,
+            None => cursor_row..=cursor_row,
+        };
+        let target = if address.is_before_first_line() {
+            None
+        } else {
+            let row = address.buffer_row(vim, &snapshot, cursor_row)?;
+            if is_move && rows.start() <= &row && &row < rows.end() {
+                bail!("E134: Cannot move a range of lines into itself");
+            }
+            Some(row)
+        };
+        if is_move
+            && target.map_or(*rows.start() == 0, |row| {
+                row + 1 == *rows.start() || rows.contains(&row)
+            })
+        {
+            continue;
+        }
+        let start = snapshot.anchor_before(Point::new(*rows.start(), 0));
+        let end = snapshot.anchor_after(Point::new(*rows.end(), snapshot.line_len(*rows.end())));
+        let target =
+            target.map(|row| snapshot.anchor_after(Point::new(row, snapshot.line_len(row))));
+        operations.push((start..end, target));
+    }
+
+    let mut cursors = Vec::new();
+    for (lines, target) in operations {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let rows = lines.start.to_point(&snapshot).row..=lines.end.to_point(&snapshot).row;
+        let text = rows
+            .clone()
+            .map(|row| line_text(&snapshot, row))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let line_count = rows.end() - rows.start() + 1;
+        let (insertion, text, last_row) = match target {
+            Some(target) => {
+                let point = target.to_point(&snapshot);
+                let last_row = if point.row < *rows.start() || !is_move {
+                    point.row + line_count
+                } else {
+                    point.row
+                };
+                (point, format!("\n{}", text), last_row)
+            }
+            None => (Point::zero(), format!("{}\n", text), line_count - 1),
+        };
+
+        let mut edits = vec![(insertion..insertion, text)];
+        if is_move {
+            edits.push((line_deletion_range(&snapshot, &rows), String::new()));
+            edits.sort_by_key(|(range, _)| range.start);
+        }
+        editor.edit(edits, cx);
+
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        cursors.push(snapshot.anchor_before(first_non_blank(&snapshot, last_row)));
+    }
+
+    if !cursors.is_empty() {
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.select_anchor_ranges(cursors.into_iter().map(|cursor| cursor..cursor))
         });
+    }
+    Ok(())
+}
+
+fn shift_lines(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    action: &ShiftCommand,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let rows = buffer_rows(vim, editor, action.range.as_ref(), cx)?;
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    editor.change_selections(None, cx, |s| {
+        s.select_ranges(rows.iter().map(|rows| {
+            Point::new(*rows.start(), 0)..Point::new(*rows.end(), snapshot.line_len(*rows.end()))
+        }))
+    });
+    for _ in 0..action.count {
+        if action.outdent {
+            editor.outdent(&Outdent, cx);
+        } else {
+            editor.indent(&Indent, cx);
+        }
+    }
+
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges(rows.iter().map(|rows| {
+            let point = first_non_blank(&snapshot, *rows.end());
+            point..point
+        }))
     });
+    Ok(())
+}
+
+fn sort_lines(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    action: &SortCommand,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let rows = match &action.range {
+        Some(range) => buffer_rows(vim, editor, Some(range), cx)?,
+        None => {
+            let max_row = editor.buffer().read(cx).snapshot(cx).max_buffer_row();
+            vec![0..=max_row]
+        }
+    };
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let key = |line: &String| {
+        if action.ignore_case {
+            line.to_lowercase()
+        } else {
+            line.clone()
+        }
+    };
+
+    let mut edits = Vec::new();
+    for rows in &rows {
+        let mut lines = rows
+            .clone()
+            .map(|row| line_text(&snapshot, row))
+            .collect::<Vec<_>>();
+        lines.sort_by_cached_key(key);
+        if action.unique {
+            lines.dedup_by(|a, b| key(&*a) == key(&*b));
+        }
+        if action.reverse {
+            lines.reverse();
+        }
+        let range =
+            Point::new(*rows.start(), 0)..Point::new(*rows.end(), snapshot.line_len(*rows.end()));
+        edits.push((range, lines.join("\n")));
+    }
+    editor.edit(edits, cx);
+
+    let start = Point::new(*rows[0].start(), 0);
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges([start..start])
+    });
+    Ok(())
+}
+
+fn retab(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    action: &RetabCommand,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let rows = match &action.range {
+        Some(range) => buffer_rows(vim, editor, Some(range), cx)?,
+        None => {
+            let max_row = editor.buffer().read(cx).snapshot(cx).max_buffer_row();
+            vec![0..=max_row]
+        }
+    };
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let mut edits = Vec::new();
+    for row in rows.into_iter().flatten() {
+        let settings = snapshot.settings_at(Point::new(row, 0), cx);
+        let tab_size = settings.tab_size.get();
+        let new_tab_size = action.tab_size.unwrap_or(tab_size);
+        let line = line_text(&snapshot, row);
+        for (range, text) in retab_line(
+            &line,
+            tab_size,
+            new_tab_size,
+            settings.hard_tabs,
+            action.all,
+        ) {
+            edits.push((
+                Point::new(row, range.start as u32)..Point::new(row, range.end as u32),
+                text,
+            ));
+        }
+    }
+    editor.edit(edits, cx);
+    Ok(())
+}
+
+/// Returns the edits that rewrite the runs of whitespace in `line` so that they
+/// span the same columns with tabs `new_tab_size` wide, as tabs and spaces or
+/// just spaces.
+fn retab_line(
+    line: &str,
+    tab_size: u32,
+    new_tab_size: u32,
+    hard_tabs: bool,
+    all: bool,
+) -> Vec<(Range<usize>, String)> {
+    let mut edits = Vec::new();
+    let mut column = 0;
+    let mut run_start = None;
+    let mut has_tab = false;
+    // the trailing newline ends a run at the end of the line.
+    for (ix, c) in line.char_indices().chain([(line.len(), '\n')]) {
+        if c == ' ' || c == '\t' {
+            if run_start.is_none() {
+                run_start = Some((ix, column));
+                has_tab = false;
+            }
+            if c == '\t' {
+                has_tab = true;
+                column += tab_size - column % tab_size;
+            } else {
+                column += 1;
+            }
+            continue;
+        }
+        if let Some((start, start_column)) = run_start.take() {
+            if has_tab || all {
+                let mut text = String::new();
+                let mut text_column = start_column;
+                while hard_tabs && text_column + new_tab_size - text_column % new_tab_size <= column
+                {
+                    text.push('\t');
+                    text_column += new_tab_size - text_column % new_tab_size;
+                }
+                text.extend(std::iter::repeat(' ').take((column - text_column) as usize));
+                if text != line[start..ix] {
+                    edits.push((start..ix, text));
+                }
+            }
+        }
+        column += 1;
+    }
+    edits
+}
+
+fn normal_command(
+    workspace: &mut Workspace,
+    action: &NormalCommand,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let mut lines = Vec::new();
+    let success = run_on_active_editor(workspace, cx, |vim, editor, cx| {
+        let rows = buffer_rows(vim, editor, action.range.as_ref(), cx)?;
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        lines = rows
+            .into_iter()
+            .flatten()
+            .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+            .collect();
+        Ok(())
+    });
+    if !success {
+        return;
+    }
+
+    let mut keystrokes = action
+        .keys
+        .chars()
+        .map(|c| match c {
+            ' ' => "space".to_string(),
+            c if c.is_ascii_uppercase() => {
+                format!("shift-{}->{}", c.to_ascii_lowercase(), c)
+            }
+            c => c.to_string(),
+        })
+        .flat_map(|keystroke| Keystroke::parse(&keystroke).log_err())
+        .collect::<Vec<_>>();
+    keystrokes.extend(Keystroke::parse("escape").log_err());
+    run_on_each_line(lines, LineCommand::Keystrokes(keystrokes), cx);
+}
+
+fn on_matching_lines(
+    workspace: &mut Workspace,
+    action: &OnMatchingLines,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let mut lines = Vec::new();
+    let found = run_on_active_editor(workspace, cx, |vim, editor, cx| {
+        let regex = pattern::translate(&action.search)?.build(true)?;
+        vim.workspace_state
+            .registers
+            .insert('/', Register::from(action.search.clone()));
+
+        let rows = match &action.range {
+            Some(range) => buffer_rows(vim, editor, Some(range), cx)?,
+            None => {
+                let max_row = editor.buffer().read(cx).snapshot(cx).max_buffer_row();
+                vec![0..=max_row]
+            }
+        };
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let matching_rows = rows
+            .into_iter()
+            .flatten()
+            .filter(|row| regex.is_match(&line_text(&snapshot, *row)) != action.invert)
+            .collect::<Vec<_>>();
+        if matching_rows.is_empty() {
+            bail!("E486: Pattern not found: {}", action.search);
+        }
+        lines = matching_rows
+            .into_iter()
+            .map(|row| snapshot.anchor_before(first_non_blank(&snapshot, row)))
+            .collect::<Vec<_>>();
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.select_anchor_ranges(lines.iter().map(|line| *line..*line))
+        });
+        Ok(())
+    });
+
+    if found && !action.command.is_empty() {
+        if let Some(result) = command_interceptor(&action.command, cx) {
+            run_on_each_line(lines, LineCommand::Action(result.action), cx);
+        }
+    }
+}
+
+/// What `:g` and `:norm` run on each of their lines.
+enum LineCommand {
+    Action(Box<dyn Action>),
+    Keystrokes(Vec<Keystroke>),
+}
+
+/// Runs `command` with a single cursor on each of `lines` in turn, as `:g` and
+/// `:norm` do in vim, so that each run sees the edits of the previous ones.
+fn run_on_each_line(lines: Vec<Anchor>, command: LineCommand, cx: &mut WindowContext) {
+    let Some(editor) = Vim::read(cx).active_editor.clone() else {
+        return;
+    };
+    cx.spawn(|mut cx| async move {
+        for line in lines {
+            editor.update(&mut cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges([line..line])
+                })
+            })?;
+            match &command {
+                LineCommand::Action(action) => {
+                    cx.update(|cx| cx.dispatch_action(action.boxed_clone()))?
+                }
+                LineCommand::Keystrokes(keystrokes) => {
+                    for keystroke in keystrokes {
+                        cx.update(|cx| {
+                            let focused = cx.focused();
+                            cx.dispatch_keystroke(keystroke.clone());
+                            // Like `SendKeystrokes`, let focus changes settle before
+                            // the next keystroke so that vim sees them.
+                            if cx.focused() != focused {
+                                cx.draw();
+                            }
+                        })?;
+                    }
+                }
+            }
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Opens the contents of all non-empty registers in a new buffer (`:registers`).
+fn show_registers(workspace: &mut Workspace, _: &ShowRegisters, cx: &mut ViewContext<Workspace>) {
+    let listing = Vim::update(cx, |vim, cx| {
+        let editor = vim
+            .active_editor
+            .as_ref()
+            .and_then(|editor| editor.upgrade());
+        let editor = editor.as_ref().map(|editor| editor.read(cx));
+
+        let mut listing = String::from("Type Name Content\n");
+        let names = ['"']
+            .into_iter()
+            .chain('0'..='9')
+            .chain('a'..='z')
+            .chain(['-', '.', ':', '%', '/', '+']);
+        for name in names {
+            let Some(register) = read_register(vim, Some(name), editor, cx) else {
+                continue;
+            };
+            if register.text.is_empty() {
+                continue;
+            }
+            let kind = if register.is_blockwise() {
+                'b'
+            } else if register.is_linewise() {
+                'l'
+            } else {
+                'c'
+            };
+            let content = register.text.replace('\n', "^J");
+            listing.push_str(&format!("  {}  \"{}   {}\n", kind, name, content));
+        }
+        listing
+    });
+
+    let project = workspace.project().clone();
+    let Some(buffer) = project
+        .update(cx, |project, cx| project.create_buffer(&listing, None, cx))
+        .log_err()
+    else {
+        return;
+    };
+    let buffer =
+        cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title("Registers".into()));
+    let editor = cx.new_view(|cx| Editor::for_multibuffer(buffer, Some(project), cx));
+    workspace.add_item_to_active_pane(Box::new(editor), cx);
 }
 
 pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
-    // Note: this is a very poor simulation of vim's command palette.
-    // In the future we should adjust it to handle parsing range syntax,
-    // and then calling the appropriate commands with/without ranges.
-    //
-    // We also need to support passing arguments to commands like :w
-    // (ideally with filename autocompletion).
-    //
-    // For now, you can only do a replace on the % range, and you can
-    // only use a specific line number range to "go to line"
+    // Note: this is still a partial simulation of vim's command line. Ranges
+    // are only understood by the commands that edit lines, and arguments to
+    // commands like :w are not supported yet (ideally with filename autocompletion).
     while query.starts_with(':') {
         query = &query[1..];
     }
 
+    if let Some(action) = search_or_ranged_command(query) {
+        let string = ":".to_owned() + query;
+        let positions = generate_positions(&string, query);
+        return Some(CommandInterceptResult {
+            action,
+            string,
+            positions,
+        });
+    }
+
     let (name, action) = match query {
         // save and quit
         "w" | "wr" | "wri" | "writ" | "write" => (
@@ -255,14 +1260,13 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             ("lNext", editor::actions::GoToPrevDiagnostic.boxed_clone())
         }
 
-        // modify the buffer (should accept [range])
-        "j" | "jo" | "joi" | "join" => ("join", JoinLines.boxed_clone()),
-        "d" | "de" | "del" | "dele" | "delet" | "delete" | "dl" | "dell" | "delel" | "deletl"
-        | "deletel" | "dp" | "dep" | "delp" | "delep" | "deletp" | "deletep" => {
-            ("delete", editor::actions::DeleteLine.boxed_clone())
+        // registers
+        "reg" | "regi" | "regis" | "regist" | "registe" | "register" | "registers" | "di"
+        | "dis" | "disp" | "displ" | "displa" | "display" => {
+            ("registers", ShowRegisters.boxed_clone())
         }
-        "sor" | "sor " | "sort" | "sort " => ("sort", SortLinesCaseSensitive.boxed_clone()),
-        "sor i" | "sort i" => ("sort i", SortLinesCaseInsensitive.boxed_clone()),
+        "marks" => ("marks", ShowMarks.boxed_clone()),
+        "as" | "asc" | "asci" | "ascii" => ("ascii", crate::normal::ShowCharacter.boxed_clone()),
 
         // Explore, etc.
         "E" | "Ex" | "Exp" | "Expl" | "Explo" | "Explor" | "Explore" => (
@@ -315,35 +1319,7 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             cx.build_action("assistant::ToggleFocus", None).unwrap(),
         ),
 
-        // goto (other ranges handled under _ => )
-        "$" => ("$", EndOfDocument.boxed_clone()),
-        "%" => ("%", EndOfDocument.boxed_clone()),
-        "0" => ("0", StartOfDocument.boxed_clone()),
-
-        _ => {
-            if query.starts_with('/') || query.starts_with('?') {
-                (
-                    query,
-                    FindCommand {
-                        query: query[1..].to_string(),
-                        backwards: query.starts_with('?'),
-                    }
-                    .boxed_clone(),
-                )
-            } else if query.starts_with('%') {
-                (
-                    query,
-                    ReplaceCommand {
-                        query: query.to_string(),
-                    }
-                    .boxed_clone(),
-                )
-            } else if let Ok(line) = query.parse::<u32>() {
-                (query, GoToLine { line }.boxed_clone())
-            } else {
-                return None;
-            }
-        }
+        _ => return None,
     };
 
     let string = ":".to_owned() + name;
@@ -356,6 +1332,168 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
     })
 }
 
+/// Parses searches like `:/pattern`, and commands that take a range like `:%s/a/b/g`
+/// or `:'<,'>d`. A range on its own moves the cursor to its last line.
+fn search_or_ranged_command(query: &str) -> Option<Box<dyn Action>> {
+    if let Some(delimiter) = query.chars().next().filter(|c| *c == '/' || *c == '?') {
+        if let (_, None) = split_pattern(&query[1..], delimiter) {
+            return Some(
+                FindCommand {
+                    query: query[1..].to_string(),
+                    backwards: delimiter == '?',
+                }
+                .boxed_clone(),
+            );
+        }
+    }
+
+    let (range, rest) = CommandRange::parse(query);
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    let (name, args) = rest.split_at(end);
+    // Matches `name` against a command that can be abbreviated down to `short`.
+    let is = |short: &str, full: &str| name.starts_with(short) && full.starts_with(name);
+
+    let delete = name.trim_end_matches(['l', 'p']);
+
+    let action = if name.is_empty() && args.starts_with(['>', '<']) {
+        let shift = args.chars().next()?;
+        let count = args.chars().take_while(|c| *c == shift).count();
+        if !args[count..].trim().is_empty() {
+            return None;
+        }
+        ShiftCommand {
+            range,
+            count,
+            outdent: args.starts_with('<'),
+        }
+        .boxed_clone()
+    } else if name.is_empty() {
+        if !args.is_empty() {
+            return None;
+        }
+        GoToLine { range: range? }.boxed_clone()
+    } else if is("se", "set") || is("setl", "setlocal") {
+        if range.is_some() || !(args.is_empty() || args.starts_with(' ')) {
+            return None;
+        }
+        SetCommand {
+            local: name.starts_with("setl"),
+            args: args.trim().to_string(),
+        }
+        .boxed_clone()
+    } else if is("s", "substitute") {
+        if !args.starts_with(|c: char| !c.is_alphanumeric() && !c.is_whitespace()) {
+            return None;
+        }
+        ReplaceCommand {
+            range,
+            query: args.to_string(),
+        }
+        .boxed_clone()
+    } else if is("g", "global") || is("v", "vglobal") {
+        let (invert, args) = match args.strip_prefix('!') {
+            Some(args) => (true, args),
+            None => (name.starts_with('v'), args),
+        };
+        let delimiter = args
+            .chars()
+            .next()
+            .filter(|c| !c.is_alphanumeric() && !c.is_whitespace())?;
+        let (search, command) = split_pattern(&args[delimiter.len_utf8()..], delimiter);
+        OnMatchingLines {
+            range,
+            search,
+            invert,
+            command: command.unwrap_or("").trim().to_string(),
+        }
+        .boxed_clone()
+    } else if is("norm", "normal") {
+        let keys = args.strip_prefix('!').unwrap_or(args);
+        NormalCommand {
+            range,
+            keys: keys.strip_prefix(' ')?.to_string(),
+        }
+        .boxed_clone()
+    } else if is("sor", "sort") {
+        let (reverse, flags) = match args.strip_prefix('!') {
+            Some(flags) => (true, flags),
+            None => (false, args),
+        };
+        let flags = flags.trim();
+        if flags.contains(|c: char| c != 'i' && c != 'u') {
+            return None;
+        }
+        SortCommand {
+            range,
+            reverse,
+            ignore_case: flags.contains('i'),
+            unique: flags.contains('u'),
+        }
+        .boxed_clone()
+    } else if is("ret", "retab") {
+        let (all, args) = match args.strip_prefix('!') {
+            Some(args) => (true, args),
+            None => (false, args),
+        };
+        let args = args.trim();
+        let tab_size = if args.is_empty() {
+            None
+        } else {
+            Some(args.parse::<u32>().ok().filter(|size| *size > 0)?)
+        };
+        RetabCommand {
+            range,
+            all,
+            tab_size,
+        }
+        .boxed_clone()
+    } else if !delete.is_empty() && "delete".starts_with(delete) {
+        DeleteCommand {
+            range,
+            register: register_argument(args)?,
+        }
+        .boxed_clone()
+    } else if is("y", "yank") {
+        YankCommand {
+            range,
+            register: register_argument(args)?,
+        }
+        .boxed_clone()
+    } else if is("m", "move") || name == "t" || is("co", "copy") {
+        let args = args.trim();
+        let (address, rest) = Position::parse(args)?;
+        if !rest.trim().is_empty() {
+            return None;
+        }
+        if name.starts_with('m') {
+            MoveCommand { range, address }.boxed_clone()
+        } else {
+            CopyCommand { range, address }.boxed_clone()
+        }
+    } else if is("j", "join") {
+        if !args.trim().is_empty() {
+            return None;
+        }
+        JoinCommand { range }.boxed_clone()
+    } else {
+        return None;
+    };
+    Some(action)
+}
+
+/// The optional register name given to `:d` and `:y`, e.g. `:d a`.
+fn register_argument(args: &str) -> Option<Option<char>> {
+    let args = args.trim();
+    let mut chars = args.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => Some(None),
+        (Some(register), None) if !register.is_ascii_digit() => Some(Some(register)),
+        _ => None,
+    }
+}
+
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
     let mut positions = Vec::new();
     let mut chars = query.chars();
@@ -382,9 +1520,31 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
 mod test {
     use std::path::Path;
 
-    use crate::test::{NeovimBackedTestContext, VimTestContext};
+    use super::{Address, CommandRange, GoToLine, Position};
+    use crate::{
+        normal::search::SUBSTITUTE_COUNT_TOAST_ID,
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
     use gpui::TestAppContext;
     use indoc::indoc;
+    use language::language_settings::AllLanguageSettings;
+    use settings::SettingsStore;
+
+    #[test]
+    fn test_go_to_line_with_line_number() {
+        let action: GoToLine = serde_json::from_value(serde_json::json!({ "line": 3 })).unwrap();
+        assert_eq!(
+            action.range,
+            CommandRange {
+                start: Position {
+                    address: Address::Line(3),
+                    offset: 0,
+                },
+                end: None,
+            }
+        );
+    }
 
     #[gpui::test]
     async fn test_command_basics(cx: &mut TestAppContext) {
@@ -519,4 +1679,153 @@ mod test {
         cx.simulate_keystrokes([":", "q", "a", "enter"]);
         cx.workspace(|workspace, cx| assert_eq!(workspace.items(cx).count(), 0));
     }
+
+    #[gpui::test]
+    async fn test_command_ranges(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc\nd\ne", Mode::Normal);
+        cx.simulate_keystrokes([":", "2", ",", "3", "d", "enter"]);
+        cx.assert_state("a\nˇd\ne", Mode::Normal);
+
+        cx.simulate_keystrokes([":", "$", "enter"]);
+        cx.assert_state("a\nd\nˇe", Mode::Normal);
+
+        cx.simulate_keystrokes([":", ".", "-", "1", "m", "0", "enter"]);
+        cx.assert_state("ˇd\na\ne", Mode::Normal);
+
+        cx.simulate_keystrokes([":", "t", ".", "enter"]);
+        cx.assert_state("d\nˇd\na\ne", Mode::Normal);
+
+        cx.simulate_keystrokes([":", "%", "y", " ", "a", "enter"]);
+        cx.simulate_keystrokes(["\"", "a", "p"]);
+        cx.assert_state("d\nd\nˇd\nd\na\ne\na\ne", Mode::Normal);
+
+        cx.simulate_keystrokes([":", "/", "a", "/", ",", "$", "d", "enter"]);
+        cx.assert_state("d\nd\nd\nˇd", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_marks_and_visual_range(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc\nd", Mode::Normal);
+        cx.simulate_keystrokes(["m", "x", "j", "j"]);
+        cx.simulate_keystrokes([":", "'", "x", ",", ".", "j", "enter"]);
+        cx.assert_state("a bˇ c\nd", Mode::Normal);
+
+        cx.set_state("a\nˇb\nc\nd", Mode::Normal);
+        cx.simulate_keystrokes(["shift-v", "j", ":", "d", "enter"]);
+        cx.assert_state("a\nˇd", Mode::Normal);
+
+        cx.set_state("ˇa\nb\nc\nd", Mode::Normal);
+        cx.simulate_keystrokes(["2", ":", "d", "enter"]);
+        cx.assert_state("ˇc\nd", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_shift_sort_and_normal(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb", Mode::Normal);
+        cx.simulate_keystrokes([":", "%", ">", ">", "enter"]);
+        cx.assert_state("        a\n        ˇb", Mode::Normal);
+        cx.simulate_keystrokes([":", "1", "<", "enter"]);
+        cx.assert_state("    ˇa\n        b", Mode::Normal);
+
+        cx.set_state("c\nˇB\na\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes([":", "s", "o", "r", "t", "enter"]);
+        cx.assert_state("ˇB\na\nb\nc\nc", Mode::Normal);
+        cx.simulate_keystrokes([":", "s", "o", "r", "t", "!", " ", "i", "u", "enter"]);
+        cx.assert_state("ˇc\nB\na", Mode::Normal);
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes([
+            ":", "1", ",", "2", "n", "o", "r", "m", " ", "A", ";", "enter",
+        ]);
+        cx.assert_state("a;\nbˇ;\nc", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_retab(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇ\tone\n  \t two\n  three", Mode::Normal);
+        cx.simulate_keystrokes([":", "r", "e", "t", "a", "b", "enter"]);
+        cx.assert_state("ˇ    one\n     two\n  three", Mode::Normal);
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.hard_tabs = Some(true);
+            });
+        });
+        cx.set_state("ˇ      one\n  two", Mode::Normal);
+        cx.simulate_keystrokes([":", "2", "r", "e", "t", "!", "enter"]);
+        cx.assert_state("ˇ      one\n  two", Mode::Normal);
+        cx.simulate_keystrokes([":", "r", "e", "t", "!", " ", "2", "enter"]);
+        cx.assert_state("ˇ\t\t\tone\n\ttwo", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_global(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇab\nb\nac\nd", Mode::Normal);
+        cx.simulate_keystrokes([":", "g", "/", "a", "/", "d", "enter"]);
+        cx.assert_state("b\nˇd", Mode::Normal);
+
+        cx.set_state("ˇab\nb\nac\nd", Mode::Normal);
+        cx.simulate_keystrokes([":", "v", "/", "a", "/", "d", "enter"]);
+        cx.assert_state("ab\nˇac", Mode::Normal);
+
+        cx.set_state("ˇab\nb\nac\nd", Mode::Normal);
+        cx.simulate_keystrokes([":", "g", "/", "a", "/", "s", "/", "/", "x", "/", "enter"]);
+        cx.assert_state("xb\nb\nˇxc\nd", Mode::Normal);
+
+        // the command runs on one line at a time, seeing the previous edits
+        cx.set_state("ˇa1\nb\na2", Mode::Normal);
+        cx.simulate_keystrokes([":", "g", "/", "a", "/", "m", "0", "enter"]);
+        cx.assert_state("ˇa2\na1\nb", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_substitute_flags(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("aˇa a\na a", Mode::Normal);
+        cx.simulate_keystrokes([":", "s", "/", "a", "/", "b", "enter"]);
+        cx.assert_state("ˇba a\na a", Mode::Normal);
+        cx.simulate_keystrokes([":", "s", "/", "a", "/", "&", "&", "/", "g", "enter"]);
+        cx.assert_state("ˇbaa aa\na a", Mode::Normal);
+        cx.simulate_keystrokes([":", "2", "s", "/", "A", "/", "c", "/", "i", "enter"]);
+        cx.assert_state("baa aa\nˇc a", Mode::Normal);
+
+        cx.set_state("ˇa\na\na\na", Mode::Normal);
+        cx.simulate_keystrokes([":", "%", "s", "/", "a", "/", "b", "/", "c", "enter"]);
+        cx.assert_state("ˇa\na\na\na", Mode::Normal);
+        cx.simulate_keystrokes(["y", "n"]);
+        cx.assert_state("b\na\nˇa\na", Mode::Normal);
+        cx.simulate_keystrokes(["l"]);
+        cx.assert_state("b\na\nˇb\na", Mode::Normal);
+        cx.simulate_keystrokes(["j"]);
+        cx.assert_state("b\na\nb\nˇa", Mode::Normal);
+
+        cx.set_state("ˇa a\nb\na", Mode::Normal);
+        cx.simulate_keystrokes([":", "%", "s", "/", "a", "/", "b", "/", "g", "n", "enter"]);
+        cx.assert_state("ˇa a\nb\na", Mode::Normal);
+        let message =
+            cx.workspace(|workspace, cx| workspace.toast_message(SUBSTITUTE_COUNT_TOAST_ID, cx));
+        assert_eq!(message.as_deref(), Some("3 matches on 2 lines"));
+    }
+
+    #[gpui::test]
+    async fn test_command_history(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes([":", "3", "enter"]);
+        cx.assert_state("a\nb\nˇc", Mode::Normal);
+        cx.simulate_keystrokes(["g", "g", ":", "up", "enter"]);
+        cx.assert_state("a\nb\nˇc", Mode::Normal);
+    }
 }
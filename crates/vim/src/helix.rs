@@ -0,0 +1,492 @@
+//! A selection-first modal mode in the style of Helix, enabled with the
+//! `helix_mode` setting.
+//!
+//! It shares its state and most of its key bindings with vim mode, but uses
+//! `Mode::HelixNormal` in place of `Mode::Normal`: motions select the text they
+//! move over (or move the cursor), and the commands that follow act on the
+//! selections. An empty selection stands for the character under the cursor.
+
+use std::sync::Arc;
+
+use editor::{display_map::DisplaySnapshot, movement, scroll::Autoscroll, DisplayPoint, Editor};
+use gpui::{actions, impl_actions, ViewContext, WindowContext};
+use language::{Point, Selection, SelectionGoal};
+use regex::Regex;
+use serde::Deserialize;
+use util::ResultExt;
+use workspace::Workspace;
+
+use crate::{
+    motion::Motion,
+    normal::paste::{paste, Paste},
+    object::Object,
+    state::{Mode, Operator},
+    utils::{copy_selections_content, yank_selections_content},
+    Vim,
+};
+
+/// Pastes after (or before) each selection, rather than replacing it.
+#[derive(Clone, Deserialize, PartialEq)]
+pub(crate) struct HelixPaste {
+    #[serde(default)]
+    before: bool,
+}
+
+actions!(
+    vim,
+    [
+        HelixInsert,
+        HelixAppend,
+        HelixDelete,
+        HelixChange,
+        HelixYank,
+        HelixSelectLine,
+        HelixCollapseSelection,
+        HelixKeepPrimarySelection,
+    ]
+);
+
+impl_actions!(vim, [HelixPaste]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_: &mut Workspace, _: &HelixInsert, cx| insert(false, cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixAppend, cx| insert(true, cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixDelete, cx| delete(false, cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixChange, cx| delete(true, cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixYank, cx| yank(cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixSelectLine, cx| select_line(cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixCollapseSelection, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                editor.change_selections(None, cx, |s| {
+                    s.move_with(|map, selection| {
+                        let cursor = cursor(map, selection);
+                        selection.collapse_to(cursor, SelectionGoal::None)
+                    })
+                })
+            });
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &HelixKeepPrimarySelection, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                editor.change_selections(None, cx, |s| {
+                    let newest = s.newest_anchor().clone();
+                    s.select_anchors(vec![newest])
+                })
+            });
+        })
+    });
+    workspace.register_action(|workspace: &mut Workspace, action: &HelixPaste, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                editor.change_selections(None, cx, |s| {
+                    s.move_with(|map, selection| {
+                        let point = if action.before {
+                            selection.start
+                        } else {
+                            cursor(map, selection)
+                        };
+                        selection.collapse_to(point, SelectionGoal::None)
+                    })
+                })
+            });
+        });
+        paste(
+            workspace,
+            &Paste {
+                before: action.before,
+                preserve_clipboard: false,
+            },
+            cx,
+        )
+    });
+}
+
+/// The position of the character the cursor is shown on, which for a forward
+/// selection is the last selected character.
+fn cursor(map: &DisplaySnapshot, selection: &Selection<DisplayPoint>) -> DisplayPoint {
+    if selection.reversed || selection.is_empty() {
+        selection.head()
+    } else {
+        movement::left(map, selection.head())
+    }
+}
+
+/// Motions that select the text they move over, rather than moving the cursor.
+fn selects_text(motion: &Motion) -> bool {
+    matches!(
+        motion,
+        Motion::NextWordStart { .. }
+            | Motion::NextWordEnd { .. }
+            | Motion::PreviousWordStart { .. }
+            | Motion::PreviousWordEnd { .. }
+            | Motion::NextSubwordStart { .. }
+            | Motion::NextSubwordEnd { .. }
+            | Motion::PreviousSubwordStart { .. }
+            | Motion::PreviousSubwordEnd { .. }
+            | Motion::FindForward { .. }
+            | Motion::FindBackward { .. }
+            | Motion::RepeatFind { .. }
+            | Motion::RepeatFindReversed { .. }
+    )
+}
+
+pub(crate) fn helix_motion(motion: Motion, times: Option<usize>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    if !selects_text(&motion) {
+                        let Some((point, goal)) = motion.move_point(
+                            map,
+                            cursor(map, selection),
+                            selection.goal,
+                            times,
+                            &text_layout_details,
+                        ) else {
+                            return;
+                        };
+                        selection.collapse_to(point, goal);
+                        return;
+                    }
+
+                    let head = selection.head();
+                    let Some((target, _)) = motion.move_point(
+                        map,
+                        head,
+                        SelectionGoal::None,
+                        times,
+                        &text_layout_details,
+                    ) else {
+                        return;
+                    };
+                    if target >= head {
+                        let end = if motion.inclusive() {
+                            movement::right(map, target)
+                        } else {
+                            target
+                        };
+                        selection.start = head;
+                        selection.end = end;
+                        selection.reversed = false;
+                    } else {
+                        // the character under a bare cursor is part of the selection.
+                        let anchor = if selection.is_empty() {
+                            movement::right(map, head)
+                        } else {
+                            head
+                        };
+                        selection.start = target;
+                        selection.end = anchor;
+                        selection.reversed = true;
+                    }
+                    selection.goal = SelectionGoal::None;
+                })
+            });
+        });
+    });
+}
+
+/// Selects the object around each cursor, for `mi` and `ma`.
+pub(crate) fn helix_object(object: Object, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let Some(Operator::Object { around }) = vim.active_operator() else {
+            return;
+        };
+        vim.pop_operator(cx);
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    if let Some(range) = object.range(map, cursor(map, selection), around) {
+                        if !range.is_empty() {
+                            selection.start = range.start;
+                            selection.end = range.end;
+                            selection.reversed = false;
+                            selection.goal = SelectionGoal::None;
+                        }
+                    }
+                })
+            });
+        });
+    });
+}
+
+/// `i` and `a`: insert before or after each selection.
+fn insert(after: bool, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.start_recording(cx);
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let point = if !after {
+                        selection.start
+                    } else if selection.is_empty() {
+                        movement::right(map, selection.end)
+                    } else {
+                        selection.end
+                    };
+                    selection.collapse_to(point, SelectionGoal::None)
+                })
+            });
+        });
+        vim.switch_mode(Mode::Insert, false, cx);
+    });
+}
+
+/// Extends bare cursors to the character under them, and returns whether all the
+/// selections cover whole lines, as `x` selects them.
+fn select_cursor_characters(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
+    editor.change_selections(None, cx, |s| {
+        s.move_with(|map, selection| {
+            if selection.is_empty() {
+                selection.end = movement::right(map, selection.end);
+                selection.reversed = false;
+            }
+        })
+    });
+    editor.selections.all::<Point>(cx).iter().all(|selection| {
+        selection.start.column == 0 && selection.end.column == 0 && !selection.is_empty()
+    })
+}
+
+/// `d` and `c`: delete the selections, and start inserting in their place for `c`.
+fn delete(change: bool, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.record_current_action(cx);
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                let linewise = select_cursor_characters(editor, cx);
+                copy_selections_content(vim, editor, linewise, cx);
+                editor.insert("", cx);
+            });
+        });
+        if change {
+            vim.switch_mode(Mode::Insert, true, cx);
+        }
+    });
+}
+
+/// `y`: copy the selections, leaving them selected.
+fn yank(cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let selections = editor.selections.disjoint_anchors();
+            let linewise = select_cursor_characters(editor, cx);
+            yank_selections_content(vim, editor, linewise, cx);
+            editor.change_selections(None, cx, |s| s.select_anchors(selections.to_vec()));
+        });
+    });
+}
+
+/// `x`: select the lines of each selection, or the next line if they are already
+/// selected.
+fn select_line(cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1) as u32;
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let max_point = editor.buffer().read(cx).snapshot(cx).max_point();
+            let line_end = |row: u32| {
+                if row < max_point.row {
+                    Point::new(row + 1, 0)
+                } else {
+                    max_point
+                }
+            };
+            let selections = editor
+                .selections
+                .all::<Point>(cx)
+                .into_iter()
+                .map(|selection| {
+                    let whole_lines = selection.start.column == 0
+                        && selection.end.column == 0
+                        && !selection.is_empty();
+                    let (start, last_row) = if whole_lines {
+                        (selection.start, selection.end.row - 1 + count)
+                    } else {
+                        (
+                            Point::new(selection.start.row, 0),
+                            selection.end.row + count - 1,
+                        )
+                    };
+                    start..line_end(last_row)
+                })
+                .collect::<Vec<_>>();
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(selections));
+        });
+    });
+}
+
+/// Handles the regex typed after `s`, splitting each selection into the matches
+/// of the regex within it once enter is pressed.
+pub(crate) fn split_selection(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        if text.as_ref() != "\n" {
+            vim.workspace_state.split_input.push_str(&text);
+            return;
+        }
+        let input = std::mem::take(&mut vim.workspace_state.split_input);
+        vim.pop_operator(cx);
+        let Some(regex) = Regex::new(&input).log_err() else {
+            return;
+        };
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut ranges = Vec::new();
+            for selection in editor.selections.all::<usize>(cx) {
+                let text = snapshot
+                    .text_for_range(selection.start..selection.end)
+                    .collect::<String>();
+                let matches = regex
+                    .find_iter(&text)
+                    .filter(|found| !found.is_empty())
+                    .map(|found| selection.start + found.start()..selection.start + found.end())
+                    .collect::<Vec<_>>();
+                if matches.is_empty() {
+                    ranges.push(selection.start..selection.end);
+                } else {
+                    ranges.extend(matches);
+                }
+            }
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(ranges));
+        });
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use editor::Editor;
+    use gpui::{Context, Entity, TestAppContext};
+    use language::{Buffer, BufferId};
+    use settings::SettingsStore;
+
+    use crate::{state::Mode, test::VimTestContext, HelixModeSetting, Vim};
+
+    async fn helix_context(cx: &mut TestAppContext) -> VimTestContext {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<HelixModeSetting>(cx, |s| *s = Some(true));
+        });
+        cx
+    }
+
+    #[gpui::test]
+    async fn test_word_motions(cx: &mut TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("ˇone two three", Mode::HelixNormal);
+        cx.simulate_keystrokes(["w"]);
+        cx.assert_state("«one ˇ»two three", Mode::HelixNormal);
+        cx.simulate_keystrokes(["w"]);
+        cx.assert_state("one «two ˇ»three", Mode::HelixNormal);
+        cx.simulate_keystrokes(["e"]);
+        cx.assert_state("one two «threeˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes(["b"]);
+        cx.assert_state("one two «ˇthree»", Mode::HelixNormal);
+        cx.simulate_keystrokes(["b"]);
+        cx.assert_state("one «ˇtwo »three", Mode::HelixNormal);
+
+        // other motions move the cursor
+        cx.simulate_keystrokes(["l"]);
+        cx.assert_state("one tˇwo three", Mode::HelixNormal);
+
+        // ; collapses the selection to the cursor
+        cx.simulate_keystrokes(["e", ";"]);
+        cx.assert_state("one twˇo three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_line(cx: &mut TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("one\ntˇwo\nthree\n", Mode::HelixNormal);
+        cx.simulate_keystrokes(["x"]);
+        cx.assert_state("one\n«two\nˇ»three\n", Mode::HelixNormal);
+        cx.simulate_keystrokes(["x"]);
+        cx.assert_state("one\n«two\nthree\nˇ»", Mode::HelixNormal);
+
+        cx.simulate_keystrokes(["d"]);
+        cx.assert_state("one\nˇ", Mode::HelixNormal);
+        cx.simulate_keystrokes(["shift-p"]);
+        cx.assert_state("one\nˇtwo\nthree\n", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_split_selection(cx: &mut TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("«a, b, cˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes(["s", "\\", "w", "enter"]);
+        cx.assert_state("«aˇ», «bˇ», «cˇ»", Mode::HelixNormal);
+
+        // , keeps only the newest selection
+        cx.simulate_keystrokes([","]);
+        cx.assert_state("a, b, «cˇ»", Mode::HelixNormal);
+
+        cx.simulate_keystrokes(["c", "d"]);
+        cx.assert_state("a, b, dˇ", Mode::Insert);
+    }
+
+    #[gpui::test]
+    async fn test_match_objects(cx: &mut TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("f(a, bˇc)", Mode::HelixNormal);
+        cx.simulate_keystrokes(["m", "i", "("]);
+        cx.assert_state("f(«a, bcˇ»)", Mode::HelixNormal);
+        cx.simulate_keystrokes(["m", "a", "("]);
+        cx.assert_state("f«(a, bc)ˇ»", Mode::HelixNormal);
+
+        cx.simulate_keystrokes(["y", "g", "l"]);
+        cx.assert_state("f(a, bc)ˇ", Mode::HelixNormal);
+        cx.simulate_keystrokes(["g", "h", "shift-p"]);
+        cx.assert_state("(a, bcˇ)f(a, bc)", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_insert_modes(cx: &mut TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes(["i", "x", "escape"]);
+        cx.assert_state("one ˇxtwo three", Mode::HelixNormal);
+
+        cx.set_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes(["a", "x", "escape"]);
+        cx.assert_state("one twoˇx three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_toggle_helix_mode_in_every_editor(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let editor1 = cx.editor.clone();
+        let buffer = cx.new_model(|_| Buffer::new(0, BufferId::new(1).unwrap(), "two"));
+        let editor2 = cx.workspace(|workspace, cx| {
+            let editor = cx.new_view(|cx| Editor::for_buffer(buffer, None, cx));
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), cx);
+            editor
+        });
+        cx.run_until_parked();
+
+        let assert_modes = |cx: &mut VimTestContext, mode: Mode| {
+            cx.read(|cx| {
+                let vim = cx.global::<Vim>();
+                for editor in [&editor1, &editor2] {
+                    assert_eq!(vim.editor_states[&editor.entity_id()].mode, mode);
+                }
+            })
+        };
+        assert_modes(&mut cx, Mode::Normal);
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<HelixModeSetting>(cx, |s| *s = Some(true));
+        });
+        assert_modes(&mut cx, Mode::HelixNormal);
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<HelixModeSetting>(cx, |s| *s = Some(false));
+        });
+        assert_modes(&mut cx, Mode::Normal);
+    }
+}
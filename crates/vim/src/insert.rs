@@ -1,6 +1,12 @@
-use crate::{normal::repeat, state::Mode, Vim};
+use crate::{
+    normal::{mark, repeat},
+    state::{Mode, Register, ReplayableAction},
+    Vim,
+};
+use std::sync::Arc;
+
 use editor::{scroll::Autoscroll, Bias};
-use gpui::{actions, Action, ViewContext};
+use gpui::{actions, Action, ViewContext, WindowContext};
 use language::SelectionGoal;
 use workspace::Workspace;
 
@@ -13,7 +19,26 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
 fn normal_before(_: &mut Workspace, action: &NormalBefore, cx: &mut ViewContext<Workspace>) {
     let should_repeat = Vim::update(cx, |vim, cx| {
         let count = vim.take_count(cx).unwrap_or(1);
+        if vim.workspace_state.recording && !vim.workspace_state.replaying {
+            // the "." register holds the text inserted most recently.
+            let inserted_text = vim
+                .workspace_state
+                .recorded_actions
+                .iter()
+                .filter_map(|action| match action {
+                    ReplayableAction::Insertion { text, .. } => Some(text.as_ref()),
+                    ReplayableAction::Action(_) => None,
+                })
+                .collect::<String>();
+            vim.workspace_state
+                .registers
+                .insert('.', Register::from(inserted_text));
+        }
         vim.stop_recording_immediately(action.boxed_clone());
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let anchor = editor.selections.newest_anchor().head();
+            mark::set_mark(vim, '^', anchor, editor, cx);
+        });
         if count <= 1 || vim.workspace_state.replaying {
             vim.update_active_editor(cx, |_, editor, cx| {
                 editor.dismiss_menus_and_popups(cx);
@@ -36,6 +61,54 @@ fn normal_before(_: &mut Workspace, action: &NormalBefore, cx: &mut ViewContext<
     }
 }
 
+/// Handles the characters typed after `ctrl-v` in insert mode.
+pub(crate) fn insert_literal(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.workspace_state.literal_input.push_str(&text);
+        let Some(literal) = literal_text(&vim.workspace_state.literal_input) else {
+            return;
+        };
+        vim.workspace_state.literal_input.clear();
+        vim.pop_operator(cx);
+        vim.update_active_editor(cx, |_, editor, cx| editor.insert(&literal, cx));
+    })
+}
+
+/// Returns the text to insert for the input typed after `ctrl-v`, or None if
+/// more is needed. `u1234`, `U0001f600`, `x41`, `o101` and `65` are the character
+/// with that code; they end early at the first character that isn't a digit,
+/// which is then inserted as well. Anything else is inserted as typed.
+fn literal_text(input: &str) -> Option<String> {
+    let mut chars = input.chars();
+    let first = chars.next()?;
+    let (radix, max_len, digits) = match first {
+        'u' => (16, 4, chars.as_str()),
+        'U' => (16, 8, chars.as_str()),
+        'x' | 'X' => (16, 2, chars.as_str()),
+        'o' | 'O' => (8, 3, chars.as_str()),
+        c if c.is_ascii_digit() => (10, 3, input),
+        _ => return Some(input.to_string()),
+    };
+    let len = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len())
+        .min(max_len);
+    if len == digits.len() && len < max_len {
+        return None;
+    }
+    let (code, rest) = digits.split_at(len);
+    let literal = u32::from_str_radix(code, radix)
+        .ok()
+        .and_then(char::from_u32);
+    let mut text = match literal {
+        Some(c) => c.to_string(),
+        None if code.is_empty() => first.to_string(),
+        None => String::new(),
+    };
+    text.push_str(rest);
+    Some(text)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
@@ -55,6 +128,19 @@ mod test {
         cx.assert_editor_state("Tesˇt");
     }
 
+    #[gpui::test]
+    async fn test_insert_literal(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.simulate_keystrokes(["i", "ctrl-v", "u", "0", "0", "e", "9"]);
+        cx.assert_state("éˇ", Mode::Insert);
+        cx.simulate_keystrokes(["ctrl-v", "x", "4", "1", "ctrl-v", "6", "6", "x"]);
+        cx.assert_state("éABxˇ", Mode::Insert);
+        cx.simulate_keystrokes(["ctrl-v", "u", "z"]);
+        cx.assert_state("éABxuzˇ", Mode::Insert);
+        cx.simulate_keystrokes(["ctrl-v", "shift-u", "0", "0", "0", "1", "f", "6", "0", "0"]);
+        cx.assert_state("éABxuz😀ˇ", Mode::Insert);
+    }
+
     #[gpui::test]
     async fn test_insert_with_counts(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -0,0 +1,317 @@
+//! Vim style key mappings configured in the `vim` settings.
+//!
+//! Each mapping becomes a key binding in the context of its vim mode. Mappings
+//! to other keys are replayed with `workspace::SendKeystrokes`; unless they are
+//! marked as recursive the replayed keys are sent with the `VimNoremap` context
+//! set, so that they do not trigger any other mapping (like vim's `noremap`).
+
+use anyhow::{anyhow, Context, Result};
+use collections::BTreeMap;
+use gpui::{impl_actions, Action, AppContext, KeyBinding, Keystroke, ViewContext, WindowContext};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use settings::{KeymapAction, Settings};
+use util::ResultExt;
+use workspace::{SendKeystrokes, Workspace};
+
+use crate::{Vim, VimSettings};
+
+/// Sends keystrokes without applying the user's vim mappings to them.
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct SendKeystrokesNoremap(pub String);
+
+impl_actions!(vim, [SendKeystrokesNoremap]);
+
+const LEADER: &str = "<leader>";
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_: &mut Workspace, action: &SendKeystrokesNoremap, cx| {
+        send_keystrokes_noremap(&action.0, cx)
+    });
+}
+
+/// The right hand side of a mapping.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum VimMapping {
+    /// Keys to send in place of the mapped keys. Other mappings are not applied
+    /// to them, as with `noremap`.
+    Keys(String),
+    /// Keys to send in place of the mapped keys. If `recursive` is set other
+    /// mappings are applied to them, as with `map`.
+    Remap {
+        keys: String,
+        #[serde(default)]
+        recursive: bool,
+    },
+    /// An action to dispatch, written as in the keymap file.
+    Action { action: KeymapAction },
+}
+
+/// The mappings for each vim mode, from key sequences (such as `"<leader> f f"`)
+/// to the keys or action they are replaced with.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct VimMappings {
+    #[serde(default)]
+    pub normal: BTreeMap<String, VimMapping>,
+    #[serde(default)]
+    pub visual: BTreeMap<String, VimMapping>,
+    #[serde(default)]
+    pub insert: BTreeMap<String, VimMapping>,
+    #[serde(default)]
+    pub operator_pending: BTreeMap<String, VimMapping>,
+}
+
+/// The user's vim mappings together with the leader they are expanded with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyMappings {
+    leader: String,
+    mappings: VimMappings,
+}
+
+impl KeyMappings {
+    pub fn from_settings(cx: &AppContext) -> Self {
+        let settings = VimSettings::get_global(cx);
+        Self {
+            leader: settings.leader.clone(),
+            mappings: settings.mappings.clone(),
+        }
+    }
+
+    /// Builds a key binding for each mapping. Mappings that can't be parsed are
+    /// logged and skipped.
+    pub fn key_bindings(&self, cx: &AppContext) -> Vec<KeyBinding> {
+        let modes = [
+            (
+                &self.mappings.normal,
+                "vim_mode == normal && vim_operator == none",
+            ),
+            (
+                &self.mappings.visual,
+                "vim_mode == visual && vim_operator == none",
+            ),
+            (&self.mappings.insert, "vim_mode == insert && !VimWaiting"),
+            (
+                &self.mappings.operator_pending,
+                "vim_mode == normal && vim_operator != none && !VimWaiting",
+            ),
+        ];
+
+        let mut bindings = Vec::new();
+        for (mappings, mode_context) in modes {
+            let context = format!("Editor && {mode_context} && !VimNoremap");
+            for (keys, mapping) in mappings {
+                if let Some(binding) = self
+                    .key_binding(keys, mapping, &context, cx)
+                    .with_context(|| format!("invalid vim mapping for {keys:?}"))
+                    .log_err()
+                {
+                    bindings.push(binding);
+                }
+            }
+        }
+        bindings
+    }
+
+    fn key_binding(
+        &self,
+        keys: &str,
+        mapping: &VimMapping,
+        context: &str,
+        cx: &AppContext,
+    ) -> Result<KeyBinding> {
+        let action = match mapping {
+            VimMapping::Keys(keys)
+            | VimMapping::Remap {
+                keys,
+                recursive: false,
+            } => SendKeystrokesNoremap(self.expand_leader(keys)).boxed_clone(),
+            VimMapping::Remap {
+                keys,
+                recursive: true,
+            } => SendKeystrokes(self.expand_leader(keys)).boxed_clone(),
+            VimMapping::Action { action } => action.build(cx)?,
+        };
+        let keys = self.expand_leader(keys);
+        if keys.is_empty() {
+            return Err(anyhow!("no keys to map"));
+        }
+        KeyBinding::load(&keys, action, Some(context))
+    }
+
+    /// Replaces each `<leader>` in a space separated sequence of keystrokes with
+    /// the configured leader.
+    fn expand_leader(&self, keys: &str) -> String {
+        keys.split_whitespace()
+            .map(|key| {
+                if key.eq_ignore_ascii_case(LEADER) {
+                    self.leader.as_str()
+                } else {
+                    key
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Dispatches each keystroke with `VimNoremap` set in the keymap context, so that
+/// only Zed's own vim bindings apply to them.
+fn send_keystrokes_noremap(keys: &str, cx: &mut WindowContext) {
+    let keystrokes = keys
+        .split_whitespace()
+        .flat_map(|key| Keystroke::parse(key).log_err())
+        .collect::<Vec<_>>();
+
+    cx.spawn(|mut cx| async move {
+        cx.update(|cx| {
+            Vim::update(cx, |vim, cx| {
+                vim.workspace_state.noremap = true;
+                vim.sync_vim_settings(cx);
+            });
+            for keystroke in keystrokes {
+                // the keymap context is read from the last frame, so it has to be
+                // redrawn for the flag (and any change of mode) to apply.
+                cx.draw();
+                cx.dispatch_keystroke(keystroke);
+            }
+            Vim::update(cx, |vim, cx| {
+                vim.workspace_state.noremap = false;
+                vim.sync_vim_settings(cx);
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod test {
+    use collections::BTreeMap;
+    use settings::SettingsStore;
+
+    use crate::{
+        mappings::{KeyMappings, VimMapping, VimMappings},
+        state::Mode,
+        test::VimTestContext,
+        VimSettings,
+    };
+
+    fn set_mappings(cx: &mut VimTestContext, leader: &str, mappings: VimMappings) {
+        let leader = leader.to_string();
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| {
+                s.leader = Some(leader);
+                s.mappings = Some(mappings);
+            });
+        });
+        cx.update(|cx| {
+            let bindings = KeyMappings::from_settings(cx).key_bindings(cx);
+            cx.bind_keys(bindings);
+        });
+    }
+
+    fn keys(keys: &str) -> VimMapping {
+        VimMapping::Keys(keys.to_string())
+    }
+
+    #[gpui::test]
+    async fn test_normal_mappings(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        set_mappings(
+            &mut cx,
+            "space",
+            VimMappings {
+                normal: BTreeMap::from_iter([
+                    ("<leader> l".to_string(), keys("l l")),
+                    ("shift-y".to_string(), keys("y $")),
+                ]),
+                ..Default::default()
+            },
+        );
+
+        cx.set_state("ˇ123456789", Mode::Normal);
+        cx.simulate_keystrokes(["space", "l"]);
+        cx.assert_state("12ˇ3456789", Mode::Normal);
+
+        cx.simulate_keystrokes(["shift-y", "$", "p"]);
+        cx.assert_state("123456789345678ˇ9", Mode::Normal);
+
+        // mappings are only used in their own mode
+        cx.set_state("ˇ12", Mode::Normal);
+        cx.simulate_keystrokes(["i", "space", "l"]);
+        cx.assert_state(" lˇ12", Mode::Insert);
+    }
+
+    #[gpui::test]
+    async fn test_recursive_mappings(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        set_mappings(
+            &mut cx,
+            "\\",
+            VimMappings {
+                normal: BTreeMap::from_iter([
+                    ("l".to_string(), keys("l l")),
+                    ("g l".to_string(), keys("l")),
+                    (
+                        "g r".to_string(),
+                        VimMapping::Remap {
+                            keys: "l".to_string(),
+                            recursive: true,
+                        },
+                    ),
+                ]),
+                ..Default::default()
+            },
+        );
+
+        cx.set_state("ˇ123456789", Mode::Normal);
+        cx.simulate_keystrokes(["l"]);
+        cx.assert_state("12ˇ3456789", Mode::Normal);
+
+        // noremap: the `l` sent is not mapped again
+        cx.simulate_keystrokes(["g", "l"]);
+        cx.assert_state("123ˇ456789", Mode::Normal);
+
+        // map: the `l` sent is mapped to `l l`
+        cx.simulate_keystrokes(["g", "r"]);
+        cx.assert_state("12345ˇ6789", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_mode_mappings(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        set_mappings(
+            &mut cx,
+            "\\",
+            VimMappings {
+                insert: BTreeMap::from_iter([("j k".to_string(), keys("escape"))]),
+                visual: BTreeMap::from_iter([("<leader> u".to_string(), keys("shift-u"))]),
+                operator_pending: BTreeMap::from_iter([("p".to_string(), keys("i shift-9"))]),
+                normal: BTreeMap::from_iter([(
+                    "<leader> w".to_string(),
+                    VimMapping::Action {
+                        action: serde_json::from_str("\"vim::NextWordStart\"").unwrap(),
+                    },
+                )]),
+            },
+        );
+
+        cx.set_state("ˇone two", Mode::Normal);
+        cx.simulate_keystrokes(["i", "a", "j", "k"]);
+        cx.assert_state("ˇaone two", Mode::Normal);
+
+        cx.simulate_keystrokes(["v", "l", "\\", "u"]);
+        cx.assert_state("ˇAOne two", Mode::Normal);
+
+        cx.simulate_keystrokes(["\\", "w"]);
+        cx.assert_state("AOne ˇtwo", Mode::Normal);
+
+        cx.set_state("f(ˇa, b)", Mode::Normal);
+        cx.simulate_keystrokes(["d", "p"]);
+        cx.assert_state("f(ˇ)", Mode::Normal);
+    }
+}
@@ -3,7 +3,7 @@ use editor::{
     movement::{
         self, find_boundary, find_preceding_boundary_display_point, FindRange, TextLayoutDetails,
     },
-    Bias, DisplayPoint, ToOffset,
+    Anchor, Bias, DisplayPoint, ToOffset,
 };
 use gpui::{actions, impl_actions, px, ViewContext, WindowContext};
 use language::{char_kind, CharKind, Point, Selection, SelectionGoal};
@@ -11,8 +11,14 @@ use serde::Deserialize;
 use workspace::Workspace;
 
 use crate::{
-    normal::normal_motion,
+    helix::helix_motion,
+    normal::{
+        mark::set_previous_context_mark,
+        normal_motion,
+        search::{search_match, SearchOffset, VimSearch},
+    },
     state::{Mode, Operator},
+    surrounds::add_surrounds_motion,
     utils::coerce_punctuation,
     visual::visual_motion,
     Vim,
@@ -94,6 +100,13 @@ pub enum Motion {
     WindowTop,
     WindowMiddle,
     WindowBottom,
+    Jump {
+        anchor: Anchor,
+        line: bool,
+    },
+    SearchMatch {
+        search: VimSearch,
+    },
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -386,10 +399,18 @@ pub(crate) fn motion(motion: Motion, cx: &mut WindowContext) {
 
     let count = Vim::update(cx, |vim, cx| vim.take_count(cx));
     let operator = Vim::read(cx).active_operator();
+    if motion.is_jump() && operator.is_none() {
+        Vim::update(cx, |vim, cx| set_previous_context_mark(vim, cx));
+    }
+    if operator == Some(Operator::AddSurrounds) {
+        Vim::update(cx, |vim, cx| add_surrounds_motion(vim, motion, count, cx));
+        return;
+    }
     match Vim::read(cx).state().mode {
         Mode::Normal => normal_motion(motion, operator, count, cx),
         Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_motion(motion, count, cx),
-        Mode::Insert => {
+        Mode::HelixNormal => helix_motion(motion, count, cx),
+        Mode::Insert | Mode::Replace => {
             // Shouldn't execute a motion in insert mode. Ignoring
         }
     }
@@ -414,6 +435,8 @@ impl Motion {
             | WindowMiddle
             | WindowBottom
             | EndOfParagraph => true,
+            Jump { line, .. } => *line,
+            SearchMatch { search } => matches!(search.offset, SearchOffset::Lines(_)),
             EndOfLine { .. }
             | Matching
             | FindForward { .. }
@@ -439,10 +462,29 @@ impl Motion {
         }
     }
 
+    /// Jumps record the cursor position in the `'` mark before moving, so that
+    /// `''` can return to it.
+    pub fn is_jump(&self) -> bool {
+        use Motion::*;
+        matches!(
+            self,
+            StartOfDocument
+                | EndOfDocument
+                | StartOfParagraph
+                | EndOfParagraph
+                | Matching
+                | WindowTop
+                | WindowMiddle
+                | WindowBottom
+                | Jump { .. }
+                | SearchMatch { .. }
+        )
+    }
+
     pub fn infallible(&self) -> bool {
         use Motion::*;
         match self {
-            StartOfDocument | EndOfDocument | CurrentLine => true,
+            StartOfDocument | EndOfDocument | CurrentLine | Jump { .. } => true,
             Down { .. }
             | Up { .. }
             | EndOfLine { .. }
@@ -470,6 +512,7 @@ impl Motion {
             | FirstNonWhitespace { .. }
             | FindBackward { .. }
             | RepeatFindReversed { .. }
+            | SearchMatch { .. }
             | WindowTop
             | WindowMiddle
             | WindowBottom
@@ -511,10 +554,12 @@ impl Motion {
             | NextSubwordStart { .. }
             | PreviousSubwordStart { .. }
             | FirstNonWhitespace { .. }
-            | FindBackward { .. } => false,
+            | FindBackward { .. }
+            | Jump { .. } => false,
             RepeatFind { last_find: motion } | RepeatFindReversed { last_find: motion } => {
                 motion.inclusive()
             }
+            SearchMatch { search } => matches!(search.offset, SearchOffset::End(_)),
         }
     }
 
@@ -702,6 +747,10 @@ impl Motion {
             WindowTop => window_top(map, point, &text_layout_details, times - 1),
             WindowMiddle => window_middle(map, point, &text_layout_details),
             WindowBottom => window_bottom(map, point, &text_layout_details, times - 1),
+            Jump { anchor, line } => (jump(map, *anchor, *line), SelectionGoal::None),
+            SearchMatch { search } => {
+                (search_match(map, point, times, search), SelectionGoal::None)
+            }
         };
 
         (new_point != point || infallible).then_some((new_point, goal))
@@ -1594,6 +1643,15 @@ fn window_bottom(
     }
 }
 
+fn jump(map: &DisplaySnapshot, anchor: Anchor, line: bool) -> DisplayPoint {
+    let point = map.clip_point(anchor.to_display_point(map), Bias::Left);
+    if line {
+        first_non_whitespace(map, false, point)
+    } else {
+        point
+    }
+}
+
 #[cfg(test)]
 mod test {
 
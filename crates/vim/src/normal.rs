@@ -2,7 +2,8 @@ mod case;
 mod change;
 mod delete;
 mod increment;
-mod paste;
+pub(crate) mod mark;
+pub(crate) mod paste;
 pub(crate) mod repeat;
 mod scroll;
 pub(crate) mod search;
@@ -15,23 +16,29 @@ use crate::{
     motion::{self, first_non_whitespace, next_line_end, right, Motion},
     object::Object,
     state::{Mode, Operator},
+    surrounds::add_surrounds_object,
     Vim,
 };
 use collections::HashSet;
 use editor::scroll::Autoscroll;
-use editor::{Bias, DisplayPoint};
+use editor::{Bias, DisplayPoint, Editor};
 use gpui::{actions, ViewContext, WindowContext};
-use language::SelectionGoal;
+use language::{Point, SelectionGoal};
 use log::error;
-use workspace::Workspace;
+use workspace::{Toast, Workspace};
 
 use self::{
-    case::{change_case, convert_to_lower_case, convert_to_upper_case},
+    case::{
+        change_case, change_case_motion, change_case_object, convert_to_lower_case,
+        convert_to_rot13, convert_to_upper_case, CaseTarget,
+    },
     change::{change_motion, change_object},
     delete::{delete_motion, delete_object},
     yank::{yank_motion, yank_object},
 };
 
+pub(crate) const SHOW_CHARACTER_TOAST_ID: usize = 0x6761;
+
 actions!(
     vim,
     [
@@ -50,7 +57,11 @@ actions!(
         ChangeCase,
         ConvertToUpperCase,
         ConvertToLowerCase,
+        ConvertToRot13,
         JoinLines,
+        JoinLinesNoWhitespace,
+        InsertAtPrevious,
+        ShowCharacter,
         Indent,
         Outdent,
     ]
@@ -66,6 +77,9 @@ pub(crate) fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace
     workspace.register_action(change_case);
     workspace.register_action(convert_to_upper_case);
     workspace.register_action(convert_to_lower_case);
+    workspace.register_action(convert_to_rot13);
+    workspace.register_action(insert_at_previous);
+    workspace.register_action(show_character);
     workspace.register_action(yank_line);
 
     workspace.register_action(|_: &mut Workspace, _: &DeleteLeft, cx| {
@@ -134,6 +148,20 @@ pub(crate) fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace
         });
     });
 
+    workspace.register_action(|_: &mut Workspace, _: &JoinLinesNoWhitespace, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.record_current_action(cx);
+            let times = vim.take_count(cx).unwrap_or(1);
+            let visual = vim.state().mode.is_visual();
+            vim.update_active_editor(cx, |_, editor, cx| {
+                join_lines_without_whitespace(editor, times, visual, cx)
+            });
+            if visual {
+                vim.switch_mode(Mode::Normal, false, cx)
+            }
+        });
+    });
+
     workspace.register_action(|_: &mut Workspace, _: &Indent, cx| {
         Vim::update(cx, |vim, cx| {
             vim.record_current_action(cx);
@@ -164,6 +192,7 @@ pub(crate) fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace
     search::register(workspace, cx);
     substitute::register(workspace, cx);
     increment::register(workspace, cx);
+    mark::register(workspace, cx);
 }
 
 pub fn normal_motion(
@@ -178,6 +207,16 @@ pub fn normal_motion(
             Some(Operator::Change) => change_motion(vim, motion, times, cx),
             Some(Operator::Delete) => delete_motion(vim, motion, times, cx),
             Some(Operator::Yank) => yank_motion(vim, motion, times, cx),
+            Some(Operator::Lowercase) => {
+                change_case_motion(vim, motion, times, CaseTarget::Lowercase, cx)
+            }
+            Some(Operator::Uppercase) => {
+                change_case_motion(vim, motion, times, CaseTarget::Uppercase, cx)
+            }
+            Some(Operator::OppositeCase) => {
+                change_case_motion(vim, motion, times, CaseTarget::OppositeCase, cx)
+            }
+            Some(Operator::Rot13) => change_case_motion(vim, motion, times, CaseTarget::Rot13, cx),
             Some(operator) => {
                 // Can't do anything for text objects, Ignoring
                 error!("Unexpected normal mode motion operator: {:?}", operator)
@@ -193,6 +232,22 @@ pub fn normal_object(object: Object, cx: &mut WindowContext) {
                 Some(Operator::Change) => change_object(vim, object, around, cx),
                 Some(Operator::Delete) => delete_object(vim, object, around, cx),
                 Some(Operator::Yank) => yank_object(vim, object, around, cx),
+                Some(Operator::Lowercase) => {
+                    change_case_object(vim, object, around, CaseTarget::Lowercase, cx)
+                }
+                Some(Operator::Uppercase) => {
+                    change_case_object(vim, object, around, CaseTarget::Uppercase, cx)
+                }
+                Some(Operator::OppositeCase) => {
+                    change_case_object(vim, object, around, CaseTarget::OppositeCase, cx)
+                }
+                Some(Operator::Rot13) => {
+                    change_case_object(vim, object, around, CaseTarget::Rot13, cx)
+                }
+                Some(Operator::AddSurrounds) => {
+                    add_surrounds_object(vim, object, around, cx);
+                    return;
+                }
                 _ => {
                     // Can't do anything for namespace operators. Ignoring
                 }
@@ -242,6 +297,21 @@ fn insert_before(_: &mut Workspace, _: &InsertBefore, cx: &mut ViewContext<Works
     });
 }
 
+fn insert_at_previous(_: &mut Workspace, _: &InsertAtPrevious, cx: &mut ViewContext<Workspace>) {
+    Vim::update(cx, |vim, cx| {
+        vim.start_recording(cx);
+        vim.switch_mode(Mode::Insert, false, cx);
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            if let Some(anchor) = mark::mark_anchor(vim, '^', &snapshot) {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges([anchor..anchor])
+                });
+            }
+        });
+    });
+}
+
 fn insert_first_non_whitespace(
     _: &mut Workspace,
     _: &InsertFirstNonWhitespace,
@@ -350,6 +420,85 @@ fn insert_line_below(_: &mut Workspace, _: &InsertLineBelow, cx: &mut ViewContex
     });
 }
 
+/// Joins `times` lines (at least two) at each cursor, or the selected lines in
+/// visual mode, without adding or removing any whitespace (`gJ`).
+fn join_lines_without_whitespace(
+    editor: &mut Editor,
+    times: usize,
+    visual: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let max_row = snapshot.max_buffer_row();
+    let mut rows = Vec::new();
+    for selection in editor.selections.all::<Point>(cx) {
+        let start = selection.start.row;
+        let mut end = if visual {
+            selection.end.row
+        } else {
+            start + times.saturating_sub(1) as u32
+        };
+        end = end.max(start + 1).min(max_row);
+        rows.extend(start..end);
+    }
+    rows.sort_unstable();
+    rows.dedup();
+    if rows.is_empty() {
+        return;
+    }
+
+    let mut cursors = Vec::new();
+    let edits = rows
+        .iter()
+        .map(|row| {
+            let start = Point::new(*row, snapshot.line_len(*row));
+            let end = Point::new(row + 1, 0);
+            // the cursor ends up where the last line of each group was joined.
+            if !rows.contains(&(row + 1)) {
+                cursors.push(snapshot.anchor_after(end));
+            }
+            (start..end, "")
+        })
+        .collect::<Vec<_>>();
+    editor.transact(cx, |editor, cx| {
+        editor.edit(edits, cx);
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.select_anchor_ranges(cursors.into_iter().map(|cursor| cursor..cursor))
+        });
+    });
+}
+
+/// Shows the code of the character under the cursor (`:ascii`).
+fn show_character(workspace: &mut Workspace, _: &ShowCharacter, cx: &mut ViewContext<Workspace>) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let editor = editor.read(cx);
+    let head = editor.selections.newest::<usize>(cx).head();
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let message = match snapshot.chars_at(head).next() {
+        None | Some('\n') => "NUL".to_string(),
+        Some(c) => {
+            let code = c as u32;
+            let name = match c {
+                ' ' => " ".to_string(),
+                '\t' => "^I".to_string(),
+                c if c.is_ascii_control() => {
+                    format!("^{}", char::from_u32(code ^ 0x40).unwrap_or(c))
+                }
+                c => c.to_string(),
+            };
+            if code < 0x100 {
+                format!("<{name}> {code}, Hex {code:02x}, Oct {code:03o}")
+            } else {
+                format!("<{name}> {code}, Hex {code:04x}, Oct {code:o}")
+            }
+        }
+    };
+    Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+    workspace.show_toast(Toast::new(SHOW_CHARACTER_TOAST_ID, message), cx);
+}
+
 fn yank_line(_: &mut Workspace, _: &YankLine, cx: &mut ViewContext<Workspace>) {
     Vim::update(cx, |vim, cx| {
         let count = vim.take_count(cx);
@@ -1157,4 +1306,41 @@ mod test {
             indoc! {"asserˇt_binding"},
         );
     }
+
+    #[gpui::test]
+    async fn test_join_lines_without_whitespace(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\n  two\nthree", Mode::Normal);
+        cx.simulate_keystrokes(["g", "shift-j"]);
+        cx.assert_state("oneˇ  two\nthree", Mode::Normal);
+
+        cx.set_state("ˇa\nb\nc\nd", Mode::Normal);
+        cx.simulate_keystrokes(["3", "g", "shift-j"]);
+        cx.assert_state("abˇc\nd", Mode::Normal);
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(["v", "j", "g", "shift-j"]);
+        cx.assert_state("aˇb\nc", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_insert_at_previous(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("one ˇtwo", Mode::Normal);
+        cx.simulate_keystrokes(["a", "x", "escape", "0", "g", "i", "y"]);
+        cx.assert_state("one txyˇwo", Mode::Insert);
+    }
+
+    #[gpui::test]
+    async fn test_show_character(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes([":", "a", "s", "c", "i", "i", "enter"]);
+        let message = cx
+            .workspace(|workspace, cx| workspace.toast_message(super::SHOW_CHARACTER_TOAST_ID, cx));
+        assert_eq!(message.as_deref(), Some("<a> 97, Hex 61, Oct 141"));
+    }
 }
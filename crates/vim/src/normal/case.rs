@@ -1,20 +1,53 @@
-use editor::scroll::Autoscroll;
-use gpui::ViewContext;
+use collections::HashMap;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{ViewContext, WindowContext};
 use language::{Bias, Point};
 use workspace::Workspace;
 
 use crate::{
-    normal::ChangeCase, normal::ConvertToLowerCase, normal::ConvertToUpperCase, state::Mode, Vim,
+    motion::Motion,
+    normal::{ChangeCase, ConvertToLowerCase, ConvertToRot13, ConvertToUpperCase},
+    object::Object,
+    state::Mode,
+    Vim,
 };
 
-pub fn change_case(_: &mut Workspace, _: &ChangeCase, cx: &mut ViewContext<Workspace>) {
-    manipulate_text(cx, |c| {
-        if c.is_lowercase() {
-            c.to_uppercase().collect::<Vec<char>>()
-        } else {
-            c.to_lowercase().collect::<Vec<char>>()
+/// The change made by `~`, `u`, `U` and `g?` in visual mode, and by the
+/// `g~`, `gu`, `gU` and `g?` operators.
+#[derive(Clone, Copy, Debug)]
+pub enum CaseTarget {
+    Lowercase,
+    Uppercase,
+    OppositeCase,
+    Rot13,
+}
+
+impl CaseTarget {
+    fn transform(self, c: char) -> Vec<char> {
+        match self {
+            CaseTarget::Lowercase => c.to_lowercase().collect(),
+            CaseTarget::Uppercase => c.to_uppercase().collect(),
+            CaseTarget::OppositeCase => {
+                if c.is_lowercase() {
+                    c.to_uppercase().collect()
+                } else {
+                    c.to_lowercase().collect()
+                }
+            }
+            CaseTarget::Rot13 => {
+                let rotated = match c {
+                    'a'..='m' | 'A'..='M' => (c as u8 + 13) as char,
+                    'n'..='z' | 'N'..='Z' => (c as u8 - 13) as char,
+                    _ => c,
+                };
+                vec![rotated]
+            }
         }
-    })
+    }
+}
+
+pub fn change_case(_: &mut Workspace, _: &ChangeCase, cx: &mut ViewContext<Workspace>) {
+    manipulate_text(cx, CaseTarget::OppositeCase)
 }
 
 pub fn convert_to_upper_case(
@@ -22,7 +55,7 @@ pub fn convert_to_upper_case(
     _: &ConvertToUpperCase,
     cx: &mut ViewContext<Workspace>,
 ) {
-    manipulate_text(cx, |c| c.to_uppercase().collect::<Vec<char>>())
+    manipulate_text(cx, CaseTarget::Uppercase)
 }
 
 pub fn convert_to_lower_case(
@@ -30,13 +63,97 @@ pub fn convert_to_lower_case(
     _: &ConvertToLowerCase,
     cx: &mut ViewContext<Workspace>,
 ) {
-    manipulate_text(cx, |c| c.to_lowercase().collect::<Vec<char>>())
+    manipulate_text(cx, CaseTarget::Lowercase)
+}
+
+pub fn convert_to_rot13(_: &mut Workspace, _: &ConvertToRot13, cx: &mut ViewContext<Workspace>) {
+    manipulate_text(cx, CaseTarget::Rot13)
+}
+
+pub fn change_case_motion(
+    vim: &mut Vim,
+    motion: Motion,
+    times: Option<usize>,
+    target: CaseTarget,
+    cx: &mut WindowContext,
+) {
+    vim.stop_recording();
+    vim.update_active_editor(cx, |_, editor, cx| {
+        let text_layout_details = editor.text_layout_details(cx);
+        editor.transact(cx, |editor, cx| {
+            editor.set_clip_at_line_ends(false, cx);
+            let mut original_columns: HashMap<_, _> = Default::default();
+            editor.change_selections(None, cx, |s| {
+                s.move_with(|map, selection| {
+                    let original_point = selection.head().to_point(map);
+                    original_columns.insert(selection.id, original_point.column);
+                    motion.expand_selection(map, selection, times, false, &text_layout_details);
+                });
+            });
+            // linewise changes leave the cursor where it was in the first line.
+            if !motion.linewise() {
+                original_columns.clear();
+            }
+            change_case_of_selections(editor, target, original_columns, cx);
+            editor.set_clip_at_line_ends(true, cx);
+        });
+    });
+}
+
+pub fn change_case_object(
+    vim: &mut Vim,
+    object: Object,
+    around: bool,
+    target: CaseTarget,
+    cx: &mut WindowContext,
+) {
+    vim.stop_recording();
+    vim.update_active_editor(cx, |_, editor, cx| {
+        editor.transact(cx, |editor, cx| {
+            editor.set_clip_at_line_ends(false, cx);
+            editor.change_selections(None, cx, |s| {
+                s.move_with(|map, selection| {
+                    object.expand_selection(map, selection, around);
+                });
+            });
+            change_case_of_selections(editor, target, HashMap::default(), cx);
+            editor.set_clip_at_line_ends(true, cx);
+        });
+    });
+}
+
+/// Changes the case of the text in each selection, leaving the cursor at its
+/// start, or at the given column of its first line.
+fn change_case_of_selections(
+    editor: &mut Editor,
+    target: CaseTarget,
+    columns: HashMap<usize, u32>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let mut edits = Vec::new();
+    let mut cursors = Vec::new();
+    for selection in editor.selections.all::<Point>(cx) {
+        let text = snapshot
+            .text_for_range(selection.start..selection.end)
+            .flat_map(|s| s.chars())
+            .flat_map(|c| target.transform(c))
+            .collect::<String>();
+        let mut cursor = selection.start;
+        if let Some(column) = columns.get(&selection.id) {
+            cursor.column = *column;
+        }
+        let cursor = snapshot.anchor_before(snapshot.clip_point(cursor, Bias::Left));
+        cursors.push(cursor..cursor);
+        edits.push((selection.start..selection.end, text));
+    }
+    editor.edit(edits, cx);
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_anchor_ranges(cursors)
+    });
 }
 
-fn manipulate_text<F>(cx: &mut ViewContext<Workspace>, transform: F)
-where
-    F: Fn(char) -> Vec<char> + Copy,
-{
+fn manipulate_text(cx: &mut ViewContext<Workspace>, target: CaseTarget) {
     Vim::update(cx, |vim, cx| {
         vim.record_current_action(cx);
         let count = vim.take_count(cx).unwrap_or(1) as u32;
@@ -63,7 +180,11 @@ where
                             cursor_positions.push(selection.start..selection.start);
                         }
                     }
-                    Mode::Insert | Mode::Normal => {
+                    Mode::HelixNormal if !selection.is_empty() => {
+                        ranges.push(selection.start..selection.end);
+                        cursor_positions.push(selection.start..selection.end);
+                    }
+                    Mode::Insert | Mode::Replace | Mode::Normal | Mode::HelixNormal => {
                         let start = selection.start;
                         let mut end = start;
                         for _ in 0..count {
@@ -85,7 +206,7 @@ where
                         let text = snapshot
                             .text_for_range(range.start..range.end)
                             .flat_map(|s| s.chars())
-                            .flat_map(|c| transform(c))
+                            .flat_map(|c| target.transform(c))
                             .collect::<String>();
 
                         buffer.edit([(range, text)], None, cx)
@@ -102,7 +223,10 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::{state::Mode, test::NeovimBackedTestContext};
+    use crate::{
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
 
     #[gpui::test]
     async fn test_change_case(cx: &mut gpui::TestAppContext) {
@@ -177,4 +301,33 @@ mod test {
         cx.simulate_shared_keystrokes(["ctrl-v", "j", "u"]).await;
         cx.assert_shared_state("ˇaa\nbb\nCc").await;
     }
+
+    #[gpui::test]
+    async fn test_change_case_operators(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("heˇllo world", Mode::Normal);
+        cx.simulate_keystrokes(["g", "shift-u", "w"]);
+        cx.assert_state("heˇLLO world", Mode::Normal);
+        cx.simulate_keystrokes(["g", "~", "i", "w"]);
+        cx.assert_state("ˇHEllo world", Mode::Normal);
+        cx.simulate_keystrokes(["g", "u", "u"]);
+        cx.assert_state("ˇhello world", Mode::Normal);
+        cx.simulate_keystrokes(["g", "?", "g", "?"]);
+        cx.assert_state("ˇuryyb jbeyq", Mode::Normal);
+
+        cx.set_state("aˇb\ncd\nef", Mode::Normal);
+        cx.simulate_keystrokes(["g", "shift-u", "j"]);
+        cx.assert_state("AˇB\nCD\nef", Mode::Normal);
+
+        cx.set_state("ˇab cd", Mode::Normal);
+        cx.simulate_keystrokes(["g", "shift-u", "e", "w", "."]);
+        cx.run_until_parked();
+        cx.assert_state("AB ˇCD", Mode::Normal);
+
+        // g? works in visual mode
+        cx.set_state("«helloˇ» world", Mode::Visual);
+        cx.simulate_keystrokes(["g", "?"]);
+        cx.assert_state("ˇuryyb world", Mode::Normal);
+    }
 }
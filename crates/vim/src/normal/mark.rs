@@ -0,0 +1,705 @@
+use std::{path::Path, sync::Arc};
+
+use editor::{scroll::Autoscroll, Anchor, Bias, Editor, MultiBufferSnapshot, ToPoint};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::{BufferId, Point};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{
+    motion::{self, Motion},
+    persistence::DB,
+    state::{GlobalMark, Jump},
+    Vim,
+};
+
+actions!(vim, [ShowMarks, JumpOlder, JumpNewer]);
+
+/// The number of positions kept in the jump list, as in vim.
+const MAX_JUMPS: usize = 100;
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|workspace: &mut Workspace, _: &ShowMarks, cx| {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let marks = Vim::update(cx, |vim, cx| list_marks(vim, editor.read(cx), cx));
+        workspace.toggle_modal(cx, |cx| MarksPicker::new(marks, cx));
+    });
+    workspace.register_action(|_: &mut Workspace, _: &JumpOlder, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1);
+        jump_older(count, cx);
+    });
+    workspace.register_action(|_: &mut Workspace, _: &JumpNewer, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1);
+        jump_newer(count, cx);
+    });
+}
+
+/// Loads the uppercase marks saved in previous sessions in the background.
+/// Marks set in the meantime take precedence over the loaded ones.
+pub(crate) fn load_global_marks(cx: &mut AppContext) {
+    cx.spawn(|mut cx| async move {
+        let marks = DB.get_global_marks().await?;
+        cx.update_global(|vim: &mut Vim, _| {
+            for (name, path, row, column) in marks {
+                let Some(name) = name.chars().next() else {
+                    continue;
+                };
+                vim.workspace_state
+                    .global_marks
+                    .entry(name)
+                    .or_insert_with(|| GlobalMark {
+                        path: Some(path.into()),
+                        point: Point::new(row, column),
+                        anchor: None,
+                    });
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Sets a mark at the cursor (`m{a-zA-Z}`).
+pub(crate) fn create_mark(vim: &mut Vim, text: Arc<str>, cx: &mut WindowContext) {
+    vim.clear_operator(cx);
+    let Some(name) = text.chars().next() else {
+        return;
+    };
+    let name = if name == '`' { '\'' } else { name };
+    if !name.is_ascii_alphabetic() && !"'[]<>".contains(name) {
+        return;
+    }
+    vim.update_active_editor(cx, |vim, editor, cx| {
+        let anchor = editor.selections.newest_anchor().head();
+        set_mark(vim, name, anchor, editor, cx);
+    });
+}
+
+/// Records `anchor` as the mark `name` for the buffer it points into.
+/// Uppercase marks are shared between buffers, and persisted if the buffer has
+/// a file.
+pub(crate) fn set_mark(
+    vim: &mut Vim,
+    name: char,
+    anchor: Anchor,
+    editor: &Editor,
+    cx: &AppContext,
+) {
+    let Some(buffer_id) = anchor.buffer_id else {
+        return;
+    };
+    if !name.is_ascii_uppercase() {
+        vim.workspace_state
+            .marks
+            .entry(buffer_id)
+            .or_default()
+            .insert(name, anchor.text_anchor);
+        return;
+    }
+
+    let multi_buffer = editor.buffer().read(cx);
+    let point = anchor.to_point(&multi_buffer.snapshot(cx));
+    let path = buffer_path(editor, buffer_id, cx);
+    vim.workspace_state.global_marks.insert(
+        name,
+        GlobalMark {
+            path: path.clone(),
+            point,
+            anchor: Some((buffer_id, anchor.text_anchor)),
+        },
+    );
+    let Some(path) = path else {
+        return;
+    };
+    cx.background_executor()
+        .spawn(async move {
+            DB.save_global_mark(
+                name.to_string(),
+                path.to_path_buf(),
+                point.row,
+                point.column,
+            )
+            .await
+            .log_err()
+        })
+        .detach();
+}
+
+fn buffer_path(editor: &Editor, buffer_id: BufferId, cx: &AppContext) -> Option<Arc<Path>> {
+    let buffer = editor.buffer().read(cx).buffer(buffer_id)?;
+    let file = buffer.read(cx).file()?.as_local()?;
+    Some(file.abs_path(cx).into())
+}
+
+/// Remembers the cursor position in the `'` mark and the jump list before a
+/// jump, so that `''` and `ctrl-o` can go back to it.
+pub(crate) fn set_previous_context_mark(vim: &mut Vim, cx: &mut WindowContext) {
+    vim.update_active_editor(cx, |vim, editor, cx| {
+        let anchor = editor.selections.newest_anchor().head();
+        set_mark(vim, '\'', anchor, editor, cx);
+        let state = &mut vim.workspace_state;
+        state.jumps.truncate(state.jump_ix);
+        push_jump(&mut state.jumps, anchor, editor, cx);
+        state.jump_ix = state.jumps.len();
+    });
+}
+
+/// Appends `anchor` to the jump list, replacing any older jump to the same line.
+fn push_jump(jumps: &mut Vec<Jump>, anchor: Anchor, editor: &Editor, cx: &AppContext) {
+    let Some(buffer_id) = anchor.buffer_id else {
+        return;
+    };
+    let point = anchor.to_point(&editor.buffer().read(cx).snapshot(cx));
+    jumps.retain(|jump| jump.buffer_id != buffer_id || jump.point.row != point.row);
+    jumps.push(Jump {
+        buffer_id,
+        anchor: anchor.text_anchor,
+        path: buffer_path(editor, buffer_id, cx),
+        point,
+    });
+    if jumps.len() > MAX_JUMPS {
+        jumps.remove(0);
+    }
+}
+
+/// Resolves the anchor of `buffer_id` to an anchor in the excerpts of `snapshot`.
+fn anchor_in_excerpts(
+    snapshot: &MultiBufferSnapshot,
+    buffer_id: BufferId,
+    anchor: &language::Anchor,
+) -> Option<Anchor> {
+    snapshot.excerpts().find_map(|(excerpt_id, buffer, range)| {
+        if buffer.remote_id() != buffer_id {
+            return None;
+        }
+        let in_excerpt = range.context.start.cmp(anchor, buffer).is_le()
+            && range.context.end.cmp(anchor, buffer).is_ge();
+        in_excerpt.then(|| snapshot.anchor_in_excerpt(excerpt_id, *anchor))?
+    })
+}
+
+/// Resolves the mark `name` to an anchor in the excerpts of `snapshot`.
+pub(crate) fn mark_anchor(vim: &Vim, name: char, snapshot: &MultiBufferSnapshot) -> Option<Anchor> {
+    if name.is_ascii_uppercase() {
+        let (buffer_id, anchor) = vim.workspace_state.global_marks.get(&name)?.anchor?;
+        return anchor_in_excerpts(snapshot, buffer_id, &anchor);
+    }
+    snapshot.excerpts().find_map(|(_, buffer, _)| {
+        let buffer_id = buffer.remote_id();
+        let anchor = vim.workspace_state.marks.get(&buffer_id)?.get(&name)?;
+        anchor_in_excerpts(snapshot, buffer_id, anchor)
+    })
+}
+
+/// Jumps to the mark typed after `'` or `` ` ``.
+pub(crate) fn jump(text: Arc<str>, line: bool, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| vim.pop_operator(cx));
+    if let Some(name) = text.chars().next() {
+        jump_to_mark(name, line, cx);
+    }
+}
+
+/// Moves the cursor to the mark `name`, to the first non-blank character of its
+/// line if `line` is set. Uppercase marks set in another buffer open its file.
+pub(crate) fn jump_to_mark(name: char, line: bool, cx: &mut WindowContext) {
+    let name = if name == '`' { '\'' } else { name };
+    let anchor = Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            mark_anchor(vim, name, &editor.buffer().read(cx).snapshot(cx))
+        })
+        .flatten()
+    });
+
+    if let Some(anchor) = anchor {
+        motion::motion(Motion::Jump { anchor, line }, cx);
+        return;
+    }
+
+    Vim::update(cx, |vim, cx| {
+        vim.clear_operator(cx);
+        if name.is_ascii_uppercase() {
+            jump_to_global_mark(vim, name, line, cx);
+        }
+    });
+}
+
+fn jump_to_global_mark(vim: &mut Vim, name: char, line: bool, cx: &mut WindowContext) {
+    let Some(path) = vim
+        .workspace_state
+        .global_marks
+        .get(&name)
+        .and_then(|mark| mark.path.clone())
+    else {
+        return;
+    };
+    set_previous_context_mark(vim, cx);
+    open_and_jump(vim, path, cx, move |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let buffer_id = snapshot.as_singleton()?.1;
+        // Prefer the live anchor if the buffer has been open since the mark
+        // was set, as the saved point doesn't follow edits.
+        Vim::update(cx, |vim, _| {
+            if let Some(anchor) = mark_anchor(vim, name, &snapshot) {
+                return Some(anchor.to_point(&snapshot));
+            }
+            let mark = vim.workspace_state.global_marks.get_mut(&name)?;
+            let point = snapshot.clip_point(mark.point, Bias::Left);
+            mark.anchor = Some((buffer_id, snapshot.anchor_before(point).text_anchor));
+            Some(point)
+        })
+        .map(|mut point| {
+            if line {
+                point.column = snapshot.indent_size_for_line(point.row).len;
+            }
+            point
+        })
+    });
+}
+
+/// Opens `path` in the workspace of the active editor and moves the cursor to
+/// the point `resolve` returns for the editor it was opened in.
+fn open_and_jump(
+    vim: &Vim,
+    path: Arc<Path>,
+    cx: &mut WindowContext,
+    resolve: impl FnOnce(&Editor, &mut ViewContext<Editor>) -> Option<Point> + 'static,
+) {
+    let Some(workspace) = vim
+        .active_editor
+        .as_ref()
+        .and_then(|editor| editor.upgrade())
+        .and_then(|editor| editor.read(cx).workspace())
+    else {
+        return;
+    };
+    let open = workspace.update(cx, |workspace, cx| {
+        workspace.open_abs_path(path.to_path_buf(), true, cx)
+    });
+    cx.spawn(|mut cx| async move {
+        let item = open.await?;
+        let Some(editor) = cx.update(|cx| item.act_as::<Editor>(cx))? else {
+            return Ok(());
+        };
+        editor.update(&mut cx, |editor, cx| {
+            if let Some(point) = resolve(editor, cx) {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Moves `count` positions back in the jump list (`ctrl-o`). The current
+/// position is added to the end of the list first, so that `ctrl-i` can return
+/// to it.
+fn jump_older(count: usize, cx: &mut WindowContext) {
+    let jump = Vim::update(cx, |vim, cx| {
+        if vim.workspace_state.jump_ix >= vim.workspace_state.jumps.len() {
+            vim.update_active_editor(cx, |vim, editor, cx| {
+                let anchor = editor.selections.newest_anchor().head();
+                push_jump(&mut vim.workspace_state.jumps, anchor, editor, cx);
+            });
+            vim.workspace_state.jump_ix = vim.workspace_state.jumps.len().saturating_sub(1);
+        }
+        let ix = vim.workspace_state.jump_ix.checked_sub(count)?;
+        vim.workspace_state.jump_ix = ix;
+        vim.workspace_state.jumps.get(ix).cloned()
+    });
+    if let Some(jump) = jump {
+        jump_to(jump, cx);
+    }
+}
+
+/// Moves `count` positions forward in the jump list (`ctrl-i`).
+fn jump_newer(count: usize, cx: &mut WindowContext) {
+    let jump = Vim::update(cx, |vim, _| {
+        let ix = vim.workspace_state.jump_ix + count;
+        let jump = vim.workspace_state.jumps.get(ix).cloned()?;
+        vim.workspace_state.jump_ix = ix;
+        Some(jump)
+    });
+    if let Some(jump) = jump {
+        jump_to(jump, cx);
+    }
+}
+
+fn jump_to(jump: Jump, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.clear_operator(cx);
+        let jumped = vim.update_active_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let anchor = anchor_in_excerpts(&snapshot, jump.buffer_id, &jump.anchor)?;
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_anchor_ranges([anchor..anchor])
+            });
+            Some(())
+        });
+        if jumped.flatten().is_some() {
+            return;
+        }
+        let Some(path) = jump.path.clone() else {
+            return;
+        };
+        open_and_jump(vim, path, cx, move |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let point = match anchor_in_excerpts(&snapshot, jump.buffer_id, &jump.anchor) {
+                Some(anchor) => anchor.to_point(&snapshot),
+                None => snapshot.clip_point(jump.point, Bias::Left),
+            };
+            Some(point)
+        });
+    });
+}
+
+struct MarkEntry {
+    name: char,
+    label: String,
+}
+
+/// Lists the marks that can be jumped to from `editor`, in the order `:marks`
+/// shows them in vim.
+fn list_marks(vim: &Vim, editor: &Editor, cx: &AppContext) -> Vec<MarkEntry> {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let mut names = vec!['\''];
+    names.extend('a'..='z');
+    names.extend('A'..='Z');
+    names.extend(['[', ']', '^', '.', '<', '>']);
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (point, text) = match mark_anchor(vim, name, &snapshot) {
+                Some(anchor) => {
+                    let point = anchor.to_point(&snapshot);
+                    let text = snapshot
+                        .text_for_range(
+                            Point::new(point.row, 0)
+                                ..Point::new(point.row, snapshot.line_len(point.row)),
+                        )
+                        .collect::<String>();
+                    (point, text)
+                }
+                None => {
+                    let mark = vim.workspace_state.global_marks.get(&name)?;
+                    let path = mark.path.as_ref()?;
+                    (mark.point, path.to_string_lossy().into_owned())
+                }
+            };
+            Some(MarkEntry {
+                name,
+                label: format!(
+                    "{} {:>6} {:>4} {}",
+                    name,
+                    point.row + 1,
+                    point.column,
+                    text.trim()
+                ),
+            })
+        })
+        .collect()
+}
+
+pub struct MarksPicker {
+    picker: View<Picker<MarksPickerDelegate>>,
+}
+
+impl MarksPicker {
+    fn new(marks: Vec<MarkEntry>, cx: &mut ViewContext<Self>) -> Self {
+        let delegate = MarksPickerDelegate::new(cx.view().downgrade(), marks);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for MarksPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for MarksPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for MarksPicker {}
+impl ModalView for MarksPicker {}
+
+pub struct MarksPickerDelegate {
+    marks_picker: WeakView<MarksPicker>,
+    marks: Vec<MarkEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl MarksPickerDelegate {
+    fn new(marks_picker: WeakView<MarksPicker>, marks: Vec<MarkEntry>) -> Self {
+        let candidates = marks
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, mark)| StringMatchCandidate::new(candidate_id, mark.label.clone()))
+            .collect();
+
+        Self {
+            marks_picker,
+            marks,
+            candidates,
+            matches: vec![],
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for MarksPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Jump to a mark...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let name = self
+            .matches
+            .get(self.selected_index)
+            .map(|mat| self.marks[mat.candidate_id].name);
+        self.dismissed(cx);
+        if let Some(name) = name {
+            WindowContext::defer(cx, move |cx| jump_to_mark(name, false, cx));
+        }
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.marks_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use language::Point;
+
+    use crate::{state::Mode, test::VimTestContext, Vim};
+
+    #[gpui::test]
+    async fn test_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                ˇline one
+                    line two
+                line three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["j", "w", "l", "m", "a", "g", "g"]);
+        cx.simulate_keystrokes(["`", "a"]);
+        cx.assert_state(
+            indoc! {"
+                line one
+                    lˇine two
+                line three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["g", "g", "'", "a"]);
+        cx.assert_state(
+            indoc! {"
+                line one
+                    ˇline two
+                line three"},
+            Mode::Normal,
+        );
+
+        // marks follow edits made before them
+        cx.simulate_keystrokes(["g", "g", "shift-o", "n", "e", "w", "escape"]);
+        cx.simulate_keystrokes(["`", "a"]);
+        cx.assert_state(
+            indoc! {"
+                new
+                line one
+                    lˇine two
+                line three"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_mark_motions(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("one ˇtwo three", Mode::Normal);
+        cx.simulate_keystrokes(["m", "b", "$", "d", "`", "b"]);
+        cx.assert_state("one ˇe", Mode::Normal);
+
+        cx.set_state("ˇone\ntwo", Mode::Normal);
+        cx.simulate_keystrokes(["j", "m", "shift-a", "g", "g", "'", "shift-a"]);
+        cx.assert_state("one\nˇtwo", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_previous_context_mark(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("oˇne\ntwo\nthree", Mode::Normal);
+        cx.simulate_keystrokes(["shift-g"]);
+        cx.assert_state("one\ntwo\nˇthree", Mode::Normal);
+        cx.simulate_keystrokes(["`", "`"]);
+        cx.assert_state("oˇne\ntwo\nthree", Mode::Normal);
+        cx.simulate_keystrokes(["'", "'"]);
+        cx.assert_state("one\ntwo\nˇthree", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_uppercase_marks_are_global(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo", Mode::Normal);
+        cx.simulate_keystrokes(["j", "m", "shift-b"]);
+        cx.read(|cx| {
+            let state = &cx.global::<Vim>().workspace_state;
+            assert!(state.marks.values().all(|marks| !marks.contains_key(&'B')));
+            assert_eq!(state.global_marks[&'B'].point, Point::new(1, 0));
+        });
+
+        // the mark follows edits in the buffer it was set in
+        cx.simulate_keystrokes(["g", "g", "shift-o", "z", "e", "r", "o", "escape"]);
+        cx.simulate_keystrokes(["`", "shift-b"]);
+        cx.assert_state("zero\none\nˇtwo", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_jump_list(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+        cx.simulate_keystrokes(["3", "shift-g"]);
+        cx.simulate_keystrokes(["shift-g"]);
+        cx.assert_state("one\ntwo\nthree\nfour\nˇfive", Mode::Normal);
+
+        cx.simulate_keystrokes(["ctrl-o"]);
+        cx.assert_state("one\ntwo\nˇthree\nfour\nfive", Mode::Normal);
+        cx.simulate_keystrokes(["ctrl-o"]);
+        cx.assert_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+        // there is nothing older to go back to
+        cx.simulate_keystrokes(["ctrl-o"]);
+        cx.assert_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+
+        cx.simulate_keystrokes(["2", "ctrl-i"]);
+        cx.assert_state("one\ntwo\nthree\nfour\nˇfive", Mode::Normal);
+        cx.simulate_keystrokes(["ctrl-i"]);
+        cx.assert_state("one\ntwo\nthree\nfour\nˇfive", Mode::Normal);
+
+        // jumping after going back drops the newer jumps
+        cx.simulate_keystrokes(["2", "ctrl-o", "j", "g", "g", "ctrl-o"]);
+        cx.assert_state("one\nˇtwo\nthree\nfour\nfive", Mode::Normal);
+        cx.simulate_keystrokes(["ctrl-i", "ctrl-i"]);
+        cx.assert_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_special_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["v", "e", "escape", "$"]);
+        cx.simulate_keystrokes(["`", "<"]);
+        cx.assert_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["`", ">"]);
+        cx.assert_state("onˇe two three", Mode::Normal);
+
+        cx.set_state("ˇone\ntwo", Mode::Normal);
+        cx.simulate_keystrokes(["j", "shift-a", "x", "escape", "g", "g"]);
+        cx.simulate_keystrokes(["`", "."]);
+        cx.assert_state("one\ntwoˇx", Mode::Normal);
+
+        cx.simulate_keystrokes(["g", "g", "w", "y", "i", "w", "$"]);
+        cx.simulate_keystrokes(["`", "["]);
+        cx.assert_state("one\nˇtwox", Mode::Normal);
+    }
+}
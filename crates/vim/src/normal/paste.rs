@@ -1,23 +1,24 @@
 use std::cmp;
 
-use editor::{
-    display_map::ToDisplayPoint, movement, scroll::Autoscroll, ClipboardSelection, DisplayPoint,
-};
-use gpui::{impl_actions, AppContext, ViewContext};
+use editor::{display_map::ToDisplayPoint, movement, scroll::Autoscroll, DisplayPoint};
+use gpui::{impl_actions, ViewContext};
 use language::{Bias, SelectionGoal};
 use serde::Deserialize;
-use settings::Settings;
 use workspace::Workspace;
 
-use crate::{state::Mode, utils::copy_selections_content, UseSystemClipboard, Vim, VimSettings};
+use crate::{
+    state::{Mode, Register},
+    utils::{copy_selections_content, read_register},
+    Vim,
+};
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct Paste {
+pub(crate) struct Paste {
     #[serde(default)]
-    before: bool,
+    pub(crate) before: bool,
     #[serde(default)]
-    preserve_clipboard: bool,
+    pub(crate) preserve_clipboard: bool,
 }
 
 impl_actions!(vim, [Paste]);
@@ -26,17 +27,7 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
     workspace.register_action(paste);
 }
 
-fn system_clipboard_is_newer(vim: &Vim, cx: &mut AppContext) -> bool {
-    cx.read_from_clipboard().is_some_and(|item| {
-        if let Some(last_state) = vim.workspace_state.registers.get(".system.") {
-            last_state != item.text()
-        } else {
-            true
-        }
-    })
-}
-
-fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
+pub(crate) fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
     Vim::update(cx, |vim, cx| {
         vim.record_current_action(cx);
         vim.update_active_editor(cx, |vim, editor, cx| {
@@ -44,33 +35,17 @@ fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
             editor.transact(cx, |editor, cx| {
                 editor.set_clip_at_line_ends(false, cx);
 
-                let (clipboard_text, clipboard_selections): (String, Option<_>) =
-                    if VimSettings::get_global(cx).use_system_clipboard == UseSystemClipboard::Never
-                        || VimSettings::get_global(cx).use_system_clipboard
-                            == UseSystemClipboard::OnYank
-                            && !system_clipboard_is_newer(vim, cx)
-                    {
-                        (
-                            vim.workspace_state
-                                .registers
-                                .get("\"")
-                                .cloned()
-                                .unwrap_or_else(|| "".to_string()),
-                            None,
-                        )
-                    } else {
-                        if let Some(item) = cx.read_from_clipboard() {
-                            let clipboard_selections = item
-                                .metadata::<Vec<ClipboardSelection>>()
-                                .filter(|clipboard_selections| {
-                                    clipboard_selections.len() > 1
-                                        && vim.state().mode != Mode::VisualLine
-                                });
-                            (item.text().clone(), clipboard_selections)
-                        } else {
-                            ("".into(), None)
-                        }
-                    };
+                let selected_register = vim.update_state(|state| state.selected_register.take());
+                let Some(Register {
+                    text: clipboard_text,
+                    clipboard_selections,
+                }) = read_register(vim, selected_register, Some(&*editor), cx)
+                else {
+                    return;
+                };
+                let clipboard_selections = clipboard_selections.filter(|clipboard_selections| {
+                    clipboard_selections.len() > 1 && vim.state().mode != Mode::VisualLine
+                });
 
                 if clipboard_text.is_empty() {
                     return;
@@ -230,6 +205,7 @@ mod test {
         test::{NeovimBackedTestContext, VimTestContext},
         UseSystemClipboard, VimSettings,
     };
+    use editor::Editor;
     use gpui::ClipboardItem;
     use indoc::indoc;
     use settings::SettingsStore;
@@ -599,4 +575,111 @@ mod test {
             Mode::Normal,
         );
     }
+
+    #[gpui::test]
+    async fn test_named_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| {
+                s.use_system_clipboard = Some(UseSystemClipboard::Never)
+            });
+        });
+
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["\"", "a", "y", "w"]);
+        cx.simulate_keystrokes(["w", "\"", "shift-a", "y", "w"]);
+        cx.simulate_keystrokes(["$", "\"", "a", "p"]);
+        cx.assert_state("one two threeone twoˇ ", Mode::Normal);
+
+        // the unnamed register follows the last written register
+        cx.set_state("ˇxyz", Mode::Normal);
+        cx.simulate_keystrokes(["p"]);
+        cx.assert_state("xone twoˇ yz", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_numbered_and_black_hole_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo\nthree\nfour", Mode::Normal);
+        cx.simulate_keystrokes(["d", "d", "d", "d"]);
+        cx.simulate_keystrokes(["\"", "_", "d", "d"]);
+        cx.assert_state("ˇfour", Mode::Normal);
+        cx.simulate_keystrokes(["\"", "2", "p"]);
+        cx.assert_state("four\nˇone", Mode::Normal);
+        cx.simulate_keystrokes(["p"]);
+        cx.assert_state("four\none\nˇtwo", Mode::Normal);
+
+        // small deletes go to the "- register, yanks to "0
+        cx.set_state("ˇab cd", Mode::Normal);
+        cx.simulate_keystrokes(["y", "w", "x", "$", "\"", "0", "p"]);
+        cx.assert_state("b cdabˇ ", Mode::Normal);
+        cx.simulate_keystrokes(["\"", "-", "p"]);
+        cx.assert_state("b cdab ˇa", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_insert_register(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇhello", Mode::Normal);
+        cx.simulate_keystrokes(["\"", "a", "y", "i", "w"]);
+        cx.simulate_keystrokes(["i", "x", "y", "escape"]);
+        cx.assert_state("xˇyhello", Mode::Normal);
+        cx.simulate_keystrokes(["shift-a", "ctrl-r", "a", "ctrl-r", "."]);
+        cx.assert_state("xyhellohelloxyˇ", Mode::Insert);
+    }
+
+    #[gpui::test]
+    async fn test_append_linewise_to_register(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo three", Mode::Normal);
+        cx.simulate_keystrokes(["\"", "a", "y", "y", "j", "\"", "shift-a", "y", "w"]);
+        cx.simulate_keystrokes(["\"", "a", "p"]);
+        cx.assert_state("one\ntwo three\nˇone\ntwo ", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_search_register(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let search_register = |cx: &mut VimTestContext| {
+            cx.read(|cx| {
+                cx.global::<Vim>()
+                    .workspace_state
+                    .registers
+                    .get(&'/')
+                    .map(|register| register.text.clone())
+            })
+        };
+
+        cx.set_state("ˇone two one", Mode::Normal);
+        cx.simulate_keystrokes(["/", "t", "w"]);
+        cx.simulate_keystrokes(["enter"]);
+        cx.run_until_parked();
+        assert_eq!(search_register(&mut cx).as_deref(), Some("tw"));
+
+        cx.simulate_keystrokes(["*"]);
+        cx.run_until_parked();
+        assert_eq!(search_register(&mut cx).as_deref(), Some("\\<two\\>"));
+    }
+
+    #[gpui::test]
+    async fn test_show_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo", Mode::Normal);
+        cx.simulate_keystrokes(["\"", "a", "y", "y", "j", "\"", "b", "y", "w"]);
+        cx.simulate_keystrokes([":", "r", "e", "g", "enter"]);
+
+        let listing = cx.workspace(|workspace, cx| {
+            workspace
+                .active_item_as::<Editor>(cx)
+                .unwrap()
+                .read(cx)
+                .text(cx)
+        });
+        assert!(listing.contains("  l  \"a   one^J\n"), "{listing}");
+        assert!(listing.contains("  c  \"b   two\n"), "{listing}");
+    }
 }
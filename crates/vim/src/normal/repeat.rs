@@ -1,14 +1,22 @@
+use std::{cell::RefCell, rc::Rc};
+
 use crate::{
     insert::NormalBefore,
     motion::Motion,
-    state::{Mode, RecordedSelection, ReplayableAction},
+    observe_action,
+    state::{Mode, Operator, RecordedSelection, ReplayableAction},
     visual::visual_motion,
     Vim,
 };
 use gpui::{actions, Action, ViewContext, WindowContext};
-use workspace::Workspace;
+use workspace::{Toast, Workspace};
+
+actions!(vim, [Repeat, EndRepeat, ToggleRecord]);
 
-actions!(vim, [Repeat, EndRepeat]);
+/// Replaying is aborted after this many actions, so that a recursive macro
+/// cannot hang the editor.
+const MAX_REPLAYED_ACTIONS: usize = 10000;
+const REPLAY_ABORTED_TOAST_ID: usize = 0x7265_7061;
 
 fn should_replay(action: &Box<dyn Action>) -> bool {
     // skip so that we don't leave the character palette open
@@ -41,13 +49,157 @@ fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
 
 pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(|_: &mut Workspace, _: &EndRepeat, cx| {
-        Vim::update(cx, |vim, cx| {
-            vim.workspace_state.replaying = false;
-            vim.switch_mode(Mode::Normal, false, cx)
-        });
+        Vim::update(cx, |vim, cx| end_repeat(vim, cx));
     });
 
     workspace.register_action(|_: &mut Workspace, _: &Repeat, cx| repeat(cx, false));
+
+    workspace.register_action(|_: &mut Workspace, _: &ToggleRecord, cx| {
+        Vim::update(cx, |vim, cx| {
+            if vim.workspace_state.recording_register.take().is_none() {
+                vim.push_operator(Operator::RecordRegister, cx);
+            }
+        })
+    });
+}
+
+fn end_repeat(vim: &mut Vim, cx: &mut WindowContext) {
+    vim.workspace_state.replaying = false;
+    vim.update_active_editor(cx, |_, editor, _| {
+        editor.show_local_selections = true;
+    });
+    vim.switch_mode(Mode::Normal, false, cx)
+}
+
+/// Replays recorded actions one at a time, deferring between each of them so
+/// that every action is fully handled before the next one is dispatched.
+///
+/// Replays started while another one is running (e.g. `.` inside a macro, or a
+/// recursive macro) are spliced in at the current position.
+#[derive(Clone)]
+pub struct Replayer(Rc<RefCell<ReplayerState>>);
+
+struct ReplayerState {
+    actions: Vec<ReplayableAction>,
+    running: bool,
+    ix: usize,
+}
+
+impl Replayer {
+    fn new() -> Self {
+        Self(Rc::new(RefCell::new(ReplayerState {
+            actions: vec![],
+            running: false,
+            ix: 0,
+        })))
+    }
+
+    pub fn replay(&mut self, actions: Vec<ReplayableAction>, cx: &mut WindowContext) {
+        let mut lock = self.0.borrow_mut();
+        let range = lock.ix..lock.ix;
+        lock.actions.splice(range, actions);
+        if lock.running {
+            return;
+        }
+        lock.running = true;
+        let this = self.clone();
+        cx.defer(move |cx| this.next(cx))
+    }
+
+    fn next(self, cx: &mut WindowContext) {
+        let mut lock = self.0.borrow_mut();
+        let aborted = lock.ix >= MAX_REPLAYED_ACTIONS;
+        let action = if aborted {
+            log::error!("Aborting replay after {} actions", MAX_REPLAYED_ACTIONS);
+            None
+        } else {
+            lock.actions.get(lock.ix).cloned()
+        };
+        lock.ix += 1;
+        drop(lock);
+
+        let Some(action) = action else {
+            let workspace = Vim::update(cx, |vim, cx| {
+                vim.workspace_state.replayer.take();
+                // The `EndRepeat` queued at the end of a `.` won't be reached.
+                if !aborted {
+                    return None;
+                }
+                end_repeat(vim, cx);
+                vim.active_editor
+                    .as_ref()
+                    .and_then(|editor| editor.upgrade())
+                    .and_then(|editor| editor.read(cx).workspace())
+            });
+            if let Some(workspace) = workspace {
+                workspace.update(cx, |workspace, cx| {
+                    let message = format!("Aborted replay after {MAX_REPLAYED_ACTIONS} actions.");
+                    workspace.show_toast(Toast::new(REPLAY_ABORTED_TOAST_ID, message), cx)
+                });
+            }
+            return;
+        };
+        match action {
+            ReplayableAction::Action(action) => {
+                if should_replay(&action) {
+                    cx.dispatch_action(action.boxed_clone());
+                    cx.defer(move |cx| observe_action(action, cx));
+                }
+            }
+            ReplayableAction::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => {
+                if let Some(editor) = Vim::read(cx)
+                    .active_editor
+                    .clone()
+                    .and_then(|editor| editor.upgrade())
+                {
+                    editor.update(cx, |editor, cx| {
+                        editor.replay_insert_event(&text, utf16_range_to_replace, cx)
+                    });
+                }
+            }
+        }
+        cx.defer(move |cx| self.next(cx));
+    }
+}
+
+fn replay(actions: Vec<ReplayableAction>, cx: &mut WindowContext) {
+    let mut replayer = Vim::update(cx, |vim, _| {
+        vim.workspace_state
+            .replayer
+            .get_or_insert_with(Replayer::new)
+            .clone()
+    });
+    replayer.replay(actions, cx);
+}
+
+/// Replays the macro recorded into `register` (`@{register}`), `@@` replays
+/// the most recently replayed register.
+pub(crate) fn replay_register(register: char, cx: &mut WindowContext) {
+    let Some(actions) = Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1);
+        vim.clear_operator(cx);
+
+        let register = if register == '@' {
+            vim.workspace_state.last_replayed_register?
+        } else {
+            register.to_ascii_lowercase()
+        };
+        vim.workspace_state.last_replayed_register = Some(register);
+
+        let recording = vim.workspace_state.recordings.get(&register)?;
+        let mut actions = Vec::with_capacity(recording.len() * count);
+        for _ in 0..count {
+            actions.extend(recording.iter().cloned());
+        }
+        Some(actions)
+    }) else {
+        return;
+    };
+
+    replay(actions, cx)
 }
 
 pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
@@ -166,34 +318,13 @@ pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
     }
 
     Vim::update(cx, |vim, _| vim.workspace_state.replaying = true);
-    let window = cx.window_handle();
-    cx.spawn(move |mut cx| async move {
-        editor.update(&mut cx, |editor, _| {
+    editor
+        .update(cx, |editor, _| {
             editor.show_local_selections = false;
-        })?;
-        for action in actions {
-            match action {
-                ReplayableAction::Action(action) => {
-                    if should_replay(&action) {
-                        window.update(&mut cx, |_, cx| cx.dispatch_action(action))
-                    } else {
-                        Ok(())
-                    }
-                }
-                ReplayableAction::Insertion {
-                    text,
-                    utf16_range_to_replace,
-                } => editor.update(&mut cx, |editor, cx| {
-                    editor.replay_insert_event(&text, utf16_range_to_replace.clone(), cx)
-                }),
-            }?
-        }
-        editor.update(&mut cx, |editor, _| {
-            editor.show_local_selections = true;
-        })?;
-        window.update(&mut cx, |_, cx| cx.dispatch_action(EndRepeat.boxed_clone()))
-    })
-    .detach_and_log_err(cx);
+        })
+        .ok();
+    actions.push(ReplayableAction::Action(EndRepeat.boxed_clone()));
+    replay(actions, cx)
 }
 
 #[cfg(test)]
@@ -207,8 +338,11 @@ mod test {
     use crate::{
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
+        Vim,
     };
 
+    use super::REPLAY_ABORTED_TOAST_ID;
+
     #[gpui::test]
     async fn test_dot_repeat(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -506,4 +640,87 @@ mod test {
         cx.simulate_shared_keystrokes(["."]).await;
         cx.assert_shared_state("ˇx hello\n").await;
     }
+
+    #[gpui::test]
+    async fn test_record_replay(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇhello world", Mode::Normal);
+        cx.simulate_keystrokes(["q", "w", "c", "w", "j", "escape", "q"]);
+        cx.assert_state("ˇj world", Mode::Normal);
+        cx.simulate_keystrokes(["2", "l", "@", "w"]);
+        cx.assert_state("j ˇj", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_record_replay_count(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇabcdefghijkl", Mode::Normal);
+        cx.simulate_keystrokes(["q", "a", "2", "x", "l", "q"]);
+        cx.assert_state("cˇdefghijkl", Mode::Normal);
+        cx.simulate_keystrokes(["2", "@", "a"]);
+        cx.assert_state("cfiˇjkl", Mode::Normal);
+
+        // @@ replays the last replayed register
+        cx.simulate_keystrokes(["@", "@"]);
+        cx.assert_state("cfiˇl", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_record_append(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇabcdef", Mode::Normal);
+        cx.simulate_keystrokes(["q", "a", "x", "q"]);
+        cx.simulate_keystrokes(["q", "shift-a", "l", "q"]);
+        cx.assert_state("bˇcdef", Mode::Normal);
+        cx.simulate_keystrokes(["@", "a"]);
+        cx.assert_state("bdˇef", Mode::Normal);
+
+        // lowercase recording replaces the register
+        cx.simulate_keystrokes(["q", "a", "q", "@", "a"]);
+        cx.assert_state("bdˇef", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_replay_nested(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc\nd\n", Mode::Normal);
+        cx.simulate_keystrokes(["q", "a", "i", "-", "escape", "q"]);
+        cx.simulate_keystrokes(["q", "b", "j", "@", "a", "q"]);
+        cx.assert_state("-a\nˇ-b\nc\nd\n", Mode::Normal);
+        cx.simulate_keystrokes(["2", "@", "b"]);
+        cx.assert_state("-a\n-b\n-c\nˇ-d\n", Mode::Normal);
+
+        // . inside a recording repeats the last change when replayed
+        cx.set_state("ˇa\nb\nc\n", Mode::Normal);
+        cx.simulate_keystrokes(["i", "+", "escape"]);
+        cx.simulate_keystrokes(["q", "c", "j", ".", "q", "@", "c"]);
+        cx.assert_state("+a\n+b\nˇ+c\n", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_repeat_aborted(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes(["x"]);
+        cx.simulate_keystrokes(["2", "0", "0", "0", "0", "."]);
+        cx.assert_state("ˇ", Mode::Normal);
+        assert!(!cx.read(|cx| cx.global::<Vim>().workspace_state.replaying));
+        cx.update_editor(|editor, _| assert!(editor.show_local_selections));
+        let message =
+            cx.workspace(|workspace, cx| workspace.toast_message(REPLAY_ABORTED_TOAST_ID, cx));
+        assert_eq!(
+            message.as_deref(),
+            Some("Aborted replay after 10000 actions.")
+        );
+
+        // Repeating still works after the aborted replay.
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes(["x", "."]);
+        cx.assert_state("ˇc", Mode::Normal);
+    }
 }
@@ -1,15 +1,32 @@
-use gpui::{actions, impl_actions, ViewContext};
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use command_palette_hooks::CommandPaletteInterceptHistory;
+use editor::{
+    display_map::{DisplaySnapshot, ToDisplayPoint},
+    scroll::Autoscroll,
+    Bias, DisplayPoint, Editor, MultiBufferSnapshot, ToPoint,
+};
+use gpui::{actions, impl_actions, ViewContext, WindowContext};
+use language::Point;
+use regex::Regex;
 use search::{buffer_search, BufferSearchBar, SearchMode, SearchOptions};
 use serde_derive::Deserialize;
-use workspace::{searchable::Direction, Workspace};
+use workspace::{searchable::Direction, Toast, Workspace};
 
 use crate::{
-    motion::Motion,
-    normal::move_cursor,
-    state::{Mode, SearchState},
+    command::{self, CommandRange},
+    helix::helix_motion,
+    motion::{motion, Motion},
+    normal::{mark::set_previous_context_mark, normal_motion},
+    pattern::{self, Pattern},
+    state::{Mode, Operator, Register, SearchState},
+    visual::visual_motion,
     Vim,
 };
 
+pub(crate) const SUBSTITUTE_COUNT_TOAST_ID: usize = 0x5375_6273;
+
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct MoveToNext {
@@ -36,20 +53,254 @@ pub struct FindCommand {
     pub backwards: bool,
 }
 
+/// `:[range]s/pattern/replacement/[flags]`, with the query starting at the
+/// delimiter after `s`.
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct ReplaceCommand {
+    pub range: Option<CommandRange>,
     pub query: String,
 }
 
-#[derive(Debug, Default)]
-struct Replacement {
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Replacement {
     search: String,
     replacement: String,
-    should_replace_all: bool,
-    is_case_sensitive: bool,
+    should_replace: bool,
+    should_replace_all_in_line: bool,
+    should_confirm: bool,
+    /// Set by the `i` and `I` flags, otherwise `ignorecase` decides.
+    is_case_sensitive: Option<bool>,
+}
+
+/// Where `/{pattern}/{offset}` leaves the cursor, relative to the match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum SearchOffset {
+    #[default]
+    None,
+    /// `[+-]N`: the start of the Nth line below (or above) the match.
+    Lines(i32),
+    /// `s[+-N]` or `b[+-N]`: N characters after the start of the match.
+    Start(i32),
+    /// `e[+-N]`: N characters after the last character of the match.
+    End(i32),
+}
+
+impl SearchOffset {
+    fn parse(offset: &str) -> Result<Self> {
+        let count = |count: &str| match count {
+            "" => Ok(0),
+            "+" => Ok(1),
+            "-" => Ok(-1),
+            count => count
+                .strip_prefix('+')
+                .unwrap_or(count)
+                .parse()
+                .map_err(|_| anyhow!("E476: Invalid search offset: {offset}")),
+        };
+        Ok(match offset.chars().next() {
+            None => SearchOffset::None,
+            Some('e') => SearchOffset::End(count(&offset[1..])?),
+            Some('s') | Some('b') => SearchOffset::Start(count(&offset[1..])?),
+            Some(_) => SearchOffset::Lines(count(offset)?),
+        })
+    }
+
+    /// Where the cursor goes for a match in the given range.
+    fn target(&self, buffer: &MultiBufferSnapshot, range: Range<usize>) -> usize {
+        match *self {
+            SearchOffset::None => range.start,
+            SearchOffset::Start(count) => offset_by_chars(buffer, range.start, count),
+            SearchOffset::End(count) => {
+                let last = if range.is_empty() {
+                    range.start
+                } else {
+                    offset_by_chars(buffer, range.end, -1)
+                };
+                offset_by_chars(buffer, last, count)
+            }
+            SearchOffset::Lines(count) => {
+                let row = buffer.offset_to_point(range.start).row as i64 + count as i64;
+                let row = row.clamp(0, buffer.max_buffer_row() as i64) as u32;
+                buffer.point_to_offset(Point::new(row, 0))
+            }
+        }
+    }
+}
+
+fn offset_by_chars(buffer: &MultiBufferSnapshot, offset: usize, count: i32) -> usize {
+    if count >= 0 {
+        buffer
+            .chars_at(offset)
+            .take(count as usize)
+            .fold(offset, |offset, c| offset + c.len_utf8())
+    } else {
+        buffer
+            .reversed_chars_at(offset)
+            .take(count.unsigned_abs() as usize)
+            .fold(offset, |offset, c| offset - c.len_utf8())
+    }
+}
+
+/// A search made with `/`, `?`, `*` or `#`, which `n` and `N` repeat.
+#[derive(Clone, Debug)]
+pub struct VimSearch {
+    pub(crate) pattern: Pattern,
+    /// Whether the search is case sensitive when the pattern has no `\c` or `\C`.
+    pub(crate) case_sensitive: bool,
+    pub(crate) direction: Direction,
+    pub(crate) offset: SearchOffset,
+    /// The compiled pattern, so that repeating the search doesn't rebuild it.
+    regex: Regex,
+}
+
+impl VimSearch {
+    pub(crate) fn new(
+        pattern: Pattern,
+        case_sensitive: bool,
+        direction: Direction,
+        offset: SearchOffset,
+    ) -> Result<Self> {
+        let regex = pattern.build(case_sensitive)?;
+        Ok(Self {
+            pattern,
+            case_sensitive,
+            direction,
+            offset,
+            regex,
+        })
+    }
+}
+
+impl PartialEq for VimSearch {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+            && self.case_sensitive == other.case_sensitive
+            && self.direction == other.direction
+            && self.offset == other.offset
+    }
+}
+
+impl Eq for VimSearch {}
+
+/// The number of lines searched around the cursor before searching twice as many.
+const SEARCH_WINDOW_ROWS: u32 = 256;
+
+/// The position of the `times`th match of `search` from `point`, with the search
+/// offset applied. The search wraps around the ends of the buffer.
+pub(crate) fn search_match(
+    map: &DisplaySnapshot,
+    point: DisplayPoint,
+    times: usize,
+    search: &VimSearch,
+) -> DisplayPoint {
+    let buffer = &map.buffer_snapshot;
+    let mut offset = point.to_offset(map, Bias::Left);
+    for _ in 0..times {
+        let target = match search.direction {
+            Direction::Next => next_target(buffer, search, Some(offset))
+                .or_else(|| next_target(buffer, search, None)),
+            Direction::Prev => previous_target(buffer, search, Some(offset))
+                .or_else(|| previous_target(buffer, search, None)),
+        };
+        let Some(target) = target else {
+            return point;
+        };
+        offset = target;
+    }
+    map.clip_point(
+        buffer.offset_to_point(offset).to_display_point(map),
+        Bias::Left,
+    )
 }
 
-actions!(vim, [SearchSubmit]);
+/// The targets of the matches in the given lines, stopping at a match that reaches
+/// the end of the text unless the lines run to the end of the buffer, as the
+/// match may continue past them.
+fn targets_in_rows<'a>(
+    buffer: &'a MultiBufferSnapshot,
+    search: &'a VimSearch,
+    rows: Range<u32>,
+    text: &'a mut String,
+) -> impl Iterator<Item = usize> + 'a {
+    let start = buffer.point_to_offset(Point::new(rows.start, 0));
+    let end = if rows.end > buffer.max_buffer_row() {
+        buffer.len()
+    } else {
+        buffer.point_to_offset(Point::new(rows.end, 0))
+    };
+    text.clear();
+    text.extend(buffer.text_for_range(start..end));
+    let text: &'a str = text;
+    let is_complete = end == buffer.len();
+    search
+        .regex
+        .captures_iter(text)
+        .take_while(move |captures| is_complete || captures.get(0).unwrap().end() < text.len())
+        .filter_map(move |captures| search.pattern.match_range(&captures))
+        .map(move |range| {
+            search
+                .offset
+                .target(buffer, start + range.start..start + range.end)
+        })
+}
+
+/// The first target after `offset`, or in the buffer if no offset is given,
+/// searching increasingly many lines from the cursor.
+fn next_target(
+    buffer: &MultiBufferSnapshot,
+    search: &VimSearch,
+    offset: Option<usize>,
+) -> Option<usize> {
+    let start_row = offset.map_or(0, |offset| buffer.offset_to_point(offset).row);
+    let mut text = String::new();
+    let mut row_count = SEARCH_WINDOW_ROWS;
+    loop {
+        let end_row = start_row.saturating_add(row_count);
+        let target = targets_in_rows(buffer, search, start_row..end_row, &mut text)
+            .find(|target| offset.map_or(true, |offset| *target > offset));
+        if target.is_some() || end_row > buffer.max_buffer_row() {
+            return target;
+        }
+        row_count = row_count.saturating_mul(2);
+    }
+}
+
+/// The last target before `offset`, or in the buffer if no offset is given,
+/// searching increasingly many lines from the cursor.
+fn previous_target(
+    buffer: &MultiBufferSnapshot,
+    search: &VimSearch,
+    offset: Option<usize>,
+) -> Option<usize> {
+    let end_row = offset.map_or(buffer.max_buffer_row(), |offset| {
+        buffer.offset_to_point(offset).row
+    }) + 1;
+    let mut text = String::new();
+    let mut row_count = SEARCH_WINDOW_ROWS;
+    loop {
+        let start_row = end_row.saturating_sub(row_count);
+        let target = targets_in_rows(buffer, search, start_row..end_row, &mut text)
+            .filter(|target| offset.map_or(true, |offset| *target < offset))
+            .last();
+        if target.is_some() || start_row == 0 {
+            return target;
+        }
+        row_count = row_count.saturating_mul(2);
+    }
+}
+
+/// Highlights the match waiting to be confirmed during a `:s///c`.
+struct SubstitutionHighlight;
+
+actions!(
+    vim,
+    [
+        SearchSubmit,
+        RepeatSubstitute,
+        RepeatSearch,
+        RepeatSearchReversed
+    ]
+);
 impl_actions!(
     vim,
     [FindCommand, ReplaceCommand, Search, MoveToPrev, MoveToNext]
@@ -61,9 +312,15 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
     workspace.register_action(search);
     workspace.register_action(search_submit);
     workspace.register_action(search_deploy);
+    workspace
+        .register_action(|workspace, _: &RepeatSearch, cx| repeat_search(workspace, false, cx));
+    workspace.register_action(|workspace, _: &RepeatSearchReversed, cx| {
+        repeat_search(workspace, true, cx)
+    });
 
     workspace.register_action(find_command);
     workspace.register_action(replace_command);
+    workspace.register_action(repeat_substitute);
 }
 
 fn move_to_next(workspace: &mut Workspace, action: &MoveToNext, cx: &mut ViewContext<Workspace>) {
@@ -83,6 +340,17 @@ fn search(workspace: &mut Workspace, action: &Search, cx: &mut ViewContext<Works
     };
     Vim::update(cx, |vim, cx| {
         let count = vim.take_count(cx).unwrap_or(1);
+        set_previous_context_mark(vim, cx);
+        let prior_selections = vim
+            .update_active_editor(cx, |_, editor, _| {
+                editor
+                    .selections
+                    .disjoint_anchors()
+                    .iter()
+                    .map(|selection| selection.range())
+                    .collect()
+            })
+            .unwrap_or_default();
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
                 search_bar.update(cx, |search_bar, cx| {
@@ -102,6 +370,7 @@ fn search(workspace: &mut Workspace, action: &Search, cx: &mut ViewContext<Works
                         direction,
                         count,
                         initial_query: query.clone(),
+                        prior_selections,
                     };
                 });
             }
@@ -111,34 +380,182 @@ fn search(workspace: &mut Workspace, action: &Search, cx: &mut ViewContext<Works
 
 // hook into the existing to clear out any vim search state on cmd+f or edit -> find.
 fn search_deploy(_: &mut Workspace, _: &buffer_search::Deploy, cx: &mut ViewContext<Workspace>) {
-    Vim::update(cx, |vim, _| vim.workspace_state.search = Default::default());
+    Vim::update(cx, |vim, _| {
+        vim.workspace_state.search = Default::default();
+        vim.workspace_state.last_search = None;
+    });
     cx.propagate();
 }
 
 fn search_submit(workspace: &mut Workspace, _: &SearchSubmit, cx: &mut ViewContext<Workspace>) {
-    Vim::update(cx, |vim, cx| {
+    let pane = workspace.active_pane().clone();
+    let result = Vim::update(cx, |vim, cx| {
+        let Some(search_bar) = pane
+            .read(cx)
+            .toolbar()
+            .read(cx)
+            .item_of_type::<BufferSearchBar>()
+        else {
+            return Ok(());
+        };
+        let query = search_bar.read(cx).query(cx);
+        let state = std::mem::take(&mut vim.workspace_state.search);
+        let direction = state.direction;
+        let delimiter = if direction == Direction::Prev {
+            '?'
+        } else {
+            '/'
+        };
+        push_search_history(delimiter, &query, cx);
+
+        let (pattern, offset) = command::split_pattern(&query, delimiter);
+        let pattern = if pattern.is_empty() {
+            previous_pattern(vim)?
+        } else {
+            pattern
+        };
+        vim.workspace_state
+            .registers
+            .insert('/', Register::from(pattern.clone()));
+        let mut translated = pattern::translate(&pattern)?;
+        if translated.case_sensitive.is_none() {
+            translated.case_sensitive = vim.workspace_state.options.case_sensitive(&pattern);
+        }
+        let hlsearch = vim.workspace_state.options.hlsearch();
+        let search = VimSearch::new(
+            translated,
+            search_bar
+                .read(cx)
+                .has_search_option(SearchOptions::CASE_SENSITIVE),
+            direction,
+            SearchOffset::parse(offset.unwrap_or_default())?,
+        )?;
+        vim.workspace_state.last_search = Some(search.clone());
+
+        if search.pattern.regex == query
+            && search.pattern.case_sensitive.is_none()
+            && search.offset == SearchOffset::None
+        {
+            search_bar.update(cx, |search_bar, cx| {
+                let mut count = state.count;
+                // in the case that the query has changed, the search bar
+                // will have selected the next match already.
+                if query != state.initial_query && direction == Direction::Next {
+                    count = count.saturating_sub(1)
+                }
+                search_bar.select_match(direction, count, cx);
+                search_bar.focus_editor(&Default::default(), cx);
+                if !hlsearch {
+                    search_bar.dismiss(&Default::default(), cx);
+                }
+            });
+            return Ok(());
+        }
+
+        // The search bar only understands the regex the pattern translates to, and
+        // may have moved the cursor while the pattern was typed, so the cursor
+        // is moved from where it was before the search.
+        let options = search.pattern.case_sensitive.map(|case_sensitive| {
+            if case_sensitive {
+                SearchOptions::CASE_SENSITIVE
+            } else {
+                SearchOptions::NONE
+            }
+        });
+        let search_task = search_bar.update(cx, |search_bar, cx| {
+            search_bar.focus_editor(&Default::default(), cx);
+            search_bar.search(&search.pattern.regex, options, cx)
+        });
+        let search_bar = search_bar.downgrade();
+        cx.spawn(|mut cx| async move {
+            search_task.await?;
+            search_bar.update(&mut cx, |search_bar, _| search_bar.add_to_history(query))?;
+            cx.update(|cx| {
+                Vim::update(cx, |vim, cx| {
+                    vim.update_active_editor(cx, |_, editor, cx| {
+                        editor.change_selections(None, cx, |s| {
+                            s.select_anchor_ranges(state.prior_selections)
+                        })
+                    });
+                });
+                move_to_match(search, state.count, cx)
+            })?;
+            if !hlsearch {
+                search_bar.update(&mut cx, |search_bar, cx| {
+                    search_bar.dismiss(&Default::default(), cx)
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+        anyhow::Ok(())
+    });
+
+    if let Err(err) = result {
+        workspace.show_error(&err, cx);
+    }
+}
+
+/// The pattern of the last search or substitution, used when none is given.
+fn previous_pattern(vim: &Vim) -> Result<String> {
+    vim.workspace_state
+        .registers
+        .get(&'/')
+        .map(|register| register.text.to_string())
+        .ok_or_else(|| anyhow!("E35: No previous regular expression"))
+}
+
+/// Adds a search to the command palette's history, where typing `/` or `?` and
+/// pressing up recalls it.
+fn push_search_history(delimiter: char, query: &str, cx: &mut WindowContext) {
+    let entry = format!("{delimiter}{query}");
+    let history = &mut cx.default_global::<CommandPaletteInterceptHistory>().0;
+    if history.last() != Some(&entry) {
+        history.push(entry);
+    }
+}
+
+/// Moves `count` matches of `search` from the cursor, applying its offset.
+fn move_to_match(search: VimSearch, count: usize, cx: &mut WindowContext) {
+    let motion = Motion::SearchMatch { search };
+    match Vim::read(cx).state().mode {
+        Mode::Normal => normal_motion(motion, None, Some(count), cx),
+        Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
+            visual_motion(motion, Some(count), cx)
+        }
+        Mode::HelixNormal => helix_motion(motion, Some(count), cx),
+        Mode::Insert | Mode::Replace => {}
+    }
+}
+
+/// `n` and `N` repeat the last search, in its own direction or the opposite one.
+fn repeat_search(workspace: &mut Workspace, reversed: bool, cx: &mut ViewContext<Workspace>) {
+    let Some(mut search) = Vim::read(cx).workspace_state.last_search.clone() else {
+        // searches started outside of vim, such as with cmd-f, are repeated by the
+        // search bar.
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1);
+        let direction = if reversed {
+            Direction::Prev
+        } else {
+            Direction::Next
+        };
         let pane = workspace.active_pane().clone();
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
                 search_bar.update(cx, |search_bar, cx| {
-                    let state = &mut vim.workspace_state.search;
-                    let mut count = state.count;
-                    let direction = state.direction;
-
-                    // in the case that the query has changed, the search bar
-                    // will have selected the next match already.
-                    if (search_bar.query(cx) != state.initial_query)
-                        && state.direction == Direction::Next
-                    {
-                        count = count.saturating_sub(1)
-                    }
-                    state.count = 1;
-                    search_bar.select_match(direction, count, cx);
-                    search_bar.focus_editor(&Default::default(), cx);
+                    search_bar.select_match(direction, count, cx)
                 });
             }
         });
-    })
+        return;
+    };
+    if reversed {
+        search.direction = match search.direction {
+            Direction::Next => Direction::Prev,
+            Direction::Prev => Direction::Next,
+        };
+    }
+    motion(Motion::SearchMatch { search }, cx)
 }
 
 pub fn move_to_internal(
@@ -150,31 +567,58 @@ pub fn move_to_internal(
     Vim::update(cx, |vim, cx| {
         let pane = workspace.active_pane().clone();
         let count = vim.take_count(cx).unwrap_or(1);
+        set_previous_context_mark(vim, cx);
 
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                // `*` and `#` follow `ignorecase`, but not `smartcase`.
+                let case_sensitive = !vim.workspace_state.options.ignorecase.unwrap_or(false);
+                let hlsearch = vim.workspace_state.options.hlsearch();
                 let search = search_bar.update(cx, |search_bar, cx| {
-                    let options = SearchOptions::CASE_SENSITIVE;
+                    let options = if case_sensitive {
+                        SearchOptions::CASE_SENSITIVE
+                    } else {
+                        SearchOptions::NONE
+                    };
                     if !search_bar.show(cx) {
                         return None;
                     }
-                    let Some(query) = search_bar.query_suggestion(cx) else {
+                    let Some(word) = search_bar.query_suggestion(cx) else {
                         return None;
                     };
-                    let mut query = regex::escape(&query);
+                    let mut pattern = pattern::escape(&word);
                     if whole_word {
-                        query = format!(r"\b{}\b", query);
+                        pattern = format!(r"\<{}\>", pattern);
                     }
+                    let delimiter = if direction == Direction::Prev {
+                        '?'
+                    } else {
+                        '/'
+                    };
+                    push_search_history(delimiter, &pattern, cx);
+                    let translated = pattern::translate(&pattern).ok()?;
+                    let query = translated.regex.clone();
+                    vim.workspace_state
+                        .registers
+                        .insert('/', Register::from(pattern.clone()));
+                    vim.workspace_state.last_search = Some(
+                        VimSearch::new(translated, case_sensitive, direction, SearchOffset::None)
+                            .ok()?,
+                    );
                     search_bar.activate_search_mode(SearchMode::Regex, cx);
-                    Some(search_bar.search(&query, Some(options), cx))
+                    Some((search_bar.search(&query, Some(options), cx), pattern))
                 });
 
-                if let Some(search) = search {
+                if let Some((search, pattern)) = search {
                     let search_bar = search_bar.downgrade();
                     cx.spawn(|_, mut cx| async move {
                         search.await?;
                         search_bar.update(&mut cx, |search_bar, cx| {
-                            search_bar.select_match(direction, count, cx)
+                            search_bar.add_to_history(pattern);
+                            search_bar.select_match(direction, count, cx);
+                            if !hlsearch {
+                                search_bar.dismiss(&Default::default(), cx);
+                            }
                         })?;
                         anyhow::Ok(())
                     })
@@ -192,6 +636,40 @@ pub fn move_to_internal(
 }
 
 fn find_command(workspace: &mut Workspace, action: &FindCommand, cx: &mut ViewContext<Workspace>) {
+    let direction = if action.backwards {
+        Direction::Prev
+    } else {
+        Direction::Next
+    };
+    let result = Vim::update(cx, |vim, _| {
+        let pattern = if action.query.is_empty() {
+            previous_pattern(vim)?
+        } else {
+            action.query.clone()
+        };
+        let mut translated = pattern::translate(&pattern)?;
+        if translated.case_sensitive.is_none() {
+            translated.case_sensitive = vim.workspace_state.options.case_sensitive(&pattern);
+        }
+        vim.workspace_state
+            .registers
+            .insert('/', Register::from(pattern));
+        vim.workspace_state.last_search = Some(VimSearch::new(
+            translated.clone(),
+            true,
+            direction,
+            SearchOffset::None,
+        )?);
+        anyhow::Ok(translated)
+    });
+    let translated = match result {
+        Ok(translated) => translated,
+        Err(err) => {
+            workspace.show_error(&err, cx);
+            return;
+        }
+    };
+
     let pane = workspace.active_pane().clone();
     pane.update(cx, |pane, cx| {
         if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
@@ -199,21 +677,15 @@ fn find_command(workspace: &mut Workspace, action: &FindCommand, cx: &mut ViewCo
                 if !search_bar.show(cx) {
                     return None;
                 }
-                let mut query = action.query.clone();
-                if query == "" {
-                    query = search_bar.query(cx);
+                let options = match translated.case_sensitive {
+                    Some(false) => SearchOptions::NONE,
+                    _ => SearchOptions::CASE_SENSITIVE,
                 };
-
                 search_bar.activate_search_mode(SearchMode::Regex, cx);
-                Some(search_bar.search(&query, Some(SearchOptions::CASE_SENSITIVE), cx))
+                Some(search_bar.search(&translated.regex, Some(options), cx))
             });
             let Some(search) = search else { return };
             let search_bar = search_bar.downgrade();
-            let direction = if action.backwards {
-                Direction::Prev
-            } else {
-                Direction::Next
-            };
             cx.spawn(|_, mut cx| async move {
                 search.await?;
                 search_bar.update(&mut cx, |search_bar, cx| {
@@ -231,67 +703,209 @@ fn replace_command(
     action: &ReplaceCommand,
     cx: &mut ViewContext<Workspace>,
 ) {
-    let replacement = parse_replace_all(&action.query);
-    let pane = workspace.active_pane().clone();
-    pane.update(cx, |pane, cx| {
-        let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() else {
-            return;
-        };
-        let search = search_bar.update(cx, |search_bar, cx| {
-            if !search_bar.show(cx) {
-                return None;
+    substitute(
+        workspace,
+        action.range.as_ref(),
+        parse_replace_all(&action.query),
+        cx,
+    )
+}
+
+/// `&` repeats the last `:s` on the current line, without its flags.
+fn repeat_substitute(
+    workspace: &mut Workspace,
+    _: &RepeatSubstitute,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(last) = Vim::read(cx).workspace_state.last_substitution.clone() else {
+        workspace.show_error(&anyhow!("E35: No previous regular expression"), cx);
+        return;
+    };
+    let replacement = Replacement {
+        search: last.search,
+        replacement: last.replacement,
+        should_replace: true,
+        ..Default::default()
+    };
+    substitute(workspace, None, replacement, cx)
+}
+
+fn substitute(
+    workspace: &mut Workspace,
+    range: Option<&CommandRange>,
+    mut replacement: Replacement,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let result = Vim::update(cx, |vim, cx| {
+        if replacement.search.is_empty() {
+            replacement.search = vim
+                .workspace_state
+                .registers
+                .get(&'/')
+                .map(|register| register.text.clone())
+                .ok_or_else(|| anyhow!("E35: No previous regular expression"))?;
+        }
+        vim.workspace_state
+            .registers
+            .insert('/', Register::from(replacement.search.clone()));
+        vim.workspace_state.last_substitution = Some(replacement.clone());
+        let pattern = pattern::translate(&replacement.search)?;
+        let case_sensitive = replacement
+            .is_case_sensitive
+            .or_else(|| {
+                vim.workspace_state
+                    .options
+                    .case_sensitive(&replacement.search)
+            })
+            .unwrap_or(true);
+        let regex = pattern.build(case_sensitive)?;
+
+        if vim.state().mode != Mode::Normal {
+            vim.switch_mode(Mode::Normal, false, cx);
+        }
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let rows = command::buffer_rows(vim, editor, range, cx)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut substitutions = Vec::new();
+            for row in rows.into_iter().flatten() {
+                let line = command::line_text(&snapshot, row);
+                for captures in regex.captures_iter(&line) {
+                    let Some(found) = pattern.match_range(&captures) else {
+                        continue;
+                    };
+                    let mut text = String::new();
+                    captures.expand(&replacement.replacement, &mut text);
+                    let start = snapshot.anchor_after(Point::new(row, found.start as u32));
+                    let end = if found.is_empty() {
+                        start
+                    } else {
+                        snapshot.anchor_before(Point::new(row, found.end as u32))
+                    };
+                    substitutions.push((start..end, text));
+                    if !replacement.should_replace_all_in_line {
+                        break;
+                    }
+                }
+            }
+            if substitutions.is_empty() {
+                return Err(anyhow!("E486: Pattern not found: {}", replacement.search));
             }
 
-            let mut options = SearchOptions::default();
-            if replacement.is_case_sensitive {
-                options.set(SearchOptions::CASE_SENSITIVE, true)
+            if !replacement.should_replace {
+                // `n` only reports the number of matches, like vim's message.
+                let mut rows = substitutions
+                    .iter()
+                    .map(|(range, _)| range.start.to_point(&snapshot).row)
+                    .collect::<Vec<_>>();
+                rows.dedup();
+                let lines = rows.len();
+                let matches = substitutions.len();
+                return anyhow::Ok(SubstituteOutcome::Counted(format!(
+                    "{matches} match{} on {lines} line{}",
+                    if matches == 1 { "" } else { "es" },
+                    if lines == 1 { "" } else { "s" },
+                )));
             }
-            let search = if replacement.search == "" {
-                search_bar.query(cx)
-            } else {
-                replacement.search
-            };
+            if replacement.should_confirm {
+                vim.workspace_state.pending_substitutions = substitutions;
+                show_next_substitution(vim, editor, cx);
+                return anyhow::Ok(SubstituteOutcome::Confirming);
+            }
+            let last_row = substitutions
+                .last()
+                .map(|(range, _)| range.start.to_point(&snapshot).row)
+                .unwrap_or_default();
+            editor.transact(cx, |editor, cx| {
+                editor.edit(substitutions, cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_ranges([Point::new(last_row, 0)..Point::new(last_row, 0)])
+                });
+            });
+            anyhow::Ok(SubstituteOutcome::Replaced)
+        })
+        .transpose()
+    });
 
-            search_bar.set_replacement(Some(&replacement.replacement), cx);
-            search_bar.activate_search_mode(SearchMode::Regex, cx);
-            Some(search_bar.search(&search, Some(options), cx))
-        });
-        let Some(search) = search else { return };
-        let search_bar = search_bar.downgrade();
-        cx.spawn(|_, mut cx| async move {
-            search.await?;
-            search_bar.update(&mut cx, |search_bar, cx| {
-                if replacement.should_replace_all {
-                    search_bar.select_last_match(cx);
-                    search_bar.replace_all(&Default::default(), cx);
-                    Vim::update(cx, |vim, cx| {
-                        move_cursor(
-                            vim,
-                            Motion::StartOfLine {
-                                display_lines: false,
-                            },
-                            None,
-                            cx,
-                        )
-                    })
+    match result {
+        Ok(Some(SubstituteOutcome::Confirming)) => Vim::update(cx, |vim, cx| {
+            vim.push_operator(Operator::ConfirmSubstitute, cx)
+        }),
+        Ok(Some(SubstituteOutcome::Counted(message))) => {
+            workspace.show_toast(Toast::new(SUBSTITUTE_COUNT_TOAST_ID, message), cx)
+        }
+        Ok(_) => {}
+        Err(err) => workspace.show_error(&err, cx),
+    }
+}
+
+enum SubstituteOutcome {
+    Replaced,
+    /// The matches are waiting to be confirmed one by one (`c`).
+    Confirming,
+    /// The matches were only counted (`n`).
+    Counted(String),
+}
+
+/// Highlights the next match of a `:s///c` and moves the cursor to it, or ends
+/// the substitution once all matches have been seen.
+fn show_next_substitution(vim: &mut Vim, editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some((range, _)) = vim.workspace_state.pending_substitutions.first().cloned() else {
+        editor.clear_background_highlights::<SubstitutionHighlight>(cx);
+        return;
+    };
+    editor.highlight_background::<SubstitutionHighlight>(
+        vec![range.clone()],
+        |colors| colors.search_match_background,
+        cx,
+    );
+    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+        s.select_anchor_ranges([range.start..range.start])
+    });
+}
+
+/// Handles the answer to "replace with ... (y/n/a/q/l)" during a `:s///c`.
+pub(crate) fn confirm_substitution(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let answer = text.chars().next();
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let pending = &mut vim.workspace_state.pending_substitutions;
+            let substitutions = match answer {
+                Some('y') | Some('l') if !pending.is_empty() => vec![pending.remove(0)],
+                Some('n') if !pending.is_empty() => {
+                    pending.remove(0);
+                    Vec::new()
                 }
-            })?;
-            anyhow::Ok(())
-        })
-        .detach_and_log_err(cx);
-    })
+                Some('a') => pending.drain(..).collect(),
+                _ => Vec::new(),
+            };
+            if !matches!(answer, Some('y') | Some('n')) {
+                pending.clear();
+            }
+            if !substitutions.is_empty() {
+                editor.transact(cx, |editor, cx| editor.edit(substitutions, cx));
+            }
+            show_next_substitution(vim, editor, cx);
+        });
+        if vim.workspace_state.pending_substitutions.is_empty() {
+            vim.clear_operator(cx);
+        }
+    });
+}
+
+/// Abandons the remaining matches of a `:s///c`.
+pub(crate) fn cancel_substitution(vim: &mut Vim, cx: &mut WindowContext) {
+    vim.workspace_state.pending_substitutions.clear();
+    vim.update_active_editor(cx, |_, editor, cx| {
+        editor.clear_background_highlights::<SubstitutionHighlight>(cx);
+    });
 }
 
-// convert a vim query into something more usable by zed.
-// we don't attempt to fully convert between the two regex syntaxes,
-// but we do flip \( and \) to ( and ) (and vice-versa) in the pattern,
-// and convert \0..\9 to $0..$9 in the replacement so that common idioms work.
+// split a vim query into its pattern, replacement and flags.
+// the pattern is kept in vim's syntax (see `pattern::translate`), while
+// \0..\9 and & are converted to ${0}..${9} in the replacement so that common
+// idioms work. The query starts with the delimiter, as in `/pattern/replacement/flags`.
 fn parse_replace_all(query: &str) -> Replacement {
     let mut chars = query.chars();
-    if Some('%') != chars.next() || Some('s') != chars.next() {
-        return Replacement::default();
-    }
-
     let Some(delimiter) = chars.next() else {
         return Replacement::default();
     };
@@ -311,11 +925,17 @@ fn parse_replace_all(query: &str) -> Replacement {
     for c in chars {
         if escaped {
             escaped = false;
-            if phase == 1 && c.is_digit(10) {
-                buffer.push('$')
-            // unescape escaped parens
-            } else if phase == 0 && c == '(' || c == ')' {
-            } else if c != delimiter {
+            if phase == 1 {
+                match c {
+                    '0'..='9' => buffer.push_str(&format!("${{{}}}", c)),
+                    'r' | 'n' => buffer.push('\n'),
+                    't' => buffer.push('\t'),
+                    '$' => buffer.push_str("$$"),
+                    _ => buffer.push(c),
+                }
+                continue;
+            }
+            if c != delimiter {
                 buffer.push('\\')
             }
             buffer.push(c)
@@ -331,11 +951,11 @@ fn parse_replace_all(query: &str) -> Replacement {
             } else {
                 break;
             }
+        } else if phase == 1 && c == '&' {
+            buffer.push_str("${0}")
+        } else if phase == 1 && c == '$' {
+            buffer.push_str("$$")
         } else {
-            // escape unescaped parens
-            if phase == 0 && c == '(' || c == ')' {
-                buffer.push('\\')
-            }
             buffer.push(c)
         }
     }
@@ -343,15 +963,19 @@ fn parse_replace_all(query: &str) -> Replacement {
     let mut replacement = Replacement {
         search,
         replacement,
-        should_replace_all: true,
-        is_case_sensitive: true,
+        should_replace: true,
+        should_replace_all_in_line: false,
+        should_confirm: false,
+        is_case_sensitive: None,
     };
 
     for c in flags.chars() {
         match c {
-            'g' | 'I' => {}
-            'c' | 'n' => replacement.should_replace_all = false,
-            'i' => replacement.is_case_sensitive = false,
+            'g' => replacement.should_replace_all_in_line = true,
+            'c' => replacement.should_confirm = true,
+            'n' => replacement.should_replace = false,
+            'i' => replacement.is_case_sensitive = Some(false),
+            'I' => replacement.is_case_sensitive = Some(true),
             _ => {}
         }
     }
@@ -361,6 +985,7 @@ fn parse_replace_all(query: &str) -> Replacement {
 
 #[cfg(test)]
 mod test {
+    use command_palette_hooks::CommandPaletteInterceptHistory;
     use editor::DisplayPoint;
     use search::BufferSearchBar;
 
@@ -485,6 +1110,94 @@ mod test {
         cx.assert_state("one two ˇone", Mode::Normal);
     }
 
+    #[gpui::test]
+    async fn test_search_offset(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two\nthree two\n", Mode::Normal);
+        cx.simulate_keystrokes(["/", "t", "w", "o", "/", "e", "enter"]);
+        cx.assert_state("one twˇo\nthree two\n", Mode::Normal);
+        // n repeats the offset
+        cx.simulate_keystrokes(["n"]);
+        cx.assert_state("one two\nthree twˇo\n", Mode::Normal);
+
+        cx.set_state("ˇone\ntwo\nthree\n", Mode::Normal);
+        cx.simulate_keystrokes(["/", "t", "h", "/", "s", "+", "2", "enter"]);
+        cx.assert_state("one\ntwo\nthˇree\n", Mode::Normal);
+
+        cx.set_state("ˇone\ntwo\nthree\n", Mode::Normal);
+        cx.simulate_keystrokes(["/", "t", "h", "/", "-", "enter"]);
+        cx.assert_state("one\nˇtwo\nthree\n", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_search_far_from_cursor(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let filler = "x\n".repeat(700);
+
+        cx.set_state(&format!("onˇe\n{filler}two one\n{filler}"), Mode::Normal);
+        cx.simulate_keystrokes(["/", "o", "n", "e", "/", "e", "enter"]);
+        cx.assert_state(&format!("one\n{filler}two onˇe\n{filler}"), Mode::Normal);
+        // the search wraps around the end and the start of the buffer
+        cx.simulate_keystrokes(["n"]);
+        cx.assert_state(&format!("onˇe\n{filler}two one\n{filler}"), Mode::Normal);
+        cx.simulate_keystrokes(["shift-n"]);
+        cx.assert_state(&format!("one\n{filler}two onˇe\n{filler}"), Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_search_vim_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇab abb b2 ab", Mode::Normal);
+        cx.simulate_keystrokes(["/", "\\", "<", "a", "b", "\\", ">", "enter"]);
+        cx.assert_state("ab abb b2 ˇab", Mode::Normal);
+        cx.simulate_keystrokes(["n"]);
+        cx.assert_state("ˇab abb b2 ab", Mode::Normal);
+
+        cx.set_state("ˇab abb ab2", Mode::Normal);
+        cx.simulate_keystrokes(["/", "a", "b", "\\", "z", "s", "2", "enter"]);
+        cx.assert_state("ab abb abˇ2", Mode::Normal);
+
+        cx.set_state("ˇone one ONE", Mode::Normal);
+        cx.simulate_keystrokes([
+            "/", "\\", "shift-c", "shift-o", "shift-n", "shift-e", "enter",
+        ]);
+        cx.assert_state("one one ˇONE", Mode::Normal);
+
+        cx.set_state("ˇone oone", Mode::Normal);
+        cx.simulate_keystrokes(["/", "\\", "v", "o", "+", "n", "enter"]);
+        cx.assert_state("one ˇoone", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_search_direction(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // n continues in the direction of the search, and N reverses it
+        cx.set_state("hi\nˇhi\nhi\n", Mode::Normal);
+        cx.simulate_keystrokes(["#"]);
+        cx.run_until_parked();
+        cx.assert_state("ˇhi\nhi\nhi\n", Mode::Normal);
+        cx.simulate_keystrokes(["n"]);
+        cx.assert_state("hi\nhi\nˇhi\n", Mode::Normal);
+        cx.simulate_keystrokes(["shift-n"]);
+        cx.assert_state("ˇhi\nhi\nhi\n", Mode::Normal);
+
+        cx.simulate_keystrokes(["?", "h", "i", "enter"]);
+        cx.assert_state("hi\nhi\nˇhi\n", Mode::Normal);
+        cx.simulate_keystrokes(["n"]);
+        cx.assert_state("hi\nˇhi\nhi\n", Mode::Normal);
+
+        // searches are added to the command history
+        cx.read(|cx| {
+            assert_eq!(
+                cx.global::<CommandPaletteInterceptHistory>().0,
+                vec!["?\\<hi\\>".to_string(), "?hi".to_string()]
+            )
+        });
+    }
+
     #[gpui::test]
     async fn test_non_vim_search(cx: &mut gpui::TestAppContext) {
         let mut cx = VimTestContext::new(cx, false).await;
@@ -508,4 +1221,16 @@ mod test {
         cx.assert_shared_state("a.c. abcd ˇa.c. abcd").await;
         cx.assert_shared_mode(Mode::Normal).await;
     }
+
+    #[gpui::test]
+    async fn test_repeat_substitute(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa a\na a", Mode::Normal);
+        cx.simulate_keystrokes([":", "s", "/", "a", "/", "b", "/", "g", "enter"]);
+        cx.assert_state("ˇb b\na a", Mode::Normal);
+        // the flags are not repeated
+        cx.simulate_keystrokes(["j", "&"]);
+        cx.assert_state("b b\nˇb a", Mode::Normal);
+    }
 }
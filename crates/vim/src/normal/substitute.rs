@@ -83,10 +83,12 @@ pub fn substitute(vim: &mut Vim, count: Option<usize>, line_mode: bool, cx: &mut
 
 #[cfg(test)]
 mod test {
+    use super::SubstituteLine;
     use crate::{
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
     };
+    use gpui::KeyBinding;
     use indoc::indoc;
 
     #[gpui::test]
@@ -230,7 +232,15 @@ mod test {
             "})
             .await;
 
-        // visual mode
+        // visual mode (zed binds S to surround the selection, as vim-surround
+        // does, so restore vim's own binding to compare with neovim)
+        cx.update(|cx| {
+            cx.bind_keys([KeyBinding::new(
+                "shift-s",
+                SubstituteLine,
+                Some("Editor && vim_mode == visual && !VimWaiting && !VimObject"),
+            )])
+        });
         cx.set_shared_state(initial_state).await;
         cx.simulate_shared_keystrokes(["v", "k", "shift-s", "o"])
             .await;
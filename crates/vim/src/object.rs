@@ -1,8 +1,8 @@
 use std::ops::Range;
 
 use crate::{
-    motion::right, normal::normal_object, state::Mode, utils::coerce_punctuation,
-    visual::visual_object, Vim,
+    helix::helix_object, motion::right, normal::normal_object, state::Mode,
+    utils::coerce_punctuation, visual::visual_object, Vim,
 };
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
@@ -98,7 +98,8 @@ fn object(object: Object, cx: &mut WindowContext) {
     match Vim::read(cx).state().mode {
         Mode::Normal => normal_object(object, cx),
         Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_object(object, cx),
-        Mode::Insert => {
+        Mode::HelixNormal => helix_object(object, cx),
+        Mode::Insert | Mode::Replace => {
             // Shouldn't execute a text object in insert mode. Ignoring
         }
     }
@@ -0,0 +1,520 @@
+//! Vim's `:set` and `:setlocal`, for the options that correspond to Zed settings.
+//!
+//! Options are not written to the settings file. Window and buffer local options
+//! override the settings of the active editor; `:set` also applies them to the
+//! editors opened afterwards, while `:setlocal` only changes the active editor.
+//! The search options (`ignorecase`, `smartcase` and `hlsearch`) are global.
+
+use std::num::NonZeroU32;
+
+use anyhow::{anyhow, Result};
+use editor::{Editor, SoftWrap};
+use gpui::{impl_actions, ViewContext};
+use language::language_settings::{self, ShowWhitespaceSetting};
+use serde_derive::Deserialize;
+use workspace::{Toast, Workspace};
+
+use crate::Vim;
+
+/// The toast that shows the values asked for with `:set {option}?`.
+pub(crate) const SHOW_OPTIONS_TOAST_ID: usize = 0x5e7_0b7;
+
+/// `:se[t] {option}...` and `:setl[ocal] {option}...`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SetCommand {
+    pub local: bool,
+    pub args: String,
+}
+
+impl_actions!(vim, [SetCommand]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(set_options);
+}
+
+/// The values given to options with `:set`. Unset options leave the settings
+/// (or for the search options, the search bar) as they are.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VimOptions {
+    pub number: Option<bool>,
+    pub relativenumber: Option<bool>,
+    pub wrap: Option<bool>,
+    /// Set by both `tabstop` and `shiftwidth`, as Zed uses the same width for both.
+    pub tabstop: Option<NonZeroU32>,
+    pub expandtab: Option<bool>,
+    pub list: Option<bool>,
+    pub colorcolumn: Option<Vec<usize>>,
+    pub ignorecase: Option<bool>,
+    pub smartcase: Option<bool>,
+    pub hlsearch: Option<bool>,
+}
+
+impl VimOptions {
+    /// Applies the window and buffer local options to an editor.
+    pub(crate) fn apply(&self, editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if let Some(number) = self.number {
+            editor.set_show_line_numbers(number, cx);
+        }
+        if let Some(relativenumber) = self.relativenumber {
+            editor.set_relative_line_numbers(relativenumber, cx);
+        }
+        if let Some(wrap) = self.wrap {
+            let soft_wrap = if wrap {
+                match editor.buffer().read(cx).settings_at(0, cx).soft_wrap {
+                    language_settings::SoftWrap::None => language_settings::SoftWrap::EditorWidth,
+                    soft_wrap => soft_wrap,
+                }
+            } else {
+                language_settings::SoftWrap::None
+            };
+            editor.set_soft_wrap_mode(soft_wrap, cx);
+        }
+        if let Some(tabstop) = self.tabstop {
+            editor.set_tab_size(tabstop, cx);
+        }
+        if let Some(expandtab) = self.expandtab {
+            editor.set_hard_tabs(!expandtab, cx);
+        }
+        if let Some(list) = self.list {
+            // `nolist` goes back to the `show_whitespaces` setting.
+            editor.set_show_whitespaces(list.then_some(ShowWhitespaceSetting::All), cx);
+        }
+        if let Some(colorcolumn) = &self.colorcolumn {
+            editor.set_wrap_guides(colorcolumn.clone(), cx);
+        }
+    }
+
+    /// Whether a search for `pattern` should be case sensitive, if `ignorecase`
+    /// has been set.
+    pub(crate) fn case_sensitive(&self, pattern: &str) -> Option<bool> {
+        let ignorecase = self.ignorecase?;
+        Some(
+            !ignorecase
+                || (self.smartcase.unwrap_or(false) && pattern.chars().any(char::is_uppercase)),
+        )
+    }
+
+    pub(crate) fn hlsearch(&self) -> bool {
+        self.hlsearch.unwrap_or(true)
+    }
+
+    fn merge(&mut self, other: &VimOptions) {
+        fn merge<T: Clone>(value: &mut Option<T>, other: &Option<T>) {
+            if other.is_some() {
+                *value = other.clone();
+            }
+        }
+        merge(&mut self.number, &other.number);
+        merge(&mut self.relativenumber, &other.relativenumber);
+        merge(&mut self.wrap, &other.wrap);
+        merge(&mut self.tabstop, &other.tabstop);
+        merge(&mut self.expandtab, &other.expandtab);
+        merge(&mut self.list, &other.list);
+        merge(&mut self.colorcolumn, &other.colorcolumn);
+        merge(&mut self.ignorecase, &other.ignorecase);
+        merge(&mut self.smartcase, &other.smartcase);
+        merge(&mut self.hlsearch, &other.hlsearch);
+    }
+
+    fn set(&mut self, option: VimOption, value: OptionValue) {
+        match (option, value) {
+            (VimOption::Number, OptionValue::Bool(value)) => self.number = Some(value),
+            (VimOption::RelativeNumber, OptionValue::Bool(value)) => {
+                self.relativenumber = Some(value)
+            }
+            (VimOption::Wrap, OptionValue::Bool(value)) => self.wrap = Some(value),
+            (VimOption::TabStop | VimOption::ShiftWidth, OptionValue::Number(value)) => {
+                self.tabstop = Some(value)
+            }
+            (VimOption::ExpandTab, OptionValue::Bool(value)) => self.expandtab = Some(value),
+            (VimOption::List, OptionValue::Bool(value)) => self.list = Some(value),
+            (VimOption::ColorColumn, OptionValue::List(value)) => self.colorcolumn = Some(value),
+            (VimOption::IgnoreCase, OptionValue::Bool(value)) => self.ignorecase = Some(value),
+            (VimOption::SmartCase, OptionValue::Bool(value)) => self.smartcase = Some(value),
+            (VimOption::HlSearch, OptionValue::Bool(value)) => self.hlsearch = Some(value),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum VimOption {
+    Number,
+    RelativeNumber,
+    Wrap,
+    TabStop,
+    ShiftWidth,
+    ExpandTab,
+    List,
+    ColorColumn,
+    IgnoreCase,
+    SmartCase,
+    HlSearch,
+}
+
+impl VimOption {
+    const ALL: [VimOption; 11] = [
+        VimOption::ColorColumn,
+        VimOption::ExpandTab,
+        VimOption::HlSearch,
+        VimOption::IgnoreCase,
+        VimOption::List,
+        VimOption::Number,
+        VimOption::RelativeNumber,
+        VimOption::ShiftWidth,
+        VimOption::SmartCase,
+        VimOption::TabStop,
+        VimOption::Wrap,
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|option| option.name() == name || option.short_name() == name)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            VimOption::Number => "number",
+            VimOption::RelativeNumber => "relativenumber",
+            VimOption::Wrap => "wrap",
+            VimOption::TabStop => "tabstop",
+            VimOption::ShiftWidth => "shiftwidth",
+            VimOption::ExpandTab => "expandtab",
+            VimOption::List => "list",
+            VimOption::ColorColumn => "colorcolumn",
+            VimOption::IgnoreCase => "ignorecase",
+            VimOption::SmartCase => "smartcase",
+            VimOption::HlSearch => "hlsearch",
+        }
+    }
+
+    fn short_name(&self) -> &'static str {
+        match self {
+            VimOption::Number => "nu",
+            VimOption::RelativeNumber => "rnu",
+            VimOption::Wrap => "wrap",
+            VimOption::TabStop => "ts",
+            VimOption::ShiftWidth => "sw",
+            VimOption::ExpandTab => "et",
+            VimOption::List => "list",
+            VimOption::ColorColumn => "cc",
+            VimOption::IgnoreCase => "ic",
+            VimOption::SmartCase => "scs",
+            VimOption::HlSearch => "hls",
+        }
+    }
+
+    fn is_boolean(&self) -> bool {
+        !matches!(
+            self,
+            VimOption::TabStop | VimOption::ShiftWidth | VimOption::ColorColumn
+        )
+    }
+
+    fn is_global(&self) -> bool {
+        matches!(
+            self,
+            VimOption::IgnoreCase | VimOption::SmartCase | VimOption::HlSearch
+        )
+    }
+
+    fn value(
+        &self,
+        editor: &Editor,
+        options: &VimOptions,
+        cx: &ViewContext<Editor>,
+    ) -> OptionValue {
+        match self {
+            VimOption::Number => OptionValue::Bool(editor.show_line_numbers(cx)),
+            VimOption::RelativeNumber => OptionValue::Bool(editor.relative_line_numbers(cx)),
+            VimOption::Wrap => {
+                OptionValue::Bool(!matches!(editor.soft_wrap_mode(cx), SoftWrap::None))
+            }
+            VimOption::TabStop | VimOption::ShiftWidth => {
+                OptionValue::Number(editor.indent_settings(cx).0)
+            }
+            VimOption::ExpandTab => OptionValue::Bool(!editor.indent_settings(cx).1),
+            VimOption::List => {
+                OptionValue::Bool(editor.show_whitespaces(cx) == ShowWhitespaceSetting::All)
+            }
+            VimOption::ColorColumn => OptionValue::List(
+                editor
+                    .wrap_guides(cx)
+                    .into_iter()
+                    .filter(|(_, is_soft_wrap)| !is_soft_wrap)
+                    .map(|(column, _)| column)
+                    .collect(),
+            ),
+            VimOption::IgnoreCase => OptionValue::Bool(options.ignorecase.unwrap_or(false)),
+            VimOption::SmartCase => OptionValue::Bool(options.smartcase.unwrap_or(false)),
+            VimOption::HlSearch => OptionValue::Bool(options.hlsearch()),
+        }
+    }
+
+    /// Formats the value as vim does, e.g. `nonumber` or `tabstop=4`.
+    fn describe(&self, value: &OptionValue) -> String {
+        match value {
+            OptionValue::Bool(true) => self.name().to_string(),
+            OptionValue::Bool(false) => format!("no{}", self.name()),
+            OptionValue::Number(number) => format!("{}={}", self.name(), number),
+            OptionValue::List(columns) => format!(
+                "{}={}",
+                self.name(),
+                columns
+                    .iter()
+                    .map(|column| column.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum OptionValue {
+    Bool(bool),
+    Number(NonZeroU32),
+    List(Vec<usize>),
+}
+
+/// What an argument to `:set` does to an option.
+#[derive(Debug, PartialEq)]
+enum Change<'a> {
+    /// `opt?`, or `opt` for an option that isn't boolean.
+    Show,
+    /// `opt` and `noopt`.
+    Enable(bool),
+    /// `invopt` and `opt!`.
+    Toggle,
+    /// `opt=value` and `opt:value`.
+    Set(&'a str),
+    /// `opt+=value`.
+    Add(&'a str),
+    /// `opt-=value`.
+    Remove(&'a str),
+}
+
+fn parse_argument(arg: &str) -> Result<(VimOption, Change)> {
+    let unknown = || anyhow!("E518: Unknown option: {}", arg);
+    let invalid = || anyhow!("E474: Invalid argument: {}", arg);
+    let option = |name: &str| VimOption::from_name(name).ok_or_else(unknown);
+
+    let (option, change) = if let Some(name) = arg.strip_suffix('?') {
+        (option(name)?, Change::Show)
+    } else if let Some(name) = arg.strip_suffix('!') {
+        (option(name)?, Change::Toggle)
+    } else if let Some(ix) = arg.find(['=', ':']) {
+        let (name, value) = (&arg[..ix], &arg[ix + 1..]);
+        if let Some(name) = name.strip_suffix('+') {
+            (option(name)?, Change::Add(value))
+        } else if let Some(name) = name.strip_suffix('-') {
+            (option(name)?, Change::Remove(value))
+        } else {
+            (option(name)?, Change::Set(value))
+        }
+    } else if let Some(option) = VimOption::from_name(arg) {
+        if option.is_boolean() {
+            (option, Change::Enable(true))
+        } else {
+            (option, Change::Show)
+        }
+    } else if let Some(name) = arg.strip_prefix("no") {
+        (option(name)?, Change::Enable(false))
+    } else if let Some(name) = arg.strip_prefix("inv") {
+        (option(name)?, Change::Toggle)
+    } else {
+        return Err(unknown());
+    };
+
+    let is_valid = match change {
+        Change::Show => true,
+        Change::Enable(_) | Change::Toggle => option.is_boolean(),
+        Change::Set(_) | Change::Add(_) | Change::Remove(_) => !option.is_boolean(),
+    };
+    if !is_valid {
+        return Err(invalid());
+    }
+    Ok((option, change))
+}
+
+/// Returns the value of an option after a change, or `None` if the change only
+/// shows the value.
+fn changed_value(arg: &str, current: OptionValue, change: Change) -> Result<Option<OptionValue>> {
+    let invalid = || anyhow!("E521: Number required after =: {}", arg);
+    let number = |value: &str| value.parse::<u32>().map_err(|_| invalid());
+    let columns = |value: &str| {
+        value
+            .split(',')
+            .filter(|column| !column.is_empty())
+            .map(|column| column.parse::<usize>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>>>()
+    };
+    let positive = |number: u32| {
+        NonZeroU32::new(number).ok_or_else(|| anyhow!("E487: Argument must be positive: {}", arg))
+    };
+
+    let value = match (change, current) {
+        (Change::Show, _) => return Ok(None),
+        (Change::Enable(enable), _) => OptionValue::Bool(enable),
+        (Change::Toggle, OptionValue::Bool(value)) => OptionValue::Bool(!value),
+        (Change::Set(value), OptionValue::Number(_)) => {
+            OptionValue::Number(positive(number(value)?)?)
+        }
+        (Change::Add(value), OptionValue::Number(current)) => {
+            OptionValue::Number(positive(current.get().saturating_add(number(value)?))?)
+        }
+        (Change::Remove(value), OptionValue::Number(current)) => {
+            OptionValue::Number(positive(current.get().saturating_sub(number(value)?))?)
+        }
+        (Change::Set(value), OptionValue::List(_)) => OptionValue::List(columns(value)?),
+        (Change::Add(value), OptionValue::List(mut current)) => {
+            for column in columns(value)? {
+                if !current.contains(&column) {
+                    current.push(column);
+                }
+            }
+            OptionValue::List(current)
+        }
+        (Change::Remove(value), OptionValue::List(mut current)) => {
+            let removed = columns(value)?;
+            current.retain(|column| !removed.contains(column));
+            OptionValue::List(current)
+        }
+        _ => return Err(anyhow!("E474: Invalid argument: {}", arg)),
+    };
+    Ok(Some(value))
+}
+
+fn set_options(workspace: &mut Workspace, action: &SetCommand, cx: &mut ViewContext<Workspace>) {
+    let result = Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let mut shown = Vec::new();
+            if action.args.is_empty() || action.args == "all" {
+                for option in VimOption::ALL {
+                    let value = option.value(editor, &vim.workspace_state.options, cx);
+                    shown.push(option.describe(&value));
+                }
+                return Ok(shown);
+            }
+
+            for arg in action.args.split_whitespace() {
+                let (option, change) = parse_argument(arg)?;
+                let current = option.value(editor, &vim.workspace_state.options, cx);
+                let Some(value) = changed_value(arg, current.clone(), change)? else {
+                    shown.push(option.describe(&current));
+                    continue;
+                };
+
+                let mut changed = VimOptions::default();
+                changed.set(option, value);
+                if !option.is_global() {
+                    changed.apply(editor, cx);
+                }
+                if option.is_global() || !action.local {
+                    vim.workspace_state.options.merge(&changed);
+                }
+            }
+            anyhow::Ok(shown)
+        })
+    });
+
+    match result {
+        Some(Ok(shown)) if !shown.is_empty() => {
+            workspace.show_toast(Toast::new(SHOW_OPTIONS_TOAST_ID, shown.join("  ")), cx)
+        }
+        Some(Err(err)) => workspace.show_error(&err, cx),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use editor::SoftWrap;
+    use language::language_settings::{AllLanguageSettings, ShowWhitespaceSetting};
+    use settings::SettingsStore;
+
+    use super::SHOW_OPTIONS_TOAST_ID;
+    use crate::{state::Mode, test::VimTestContext};
+
+    fn ex_command(cx: &mut VimTestContext, command: &str) {
+        cx.simulate_keystroke(":");
+        for c in command.chars() {
+            cx.simulate_keystroke(&c.to_string());
+        }
+        cx.simulate_keystroke("enter");
+    }
+
+    #[gpui::test]
+    async fn test_set_options(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two", Mode::Normal);
+        ex_command(&mut cx, "set nonu rnu ts=8 noet list cc=80,100");
+        cx.update_editor(|editor, cx| {
+            assert!(!editor.show_line_numbers(cx));
+            assert!(editor.relative_line_numbers(cx));
+            assert_eq!(editor.indent_settings(cx).0.get(), 8);
+            assert!(editor.indent_settings(cx).1);
+            assert_eq!(editor.show_whitespaces(cx), ShowWhitespaceSetting::All);
+            assert_eq!(
+                editor.wrap_guides(cx).into_iter().collect::<Vec<_>>(),
+                vec![(80, false), (100, false)]
+            );
+        });
+
+        ex_command(&mut cx, "setlocal invlist ts-=4 cc-=80 nowrap");
+        cx.update_editor(|editor, cx| {
+            assert_eq!(
+                editor.show_whitespaces(cx),
+                ShowWhitespaceSetting::Selection
+            );
+            assert_eq!(editor.indent_settings(cx).0.get(), 4);
+            assert_eq!(
+                editor.wrap_guides(cx).into_iter().collect::<Vec<_>>(),
+                vec![(100, false)]
+            );
+            assert!(matches!(editor.soft_wrap_mode(cx), SoftWrap::None));
+        });
+
+        ex_command(&mut cx, "set ts? cc?");
+        let message =
+            cx.workspace(|workspace, cx| workspace.toast_message(SHOW_OPTIONS_TOAST_ID, cx));
+        assert_eq!(message.as_deref(), Some("tabstop=4  colorcolumn=100"));
+
+        // nolist goes back to the show_whitespaces setting
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<AllLanguageSettings>(cx, |s| {
+                s.defaults.show_whitespaces = Some(ShowWhitespaceSetting::None)
+            });
+        });
+        cx.update_editor(|editor, cx| {
+            assert_eq!(editor.show_whitespaces(cx), ShowWhitespaceSetting::None);
+        });
+
+        // tabs are indented with the new settings
+        cx.set_state("ˇone", Mode::Normal);
+        cx.simulate_keystrokes([">", ">"]);
+        cx.assert_state("\tˇone", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_set_ignorecase(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone One ONE", Mode::Normal);
+        ex_command(&mut cx, "set ic scs");
+        cx.simulate_keystrokes(["*"]);
+        cx.run_until_parked();
+        cx.assert_state("one ˇOne ONE", Mode::Normal);
+
+        // smartcase: a pattern with uppercase letters is case sensitive
+        cx.simulate_keystrokes(["/", "shift-o", "shift-n", "shift-e", "enter"]);
+        cx.run_until_parked();
+        cx.assert_state("one One ˇONE", Mode::Normal);
+
+        cx.set_state("ˇone One ONE", Mode::Normal);
+        ex_command(&mut cx, "%s/one/x/g");
+        cx.assert_state("ˇx x x", Mode::Normal);
+    }
+}
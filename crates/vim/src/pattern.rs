@@ -0,0 +1,434 @@
+//! Translation of vim's regular expressions into the syntax of the `regex` crate,
+//! which is what zed's searches use.
+//!
+//! Most of `:help pattern` is supported: the `\v`, `\m`, `\M` and `\V` magic
+//! levels, `\<` and `\>` word boundaries, `\{n,m}` multis, the character class
+//! escapes, `\zs`/`\ze` and the `\c`/`\C` case flags. Features that have no
+//! equivalent in the `regex` crate (back references, look-around) are reported as
+//! errors instead of silently searching for something else.
+
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex, RegexBuilder};
+
+/// The name of the capture group holding the part of a match between `\zs` and
+/// `\ze`.
+pub(crate) const MATCH_GROUP: &str = "vimmatch";
+
+/// The characters that have a special meaning, with or without a backslash,
+/// depending on the magic level.
+const SPECIAL: &str = "()|+?={}<>@%&.*[]~^$";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Magic {
+    /// `\v`: every special character is special without a backslash.
+    Very,
+    /// `\m`, the default: `.`, `*`, `[`, `~`, `^` and `$` are special.
+    Magic,
+    /// `\M`: only `^` and `$` are special.
+    NoMagic,
+    /// `\V`: only the backslash is special.
+    VeryNoMagic,
+}
+
+impl Magic {
+    fn special_without_backslash(&self, c: char) -> bool {
+        match self {
+            Magic::Very => true,
+            Magic::Magic => ".*[~^$".contains(c),
+            Magic::NoMagic => "^$".contains(c),
+            Magic::VeryNoMagic => false,
+        }
+    }
+}
+
+/// A vim pattern translated for the `regex` crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Pattern {
+    pub regex: String,
+    /// Set by `\c` (false) or `\C` (true) in the pattern.
+    pub case_sensitive: Option<bool>,
+    /// Whether the pattern used `\zs` or `\ze`, so that the match itself is in
+    /// the [`MATCH_GROUP`] capture group.
+    pub has_match_group: bool,
+}
+
+impl Pattern {
+    /// Compiles the pattern, ignoring case if `case_sensitive` is false and the
+    /// pattern has no `\C`. `^` and `$` match at line boundaries, as in vim.
+    pub fn build(&self, case_sensitive: bool) -> Result<Regex> {
+        Ok(RegexBuilder::new(&self.regex)
+            .case_insensitive(!self.case_sensitive.unwrap_or(case_sensitive))
+            .multi_line(true)
+            .build()?)
+    }
+
+    /// The range of a match, narrowed to the part between `\zs` and `\ze`.
+    pub fn match_range(&self, captures: &Captures) -> Option<Range<usize>> {
+        let found = if self.has_match_group {
+            captures.name(MATCH_GROUP)
+        } else {
+            captures.get(0)
+        };
+        found.map(|found| found.range())
+    }
+}
+
+/// One character of the pattern, with whether it has a special meaning.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Special(char),
+    /// A backslash followed by a letter, digit or `_`, which is special in every
+    /// magic level.
+    Escape(char),
+    Literal(char),
+}
+
+struct Translator<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    magic: Magic,
+    output: String,
+    case_sensitive: Option<bool>,
+    match_start: Option<usize>,
+    match_end: Option<usize>,
+}
+
+impl<'a> Translator<'a> {
+    fn next_token(&mut self) -> Option<Token> {
+        let c = self.chars.next()?;
+        if c != '\\' {
+            if SPECIAL.contains(c) && self.magic.special_without_backslash(c) {
+                return Some(Token::Special(c));
+            }
+            return Some(Token::Literal(c));
+        }
+        let Some(c) = self.chars.next() else {
+            return Some(Token::Literal('\\'));
+        };
+        if c.is_ascii_alphanumeric() || c == '_' {
+            Some(Token::Escape(c))
+        } else if SPECIAL.contains(c) && !self.magic.special_without_backslash(c) {
+            Some(Token::Special(c))
+        } else {
+            Some(Token::Literal(c))
+        }
+    }
+
+    fn peek_token(&self) -> Option<Token> {
+        let mut translator = Translator {
+            chars: self.chars.clone(),
+            magic: self.magic,
+            output: String::new(),
+            case_sensitive: None,
+            match_start: None,
+            match_end: None,
+        };
+        translator.next_token()
+    }
+
+    /// `^` is only special at the start of a branch.
+    fn at_branch_start(&self) -> bool {
+        self.output.is_empty()
+            || self.output.ends_with("(?:")
+            || (self.output.ends_with('(') || self.output.ends_with('|'))
+                && !self.output.ends_with("\\(")
+                && !self.output.ends_with("\\|")
+    }
+
+    /// `$` is only special at the end of a branch.
+    fn at_branch_end(&self) -> bool {
+        matches!(
+            self.peek_token(),
+            None | Some(Token::Special('|')) | Some(Token::Special(')'))
+        )
+    }
+
+    fn push_literal(&mut self, c: char) {
+        let mut buffer = [0; 4];
+        self.output
+            .push_str(&regex::escape(c.encode_utf8(&mut buffer)));
+    }
+
+    fn translate(&mut self) -> Result<()> {
+        while let Some(token) = self.next_token() {
+            match token {
+                Token::Special('^') if self.at_branch_start() => self.output.push('^'),
+                Token::Special('$') if self.at_branch_end() => self.output.push('$'),
+                Token::Literal(c) => self.push_literal(c),
+                Token::Special(c) => self.special(c)?,
+                Token::Escape(c) => self.escape(c)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn special(&mut self, c: char) -> Result<()> {
+        match c {
+            '(' | ')' | '|' | '+' | '.' | '*' => self.output.push(c),
+            '?' | '=' => self.output.push('?'),
+            '<' | '>' => self.output.push_str("\\b"),
+            '{' => self.multi()?,
+            '[' => self.collection(),
+            '%' => {
+                if self.peek_token() == Some(Token::Special('(')) {
+                    self.next_token();
+                    self.output.push_str("(?:");
+                } else {
+                    return Err(anyhow!("E71: Invalid character after \\%"));
+                }
+            }
+            '@' => return Err(anyhow!("look-around (\\@) is not supported")),
+            '&' => return Err(anyhow!("concats (\\&) are not supported")),
+            // `^`, `$` and `~` in the middle of a pattern match themselves.
+            _ => self.push_literal(c),
+        }
+        Ok(())
+    }
+
+    fn escape(&mut self, c: char) -> Result<()> {
+        let class = match c {
+            's' | 'S' | 'd' | 'D' | 'w' | 'W' => {
+                self.output.push('\\');
+                self.output.push(c);
+                return Ok(());
+            }
+            'a' => "[a-zA-Z]",
+            'A' => "[^a-zA-Z]",
+            'l' => "[a-z]",
+            'L' => "[^a-z]",
+            'u' => "[A-Z]",
+            'U' => "[^A-Z]",
+            'x' => "[0-9A-Fa-f]",
+            'X' => "[^0-9A-Fa-f]",
+            'o' => "[0-7]",
+            'O' => "[^0-7]",
+            'h' => "[A-Za-z_]",
+            'H' => "[^A-Za-z_]",
+            'n' => "\\n",
+            't' => "\\t",
+            'r' => "\\r",
+            'e' => "\\x1b",
+            'c' | 'C' => {
+                self.case_sensitive = Some(c == 'C');
+                return Ok(());
+            }
+            'v' | 'm' | 'M' | 'V' => {
+                self.magic = match c {
+                    'v' => Magic::Very,
+                    'm' => Magic::Magic,
+                    'M' => Magic::NoMagic,
+                    _ => Magic::VeryNoMagic,
+                };
+                return Ok(());
+            }
+            'z' => {
+                match self.chars.next() {
+                    Some('s') => self.match_start = Some(self.output.len()),
+                    Some('e') => self.match_end = Some(self.output.len()),
+                    _ => return Err(anyhow!("E68: Invalid character after \\z")),
+                }
+                return Ok(());
+            }
+            '_' => match self.chars.next() {
+                Some('s') => "\\s",
+                Some('.') => "(?s:.)",
+                _ => return Err(anyhow!("only \\_s and \\_. are supported after \\_")),
+            },
+            '1'..='9' => return Err(anyhow!("back references (\\{c}) are not supported")),
+            c => {
+                self.push_literal(c);
+                return Ok(());
+            }
+        };
+        self.output.push_str(class);
+        Ok(())
+    }
+
+    /// Translates `\{n,m}` (after the `{`), including the lazy `\{-n,m}` form.
+    fn multi(&mut self) -> Result<()> {
+        let mut lazy = false;
+        let mut bounds = String::new();
+        loop {
+            match self.chars.next() {
+                Some('\\') if self.chars.peek() == Some(&'}') => {
+                    self.chars.next();
+                    break;
+                }
+                Some('}') => break,
+                Some('-') if bounds.is_empty() && !lazy => lazy = true,
+                Some(c) if c.is_ascii_digit() || c == ',' => bounds.push(c),
+                _ => return Err(anyhow!("E554: Syntax error in \\{{...}}")),
+            }
+        }
+
+        let (min, max) = match bounds.split_once(',') {
+            Some((min, max)) => (min, Some(max)),
+            None => (bounds.as_str(), None),
+        };
+        let min = if min.is_empty() { "0" } else { min };
+        match max {
+            None if bounds.is_empty() => self.output.push('*'),
+            None => self.output.push_str(&format!("{{{min}}}")),
+            Some("") => self.output.push_str(&format!("{{{min},}}")),
+            Some(max) => self.output.push_str(&format!("{{{min},{max}}}")),
+        }
+        if lazy {
+            self.output.push('?');
+        }
+        Ok(())
+    }
+
+    /// Copies a `[...]` collection. Without a closing `]` the `[` matches itself,
+    /// as in vim.
+    fn collection(&mut self) {
+        let mut chars = self.chars.clone();
+        let mut class = String::from("[");
+        if chars.peek() == Some(&'^') {
+            class.push('^');
+            chars.next();
+        }
+        if chars.peek() == Some(&']') {
+            class.push_str("\\]");
+            chars.next();
+        }
+        while let Some(c) = chars.next() {
+            match c {
+                ']' => {
+                    class.push(']');
+                    self.output.push_str(&class);
+                    self.chars = chars;
+                    return;
+                }
+                '[' if chars.peek() == Some(&':') => {
+                    class.push(c);
+                    for c in chars.by_ref() {
+                        class.push(c);
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
+                '\\' => match chars.next() {
+                    Some('n') => class.push_str("\\n"),
+                    Some('t') => class.push_str("\\t"),
+                    Some('e') => class.push_str("\\x1b"),
+                    Some(c) => {
+                        class.push('\\');
+                        class.push(c);
+                    }
+                    None => break,
+                },
+                // these start nested classes and set operations in the regex crate.
+                '[' | '&' | '~' => {
+                    class.push('\\');
+                    class.push(c);
+                }
+                '-' if chars.peek() == Some(&'-') => class.push_str("\\-"),
+                c => class.push(c),
+            }
+        }
+        self.push_literal('[');
+    }
+}
+
+/// Translates a vim pattern, as typed after `/` or in `:s/{pattern}/`, into a
+/// regex for the `regex` crate.
+pub(crate) fn translate(pattern: &str) -> Result<Pattern> {
+    let mut translator = Translator {
+        chars: pattern.chars().peekable(),
+        magic: Magic::Magic,
+        output: String::new(),
+        case_sensitive: None,
+        match_start: None,
+        match_end: None,
+    };
+    translator.translate()?;
+
+    let Translator {
+        mut output,
+        case_sensitive,
+        match_start,
+        match_end,
+        ..
+    } = translator;
+    let has_match_group = match_start.is_some() || match_end.is_some();
+    if has_match_group {
+        let start = match_start.unwrap_or(0);
+        let end = match_end.unwrap_or(output.len()).max(start);
+        output.insert(end, ')');
+        output.insert_str(start, &format!("(?P<{MATCH_GROUP}>"));
+    }
+
+    Ok(Pattern {
+        regex: output,
+        case_sensitive,
+        has_match_group,
+    })
+}
+
+/// Escapes text so that a vim pattern matches it literally.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\/.*[]~^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::{escape, translate};
+
+    fn regex(pattern: &str) -> String {
+        translate(pattern).unwrap().regex
+    }
+
+    #[test]
+    fn test_translate_magic() {
+        assert_eq!(regex("foo.*bar"), "foo.*bar");
+        assert_eq!(regex(r"\<foo\>"), r"\bfoo\b");
+        assert_eq!(regex(r"\(a\|b\)\+"), "(a|b)+");
+        assert_eq!(regex("(a|b)+"), r"\(a\|b\)\+");
+        assert_eq!(regex(r"a\{2,3}b\{-}c\{,4}"), "a{2,3}b*?c{0,4}");
+        assert_eq!(regex(r"\%(ab\)\=c"), "(?:ab)?c");
+        assert_eq!(regex(r"\a\u\x"), "[a-zA-Z][A-Z][0-9A-Fa-f]");
+        assert_eq!(regex("^a$"), "^a$");
+        assert_eq!(regex("a^b$c"), r"a\^b\$c");
+        assert_eq!(regex("[[:alpha:]_-]x"), "[[:alpha:]_-]x");
+        assert_eq!(regex("a[b"), r"a\[b");
+        assert_eq!(regex(r"a\/b"), "a/b");
+    }
+
+    #[test]
+    fn test_translate_magic_levels() {
+        assert_eq!(regex(r"\v<(a|b){2}>"), r"\b(a|b){2}\b");
+        assert_eq!(regex(r"\v\(a\)"), r"\(a\)");
+        assert_eq!(regex(r"\Ma.b\.c"), r"a\.b.c");
+        assert_eq!(regex(r"\V^a.*b$"), r"\^a\.\*b\$");
+        assert_eq!(regex(r"\V\^a\.b\$"), r"^a.b$");
+    }
+
+    #[test]
+    fn test_translate_flags() {
+        let pattern = translate(r"foo\c").unwrap();
+        assert_eq!(pattern.regex, "foo");
+        assert_eq!(pattern.case_sensitive, Some(false));
+
+        let pattern = translate(r"foo\zsbar\zebaz").unwrap();
+        assert_eq!(pattern.regex, "foo(?P<vimmatch>bar)baz");
+        assert!(pattern.has_match_group);
+
+        assert!(translate(r"\(a\)\1").is_err());
+        assert!(translate(r"a\@=").is_err());
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a.b/c"), r"a\.b\/c");
+        assert_eq!(regex(&escape("a.b*[c]")), r"a\.b\*\[c\]");
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // vim_global_marks(
+    //   mark: String,
+    //   path: PathBuf,
+    //   point_row: u32,
+    //   point_column: u32,
+    // )
+    pub static ref DB: VimDb<()> =
+        &[sql! (
+            CREATE TABLE vim_global_marks(
+                mark TEXT NOT NULL PRIMARY KEY,
+                path BLOB NOT NULL,
+                point_row INTEGER NOT NULL,
+                point_column INTEGER NOT NULL
+            ) STRICT;
+        )];
+);
+
+impl VimDb {
+    query! {
+        pub async fn get_global_marks() -> Result<Vec<(String, PathBuf, u32, u32)>> {
+            SELECT mark, path, point_row, point_column
+            FROM vim_global_marks
+        }
+    }
+
+    query! {
+        pub async fn save_global_mark(mark: String, path: PathBuf, row: u32, column: u32) -> Result<()> {
+            INSERT OR REPLACE INTO vim_global_marks
+                (mark, path, point_row, point_column)
+            VALUES
+                (?1, ?2, ?3, ?4)
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use editor::{movement, scroll::Autoscroll, Bias, ToOffset};
+use gpui::{actions, ViewContext, WindowContext};
+use language::{Point, SelectionGoal};
+use workspace::Workspace;
+
+use crate::{state::Mode, Vim};
+
+actions!(vim, [ToggleReplace, UndoReplace]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_: &mut Workspace, _: &ToggleReplace, cx| {
+        Vim::update(cx, |vim, cx| {
+            if vim.state().mode == Mode::Replace {
+                vim.switch_mode(Mode::Insert, false, cx);
+                return;
+            }
+            vim.workspace_state.replacements.clear();
+            vim.start_recording(cx);
+            vim.switch_mode(Mode::Replace, false, cx);
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &UndoReplace, cx| {
+        Vim::update(cx, |vim, cx| undo_replace(vim, cx))
+    });
+}
+
+/// Overwrites the character under each cursor with the text typed in replace
+/// mode. Newlines, and text typed at the end of a line, are inserted instead.
+pub(crate) fn multi_replace(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let mut replacements = Vec::new();
+                for selection in editor.selections.all::<Point>(cx) {
+                    let start = selection.head();
+                    let mut end = start;
+                    for c in text.chars() {
+                        if c != '\n' && end.column < snapshot.line_len(end.row) {
+                            end = snapshot.clip_point(end + Point::new(0, 1), Bias::Right);
+                        }
+                    }
+                    let replaced = snapshot.text_for_range(start..end).collect::<String>();
+                    let range = snapshot.anchor_before(start)..snapshot.anchor_after(end);
+                    replacements.push((range, replaced));
+                }
+
+                editor.edit(
+                    replacements
+                        .iter()
+                        .map(|(range, _)| (range.clone(), text.clone())),
+                    cx,
+                );
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges(
+                        replacements
+                            .iter()
+                            .map(|(range, _)| range.end..range.end)
+                            .collect::<Vec<_>>(),
+                    )
+                });
+                vim.workspace_state.replacements.push(replacements);
+            });
+        });
+    });
+}
+
+/// Handles backspace in replace mode: the text overwritten by the last character
+/// typed is restored. Once there is nothing left to restore the cursors just move
+/// left, as they do in vim.
+fn undo_replace(vim: &mut Vim, cx: &mut WindowContext) {
+    vim.update_active_editor(cx, |vim, editor, cx| {
+        editor.transact(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let cursors = editor
+                .selections
+                .all::<usize>(cx)
+                .into_iter()
+                .map(|selection| selection.head())
+                .collect::<Vec<_>>();
+            let replacements = &mut vim.workspace_state.replacements;
+            // the cursors may have been moved since the last character was typed.
+            let cursors_unmoved = replacements.last().is_some_and(|last| {
+                last.len() == cursors.len()
+                    && last
+                        .iter()
+                        .zip(&cursors)
+                        .all(|((range, _), cursor)| range.end.to_offset(&snapshot) == *cursor)
+            });
+            if !cursors_unmoved {
+                replacements.clear();
+            }
+
+            if let Some(last) = replacements.pop() {
+                let cursors = last
+                    .iter()
+                    .map(|(range, _)| range.start..range.start)
+                    .collect::<Vec<_>>();
+                editor.edit(last, cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges(cursors)
+                });
+            } else {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.move_cursors_with(|map, cursor, _| {
+                        (movement::left(map, cursor), SelectionGoal::None)
+                    })
+                });
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_replace_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇabcd\nef", Mode::Normal);
+        cx.simulate_keystrokes(["shift-r", "x", "y"]);
+        cx.assert_state("xyˇcd\nef", Mode::Replace);
+        cx.simulate_keystrokes(["backspace"]);
+        cx.assert_state("xˇbcd\nef", Mode::Replace);
+
+        // text typed past the end of the line is appended
+        cx.simulate_keystrokes(["z", "w", "v", "u"]);
+        cx.assert_state("xzwvuˇ\nef", Mode::Replace);
+        cx.simulate_keystrokes(["escape"]);
+        cx.assert_state("xzwvˇu\nef", Mode::Normal);
+
+        cx.simulate_keystrokes(["j", "0", "."]);
+        cx.run_until_parked();
+        cx.assert_state("xzwvu\nxzwvˇu", Mode::Normal);
+
+        // backspace past the start of the replacement only moves the cursor
+        cx.set_state("abˇcd", Mode::Normal);
+        cx.simulate_keystrokes(["shift-r", "x", "backspace", "backspace"]);
+        cx.assert_state("aˇbcd", Mode::Replace);
+    }
+
+    #[gpui::test]
+    async fn test_replace_mode_multiple_cursors(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇab ˇcd", Mode::Normal);
+        cx.simulate_keystrokes(["shift-r", "x"]);
+        cx.assert_state("xˇb xˇd", Mode::Replace);
+        cx.simulate_keystrokes(["backspace"]);
+        cx.assert_state("ˇab ˇcd", Mode::Replace);
+    }
+}
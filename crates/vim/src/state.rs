@@ -1,20 +1,31 @@
-use std::{fmt::Display, ops::Range, sync::Arc};
+use std::{fmt::Display, ops::Range, path::Path, sync::Arc};
 
 use collections::HashMap;
+use editor::{Anchor, ClipboardSelection};
 use gpui::{Action, KeyContext};
-use language::CursorShape;
+use language::{BufferId, CursorShape, Point};
 use serde::{Deserialize, Serialize};
 use workspace::searchable::Direction;
 
-use crate::motion::Motion;
+use crate::{
+    motion::Motion,
+    normal::{
+        repeat::Replayer,
+        search::{Replacement, VimSearch},
+    },
+    options::VimOptions,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Mode {
     Normal,
     Insert,
+    Replace,
     Visual,
     VisualLine,
     VisualBlock,
+    /// The selection-first normal mode of `helix_mode`.
+    HelixNormal,
 }
 
 impl Display for Mode {
@@ -22,9 +33,11 @@ impl Display for Mode {
         match self {
             Mode::Normal => write!(f, "NORMAL"),
             Mode::Insert => write!(f, "INSERT"),
+            Mode::Replace => write!(f, "REPLACE"),
             Mode::Visual => write!(f, "VISUAL"),
             Mode::VisualLine => write!(f, "VISUAL LINE"),
             Mode::VisualBlock => write!(f, "VISUAL BLOCK"),
+            Mode::HelixNormal => write!(f, "NORMAL"),
         }
     }
 }
@@ -32,7 +45,7 @@ impl Display for Mode {
 impl Mode {
     pub fn is_visual(&self) -> bool {
         match self {
-            Mode::Normal | Mode::Insert => false,
+            Mode::Normal | Mode::Insert | Mode::Replace | Mode::HelixNormal => false,
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => true,
         }
     }
@@ -53,12 +66,30 @@ pub enum Operator {
     Object { around: bool },
     FindForward { before: bool },
     FindBackward { after: bool },
+    RecordRegister,
+    ReplayRegister,
+    Register,
+    Mark,
+    Jump { line: bool },
+    ConfirmSubstitute,
+    AddSurrounds,
+    ChangeSurrounds,
+    DeleteSurrounds,
+    SurroundWith,
+    Lowercase,
+    Uppercase,
+    OppositeCase,
+    Rot13,
+    Literal,
+    SplitSelection,
 }
 
 #[derive(Default, Clone)]
 pub struct EditorState {
     pub mode: Mode,
     pub last_mode: Mode,
+    /// The visual mode most recently left, which `gv` returns to.
+    pub last_visual_mode: Option<Mode>,
 
     /// pre_count is the number before an operator is specified (3 in 3d2d)
     pub pre_count: Option<usize>,
@@ -66,6 +97,9 @@ pub struct EditorState {
     pub post_count: Option<usize>,
 
     pub operator_stack: Vec<Operator>,
+
+    /// The register chosen with `"{register}` for the next yank, delete or put.
+    pub selected_register: Option<char>,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -100,7 +134,119 @@ pub struct WorkspaceState {
     pub recorded_actions: Vec<ReplayableAction>,
     pub recorded_selection: RecordedSelection,
 
-    pub registers: HashMap<String, String>,
+    pub registers: HashMap<char, Register>,
+    /// The text most recently written to the system clipboard by a yank, so that
+    /// pasting can tell whether the clipboard was changed outside of vim.
+    pub last_yank: Option<String>,
+
+    /// The register currently being recorded into with `q{register}`.
+    pub recording_register: Option<char>,
+    /// The register most recently replayed with `@{register}`, used by `@@`.
+    pub last_replayed_register: Option<char>,
+    pub recordings: HashMap<char, Vec<ReplayableAction>>,
+    pub replayer: Option<Replayer>,
+
+    /// Marks set in each buffer, including the special marks maintained by vim.
+    pub marks: HashMap<BufferId, HashMap<char, language::Anchor>>,
+    /// Uppercase marks, which are shared between buffers and persisted.
+    pub global_marks: HashMap<char, GlobalMark>,
+    /// The positions jumped from, oldest first.
+    pub jumps: Vec<Jump>,
+    /// The position in `jumps` that `ctrl-o` and `ctrl-i` last moved to, or
+    /// `jumps.len()` if they haven't been used since the last jump.
+    pub jump_ix: usize,
+
+    /// The matches of a `:s///c` that are still waiting to be confirmed, with the
+    /// text each will be replaced by.
+    pub pending_substitutions: Vec<(Range<Anchor>, String)>,
+
+    /// What `ys{motion}`, `cs{char}` or visual `S` is waiting to surround, and
+    /// what has been typed so far to surround it with.
+    pub pending_surround: Option<PendingSurround>,
+    pub surround_input: String,
+
+    /// The text overwritten by each character typed in replace mode, so that
+    /// backspace can restore it.
+    pub replacements: Vec<Vec<(Range<Anchor>, String)>>,
+    /// The characters typed so far after `ctrl-v` in insert mode.
+    pub literal_input: String,
+    /// The regex typed so far after `s` in helix mode.
+    pub split_input: String,
+    /// The most recent `:s`, repeated by `&`.
+    pub last_substitution: Option<Replacement>,
+    /// The most recent search, repeated by `n` and `N`.
+    pub last_search: Option<VimSearch>,
+    /// The options set with `:set`.
+    pub options: VimOptions,
+    /// Set while the keys of a non-recursive mapping are being sent, so that
+    /// they are not mapped again.
+    pub noremap: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum PendingSurround {
+    /// Ranges to wrap in the new surround.
+    Add(Vec<Range<Anchor>>),
+    /// Opening and closing markers to replace with the new surround.
+    Change(Vec<(Range<Anchor>, Range<Anchor>)>),
+}
+
+/// The location of an uppercase mark, used to reopen its file when the mark
+/// is not set in the buffer being jumped from.
+#[derive(Clone, Debug)]
+pub struct GlobalMark {
+    /// The file the mark was set in, if the buffer has one.
+    pub path: Option<Arc<Path>>,
+    pub point: Point,
+    /// The mark in the buffer it was set in, which follows edits unlike `point`.
+    /// Marks loaded from a previous session get one once their file is opened.
+    pub anchor: Option<(BufferId, language::Anchor)>,
+}
+
+/// A position recorded in the jump list, which `ctrl-o` and `ctrl-i` move through.
+#[derive(Clone, Debug)]
+pub struct Jump {
+    pub buffer_id: BufferId,
+    pub anchor: language::Anchor,
+    /// The file of the buffer, used to reopen it when jumping from another one.
+    pub path: Option<Arc<Path>>,
+    pub point: Point,
+}
+
+/// The contents of a register. The clipboard selections record whether the
+/// text was copied linewise or from several (block) selections.
+#[derive(Clone, Debug, Default)]
+pub struct Register {
+    pub text: String,
+    pub clipboard_selections: Option<Vec<ClipboardSelection>>,
+}
+
+impl From<String> for Register {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            clipboard_selections: None,
+        }
+    }
+}
+
+impl Register {
+    /// Whether the text was yanked or deleted linewise. Text without clipboard
+    /// selections, such as inserted text, is characterwise.
+    pub fn is_linewise(&self) -> bool {
+        self.clipboard_selections
+            .as_ref()
+            .is_some_and(|selections| {
+                !selections.is_empty()
+                    && selections.iter().all(|selection| selection.is_entire_line)
+            })
+    }
+
+    pub fn is_blockwise(&self) -> bool {
+        self.clipboard_selections
+            .as_ref()
+            .is_some_and(|selections| selections.len() > 1)
+    }
 }
 
 #[derive(Debug)]
@@ -132,6 +278,9 @@ pub struct SearchState {
     pub direction: Direction,
     pub count: usize,
     pub initial_query: String,
+    /// The selections when the search started, which a search with an offset
+    /// moves from.
+    pub prior_selections: Vec<Range<Anchor>>,
 }
 
 impl Default for SearchState {
@@ -140,6 +289,7 @@ impl Default for SearchState {
             direction: Direction::Next,
             count: 1,
             initial_query: "".to_string(),
+            prior_selections: Vec::new(),
         }
     }
 }
@@ -156,24 +306,40 @@ impl EditorState {
             }
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => CursorShape::Block,
             Mode::Insert => CursorShape::Bar,
+            Mode::Replace => CursorShape::Underscore,
+            Mode::HelixNormal => CursorShape::Block,
         }
     }
 
     pub fn vim_controlled(&self) -> bool {
-        !matches!(self.mode, Mode::Insert)
+        !matches!(self.mode, Mode::Insert | Mode::Replace)
             || matches!(
                 self.operator_stack.last(),
-                Some(Operator::FindForward { .. }) | Some(Operator::FindBackward { .. })
+                Some(Operator::FindForward { .. })
+                    | Some(Operator::FindBackward { .. })
+                    | Some(Operator::Register)
+                    | Some(Operator::Literal)
             )
     }
 
+    /// In replace mode typed text is overwritten by vim rather than inserted by
+    /// the editor, even though the keymap behaves as it does in insert mode.
+    pub fn editor_input_enabled(&self) -> bool {
+        self.mode != Mode::Replace && !self.vim_controlled()
+    }
+
     pub fn should_autoindent(&self) -> bool {
         !(self.mode == Mode::Insert && self.last_mode == Mode::VisualBlock)
     }
 
     pub fn clip_at_line_ends(&self) -> bool {
         match self.mode {
-            Mode::Insert | Mode::Visual | Mode::VisualLine | Mode::VisualBlock => false,
+            Mode::Insert
+            | Mode::Replace
+            | Mode::Visual
+            | Mode::VisualLine
+            | Mode::VisualBlock
+            | Mode::HelixNormal => false,
             Mode::Normal => true,
         }
     }
@@ -190,6 +356,8 @@ impl EditorState {
                 Mode::Normal => "normal",
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => "visual",
                 Mode::Insert => "insert",
+                Mode::Replace => "replace",
+                Mode::HelixNormal => "helix_normal",
             },
         );
 
@@ -233,15 +401,44 @@ impl Operator {
             Operator::FindForward { before: true } => "t",
             Operator::FindBackward { after: false } => "F",
             Operator::FindBackward { after: true } => "T",
+            Operator::RecordRegister => "q",
+            Operator::ReplayRegister => "@",
+            Operator::Register => "\"",
+            Operator::Mark => "m",
+            Operator::Jump { line: true } => "'",
+            Operator::Jump { line: false } => "`",
+            Operator::ConfirmSubstitute => "s",
+            Operator::AddSurrounds => "ys",
+            Operator::ChangeSurrounds => "cs",
+            Operator::DeleteSurrounds => "ds",
+            Operator::SurroundWith => "S",
+            Operator::Lowercase => "gu",
+            Operator::Uppercase => "gU",
+            // `~` and `?` can't be used in keymap contexts.
+            Operator::OppositeCase => "gtilde",
+            Operator::Rot13 => "grot13",
+            Operator::Literal => "ctrl-v",
+            Operator::SplitSelection => "split",
         }
     }
 
     pub fn context_flags(&self) -> &'static [&'static str] {
         match self {
             Operator::Object { .. } => &["VimObject"],
-            Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace => {
-                &["VimWaiting"]
-            }
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::RecordRegister
+            | Operator::ReplayRegister
+            | Operator::Register
+            | Operator::Mark
+            | Operator::Jump { .. }
+            | Operator::ConfirmSubstitute
+            | Operator::ChangeSurrounds
+            | Operator::DeleteSurrounds
+            | Operator::SurroundWith
+            | Operator::Literal
+            | Operator::SplitSelection => &["VimWaiting"],
             _ => &[],
         }
     }
@@ -0,0 +1,596 @@
+use std::{ops::Range, sync::Arc};
+
+use editor::{
+    display_map::DisplaySnapshot, scroll::Autoscroll, Anchor, Bias, DisplayPoint, Editor, ToOffset,
+};
+use gpui::{actions, ViewContext, WindowContext};
+use language::{Point, Selection};
+use workspace::Workspace;
+
+use crate::{
+    motion::Motion,
+    object::Object,
+    state::{Mode, Operator, PendingSurround},
+    Vim,
+};
+
+actions!(vim, [SurroundSelection]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_: &mut Workspace, _: &SurroundSelection, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.start_recording(cx);
+            let ranges = vim
+                .update_active_editor(cx, |vim, editor, cx| {
+                    let linewise = vim.state().mode == Mode::VisualLine;
+                    let (map, selections) = editor.selections.all_display(cx);
+                    selections
+                        .into_iter()
+                        .filter_map(|selection| surrounded_range(&map, selection, linewise, false))
+                        .collect()
+                })
+                .unwrap_or_default();
+            start_surround(vim, PendingSurround::Add(ranges), cx);
+        })
+    });
+}
+
+/// Handles the motion typed after `ys`.
+pub(crate) fn add_surrounds_motion(
+    vim: &mut Vim,
+    motion: Motion,
+    times: Option<usize>,
+    cx: &mut WindowContext,
+) {
+    let ranges = vim
+        .update_active_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.set_clip_at_line_ends(false, cx);
+            let (map, selections) = editor.selections.all_display(cx);
+            editor.set_clip_at_line_ends(true, cx);
+            selections
+                .into_iter()
+                .filter_map(|mut selection| {
+                    motion
+                        .expand_selection(&map, &mut selection, times, false, &text_layout_details)
+                        .then(|| surrounded_range(&map, selection, motion.linewise(), true))?
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    start_surround(vim, PendingSurround::Add(ranges), cx);
+}
+
+/// Handles the text object typed after `ys`.
+pub(crate) fn add_surrounds_object(
+    vim: &mut Vim,
+    object: Object,
+    around: bool,
+    cx: &mut WindowContext,
+) {
+    let ranges = vim
+        .update_active_editor(cx, |_, editor, cx| {
+            let (map, selections) = editor.selections.all_display(cx);
+            selections
+                .into_iter()
+                .filter_map(|mut selection| {
+                    object
+                        .expand_selection(&map, &mut selection, around)
+                        .then(|| surrounded_range(&map, selection, false, true))?
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    start_surround(vim, PendingSurround::Add(ranges), cx);
+}
+
+/// Handles the character typed after `cs`, naming the surround to replace.
+pub(crate) fn change_surrounds(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let pairs = text
+            .chars()
+            .next()
+            .and_then(|target| {
+                vim.update_active_editor(cx, |_, editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    surrounding_pairs(editor, target, cx)
+                        .into_iter()
+                        .map(|(open, close)| {
+                            (
+                                snapshot.anchor_before(open.start)..snapshot.anchor_after(open.end),
+                                snapshot.anchor_before(close.start)
+                                    ..snapshot.anchor_after(close.end),
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .unwrap_or_default();
+        start_surround(vim, PendingSurround::Change(pairs), cx);
+    });
+}
+
+/// Handles the character typed after `ds`, removing that surround around each cursor.
+pub(crate) fn delete_surrounds(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.stop_recording();
+        vim.clear_operator(cx);
+        let Some(target) = text.chars().next() else {
+            return;
+        };
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let pairs = surrounding_pairs(editor, target, cx);
+            if pairs.is_empty() {
+                return;
+            }
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let cursors = pairs
+                .iter()
+                .map(|(open, _)| snapshot.anchor_before(open.start))
+                .collect::<Vec<_>>();
+            editor.transact(cx, |editor, cx| {
+                editor.edit(
+                    pairs
+                        .into_iter()
+                        .flat_map(|(open, close)| [(open, ""), (close, "")]),
+                    cx,
+                );
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges(cursors.into_iter().map(|cursor| cursor..cursor))
+                });
+            });
+        });
+    });
+}
+
+/// Handles the characters typed to say what to surround with, once `ys{motion}`,
+/// `cs{char}` or visual `S` has chosen what to surround.
+pub(crate) fn surround_with(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.workspace_state.surround_input.push_str(&text);
+        let (open, close) = match parse_surround(&vim.workspace_state.surround_input) {
+            SurroundInput::Incomplete => return,
+            SurroundInput::Invalid => {
+                vim.stop_recording();
+                vim.workspace_state.pending_surround = None;
+                vim.clear_operator(cx);
+                return;
+            }
+            SurroundInput::With { open, close } => (open, close),
+        };
+        // the last character typed ends what `.` repeats
+        vim.stop_recording();
+        let pending = vim.workspace_state.pending_surround.take();
+        vim.clear_operator(cx);
+        if vim.state().mode.is_visual() {
+            vim.switch_mode(Mode::Normal, false, cx);
+        }
+
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let (edits, cursors): (Vec<_>, Vec<_>) = match pending {
+                Some(PendingSurround::Add(ranges)) => ranges
+                    .into_iter()
+                    .map(|range| {
+                        (
+                            [
+                                (range.start..range.start, open.clone()),
+                                (range.end..range.end, close.clone()),
+                            ],
+                            range.start,
+                        )
+                    })
+                    .unzip(),
+                Some(PendingSurround::Change(pairs)) => pairs
+                    .into_iter()
+                    .map(|(open_range, close_range)| {
+                        let cursor = open_range.start;
+                        (
+                            [(open_range, open.clone()), (close_range, close.clone())],
+                            cursor,
+                        )
+                    })
+                    .unzip(),
+                None => return,
+            };
+            if edits.is_empty() {
+                return;
+            }
+            editor.transact(cx, |editor, cx| {
+                editor.edit(edits.into_iter().flatten(), cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges(cursors.into_iter().map(|cursor| cursor..cursor))
+                });
+            });
+        });
+    });
+}
+
+/// Replaces the operator with one waiting for what to surround with.
+fn start_surround(vim: &mut Vim, pending: PendingSurround, cx: &mut WindowContext) {
+    vim.clear_operator(cx);
+    let is_empty = match &pending {
+        PendingSurround::Add(ranges) => ranges.is_empty(),
+        PendingSurround::Change(pairs) => pairs.is_empty(),
+    };
+    if is_empty {
+        vim.stop_recording();
+        return;
+    }
+    vim.workspace_state.pending_surround = Some(pending);
+    vim.workspace_state.surround_input.clear();
+    vim.push_operator(Operator::SurroundWith, cx);
+}
+
+/// The text a selection covers, or the contents of its lines (from the first
+/// non-blank character) if it is linewise.
+fn surrounded_range(
+    map: &DisplaySnapshot,
+    selection: Selection<DisplayPoint>,
+    linewise: bool,
+    trim_trailing_whitespace: bool,
+) -> Option<Range<Anchor>> {
+    let buffer = &map.buffer_snapshot;
+    let start = selection.start.to_point(map);
+    let end = selection.end.to_point(map);
+    let (start, end) = if linewise {
+        let end_row = if end.column == 0 && end.row > start.row {
+            end.row - 1
+        } else {
+            end.row
+        };
+        (
+            Point::new(start.row, buffer.indent_size_for_line(start.row).len),
+            Point::new(end_row, buffer.line_len(end_row)),
+        )
+    } else {
+        (start, end)
+    };
+
+    let start = start.to_offset(buffer);
+    let mut end = end.to_offset(buffer);
+    if trim_trailing_whitespace {
+        for c in buffer.reversed_chars_at(end) {
+            if end <= start || !c.is_whitespace() {
+                break;
+            }
+            end -= c.len_utf8();
+        }
+    }
+    (start < end).then(|| buffer.anchor_before(start)..buffer.anchor_after(end))
+}
+
+enum SurroundInput {
+    /// A tag or function name is still being typed.
+    Incomplete,
+    Invalid,
+    With {
+        open: String,
+        close: String,
+    },
+}
+
+/// Parses what was typed to surround with: a character, `<tag attr="...">` (or
+/// `ttag>`), or `fname(` (`Fname(` to pad with spaces). Tags and function names
+/// can also be finished with enter.
+fn parse_surround(input: &str) -> SurroundInput {
+    let mut chars = input.chars();
+    let Some(first) = chars.next() else {
+        return SurroundInput::Incomplete;
+    };
+    let rest = chars.as_str();
+    let pair = |open: &str, close: &str| SurroundInput::With {
+        open: open.to_string(),
+        close: close.to_string(),
+    };
+
+    match first {
+        '<' | 't' => {
+            let Some(tag) = rest.strip_suffix('>').or_else(|| rest.strip_suffix('\n')) else {
+                return SurroundInput::Incomplete;
+            };
+            let name = tag
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default();
+            if name.is_empty() {
+                return SurroundInput::Invalid;
+            }
+            SurroundInput::With {
+                open: format!("<{}>", tag),
+                close: format!("</{}>", name),
+            }
+        }
+        'f' | 'F' => {
+            let Some(name) = rest.strip_suffix('(').or_else(|| rest.strip_suffix('\n')) else {
+                return SurroundInput::Incomplete;
+            };
+            if name.is_empty() {
+                return SurroundInput::Invalid;
+            }
+            if first == 'F' {
+                pair(&format!("{}( ", name), " )")
+            } else {
+                pair(&format!("{}(", name), ")")
+            }
+        }
+        '(' => pair("( ", " )"),
+        ')' | 'b' => pair("(", ")"),
+        '{' => pair("{ ", " }"),
+        '}' | 'B' => pair("{", "}"),
+        '[' => pair("[ ", " ]"),
+        ']' | 'r' => pair("[", "]"),
+        '>' | 'a' => pair("<", ">"),
+        c if c.is_alphanumeric() || c.is_whitespace() => SurroundInput::Invalid,
+        c => pair(&c.to_string(), &c.to_string()),
+    }
+}
+
+/// The text object that `cs{target}` and `ds{target}` operate on, whether the
+/// whitespace just inside the markers goes too, and whether it is a function call.
+fn surround_target(target: char) -> Option<(Object, bool, bool)> {
+    Some(match target {
+        '(' => (Object::Parentheses, true, false),
+        ')' | 'b' => (Object::Parentheses, false, false),
+        '{' => (Object::CurlyBrackets, true, false),
+        '}' | 'B' => (Object::CurlyBrackets, false, false),
+        '[' => (Object::SquareBrackets, true, false),
+        ']' | 'r' => (Object::SquareBrackets, false, false),
+        '<' | '>' | 'a' => (Object::AngleBrackets, false, false),
+        '\'' => (Object::Quotes, false, false),
+        '"' => (Object::DoubleQuotes, false, false),
+        '`' => (Object::BackQuotes, false, false),
+        '|' => (Object::VerticalBars, false, false),
+        't' => (Object::Tag, false, false),
+        'f' => (Object::Parentheses, false, true),
+        _ => return None,
+    })
+}
+
+/// The offsets of the opening and closing markers of the `target` surround
+/// around each cursor, without duplicates.
+fn surrounding_pairs(
+    editor: &mut Editor,
+    target: char,
+    cx: &mut ViewContext<Editor>,
+) -> Vec<(Range<usize>, Range<usize>)> {
+    let Some((object, trim_whitespace, is_function)) = surround_target(target) else {
+        return Vec::new();
+    };
+    let (map, selections) = editor.selections.all_display(cx);
+    let buffer = &map.buffer_snapshot;
+    let is_padding = |c: char| c == ' ' || c == '\t';
+
+    let mut pairs = Vec::new();
+    for selection in selections {
+        let head = selection.head();
+        let Some(around) = object.range(&map, head, true) else {
+            continue;
+        };
+        let mut start = around.start.to_offset(&map, Bias::Left);
+        let mut end = around.end.to_offset(&map, Bias::Left);
+
+        let (mut open, mut close) = if object == Object::Tag {
+            let Some(inside) = object.range(&map, head, false) else {
+                continue;
+            };
+            (
+                start..inside.start.to_offset(&map, Bias::Left),
+                inside.end.to_offset(&map, Bias::Left)..end,
+            )
+        } else {
+            // quotes are selected along with the whitespace around them
+            for c in buffer.chars_at(start) {
+                if start >= end || !is_padding(c) {
+                    break;
+                }
+                start += c.len_utf8();
+            }
+            for c in buffer.reversed_chars_at(end) {
+                if end <= start || !is_padding(c) {
+                    break;
+                }
+                end -= c.len_utf8();
+            }
+            if end <= start + 1 {
+                continue;
+            }
+            (start..start + 1, end - 1..end)
+        };
+
+        if trim_whitespace {
+            for c in buffer.chars_at(open.end) {
+                if open.end >= close.start || !is_padding(c) {
+                    break;
+                }
+                open.end += c.len_utf8();
+            }
+            for c in buffer.reversed_chars_at(close.start) {
+                if close.start <= open.end || !is_padding(c) {
+                    break;
+                }
+                close.start -= c.len_utf8();
+            }
+        }
+        if is_function {
+            for c in buffer.reversed_chars_at(open.start) {
+                if !(c.is_alphanumeric() || c == '_' || c == '.' || c == ':') {
+                    break;
+                }
+                open.start -= c.len_utf8();
+            }
+        }
+        pairs.push((open, close));
+    }
+
+    pairs.sort_by_key(|(open, _)| open.start);
+    pairs.dedup();
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_add_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", ")"]);
+        cx.assert_state("The ˇ(quick) brown fox", Mode::Normal);
+
+        cx.set_state("The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "("]);
+        cx.assert_state("The ˇ( quick ) brown fox", Mode::Normal);
+
+        cx.set_state("The ˇquick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "e", "\""]);
+        cx.assert_state("The ˇ\"quick\" brown fox", Mode::Normal);
+
+        cx.set_state("The ˇquick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "w", "'"]);
+        cx.assert_state("The ˇ'quick' brown fox", Mode::Normal);
+
+        cx.set_state("    The ˇquick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "s", "]"]);
+        cx.assert_state("    ˇ[The quick brown fox]", Mode::Normal);
+
+        // every cursor is surrounded in one undoable edit
+        cx.set_state("ˇone ˇtwo", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "b"]);
+        cx.assert_state("ˇ(one) ˇ(two)", Mode::Normal);
+        cx.simulate_keystrokes(["u"]);
+        cx.assert_state("ˇone ˇtwo", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_add_tag_and_function_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The ˇquick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "<", "e", "m", ">"]);
+        cx.assert_state("The ˇ<em>quick</em> brown fox", Mode::Normal);
+
+        cx.set_state("The ˇquick brown fox", Mode::Normal);
+        cx.simulate_keystrokes([
+            "y", "s", "i", "w", "<", "a", "space", "i", "d", "=", "1", ">",
+        ]);
+        cx.assert_state("The ˇ<a id=1>quick</a> brown fox", Mode::Normal);
+
+        cx.set_state("let x = ˇvalue;", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "f", "shift-s", "o", "m", "e", "("]);
+        cx.assert_state("let x = ˇSome(value);", Mode::Normal);
+
+        cx.set_state("let x = ˇvalue;", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "f", "o", "k", "enter"]);
+        cx.assert_state("let x = ˇok(value);", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_visual_surround(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The ˇquick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["v", "e", "e", "shift-s", "{"]);
+        cx.assert_state("The ˇ{ quick brown } fox", Mode::Normal);
+
+        cx.set_state(
+            indoc! {"
+                ˇone
+                  two
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["shift-v", "j", "shift-s", "\""]);
+        cx.assert_state(
+            indoc! {"
+                ˇ\"one
+                  two\"
+                three"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_repeat_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "<", "b", ">"]);
+        cx.assert_state("ˇ<b>one</b> two", Mode::Normal);
+        cx.simulate_keystrokes(["$", "."]);
+        cx.assert_state("<b>one</b> ˇ<b>two</b>", Mode::Normal);
+
+        cx.set_state("\"ˇone\" \"two\"", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "\"", "b"]);
+        cx.assert_state("ˇ(one) \"two\"", Mode::Normal);
+        cx.simulate_keystrokes(["$", "h", "."]);
+        cx.assert_state("(one) ˇ(two)", Mode::Normal);
+
+        cx.simulate_keystrokes(["l", "d", "s", "b"]);
+        cx.assert_state("(one) ˇtwo", Mode::Normal);
+        cx.simulate_keystrokes(["0", "l", "."]);
+        cx.assert_state("ˇone two", Mode::Normal);
+
+        cx.set_state("ˇone two", Mode::Normal);
+        cx.simulate_keystrokes(["v", "l", "l", "shift-s", "]"]);
+        cx.assert_state("ˇ[one] two", Mode::Normal);
+        cx.simulate_keystrokes(["$", "h", "h", "."]);
+        cx.assert_state("[one] ˇ[two]", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_change_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("let s = \"heˇllo\";", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "\"", "'"]);
+        cx.assert_state("let s = ˇ'hello';", Mode::Normal);
+
+        cx.set_state("call( aˇrg )", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "(", "]"]);
+        cx.assert_state("callˇ[arg]", Mode::Normal);
+
+        cx.set_state("ˇ(one) (tˇwo)", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "b", "{"]);
+        cx.assert_state("ˇ{ one } ˇ{ two }", Mode::Normal);
+
+        cx.set_state("x = foo(bˇar)", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "f", "f", "b", "a", "z", "("]);
+        cx.assert_state("x = ˇbaz(bar)", Mode::Normal);
+
+        // an unknown target leaves the text alone
+        cx.set_state("heˇllo", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "\"", "'"]);
+        cx.assert_state("heˇllo", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_delete_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("let s = \"heˇllo\";", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "\""]);
+        cx.assert_state("let s = ˇhello;", Mode::Normal);
+
+        cx.set_state("call( aˇrg )", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", ")"]);
+        cx.assert_state("callˇ arg ", Mode::Normal);
+
+        cx.set_state("call( aˇrg )", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "("]);
+        cx.assert_state("callˇarg", Mode::Normal);
+
+        cx.set_state("x = foo.bar(bˇaz)", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "f"]);
+        cx.assert_state("x = ˇbaz", Mode::Normal);
+
+        cx.set_state("[ˇa] [ˇb]", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "]"]);
+        cx.assert_state("ˇa ˇb", Mode::Normal);
+        cx.simulate_keystrokes(["u"]);
+        cx.assert_state("[ˇa] [ˇb]", Mode::Normal);
+    }
+}
@@ -370,6 +370,7 @@ impl NeovimConnection {
 
         let mode = match nvim_mode_text.as_ref() {
             "i" => Some(Mode::Insert),
+            "R" => Some(Mode::Replace),
             "n" => Some(Mode::Normal),
             "v" => Some(Mode::Visual),
             "V" => Some(Mode::VisualLine),
@@ -439,7 +440,7 @@ impl NeovimConnection {
                     Point::new(selection_row, selection_col)..Point::new(cursor_row, cursor_col),
                 )
             }
-            Some(Mode::Insert) | Some(Mode::Normal) | None => selections
+            Some(Mode::Insert) | Some(Mode::Normal) | Some(Mode::Replace) | None => selections
                 .push(Point::new(selection_row, selection_col)..Point::new(cursor_row, cursor_col)),
         }
 
@@ -1,11 +1,16 @@
 use std::time::Duration;
 
+use command_palette_hooks::CommandPaletteInterceptHistory;
 use editor::{ClipboardSelection, Editor};
-use gpui::{ClipboardItem, ViewContext};
+use gpui::{AppContext, ClipboardItem, ViewContext};
 use language::{CharKind, Point};
 use settings::Settings;
 
-use crate::{state::Mode, UseSystemClipboard, Vim, VimSettings};
+use crate::{
+    normal::mark::set_mark,
+    state::{Mode, Register},
+    UseSystemClipboard, Vim, VimSettings,
+};
 
 pub struct HighlightOnYank;
 
@@ -83,21 +88,24 @@ fn copy_selections_content_internal(
         }
     }
 
-    let setting = VimSettings::get_global(cx).use_system_clipboard;
-    if setting == UseSystemClipboard::Always || setting == UseSystemClipboard::OnYank && is_yank {
-        cx.write_to_clipboard(ClipboardItem::new(text.clone()).with_metadata(clipboard_selections));
-        vim.workspace_state
-            .registers
-            .insert(".system.".to_string(), text.clone());
-    } else {
-        vim.workspace_state.registers.insert(
-            ".system.".to_string(),
-            cx.read_from_clipboard()
-                .map(|item| item.text().clone())
-                .unwrap_or_default(),
-        );
+    let register = vim.update_state(|state| state.selected_register.take());
+    write_registers(
+        vim,
+        Register {
+            text,
+            clipboard_selections: Some(clipboard_selections),
+        },
+        register,
+        is_yank,
+        linewise,
+        cx,
+    );
+
+    if let (Some(first), Some(last)) = (ranges_to_highlight.first(), ranges_to_highlight.last()) {
+        set_mark(vim, '[', first.start, editor, cx);
+        set_mark(vim, ']', last.end, editor, cx);
     }
-    vim.workspace_state.registers.insert("\"".to_string(), text);
+
     if !is_yank || vim.state().mode == Mode::Visual {
         return;
     }
@@ -119,6 +127,184 @@ fn copy_selections_content_internal(
     .detach();
 }
 
+/// Stores yanked or deleted text in `register`, or in the unnamed and numbered
+/// registers if no register was given.
+pub fn write_registers(
+    vim: &mut Vim,
+    content: Register,
+    register: Option<char>,
+    is_yank: bool,
+    linewise: bool,
+    cx: &AppContext,
+) {
+    let registers = &mut vim.workspace_state.registers;
+    match register {
+        Some('_') => {}
+        Some(register @ ('a'..='z' | 'A'..='Z')) => {
+            let name = register.to_ascii_lowercase();
+            let content = match registers.remove(&name) {
+                Some(existing) if register.is_ascii_uppercase() => {
+                    append_to_register(existing, content)
+                }
+                _ => content,
+            };
+            registers.insert(name, content.clone());
+            registers.insert('"', content);
+        }
+        Some('+' | '*') => {
+            write_to_clipboard(vim, &content, cx);
+            vim.workspace_state.registers.insert('"', content);
+        }
+        Some(register @ ('0'..='9' | '-')) => {
+            registers.insert(register, content.clone());
+            registers.insert('"', content);
+        }
+        // the remaining registers are read-only
+        Some(register) if register != '"' => {}
+        _ => {
+            if is_yank {
+                registers.insert('0', content.clone());
+            } else if linewise || content.text.contains('\n') {
+                for ix in (b'1'..b'9').rev() {
+                    if let Some(previous) = registers.remove(&(ix as char)) {
+                        registers.insert((ix + 1) as char, previous);
+                    }
+                }
+                registers.insert('1', content.clone());
+            } else {
+                registers.insert('-', content.clone());
+            }
+
+            let setting = VimSettings::get_global(cx).use_system_clipboard;
+            if setting == UseSystemClipboard::Always
+                || setting == UseSystemClipboard::OnYank && is_yank
+            {
+                write_to_clipboard(vim, &content, cx);
+            } else {
+                vim.workspace_state.last_yank =
+                    cx.read_from_clipboard().map(|item| item.text().clone());
+            }
+            vim.workspace_state.registers.insert('"', content);
+        }
+    }
+}
+
+/// Returns the contents of `register`, or of the unnamed register if none was given.
+pub fn read_register(
+    vim: &Vim,
+    register: Option<char>,
+    editor: Option<&Editor>,
+    cx: &AppContext,
+) -> Option<Register> {
+    match register.unwrap_or('"') {
+        '_' => None,
+        '"' => {
+            let setting = VimSettings::get_global(cx).use_system_clipboard;
+            if setting == UseSystemClipboard::Never
+                || setting == UseSystemClipboard::OnYank && !system_clipboard_is_newer(vim, cx)
+            {
+                vim.workspace_state.registers.get(&'"').cloned()
+            } else {
+                read_from_clipboard(cx)
+            }
+        }
+        '+' | '*' => read_from_clipboard(cx),
+        '%' => {
+            let buffer = editor?.buffer().read(cx).as_singleton()?;
+            let file = buffer.read(cx).file()?;
+            Some(Register::from(file.path().to_string_lossy().to_string()))
+        }
+        ':' => {
+            // searches made with `/` and `?` share the history, but aren't commands.
+            let query = cx
+                .try_global::<CommandPaletteInterceptHistory>()?
+                .0
+                .iter()
+                .rev()
+                .find(|query| !query.starts_with(['/', '?']))?;
+            Some(Register::from(query.trim_start_matches(':').to_string()))
+        }
+        register => vim
+            .workspace_state
+            .registers
+            .get(&register.to_ascii_lowercase())
+            .cloned(),
+    }
+}
+
+/// Appends to a named register (`"A` to `"Z`). Like in Vim, the result is
+/// linewise if either part is, and blockwise if both parts are.
+fn append_to_register(existing: Register, content: Register) -> Register {
+    if existing.text.is_empty() {
+        return content;
+    }
+
+    if existing.is_blockwise() && content.is_blockwise() {
+        let mut clipboard_selections = existing.clipboard_selections.unwrap_or_default();
+        clipboard_selections.extend(content.clipboard_selections.unwrap_or_default());
+        return Register {
+            text: existing.text + "\n" + &content.text,
+            clipboard_selections: Some(clipboard_selections),
+        };
+    }
+
+    let existing_linewise = existing.is_linewise();
+    let content_linewise = content.is_linewise();
+    let first_line_indent = existing
+        .clipboard_selections
+        .iter()
+        .chain(content.clipboard_selections.iter())
+        .flatten()
+        .next()
+        .map_or(0, |selection| selection.first_line_indent);
+
+    let mut text = existing.text;
+    if content_linewise && !existing_linewise {
+        text.push('\n');
+    }
+    text.push_str(&content.text);
+    if existing_linewise && !content_linewise {
+        text.push('\n');
+    }
+    Register {
+        clipboard_selections: Some(vec![ClipboardSelection {
+            len: text.len(),
+            is_entire_line: existing_linewise || content_linewise,
+            first_line_indent,
+        }]),
+        text,
+    }
+}
+
+fn write_to_clipboard(vim: &mut Vim, content: &Register, cx: &AppContext) {
+    let item = match &content.clipboard_selections {
+        Some(clipboard_selections) => {
+            ClipboardItem::new(content.text.clone()).with_metadata(clipboard_selections.clone())
+        }
+        None => ClipboardItem::new(content.text.clone()),
+    };
+    cx.write_to_clipboard(item);
+    vim.workspace_state.last_yank = Some(content.text.clone());
+}
+
+fn read_from_clipboard(cx: &AppContext) -> Option<Register> {
+    let item = cx.read_from_clipboard()?;
+    Some(Register {
+        text: item.text().clone(),
+        clipboard_selections: item.metadata::<Vec<ClipboardSelection>>(),
+    })
+}
+
+fn system_clipboard_is_newer(vim: &Vim, cx: &AppContext) -> bool {
+    cx.read_from_clipboard().is_some_and(|item| {
+        if let Some(last_state) = vim.workspace_state.last_yank.as_ref() {
+            last_state != item.text()
+        } else {
+            true
+        }
+    })
+}
+
 pub fn coerce_punctuation(kind: CharKind, treat_punctuation_as_word: bool) -> CharKind {
     if treat_punctuation_as_word && kind == CharKind::Punctuation {
         CharKind::Word
@@ -5,12 +5,19 @@ mod test;
 
 mod command;
 mod editor_events;
+mod helix;
 mod insert;
+mod mappings;
 mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod options;
+mod pattern;
+mod persistence;
+mod replace;
 mod state;
+mod surrounds;
 mod utils;
 mod visual;
 
@@ -19,22 +26,28 @@ use collections::HashMap;
 use command_palette_hooks::{CommandPaletteFilter, CommandPaletteInterceptor};
 use editor::{
     movement::{self, FindRange},
-    Editor, EditorEvent, EditorMode,
+    Bias, Editor, EditorEvent, EditorMode, ToOffset,
 };
 use gpui::{
     actions, impl_actions, Action, AppContext, EntityId, Global, KeystrokeEvent, Subscription,
     View, ViewContext, WeakView, WindowContext,
 };
 use language::{CursorShape, Point, Selection, SelectionGoal};
+pub use mappings::{KeyMappings, VimMapping, VimMappings};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
-use normal::normal_replace;
+use normal::{
+    mark, normal_replace,
+    repeat::replay_register,
+    search::{cancel_substitution, confirm_substitution},
+};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
 use settings::{update_settings_file, Settings, SettingsStore};
 use state::{EditorState, Mode, Operator, RecordedSelection, WorkspaceState};
 use std::{ops::Range, sync::Arc};
+use utils::read_register;
 use visual::{visual_block_motion, visual_replace};
 use workspace::{self, Workspace};
 
@@ -45,6 +58,12 @@ use crate::state::ReplayableAction;
 /// Default: false
 pub struct VimModeSetting(pub bool);
 
+/// Whether or not to enable the Helix style selection-first mode, which is
+/// built on vim mode.
+///
+/// Default: false
+pub struct HelixModeSetting(pub bool);
+
 /// An Action to Switch between modes
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct SwitchMode(pub Mode);
@@ -74,6 +93,7 @@ impl_actions!(vim, [SwitchMode, PushOperator, Number]);
 pub fn init(cx: &mut AppContext) {
     cx.set_global(Vim::default());
     VimModeSetting::register(cx);
+    HelixModeSetting::register(cx);
     VimSettings::register(cx);
 
     cx.observe_keystrokes(observe_keystrokes).detach();
@@ -89,11 +109,19 @@ pub fn init(cx: &mut AppContext) {
         filter.hidden_namespaces.insert("vim");
     });
     cx.update_global(|vim: &mut Vim, cx: &mut AppContext| {
-        vim.set_enabled(VimModeSetting::get_global(cx).0, cx)
+        vim.set_enabled(
+            VimModeSetting::get_global(cx).0,
+            HelixModeSetting::get_global(cx).0,
+            cx,
+        )
     });
     cx.observe_global::<SettingsStore>(|cx| {
         cx.update_global(|vim: &mut Vim, cx: &mut AppContext| {
-            vim.set_enabled(VimModeSetting::get_global(cx).0, cx)
+            vim.set_enabled(
+                VimModeSetting::get_global(cx).0,
+                HelixModeSetting::get_global(cx).0,
+                cx,
+            )
         });
     })
     .detach();
@@ -134,6 +162,11 @@ fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
     command::register(workspace, cx);
     object::register(workspace, cx);
     visual::register(workspace, cx);
+    surrounds::register(workspace, cx);
+    replace::register(workspace, cx);
+    mappings::register(workspace, cx);
+    helix::register(workspace, cx);
+    options::register(workspace, cx);
 }
 
 /// Called whenever an keystroke is typed so vim can observe all actions
@@ -145,28 +178,72 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
         .map(|action| action.boxed_clone())
     {
         Vim::update(cx, |vim, _| {
-            if vim.workspace_state.recording {
+            if let Some(register) = vim.workspace_state.recording_register {
                 vim.workspace_state
-                    .recorded_actions
+                    .recordings
+                    .entry(register)
+                    .or_default()
                     .push(ReplayableAction::Action(action.boxed_clone()));
-
-                if vim.workspace_state.stop_recording_after_next_action {
-                    vim.workspace_state.recording = false;
-                    vim.workspace_state.stop_recording_after_next_action = false;
-                }
             }
         });
 
-        // Keystroke is handled by the vim system, so continue forward
-        if action.name().starts_with("vim::") {
-            return;
-        }
+        observe_action(action, cx);
     } else if cx.has_pending_keystrokes() {
         return;
+    } else {
+        clear_operator_after_keystroke(cx);
     }
+}
 
+/// Called for every action that is dispatched, whether typed or replayed, so
+/// that it can be recorded for `.`.
+pub(crate) fn observe_action(action: Box<dyn Action>, cx: &mut WindowContext) {
+    // The keys sent by a mapping are observed as they are dispatched.
+    if action.as_any().is::<workspace::SendKeystrokes>()
+        || action.as_any().is::<mappings::SendKeystrokesNoremap>()
+    {
+        return;
+    }
+
+    Vim::update(cx, |vim, _| {
+        if vim.workspace_state.recording {
+            vim.workspace_state
+                .recorded_actions
+                .push(ReplayableAction::Action(action.boxed_clone()));
+
+            if vim.workspace_state.stop_recording_after_next_action {
+                vim.workspace_state.recording = false;
+                vim.workspace_state.stop_recording_after_next_action = false;
+            }
+        }
+    });
+
+    // Keystroke is handled by the vim system, so continue forward
+    if action.name().starts_with("vim::") {
+        return;
+    }
+
+    clear_operator_after_keystroke(cx);
+}
+
+fn clear_operator_after_keystroke(cx: &mut WindowContext) {
     Vim::update(cx, |vim, cx| match vim.active_operator() {
-        Some(Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace) => {}
+        Some(
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::RecordRegister
+            | Operator::ReplayRegister
+            | Operator::Register
+            | Operator::Mark
+            | Operator::Jump { .. }
+            | Operator::ConfirmSubstitute
+            | Operator::ChangeSurrounds
+            | Operator::DeleteSurrounds
+            | Operator::SurroundWith
+            | Operator::Literal
+            | Operator::SplitSelection,
+        ) => {}
         Some(_) => {
             vim.clear_operator(cx);
         }
@@ -180,6 +257,8 @@ struct Vim {
     active_editor: Option<WeakView<Editor>>,
     editor_subscription: Option<Subscription>,
     enabled: bool,
+    /// Whether `helix_mode` is on, in which case the normal mode is `Mode::HelixNormal`.
+    helix: bool,
     editor_states: HashMap<EntityId, EditorState>,
     workspace_state: WorkspaceState,
     default_state: EditorState,
@@ -205,6 +284,13 @@ impl Vim {
         }
 
         self.active_editor = Some(editor.clone().downgrade());
+        if !self.editor_states.contains_key(&editor.entity_id()) {
+            // options given to `:set` also apply to editors opened afterwards.
+            let options = self.workspace_state.options.clone();
+            editor.update(cx, |editor, cx| options.apply(editor, cx));
+            self.editor_states
+                .insert(editor.entity_id(), EditorState::default());
+        }
         self.editor_subscription = Some(cx.subscribe(&editor, |editor, event, cx| match event {
             EditorEvent::SelectionsChanged { local: true } => {
                 let editor = editor.read(cx);
@@ -215,13 +301,24 @@ impl Vim {
                 }
             }
             EditorEvent::InputIgnored { text } => {
+                // record before handling the input, so that the register name
+                // typed after `q` doesn't end up in the recording itself.
+                Vim::record_macro_insertion(text, None, cx);
                 Vim::active_editor_input_ignored(text.clone(), cx);
                 Vim::record_insertion(text, None, cx)
             }
             EditorEvent::InputHandled {
                 text,
                 utf16_range_to_replace: range_to_replace,
-            } => Vim::record_insertion(text, range_to_replace.clone(), cx),
+            } => {
+                Vim::record_macro_insertion(text, range_to_replace.clone(), cx);
+                Vim::record_insertion(text, range_to_replace.clone(), cx)
+            }
+            EditorEvent::Edited => Vim::update(cx, |vim, cx| {
+                let editor = editor.read(cx);
+                let anchor = editor.selections.newest_anchor().head();
+                mark::set_mark(vim, '.', anchor, editor, cx);
+            }),
             _ => {}
         }));
 
@@ -229,7 +326,9 @@ impl Vim {
         let editor_mode = editor.mode();
         let newest_selection_empty = editor.selections.newest::<usize>(cx).is_empty();
 
-        if editor_mode == EditorMode::Full
+        if self.helix && self.state().mode == Mode::Normal {
+            self.switch_mode(Mode::HelixNormal, true, cx);
+        } else if editor_mode == EditorMode::Full
                 && !newest_selection_empty
                 && self.state().mode == Mode::Normal
                 // When following someone, don't switch vim mode.
@@ -262,6 +361,44 @@ impl Vim {
         });
     }
 
+    fn record_macro_insertion(
+        text: &Arc<str>,
+        range_to_replace: Option<Range<isize>>,
+        cx: &mut WindowContext,
+    ) {
+        Vim::update(cx, |vim, _| {
+            // insertions made while replaying are covered by the action that
+            // started the replay.
+            if vim.workspace_state.replayer.is_some() {
+                return;
+            }
+            if let Some(register) = vim.workspace_state.recording_register {
+                vim.workspace_state
+                    .recordings
+                    .entry(register)
+                    .or_default()
+                    .push(ReplayableAction::Insertion {
+                        text: text.clone(),
+                        utf16_range_to_replace: range_to_replace,
+                    });
+            }
+        });
+    }
+
+    /// Starts recording keystrokes into `register` (`q{register}`). Uppercase
+    /// registers append to the existing recording.
+    fn record_register(&mut self, register: char, cx: &mut WindowContext) {
+        self.clear_operator(cx);
+        if !register.is_ascii_alphanumeric() && register != '"' {
+            return;
+        }
+        let name = register.to_ascii_lowercase();
+        if !register.is_ascii_uppercase() {
+            self.workspace_state.recordings.remove(&name);
+        }
+        self.workspace_state.recording_register = Some(name);
+    }
+
     fn update_active_editor<S>(
         &mut self,
         cx: &mut WindowContext,
@@ -347,6 +484,14 @@ impl Vim {
     }
 
     fn switch_mode(&mut self, mode: Mode, leave_selections: bool, cx: &mut WindowContext) {
+        let mode = if self.helix && mode == Mode::Normal {
+            Mode::HelixNormal
+        } else {
+            mode
+        };
+        if !self.workspace_state.pending_substitutions.is_empty() {
+            cancel_substitution(self, cx);
+        }
         let state = self.state();
         let last_mode = state.mode;
         let prior_mode = state.last_mode;
@@ -354,6 +499,9 @@ impl Vim {
             state.last_mode = last_mode;
             state.mode = mode;
             state.operator_stack.clear();
+            if last_mode.is_visual() && !mode.is_visual() {
+                state.last_visual_mode = Some(last_mode);
+            }
         });
         if mode != Mode::Insert {
             self.take_count(cx);
@@ -367,7 +515,20 @@ impl Vim {
         }
 
         // Adjust selections
-        self.update_active_editor(cx, |_, editor, cx| {
+        self.update_active_editor(cx, |vim, editor, cx| {
+            if last_mode.is_visual() && !mode.is_visual() {
+                // `'>` is the last selected character, not the end of the selection.
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let start = editor.selections.oldest_anchor().start;
+                let mut end = editor.selections.newest_anchor().end;
+                if end.cmp(&start, &snapshot).is_gt() {
+                    let offset = end.to_offset(&snapshot).saturating_sub(1);
+                    end = snapshot.anchor_before(snapshot.clip_offset(offset, Bias::Left));
+                }
+                mark::set_mark(vim, '<', start, editor, cx);
+                mark::set_mark(vim, '>', end, editor, cx);
+            }
+
             if last_mode != Mode::VisualBlock && last_mode.is_visual() && mode == Mode::VisualBlock
             {
                 visual_block_motion(true, editor, cx, |_, point, goal| Some((point, goal)))
@@ -392,7 +553,8 @@ impl Vim {
                 }
 
                 s.move_with(|map, selection| {
-                    if last_mode.is_visual() && !mode.is_visual() {
+                    // helix keeps the selection made in visual mode.
+                    if last_mode.is_visual() && !mode.is_visual() && mode != Mode::HelixNormal {
                         let mut point = selection.head();
                         if !selection.reversed && !selection.is_empty() {
                             point = movement::left(map, selection.head());
@@ -444,10 +606,25 @@ impl Vim {
     fn push_operator(&mut self, operator: Operator, cx: &mut WindowContext) {
         if matches!(
             operator,
-            Operator::Change | Operator::Delete | Operator::Replace
+            Operator::Change
+                | Operator::Delete
+                | Operator::Replace
+                | Operator::Lowercase
+                | Operator::Uppercase
+                | Operator::OppositeCase
+                | Operator::Rot13
+                | Operator::AddSurrounds
+                | Operator::ChangeSurrounds
+                | Operator::DeleteSurrounds
         ) {
             self.start_recording(cx)
         };
+        if operator == Operator::Literal {
+            self.workspace_state.literal_input.clear();
+        }
+        if operator == Operator::SplitSelection {
+            self.workspace_state.split_input.clear();
+        }
         self.update_state(|state| state.operator_stack.push(operator));
         self.sync_vim_settings(cx);
     }
@@ -464,10 +641,29 @@ impl Vim {
     }
     fn clear_operator(&mut self, cx: &mut WindowContext) {
         self.take_count(cx);
-        self.update_state(|state| state.operator_stack.clear());
+        self.update_state(|state| {
+            state.operator_stack.clear();
+            state.selected_register.take();
+        });
         self.sync_vim_settings(cx);
     }
 
+    /// Chooses the register used by the next yank, delete or put (`"{register}`).
+    fn select_register(&mut self, register: char, cx: &mut WindowContext) {
+        let valid = register.is_ascii_alphanumeric() || "\"-_+*.:/%".contains(register);
+        self.update_state(|state| state.selected_register = valid.then_some(register));
+        self.sync_vim_settings(cx);
+    }
+
+    /// Inserts the contents of a register at the cursor (`ctrl-r {register}` in insert mode).
+    fn insert_register(&mut self, register: char, cx: &mut WindowContext) {
+        self.update_active_editor(cx, |vim, editor, cx| {
+            if let Some(content) = read_register(vim, Some(register), Some(&*editor), cx) {
+                editor.insert(&content.text, cx);
+            }
+        });
+    }
+
     fn active_operator(&self) -> Option<Operator> {
         self.state().operator_stack.last().copied()
     }
@@ -515,12 +711,35 @@ impl Vim {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_replace(text, cx),
                 _ => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
             },
+            Some(Operator::RecordRegister) => {
+                let register = text.chars().next().unwrap();
+                Vim::update(cx, |vim, cx| vim.record_register(register, cx))
+            }
+            Some(Operator::ReplayRegister) => replay_register(text.chars().next().unwrap(), cx),
+            Some(Operator::Register) => Vim::update(cx, |vim, cx| {
+                let register = text.chars().next().unwrap();
+                vim.pop_operator(cx);
+                match vim.state().mode {
+                    Mode::Insert | Mode::Replace => vim.insert_register(register, cx),
+                    _ => vim.select_register(register, cx),
+                }
+            }),
+            Some(Operator::Mark) => Vim::update(cx, |vim, cx| mark::create_mark(vim, text, cx)),
+            Some(Operator::Jump { line }) => mark::jump(text, line, cx),
+            Some(Operator::ConfirmSubstitute) => confirm_substitution(text, cx),
+            Some(Operator::ChangeSurrounds) => surrounds::change_surrounds(text, cx),
+            Some(Operator::DeleteSurrounds) => surrounds::delete_surrounds(text, cx),
+            Some(Operator::SurroundWith) => surrounds::surround_with(text, cx),
+            Some(Operator::Literal) => insert::insert_literal(text, cx),
+            Some(Operator::SplitSelection) => helix::split_selection(text, cx),
+            None if Vim::read(cx).state().mode == Mode::Replace => replace::multi_replace(text, cx),
             _ => {}
         }
     }
 
-    fn set_enabled(&mut self, enabled: bool, cx: &mut AppContext) {
-        if self.enabled == enabled {
+    fn set_enabled(&mut self, vim_mode: bool, helix_mode: bool, cx: &mut AppContext) {
+        let enabled = vim_mode || helix_mode;
+        if self.enabled == enabled && self.helix == helix_mode {
             return;
         }
         if !enabled {
@@ -533,6 +752,8 @@ impl Vim {
         }
 
         self.enabled = true;
+        self.helix = helix_mode;
+        mark::load_global_marks(cx);
         cx.update_global::<CommandPaletteFilter, _>(|filter, _| {
             filter.hidden_namespaces.remove("vim");
         });
@@ -540,6 +761,38 @@ impl Vim {
             command::command_interceptor,
         )));
 
+        // Switch every editor Vim is attached to into the new normal mode, then
+        // reactivate the active one below.
+        for window in cx.windows() {
+            let Some(window) = window.downcast::<Workspace>() else {
+                continue;
+            };
+            window
+                .update(cx, |workspace, cx| {
+                    let editors = workspace.items_of_type::<Editor>(cx).collect::<Vec<_>>();
+                    for editor in editors {
+                        if self.editor_states.contains_key(&editor.entity_id()) {
+                            self.activate_editor(editor, cx);
+                            self.switch_mode(Mode::Normal, false, cx);
+                        }
+                    }
+                })
+                .ok();
+        }
+        // Editors outside of workspaces are switched when they're next activated.
+        let (normal_mode, other_normal_mode) = if helix_mode {
+            (Mode::HelixNormal, Mode::Normal)
+        } else {
+            (Mode::Normal, Mode::HelixNormal)
+        };
+        for state in self.editor_states.values_mut() {
+            if state.mode == other_normal_mode {
+                state.mode = normal_mode;
+            }
+        }
+        self.active_editor = None;
+        self.editor_subscription = None;
+
         if let Some(active_window) = cx
             .active_window()
             .and_then(|window| window.downcast::<Workspace>())
@@ -585,11 +838,15 @@ impl Vim {
             editor.set_cursor_shape(state.cursor_shape(), cx);
             editor.set_clip_at_line_ends(state.clip_at_line_ends(), cx);
             editor.set_collapse_matches(true);
-            editor.set_input_enabled(!state.vim_controlled());
+            editor.set_input_enabled(state.editor_input_enabled());
             editor.set_autoindent(state.should_autoindent());
             editor.selections.line_mode = matches!(state.mode, Mode::VisualLine);
             if editor.is_focused(cx) {
-                editor.set_keymap_context_layer::<Self>(state.keymap_context_layer(), cx);
+                let mut context = state.keymap_context_layer();
+                if vim.workspace_state.noremap {
+                    context.add("VimNoremap");
+                }
+                editor.set_keymap_context_layer::<Self>(context, cx);
             } else {
                 editor.remove_keymap_context_layer::<Self>(cx);
             }
@@ -609,6 +866,22 @@ impl Vim {
     }
 }
 
+impl Settings for HelixModeSetting {
+    const KEY: Option<&'static str> = Some("helix_mode");
+
+    type FileContent = Option<bool>;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut AppContext,
+    ) -> Result<Self> {
+        Ok(Self(user_values.iter().rev().find_map(|v| **v).unwrap_or(
+            default_value.ok_or_else(Self::missing_default)?,
+        )))
+    }
+}
+
 impl Settings for VimModeSetting {
     const KEY: Option<&'static str> = Some("vim_mode");
 
@@ -638,20 +911,24 @@ pub enum UseSystemClipboard {
 }
 
 #[derive(Deserialize)]
-struct VimSettings {
+pub struct VimSettings {
     // all vim uses vim clipboard
     // vim always uses system cliupbaord
     // some magic where yy is system and dd is not.
     pub use_system_clipboard: UseSystemClipboard,
     pub use_multiline_find: bool,
     pub use_smartcase_find: bool,
+    pub leader: String,
+    pub mappings: VimMappings,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
-struct VimSettingsContent {
+pub struct VimSettingsContent {
     pub use_system_clipboard: Option<UseSystemClipboard>,
     pub use_multiline_find: Option<bool>,
     pub use_smartcase_find: Option<bool>,
+    pub leader: Option<String>,
+    pub mappings: Option<VimMappings>,
 }
 
 impl Settings for VimSettings {
@@ -6,7 +6,7 @@ use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Bias, DisplayPoint, Editor,
+    Bias, DisplayPoint, Editor, ToOffset,
 };
 use gpui::{actions, ViewContext, WindowContext};
 use language::{Point, Selection, SelectionGoal};
@@ -14,6 +14,7 @@ use workspace::Workspace;
 
 use crate::{
     motion::{start_of_line, Motion},
+    normal::mark,
     object::Object,
     state::{Mode, Operator},
     utils::{copy_selections_content, yank_selections_content},
@@ -31,6 +32,7 @@ actions!(
         OtherEnd,
         SelectNext,
         SelectPrevious,
+        RestoreVisualSelection,
     ]
 );
 
@@ -47,6 +49,7 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
         },
     );
     workspace.register_action(other_end);
+    workspace.register_action(restore_visual_selection);
     workspace.register_action(delete);
     workspace.register_action(yank);
 
@@ -328,6 +331,39 @@ pub fn other_end(_: &mut Workspace, _: &OtherEnd, cx: &mut ViewContext<Workspace
     });
 }
 
+/// Selects the text between the `'<` and `'>` marks again, in the visual mode
+/// that was last used (`gv`).
+pub fn restore_visual_selection(
+    _: &mut Workspace,
+    _: &RestoreVisualSelection,
+    cx: &mut ViewContext<Workspace>,
+) {
+    Vim::update(cx, |vim, cx| {
+        let range = vim
+            .update_active_editor(cx, |vim, editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let start = mark::mark_anchor(vim, '<', &snapshot)?.to_offset(&snapshot);
+                let end = mark::mark_anchor(vim, '>', &snapshot)?.to_offset(&snapshot);
+                // `'>` is the last selected character, not the end of the selection.
+                let end = end + snapshot.chars_at(end).next().map_or(0, |c| c.len_utf8());
+                Some(start..end)
+            })
+            .flatten();
+        let Some(range) = range else {
+            return;
+        };
+
+        let mode = vim.state().last_visual_mode.unwrap_or(Mode::Visual);
+        vim.switch_mode(mode, true, cx);
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges([range]));
+            if mode == Mode::VisualBlock {
+                visual_block_motion(true, editor, cx, |_, point, goal| Some((point, goal)))
+            }
+        });
+    });
+}
+
 pub fn delete(_: &mut Workspace, _: &VisualDelete, cx: &mut ViewContext<Workspace>) {
     Vim::update(cx, |vim, cx| {
         vim.record_current_action(cx);
@@ -1047,4 +1083,18 @@ mod test {
         cx.simulate_keystrokes(["cmd-shift-p", "escape"]);
         assert_eq!(cx.mode(), Mode::VisualBlock);
     }
+
+    #[gpui::test]
+    async fn test_restore_visual_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["v", "e", "escape", "w", "w"]);
+        cx.assert_state("one two ˇthree", Mode::Normal);
+        cx.simulate_keystrokes(["g", "v"]);
+        cx.assert_state("«oneˇ» two three", Mode::Visual);
+
+        cx.simulate_keystrokes(["escape", "$", "g", "v", "d"]);
+        cx.assert_state("ˇ two three", Mode::Normal);
+    }
 }
@@ -154,6 +154,24 @@ impl Workspace {
         self.dismiss_notification::<simple_message_notification::MessageNotification>(id, cx);
     }
 
+    /// The message of the toast with the given id, if it is shown.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn toast_message(&self, id: usize, cx: &AppContext) -> Option<SharedString> {
+        let type_id = TypeId::of::<simple_message_notification::MessageNotification>();
+        self.notifications
+            .iter()
+            .find(|(existing_type_id, existing_id, _)| {
+                (*existing_type_id, *existing_id) == (type_id, id)
+            })
+            .and_then(|(_, _, notification)| {
+                notification
+                    .to_any()
+                    .downcast::<simple_message_notification::MessageNotification>()
+                    .ok()
+            })
+            .map(|notification| notification.read(cx).message())
+    }
+
     fn dismiss_notification_internal(
         &mut self,
         type_id: TypeId,
@@ -317,6 +335,10 @@ pub mod simple_message_notification {
             self
         }
 
+        pub fn message(&self) -> SharedString {
+            self.message.clone()
+        }
+
         pub fn dismiss(&mut self, cx: &mut ViewContext<Self>) {
             cx.emit(DismissEvent);
         }
@@ -53,8 +53,8 @@ use welcome::{show_welcome_view, BaseKeymap, FIRST_OPEN};
 use workspace::{AppState, WorkspaceStore};
 use zed::{
     app_menus, build_window_options, ensure_only_instance, handle_cli_connection,
-    handle_keymap_file_changes, initialize_workspace, open_paths_with_positions, IsOnlyInstance,
-    OpenListener, OpenRequest,
+    handle_keymap_file_changes, initialize_workspace, open_paths_with_positions, watch_snippets,
+    IsOnlyInstance, OpenListener, OpenRequest,
 };
 
 #[global_allocator]
@@ -183,6 +183,8 @@ fn main() {
 
         load_user_themes_in_background(fs.clone(), cx);
         watch_themes(fs.clone(), cx);
+        watch_snippets(paths::SNIPPETS_DIR.clone(), fs.clone(), cx);
+        spell_check::init(fs.clone(), cx);
 
         cx.spawn(|_| watch_languages(fs.clone(), languages.clone()))
             .detach();
@@ -238,6 +240,7 @@ fn main() {
         recent_projects::init(cx);
 
         go_to_line::init(cx);
+        image_viewer::init(cx);
         file_finder::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
@@ -251,6 +254,7 @@ fn main() {
 
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
+        encoding_selector::init(cx);
         theme_selector::init(cx);
         language_tools::init(cx);
         call::init(app_state.client.clone(), app_state.user_store.clone(), cx);
@@ -465,6 +469,9 @@ fn init_paths() {
     std::fs::create_dir_all(&*util::paths::EXTENSIONS_DIR)
         .expect("could not create extensions path");
     std::fs::create_dir_all(&*util::paths::LANGUAGES_DIR).expect("could not create languages path");
+    std::fs::create_dir_all(&*util::paths::SNIPPETS_DIR).expect("could not create snippets path");
+    std::fs::create_dir_all(&*util::paths::DICTIONARIES_DIR)
+        .expect("could not create dictionaries path");
     std::fs::create_dir_all(&*util::paths::DB_DIR).expect("could not create database path");
     std::fs::create_dir_all(&*util::paths::LOGS_DIR).expect("could not create logs path");
     std::fs::create_dir_all(&*util::paths::TEMP_DIR).expect("could not create tmp path");
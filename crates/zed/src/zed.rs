@@ -18,6 +18,7 @@ pub use open_listener::*;
 use anyhow::Context as _;
 use assets::Assets;
 use futures::{channel::mpsc, select_biased, StreamExt};
+use language::language_settings::AllLanguageSettings;
 use project::TaskSourceKind;
 use project_panel::ProjectPanel;
 use quick_action_bar::QuickActionBar;
@@ -28,7 +29,14 @@ use settings::{
     initial_local_settings_content, initial_tasks_content, watch_config_file, KeymapFile, Settings,
     SettingsStore, DEFAULT_KEYMAP_PATH,
 };
-use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
+use spell_check::SpellChecker;
+use std::{
+    borrow::Cow,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use task::{oneshot_source::OneshotSource, static_source::StaticSource};
 use terminal_view::terminal_panel::{self, TerminalPanel};
 use util::{
@@ -37,7 +45,7 @@ use util::{
     ResultExt,
 };
 use uuid::Uuid;
-use vim::VimModeSetting;
+use vim::{HelixModeSetting, KeyMappings, VimModeSetting, VimSettings};
 use welcome::BaseKeymap;
 use workspace::{
     create_and_open_local_file, notifications::simple_message_notification::MessageNotification,
@@ -129,6 +137,10 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             activity_indicator::ActivityIndicator::new(workspace, app_state.languages.clone(), cx);
         let active_buffer_language =
             cx.new_view(|_| language_selector::ActiveBufferLanguage::new(workspace));
+        let active_buffer_encoding =
+            cx.new_view(|_| encoding_selector::ActiveBufferEncoding::new(workspace));
+        let image_info = cx.new_view(|_| image_viewer::ImageInfo::new());
+        let large_file_indicator = cx.new_view(|_| editor::LargeFileIndicator::new());
         let vim_mode_indicator = cx.new_view(|cx| vim::ModeIndicator::new(cx));
         let cursor_position =
             cx.new_view(|_| go_to_line::cursor_position::CursorPosition::new(workspace));
@@ -136,6 +148,9 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             status_bar.add_left_item(diagnostic_summary, cx);
             status_bar.add_left_item(activity_indicator, cx);
             status_bar.add_right_item(copilot, cx);
+            status_bar.add_right_item(large_file_indicator, cx);
+            status_bar.add_right_item(image_info, cx);
+            status_bar.add_right_item(active_buffer_encoding, cx);
             status_bar.add_right_item(active_buffer_language, cx);
             status_bar.add_right_item(vim_mode_indicator, cx);
             status_bar.add_right_item(cursor_position, cx);
@@ -143,6 +158,12 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
 
         auto_update::notify_of_any_new_update(cx);
 
+        notify_if_spell_check_unavailable(workspace, cx);
+        cx.observe_global::<SpellChecker>(notify_if_spell_check_unavailable)
+            .detach();
+        cx.observe_global::<SettingsStore>(notify_if_spell_check_unavailable)
+            .detach();
+
         let handle = cx.view().downgrade();
         cx.on_window_should_close(move |cx| {
             handle
@@ -584,22 +605,48 @@ fn open_log_file(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
         .detach();
 }
 
+const SPELL_CHECK_UNAVAILABLE_NOTIFICATION_ID: usize = 0x7370_656c;
+
+/// Tells the user that spell checking is unavailable, once per session, when
+/// they enabled it and the dictionary set in their settings couldn't be loaded.
+fn notify_if_spell_check_unavailable(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    if !AllLanguageSettings::get_global(cx).spell_check_enabled() {
+        return;
+    }
+    let Some(error) =
+        SpellChecker::try_global(cx).and_then(|checker| checker.dictionary_error().cloned())
+    else {
+        return;
+    };
+    workspace.show_notification_once(SPELL_CHECK_UNAVAILABLE_NOTIFICATION_ID, cx, |cx| {
+        cx.new_view(|_| MessageNotification::new(format!("Spell checking is unavailable: {error}")))
+    });
+}
+
 pub fn handle_keymap_file_changes(
     mut user_keymap_file_rx: mpsc::UnboundedReceiver<String>,
     cx: &mut AppContext,
 ) {
     BaseKeymap::register(cx);
     VimModeSetting::register(cx);
+    HelixModeSetting::register(cx);
+    VimSettings::register(cx);
 
     let (base_keymap_tx, mut base_keymap_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
-    let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
+    let mut old_vim_enabled = vim_enabled(cx);
+    let mut old_vim_mappings = KeyMappings::from_settings(cx);
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
-        let new_vim_enabled = VimModeSetting::get_global(cx).0;
-        if new_base_keymap != old_base_keymap || new_vim_enabled != old_vim_enabled {
+        let new_vim_enabled = vim_enabled(cx);
+        let new_vim_mappings = KeyMappings::from_settings(cx);
+        if new_base_keymap != old_base_keymap
+            || new_vim_enabled != old_vim_enabled
+            || new_vim_mappings != old_vim_mappings
+        {
             old_base_keymap = new_base_keymap;
             old_vim_enabled = new_vim_enabled;
+            old_vim_mappings = new_vim_mappings;
             base_keymap_tx.unbounded_send(()).unwrap();
         }
     })
@@ -637,8 +684,10 @@ fn reload_keymaps(cx: &mut AppContext, keymap_content: &KeymapFile) {
 
 pub fn load_default_keymap(cx: &mut AppContext) {
     KeymapFile::load_asset(DEFAULT_KEYMAP_PATH, cx).unwrap();
-    if VimModeSetting::get_global(cx).0 {
+    if vim_enabled(cx) {
         KeymapFile::load_asset("keymaps/vim.json", cx).unwrap();
+        let bindings = KeyMappings::from_settings(cx).key_bindings(cx);
+        cx.bind_keys(bindings);
     }
 
     if let Some(asset_path) = BaseKeymap::get_global(cx).asset_path() {
@@ -646,6 +695,11 @@ pub fn load_default_keymap(cx: &mut AppContext) {
     }
 }
 
+/// The vim keymap is also used by helix mode, which is built on top of it.
+fn vim_enabled(cx: &AppContext) -> bool {
+    VimModeSetting::get_global(cx).0 || HelixModeSetting::get_global(cx).0
+}
+
 fn open_local_settings_file(
     workspace: &mut Workspace,
     _: &OpenLocalSettings,
@@ -855,6 +909,45 @@ fn open_settings_file(
     .detach_and_log_err(cx);
 }
 
+/// Spawns a background task to load the user snippets and reload them when the
+/// snippets directory changes.
+pub fn watch_snippets(snippets_dir: PathBuf, fs: Arc<dyn fs::Fs>, cx: &mut AppContext) {
+    cx.spawn(|cx| async move {
+        let mut events = fs.watch(&snippets_dir, Duration::from_millis(100)).await;
+
+        let mut paths = Vec::new();
+        if let Some(mut entries) = fs.read_dir(&snippets_dir).await.log_err() {
+            while let Some(path) = entries.next().await {
+                paths.extend(path.log_err());
+            }
+        }
+
+        loop {
+            for path in paths {
+                let is_snippet_file = path.extension().map_or(false, |extension| {
+                    extension == "json" || extension == "code-snippets"
+                });
+                if !is_snippet_file {
+                    continue;
+                }
+
+                let content = fs.load(&path).await.ok();
+                cx.update(|cx| {
+                    editor::UserSnippets::set_snippet_file(path, content.as_deref(), cx)
+                })?
+                .log_err();
+            }
+
+            match events.next().await {
+                Some(changed_paths) => paths = changed_paths,
+                None => break,
+            }
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2798,6 +2891,74 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_watch_snippets(cx: &mut gpui::TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                "/snippets",
+                json!({
+                    "rust.json": r#"{ "Main": { "prefix": "main", "body": "fn main() {}" } }"#,
+                    "notes.txt": "not a snippet file",
+                }),
+            )
+            .await;
+
+        cx.update(|cx| watch_snippets(PathBuf::from("/snippets"), app_state.fs.clone(), cx));
+        cx.background_executor.run_until_parked();
+
+        let snippet_names = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                cx.try_global::<editor::UserSnippets>()
+                    .map(|snippets| {
+                        snippets
+                            .snippets_for_language(Some("Rust"))
+                            .map(|snippet| snippet.name.clone())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+            })
+        };
+        assert_eq!(snippet_names(cx), ["Main"]);
+
+        // Changing a snippet file reloads its snippets.
+        app_state
+            .fs
+            .save(
+                "/snippets/rust.json".as_ref(),
+                &r#"{ "Test": { "prefix": "test", "body": "fn test_$1() {}" } }"#.into(),
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(snippet_names(cx), ["Test"]);
+
+        // Global snippet files apply to every language.
+        app_state
+            .fs
+            .save(
+                "/snippets/all.code-snippets".as_ref(),
+                &r#"{ "Todo": { "prefix": "todo", "body": "TODO: $0" } }"#.into(),
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(snippet_names(cx), ["Todo", "Test"]);
+
+        // Deleting a snippet file removes its snippets.
+        app_state
+            .fs
+            .remove_file("/snippets/rust.json".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(snippet_names(cx), ["Todo"]);
+    }
+
     #[gpui::test]
     fn test_bundled_settings_and_themes(cx: &mut AppContext) {
         cx.text_system()